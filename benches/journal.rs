@@ -0,0 +1,83 @@
+//! how long `jrn` takes with a journal of ten years of entries: loading it,
+//! and what `jrn list` and choosing an entry do with it. run with
+//! `cargo bench`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use jrn::{
+    date::Date,
+    db::State,
+    encryptor::ZeroSecurity,
+    search::{self, Query},
+    storage::InMemoryStorage,
+    test_util::{self, PASSWORD},
+};
+
+/// how many entries the journal has
+const ENTRIES: i64 = 10_000;
+
+/// a journal with an entry for each of [`ENTRIES`] days, with a few lines of
+/// text in each
+fn journal() -> State {
+    let first = test_util::date("2000-01-01");
+    let mut state = test_util::state([]);
+    for day in 0..ENTRIES {
+        let date = first.add_days(day).unwrap();
+        let content = format!("# day {day}\n\nwent for a walk, read a bit, and #wrote this.\nnothing else happened on {date}.\n");
+        state.set_entry(&date, &content);
+    }
+    state
+}
+
+fn load(c: &mut Criterion) {
+    let storage = test_util::storage(&journal()).unwrap();
+    c.bench_function("load", |b| {
+        b.iter(|| {
+            let mut state = State::new();
+            state.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+            state
+        })
+    });
+}
+
+fn save(c: &mut Criterion) {
+    let state = journal();
+    c.bench_function("save", |b| {
+        b.iter(|| state.save_to(&InMemoryStorage::new(), &ZeroSecurity).unwrap())
+    });
+}
+
+fn list(c: &mut Criterion) {
+    let state = journal();
+    // what `jrn list` prints for every entry
+    c.bench_function("list", |b| {
+        b.iter(|| {
+            let mut dates = state.entries.keys().collect::<Vec<_>>();
+            dates.sort();
+            dates
+                .into_iter()
+                .map(|date| format!("- {date} ({}){}", date.annotation(), if state.is_starred(date) { " *" } else { "" }))
+                .collect::<Vec<_>>()
+        })
+    });
+    // the choices when choosing an entry, newest first
+    c.bench_function("choose", |b| {
+        b.iter(|| {
+            let mut dates = state.entries.keys().copied().collect::<Vec<Date>>();
+            dates.sort_by(|a, b| b.cmp(a));
+            dates.iter().map(Date::label).collect::<Vec<_>>()
+        })
+    });
+}
+
+fn search(c: &mut Criterion) {
+    let state = journal();
+    let query = Query {
+        text: Some("walk".into()),
+        tags: vec!["wrote".into()],
+        ..Query::default()
+    };
+    c.bench_function("search", |b| b.iter(|| search::search(&state, black_box(&query))));
+}
+
+criterion_group!(benches, load, save, list, search);
+criterion_main!(benches);
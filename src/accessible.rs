@@ -0,0 +1,117 @@
+//! module for the plain prompts used with `--accessible`: numbered lists and
+//! answers read a line at a time, instead of requestty's widgets, which move
+//! the cursor around and redraw themselves. those are hard to follow with a
+//! screen reader or a braille display. passwords aren't echoed at all, not
+//! even as `*`.
+//!
+//! messages are printed as they are, so they should be translated already.
+
+use std::{
+    io::{stdin, stdout, IsTerminal, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use requestty::{
+    prompt::{
+        backend::{self, Backend},
+        events::{self, EventIterator, KeyCode, KeyModifiers},
+    },
+    ErrorKind,
+};
+
+use crate::tr;
+
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+/// turns the plain prompts on or off
+pub fn set_accessible(accessible: bool) {
+    ACCESSIBLE.store(accessible, Ordering::Relaxed);
+}
+
+/// whether the plain prompts are used (see [`set_accessible()`])
+pub fn is_accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// prints `message` and reads the answer, without the line break
+fn ask(message: &str) -> requestty::Result<String> {
+    print!("{message} ");
+    stdout().flush()?;
+    let mut line = String::new();
+    if stdin().read_line(&mut line)? == 0 {
+        return Err(ErrorKind::Eof);
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).into())
+}
+
+/// asks for a line of text
+pub fn input(message: &str) -> requestty::Result<String> {
+    ask(message)
+}
+
+/// asks a yes or no question until it gets `y` or `n`
+pub fn confirm(message: &str) -> requestty::Result<bool> {
+    loop {
+        match ask(message)?.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => println!("{}", tr!("Please answer y or n.")),
+        }
+    }
+}
+
+/// prints `choices` as a numbered list and asks for the number of one, until
+/// it gets one that's on the list. returns the index of the chosen one.
+pub fn select(message: &str, choices: &[String]) -> requestty::Result<usize> {
+    println!("{message}");
+    for (i, choice) in choices.iter().enumerate() {
+        println!("{}. {choice}", i + 1);
+    }
+    loop {
+        let answer = ask(&tr!("Number (1 to {0}):", choices.len()))?;
+        match answer.trim().parse::<usize>() {
+            Ok(number) if (1..=choices.len()).contains(&number) => return Ok(number - 1),
+            _ => println!("{}", tr!("Please type a number from 1 to {0}.", choices.len())),
+        }
+    }
+}
+
+/// asks for a password without showing anything while it's typed
+pub fn password(message: &str) -> requestty::Result<String> {
+    if !stdin().is_terminal() {
+        return ask(message);
+    }
+
+    print!("{message} ");
+    stdout().flush()?;
+    let stdout = stdout();
+    let mut backend = backend::get_backend(stdout.lock());
+    let mut events = events::get_events();
+    backend.enable_raw_mode()?;
+    let password = read_password(&mut events);
+    // the terminal is put back however it went
+    backend.disable_raw_mode()?;
+    println!();
+    password
+}
+
+fn read_password(events: &mut impl EventIterator) -> requestty::Result<String> {
+    let mut password = String::new();
+    loop {
+        let key = events.next_event()?;
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Err(ErrorKind::Interrupted),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) && password.is_empty() => {
+                return Err(ErrorKind::Eof)
+            }
+            KeyCode::Null => return Err(ErrorKind::Eof),
+            KeyCode::Esc => return Err(ErrorKind::Aborted),
+            KeyCode::Enter => return Ok(password),
+            KeyCode::Backspace => {
+                password.pop();
+            }
+            KeyCode::Char(c) => password.push(c),
+            _ => {}
+        }
+    }
+}
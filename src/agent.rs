@@ -0,0 +1,437 @@
+//! module for `jrn daemon`, which keeps the keys of unlocked journals in
+//! memory (like `ssh-agent`), so `jrn` doesn't have to ask for the password,
+//! check it, and derive the key every time it runs.
+//!
+//! the daemon listens on a unix socket (see [`socket_path()`]) only the user
+//! can connect to. it takes one JSON request per line and answers each
+//! with one JSON line, either `{"ok": ...}` or `{"error": "..."}`:
+//! - `{"method": "get", "journal": "/path/to/jrn.json"}` gives the journal's
+//!   keys, or `null`
+//! - `{"method": "put", "journal": "/path/to/jrn.json", "keys": {...}}`
+//!   remembers them
+//! - `{"method": "forget"}` forgets every key
+//! - `{"method": "stop"}` forgets every key and stops the daemon
+//! - `{"method": "status"}` gives the daemon's process id, socket, and
+//!   timeout, and the journals it has keys for (and which `jrn` has them
+//!   open)
+//!
+//! keys that weren't used for the daemon's timeout are forgotten.
+//!
+//! while a daemon has a journal's keys, it claims the journal with a file next
+//! to it (see [`claim()`]), so no other daemon (on another socket, or of
+//! another user) takes its keys too. a daemon only takes the keys of journals
+//! owned by the user it runs as.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use jrn::db::Keys;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    cli::{Daemon, DaemonSubCommand},
+    lock, tr,
+};
+
+/// how long keys are kept without being used, unless `--timeout` is given
+const DEFAULT_TIMEOUT_MINUTES: u64 = 15;
+
+#[derive(Debug, Error)]
+/// how running or talking to the daemon could go wrong
+pub enum AgentError {
+    /// the socket couldn't be set up
+    #[error("{}", tr!("couldn't listen on {0}: {1}", .0.display(), .1))]
+    Socket(PathBuf, #[source] io::Error),
+    /// another daemon is already listening on the socket
+    #[error("{}", tr!("a daemon is already running on {0}", .0.display()))]
+    AlreadyRunning(PathBuf),
+    /// no daemon is listening on the socket
+    #[error("{}", tr!("no daemon is running (start one with `jrn daemon`)"))]
+    NotRunning,
+    /// there are no unix sockets on this platform
+    #[error("{}", tr!("jrn daemon needs unix sockets, which this platform doesn't have"))]
+    Unsupported,
+    /// the daemon wouldn't do what it was asked
+    #[error("{}", tr!("the daemon refused: {0}", .0))]
+    Refused(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+/// a request sent to the daemon
+enum Request {
+    Get { journal: String },
+    Put { journal: String, keys: Keys },
+    Forget,
+    Stop,
+    Status,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// which daemon has a journal's keys, kept next to the journal while it does
+/// (see [`claim_path()`])
+struct Claim {
+    /// the daemon's process id
+    pid: u32,
+    /// the socket the daemon listens on
+    socket: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// what `jrn daemon status` prints
+struct Status {
+    pid: u32,
+    socket: PathBuf,
+    /// in seconds
+    timeout: u64,
+    journals: Vec<HeldJournal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// a journal the daemon has keys for, in a [`Status`]
+struct HeldJournal {
+    journal: String,
+    /// how long ago the keys were last used, in seconds
+    idle: u64,
+    /// the process id of the `jrn` that has the journal open (see
+    /// [`lock`](crate::lock))
+    #[serde(default)]
+    open_in: Option<u32>,
+}
+
+/// the daemon's socket: `$JRN_AGENT_SOCKET`, or `jrn/agent.sock` in
+/// `$XDG_RUNTIME_DIR` (or in `~/.cache`, without one). not in the shared
+/// temporary directory, where someone else could get there first.
+pub fn socket_path() -> PathBuf {
+    if let Some(socket) = env::var_os("JRN_AGENT_SOCKET").filter(|socket| !socket.is_empty()) {
+        return socket.into();
+    }
+
+    let dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+        .unwrap_or_else(env::temp_dir);
+    dir.join("jrn").join("agent.sock")
+}
+
+/// what the daemon knows a journal by: its full path
+fn journal_id(journal: &str) -> String {
+    fs::canonicalize(journal)
+        .unwrap_or_else(|_| Path::new(journal).into())
+        .display()
+        .to_string()
+}
+
+/// the keys the daemon has for `journal`, if one is running and has them
+pub fn keys(journal: &str) -> Option<Keys> {
+    let response = request(&json!({ "method": "get", "journal": journal_id(journal) })).ok()?;
+    serde_json::from_value(response.get("ok")?.clone()).ok()
+}
+
+/// gives the keys for `journal` to the daemon, if one is running. fails if
+/// the daemon won't take them, like when another daemon has the journal's
+/// keys already.
+pub fn remember(journal: &str, keys: &Keys) -> Result<(), AgentError> {
+    let Ok(response) = request(&json!({ "method": "put", "journal": journal_id(journal), "keys": keys })) else {
+        return Ok(());
+    };
+    match response.get("error").and_then(Value::as_str) {
+        Some(error) => Err(AgentError::Refused(error.into())),
+        None => Ok(()),
+    }
+}
+
+/// the file next to `journal` that says which daemon has its keys
+fn claim_path(journal: &str) -> PathBuf {
+    format!("{journal}.daemon").into()
+}
+
+fn read_claim(path: &Path) -> Option<Claim> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// runs `jrn daemon`: starts the daemon, tells a running one to forget its
+/// keys or stop, or prints its status
+pub fn run(opts: &Daemon) -> Result<(), AgentError> {
+    if let Some(DaemonSubCommand::Status(_)) = opts.subcommand {
+        return print_status();
+    }
+
+    let method = match (opts.forget, opts.stop) {
+        (_, true) => "stop",
+        (true, false) => "forget",
+        (false, false) => {
+            let timeout = opts.timeout.unwrap_or(DEFAULT_TIMEOUT_MINUTES);
+            return serve(&socket_path(), std::time::Duration::from_secs(timeout.saturating_mul(60)));
+        }
+    };
+
+    request(&json!({ "method": method })).map_err(|_| AgentError::NotRunning)?;
+    match method {
+        "stop" => println!("{}", tr!("stopped the daemon")),
+        _ => println!("{}", tr!("the daemon forgot every key")),
+    }
+    Ok(())
+}
+
+/// prints the running daemon's process id, socket, and timeout, and the
+/// journals it has keys for, with which `jrn` has them open
+fn print_status() -> Result<(), AgentError> {
+    let response = request(&json!({ "method": "status" })).map_err(|_| AgentError::NotRunning)?;
+    let status = response
+        .get("ok")
+        .and_then(|status| serde_json::from_value::<Status>(status.clone()).ok())
+        .ok_or(AgentError::NotRunning)?;
+
+    println!(
+        "{}",
+        tr!(
+            "a daemon (process {0}) is running on {1}, and forgets keys after {2} minutes",
+            status.pid,
+            status.socket.display(),
+            status.timeout / 60
+        )
+    );
+    if status.journals.is_empty() {
+        println!("{}", tr!("it has no keys"));
+        return Ok(());
+    }
+    println!("{}", tr!("it has the keys for:"));
+    for held in &status.journals {
+        let used = tr!("used {0} minutes ago", held.idle / 60);
+        match held.open_in {
+            Some(pid) => println!("  {} ({used}, {})", held.journal, tr!("open in process {0}", pid)),
+            None => println!("  {} ({used})", held.journal),
+        }
+    }
+    Ok(())
+}
+
+/// sends one request to the daemon and gives back its answer
+#[cfg(unix)]
+fn request(request: &Value) -> io::Result<Value> {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        time::Duration,
+    };
+
+    let mut stream = UnixStream::connect(socket_path())?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    writeln!(stream, "{request}")?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(not(unix))]
+fn request(_request: &Value) -> io::Result<Value> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// listens on `socket` until asked to stop, forgetting keys that weren't used
+/// for `timeout`
+#[cfg(unix)]
+fn serve(socket: &Path, timeout: std::time::Duration) -> Result<(), AgentError> {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader, Write},
+        os::unix::{
+            fs::{DirBuilderExt, MetadataExt, PermissionsExt},
+            net::{UnixListener, UnixStream},
+        },
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    let socket_error = |e| AgentError::Socket(socket.into(), e);
+
+    if UnixStream::connect(socket).is_ok() {
+        return Err(AgentError::AlreadyRunning(socket.into()));
+    }
+    if let Some(dir) = socket.parent().filter(|dir| !dir.exists()) {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(socket_error)?;
+    }
+    // left behind by a daemon that didn't stop cleanly
+    let _ = fs::remove_file(socket);
+    let listener = UnixListener::bind(socket).map_err(socket_error)?;
+    // `$JRN_AGENT_SOCKET` can be in a directory others can get into
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600)).map_err(socket_error)?;
+    // only the journals of the user the daemon runs as are served, and the
+    // socket was just made by that user
+    let owner = fs::metadata(socket).map_err(socket_error)?.uid();
+    println!("{}", tr!("listening on {0}", socket.display()));
+
+    let keys = Arc::new(Mutex::new(HashMap::<String, (Keys, Instant)>::new()));
+    {
+        let keys = Arc::clone(&keys);
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_secs(10).min(timeout));
+            let mut keys = keys.lock().unwrap_or_else(|e| e.into_inner());
+            keys.retain(|journal, (_, used)| {
+                let keep = used.elapsed() < timeout;
+                if !keep {
+                    release(journal);
+                }
+                keep
+            });
+        });
+    }
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+
+        let mut stop = false;
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let mut keys = keys.lock().unwrap_or_else(|e| e.into_inner());
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(Request::Get { journal }) => match keys.get_mut(&journal) {
+                    Some((journal_keys, used)) => {
+                        *used = Instant::now();
+                        json!({ "ok": journal_keys })
+                    }
+                    None => json!({ "ok": Value::Null }),
+                },
+                Ok(Request::Put { journal, keys: journal_keys }) => {
+                    let claimed = match keys.contains_key(&journal) {
+                        true => Ok(()),
+                        false => claim(&journal, socket, owner),
+                    };
+                    match claimed {
+                        Ok(()) => {
+                            keys.insert(journal, (journal_keys, Instant::now()));
+                            json!({ "ok": Value::Null })
+                        }
+                        Err(e) => json!({ "error": e }),
+                    }
+                }
+                Ok(Request::Forget) => {
+                    keys.drain().for_each(|(journal, _)| release(&journal));
+                    json!({ "ok": Value::Null })
+                }
+                Ok(Request::Stop) => {
+                    keys.drain().for_each(|(journal, _)| release(&journal));
+                    stop = true;
+                    json!({ "ok": Value::Null })
+                }
+                Ok(Request::Status) => {
+                    let mut journals = keys
+                        .iter()
+                        .map(|(journal, (_, used))| HeldJournal {
+                            journal: journal.clone(),
+                            idle: used.elapsed().as_secs(),
+                            open_in: lock::holder(journal),
+                        })
+                        .collect::<Vec<_>>();
+                    journals.sort_by(|a, b| a.journal.cmp(&b.journal));
+                    let status = Status {
+                        pid: process::id(),
+                        socket: socket.into(),
+                        timeout: timeout.as_secs(),
+                        journals,
+                    };
+                    json!({ "ok": status })
+                }
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+            if writeln!(stream, "{response}").is_err() || stop {
+                break;
+            }
+        }
+
+        if stop {
+            break;
+        }
+    }
+
+    let _ = fs::remove_file(socket);
+    Ok(())
+}
+
+/// claims `journal` for the daemon on `socket` (see [`claim_path()`]), unless
+/// it isn't `owner`'s, or another daemon that's still running has claimed it.
+/// gives back why it couldn't.
+#[cfg(unix)]
+fn claim(journal: &str, socket: &Path, owner: u32) -> Result<(), String> {
+    use std::os::unix::{
+        fs::{MetadataExt, OpenOptionsExt},
+        net::UnixStream,
+    };
+
+    let metadata = fs::metadata(journal).map_err(|e| tr!("couldn't read {0}: {1}", journal, e))?;
+    if metadata.uid() != owner {
+        return Err(tr!("{0} belongs to another user", journal));
+    }
+
+    let path = claim_path(journal);
+    let ours = Claim {
+        pid: process::id(),
+        socket: socket.into(),
+    };
+    // a second try, after a claim that was left behind is removed
+    for _ in 0..2 {
+        let file = fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path);
+        match file {
+            Ok(file) => {
+                return serde_json::to_writer(file, &ours).map_err(|e| tr!("couldn't claim {0}: {1}", journal, e));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(tr!("couldn't claim {0}: {1}", journal, e)),
+        }
+
+        if let Some(claim) = read_claim(&path) {
+            // a socket that can't be connected to, because it belongs to
+            // someone else, can still have a daemon behind it
+            let running = match UnixStream::connect(&claim.socket) {
+                Ok(_) => true,
+                Err(e) => e.kind() == io::ErrorKind::PermissionDenied,
+            };
+            if claim.socket != socket && running {
+                return Err(tr!(
+                    "another daemon (process {0}, on {1}) has the keys for {2}",
+                    claim.pid,
+                    claim.socket.display(),
+                    journal
+                ));
+            }
+        }
+        // left behind by a daemon that's gone, or an earlier one on this
+        // socket
+        let _ = fs::remove_file(&path);
+    }
+    Err(tr!("couldn't claim {0}: {1}", journal, path.display()))
+}
+
+/// removes the daemon's claim on `journal`, if it's still this daemon's
+fn release(journal: &str) {
+    let path = claim_path(journal);
+    if read_claim(&path).is_some_and(|claim| claim.pid == process::id()) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(not(unix))]
+fn serve(_socket: &Path, _timeout: std::time::Duration) -> Result<(), AgentError> {
+    Err(AgentError::Unsupported)
+}
@@ -0,0 +1,155 @@
+//! module for archives (`.jrnpack` files): the journal file and the
+//! configuration file bundled into one file, encrypted with a key from the
+//! journal's password, for backups and for moving to another machine (see
+//! [`Archive`])
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    date::Date,
+    db::{EncryptedEntry, LoadError, State},
+    encryptor::{DecryptError, EncryptError, Encryptor},
+};
+
+/// what [`Archive::format`] always is, to tell archives from other files
+pub const FORMAT: &str = "jrnpack";
+/// the version of the archive format made by this version of `jrn`. archives
+/// with a newer one can't be opened.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// what's in an archive, once it's decrypted
+pub struct Contents {
+    /// the name the journal file had, like `jrn.json`
+    pub journal_name: String,
+    /// the journal file, as it was (its entries are still encrypted)
+    pub journal: String,
+    /// the name and contents of the configuration file, if there was one
+    pub config: Option<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// an archive as it's stored: a little about it in the clear, and the
+/// [`Contents`] encrypted
+pub struct Archive {
+    /// always [`FORMAT`]
+    pub format: String,
+    /// the version of the format (see [`VERSION`])
+    pub version: u32,
+    /// when the archive was made, as a unix timestamp
+    pub created: i64,
+    /// the hash of the password, to tell an incorrect password from a
+    /// corrupted archive
+    pub password_hash: String,
+    /// the salt of the key, in base64
+    pub kdf_salt: String,
+    /// the nonce the contents were encrypted with, in base64
+    pub nonce: String,
+    /// the encrypted [`Contents`] as JSON, in base64
+    pub contents: String,
+}
+
+#[derive(Debug, Error)]
+/// how making or opening an archive could go wrong
+pub enum ArchiveError {
+    /// the file isn't an archive
+    #[error("not a jrn archive: {0}")]
+    NotAnArchive(String),
+    /// the archive was made by a newer version of `jrn`
+    #[error("the archive is version {0}, but this jrn only opens version {VERSION} (update jrn to open it)")]
+    UnsupportedVersion(u32),
+    /// the password doesn't open the archive
+    #[error("incorrect password")]
+    IncorrectPassword,
+    /// the password was right, but the contents couldn't be decrypted
+    #[error("the archive is corrupted")]
+    Corrupted,
+    /// the contents couldn't be encrypted (see [`EncryptError`])
+    #[error("couldn't encrypt the archive: {0}")]
+    Encrypt(#[from] EncryptError),
+    /// the journal in the archive doesn't open with the password (see
+    /// [`Archive::verify()`])
+    #[error("the journal in the archive doesn't open: {0}")]
+    Journal(#[from] LoadError),
+}
+
+impl From<DecryptError> for ArchiveError {
+    fn from(value: DecryptError) -> Self {
+        match value {
+            DecryptError::IncorrectPassword => Self::IncorrectPassword,
+            _ => Self::Corrupted,
+        }
+    }
+}
+
+impl Archive {
+    /// encrypts `contents` with a key from `password`, made at `created` (a
+    /// unix timestamp)
+    pub fn create<E: Encryptor>(contents: &Contents, password: &str, created: i64, e: &E) -> Result<Self, ArchiveError> {
+        let json = serde_json::to_string(contents).expect("the contents are only strings");
+        let kdf_salt = e.make_kdf_salt();
+        let key = e.gen_key(password, kdf_salt);
+        // the date only shows up in errors
+        let encrypted = e.encrypt_journal_entry(key, &json, &Date::today())?;
+
+        Ok(Self {
+            format: FORMAT.into(),
+            version: VERSION,
+            created,
+            password_hash: e.hash_password(password)?,
+            kdf_salt: BASE64_STANDARD.encode(kdf_salt),
+            nonce: BASE64_STANDARD.encode(encrypted.nonce),
+            contents: BASE64_STANDARD.encode(encrypted.digest),
+        })
+    }
+
+    /// parses an archive file, checking its format and version
+    pub fn parse(json: &str) -> Result<Self, ArchiveError> {
+        let archive = serde_json::from_str::<Self>(json).map_err(|e| ArchiveError::NotAnArchive(e.to_string()))?;
+        if archive.format != FORMAT {
+            return Err(ArchiveError::NotAnArchive(archive.format));
+        }
+        if archive.version > VERSION {
+            return Err(ArchiveError::UnsupportedVersion(archive.version));
+        }
+        Ok(archive)
+    }
+
+    /// the archive as JSON, to be written to a file
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("an archive is only strings and numbers")
+    }
+
+    /// decrypts the contents with `password`
+    pub fn open<E: Encryptor>(&self, password: &str, e: &E) -> Result<Contents, ArchiveError> {
+        if !e.verify_password(&self.password_hash, password)? {
+            return Err(ArchiveError::IncorrectPassword);
+        }
+        let decode = |base64: &str| BASE64_STANDARD.decode(base64).map_err(|_| ArchiveError::Corrupted);
+        let kdf_salt = decode(&self.kdf_salt)?.try_into().map_err(|_| ArchiveError::Corrupted)?;
+        let nonce = decode(&self.nonce)?.try_into().map_err(|_| ArchiveError::Corrupted)?;
+        let encrypted = EncryptedEntry {
+            date: Date::today(),
+            nonce,
+            digest: decode(&self.contents)?,
+            chain: None,
+            meta: None,
+            public: false,
+        };
+
+        let key = e.gen_key(password, kdf_salt);
+        let (_, json) = e.decrypt_journal_entry(key, &encrypted)?;
+        serde_json::from_str(&json).map_err(|_| ArchiveError::Corrupted)
+    }
+
+    /// opens the archive, and then the journal in it, with `password`,
+    /// returning the contents and the journal
+    pub fn verify<E: Encryptor>(&self, password: &str, e: &E) -> Result<(Contents, State), ArchiveError> {
+        let contents = self.open(password, e)?;
+        let mut state = State::new();
+        state.load_json(&contents.journal, password, e)?;
+        Ok((contents, state))
+    }
+}
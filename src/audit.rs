@@ -0,0 +1,77 @@
+//! module for the audit log: when the journal was unlocked, when someone
+//! failed to unlock it, and when it was changed (see
+//! [`State::audit`](crate::db::State::audit)). the log is encrypted with the
+//! journal, except for failed unlocks, which happen without the password.
+//! those are kept next to it in the clear until the journal is unlocked again
+//! (see [`record_failed_unlock()`]).
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    date::Date,
+    db::{SaveError, StoredJournal},
+    storage::Storage,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// something that happened to the journal
+pub enum AuditAction {
+    /// the journal was unlocked
+    Unlocked,
+    /// someone tried to unlock the journal with an incorrect password
+    FailedUnlock,
+    /// the journal was saved with changes to the entries on these dates (none
+    /// for other changes, like a new password)
+    Modified {
+        /// the dates of the added, changed, and removed entries
+        dates: Vec<Date>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// an entry in the audit log
+pub struct AuditEvent {
+    /// when it happened, in seconds since the unix epoch
+    pub time: i64,
+    /// what happened
+    pub action: AuditAction,
+}
+
+impl AuditEvent {
+    /// `action`, happening now
+    pub fn now(action: AuditAction) -> Self {
+        Self {
+            time: Utc::now().timestamp(),
+            action,
+        }
+    }
+
+    /// when it happened
+    pub fn date_time(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.time, 0).unwrap_or_default()
+    }
+}
+
+/// how many failed unlocks there were since the journal was last unlocked
+pub fn failed_since_unlock(events: &[AuditEvent]) -> usize {
+    events
+        .iter()
+        .rev()
+        .take_while(|event| event.action != AuditAction::Unlocked)
+        .filter(|event| event.action == AuditAction::FailedUnlock)
+        .count()
+}
+
+/// notes a failed unlock in the stored journal, without the password, if it
+/// has an audit log. gives back whether it has one.
+pub fn record_failed_unlock<S: Storage>(storage: &S) -> Result<bool, SaveError> {
+    let mut stored = serde_json::from_reader::<_, StoredJournal>(storage.reader()?)?;
+    if stored.audit.is_none() {
+        return Ok(false);
+    }
+
+    stored.failed_unlocks.push(Utc::now().timestamp());
+    storage.write_with(&mut |writer| Ok(serde_json::to_writer(writer, &stored)?))?;
+    Ok(true)
+}
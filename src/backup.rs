@@ -0,0 +1,166 @@
+//! module for rotating backups of the journal file, taken before every save,
+//! and for restoring them
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{date, db::StoredJournal, storage::write_private};
+
+/// how many backups are kept if [`BackupConfig::keep`] isn't set
+pub const DEFAULT_KEEP: usize = 5;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// the `[backup]` section of a configuration file
+pub struct BackupConfig {
+    /// whether to back up the journal before saving. NOTE: `None` and
+    /// `Some(false)` will be treated the same.
+    pub enabled: Option<bool>,
+    /// the directory to put backups in. can be on a different device than the
+    /// journal. defaults to the journal's directory.
+    pub directory: Option<String>,
+    /// how many backups to keep. the oldest are removed first.
+    pub keep: Option<usize>,
+}
+
+#[derive(Debug, Error)]
+/// how backing up could go wrong
+pub enum BackupError {
+    /// the backup directory could not be created or read
+    #[error("couldn't use backup directory: {0}")]
+    Directory(#[source] std::io::Error),
+    /// the journal could not be copied into the backup directory
+    #[error("couldn't copy journal: {0}")]
+    Copy(#[source] std::io::Error),
+    /// an old backup could not be removed
+    #[error("couldn't remove old backup {path}: {1}", path = .0.display())]
+    Remove(PathBuf, #[source] std::io::Error),
+}
+
+#[derive(Debug, Error)]
+/// how restoring a backup could go wrong
+pub enum RestoreError {
+    /// the backup could not be read
+    #[error("couldn't read backup {path}: {1}", path = .0.display())]
+    Read(PathBuf, #[source] std::io::Error),
+    /// the backup is not a journal
+    #[error("{path} isn't a journal: {1}", path = .0.display())]
+    Invalid(PathBuf, #[source] serde_json::Error),
+    /// the journal could not be backed up before it was replaced
+    #[error(transparent)]
+    Backup(#[from] BackupError),
+    /// the journal could not be replaced
+    #[error("couldn't replace journal: {0}")]
+    Replace(#[source] std::io::Error),
+}
+
+impl BackupConfig {
+    /// whether backups are turned on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    /// the directory backups of the given journal go in
+    pub fn directory_for(&self, journal_path: &str) -> PathBuf {
+        match &self.directory {
+            Some(directory) => PathBuf::from(directory),
+            None => match Path::new(journal_path).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.into(),
+                _ => PathBuf::from("."),
+            },
+        }
+    }
+}
+
+fn file_name(journal_path: &str) -> String {
+    Path::new(journal_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or("jrn.json".into())
+}
+
+/// lists the backups of the given journal, newest first
+pub fn list_backups(
+    journal_path: &str,
+    config: &BackupConfig,
+) -> Result<Vec<PathBuf>, BackupError> {
+    let directory = config.directory_for(journal_path);
+    let prefix = format!("{}.", file_name(journal_path));
+
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = fs::read_dir(&directory)
+        .map_err(BackupError::Directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with(&prefix) && name.ends_with(".bak")
+        })
+        .collect::<Vec<_>>();
+
+    // the timestamp in the name sorts chronologically
+    backups.sort();
+    backups.reverse();
+
+    Ok(backups)
+}
+
+/// copies the journal into the backup directory and removes the oldest
+/// backups past [`BackupConfig::keep`]. returns the path of the new backup, or
+/// `None` if there was no journal to back up yet.
+pub fn backup(
+    journal_path: &str,
+    config: &BackupConfig,
+) -> Result<Option<PathBuf>, BackupError> {
+    if !Path::new(journal_path).exists() {
+        return Ok(None);
+    }
+
+    let directory = config.directory_for(journal_path);
+    fs::create_dir_all(&directory).map_err(BackupError::Directory)?;
+
+    let timestamp = date::now().format("%Y%m%dT%H%M%S%3f");
+    let backup_path =
+        directory.join(format!("{}.{timestamp}.bak", file_name(journal_path)));
+
+    // copied by hand so the backup is private even if the journal isn't
+    let journal = fs::read(journal_path).map_err(BackupError::Copy)?;
+    write_private(&backup_path, &journal).map_err(BackupError::Copy)?;
+
+    let keep = config.keep.unwrap_or(DEFAULT_KEEP);
+    for old in list_backups(journal_path, config)?.into_iter().skip(keep) {
+        fs::remove_file(&old).map_err(|e| BackupError::Remove(old.clone(), e))?;
+    }
+
+    Ok(Some(backup_path))
+}
+
+/// reads the backup at `backup_path`, checking that it's a journal (but not
+/// that it can be decrypted)
+pub fn read_backup(backup_path: &Path) -> Result<String, RestoreError> {
+    let backup = fs::read_to_string(backup_path).map_err(|e| RestoreError::Read(backup_path.into(), e))?;
+    serde_json::from_str::<StoredJournal>(&backup).map_err(|e| RestoreError::Invalid(backup_path.into(), e))?;
+    Ok(backup)
+}
+
+/// replaces the journal with `backup` (as read by [`read_backup()`]) all at
+/// once. the replaced journal is backed up first, even if backups are off, so
+/// restoring can be undone. returns the path of that backup, or `None` if
+/// there was no journal yet.
+pub fn restore(
+    journal_path: &str,
+    backup: &str,
+    config: &BackupConfig,
+) -> Result<Option<PathBuf>, RestoreError> {
+    let replaced = self::backup(journal_path, config)?;
+    write_private(Path::new(journal_path), backup.as_bytes()).map_err(RestoreError::Replace)?;
+    Ok(replaced)
+}
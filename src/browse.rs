@@ -0,0 +1,176 @@
+//! module for browsing the journal: a list of the entries, newest first with
+//! their first line, that narrows down to the ones containing what's typed
+//! (like `jrn search`, as you type) until one is picked (see [`choose()`])
+
+use std::io::stdout;
+
+use jrn::{
+    date::Date,
+    db::State,
+    search::{self, Hit, Query},
+};
+use requestty::{
+    prompt::{
+        backend::{self, Backend, ClearType},
+        events::{self, EventIterator, KeyCode, KeyModifiers},
+        style::{Attributes, Color},
+    },
+    symbols, ErrorKind,
+};
+
+use crate::{i18n::translate, tr};
+
+/// how many entries are shown at once, at most
+const PAGE: usize = 10;
+
+/// lets the user narrow the entries down by typing, and pick one with the
+/// arrow keys and <Enter>
+pub fn choose(state: &State, message: &str) -> requestty::Result<Date> {
+    let stdout = stdout();
+    let mut backend = backend::get_backend(stdout.lock());
+    let mut events = events::get_events();
+
+    backend.enable_raw_mode()?;
+    backend.hide_cursor()?;
+    let chosen = run(state, translate(message), &mut backend, &mut events);
+    // the terminal is put back however it went
+    backend.show_cursor()?;
+    backend.disable_raw_mode()?;
+    chosen
+}
+
+/// the entries containing `filter`, newest first
+fn matching(state: &State, filter: &str) -> Vec<Hit> {
+    let query = Query {
+        text: Some(filter.into()).filter(|filter: &String| !filter.trim().is_empty()),
+        ..Query::default()
+    };
+    let mut hits = search::search(state, &query);
+    hits.reverse();
+    hits
+}
+
+fn run(state: &State, message: &str, backend: &mut impl Backend, events: &mut impl EventIterator) -> requestty::Result<Date> {
+    let height = backend.size()?.height as usize;
+    let page = PAGE.min(height.saturating_sub(2)).max(1);
+    // makes room first, so the terminal doesn't scroll while drawing
+    for _ in 0..=page {
+        backend.write_all(b"\r\n")?;
+    }
+    let top = backend.get_cursor_pos()?.1.saturating_sub(page as u16 + 1);
+
+    let mut filter = String::new();
+    let mut hits = matching(state, &filter);
+    let mut selected = 0;
+    loop {
+        render(backend, top, message, &filter, &hits, selected, page)?;
+
+        let key = events.next_event()?;
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Interrupted);
+            }
+            KeyCode::Null => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Eof);
+            }
+            KeyCode::Esc => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Aborted);
+            }
+            KeyCode::Enter => {
+                let Some(hit) = hits.get(selected) else {
+                    continue;
+                };
+                clear(backend, top)?;
+                finish(backend, message, &hit.date)?;
+                return Ok(hit.date);
+            }
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected = (selected + 1).min(hits.len().saturating_sub(1)),
+            KeyCode::PageUp => selected = selected.saturating_sub(page),
+            KeyCode::PageDown => selected = (selected + page).min(hits.len().saturating_sub(1)),
+            KeyCode::Backspace => {
+                filter.pop();
+                hits = matching(state, &filter);
+                selected = 0;
+            }
+            KeyCode::Char(c) => {
+                filter.push(c);
+                hits = matching(state, &filter);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// clears what was drawn, from the line `top` down
+pub fn clear(backend: &mut impl Backend, top: u16) -> std::io::Result<()> {
+    backend.move_cursor_to(0, top)?;
+    backend.clear(ClearType::FromCursorDown)
+}
+
+/// draws the question, what was typed so far, and a page of the entries
+/// around the selected one
+fn render(
+    backend: &mut impl Backend,
+    top: u16,
+    message: &str,
+    filter: &str,
+    hits: &[Hit],
+    selected: usize,
+    page: usize,
+) -> std::io::Result<()> {
+    let width = backend.size()?.width as usize;
+    clear(backend, top)?;
+
+    backend.set_fg(Color::LightGreen)?;
+    write!(backend, "? ")?;
+    backend.set_fg(Color::Reset)?;
+    backend.set_attributes(Attributes::BOLD)?;
+    write!(backend, "{message} ")?;
+    backend.set_attributes(Attributes::empty())?;
+    write!(backend, "{filter}")?;
+
+    let start = selected.saturating_sub(page - 1);
+    for (i, hit) in hits.iter().enumerate().skip(start).take(page) {
+        let line = format!("{} {}", hit.date.label(), hit.excerpt);
+        let line = line.chars().take(width.saturating_sub(3)).collect::<String>();
+        write!(backend, "\r\n")?;
+        match i == selected {
+            true => {
+                backend.set_fg(Color::Cyan)?;
+                write!(backend, "{} {line}", symbols::current().pointer)?;
+                backend.set_fg(Color::Reset)?;
+            }
+            false => write!(backend, "  {line}")?,
+        }
+    }
+    if hits.is_empty() {
+        backend.set_fg(Color::DarkGrey)?;
+        write!(backend, "\r\n  {}", tr!("no entries contain that"))?;
+        backend.set_fg(Color::Reset)?;
+    }
+
+    backend.flush()
+}
+
+/// leaves the question with the chosen entry, like the other prompts do
+pub fn finish(backend: &mut impl Backend, message: &str, date: &Date) -> std::io::Result<()> {
+    let symbols = symbols::current();
+    backend.set_fg(Color::LightGreen)?;
+    write!(backend, "{} ", symbols.completed)?;
+    backend.set_fg(Color::Reset)?;
+    backend.set_attributes(Attributes::BOLD)?;
+    write!(backend, "{message} ")?;
+    backend.set_attributes(Attributes::empty())?;
+    backend.set_fg(Color::DarkGrey)?;
+    write!(backend, "{} ", symbols.middle_dot)?;
+    backend.set_fg(Color::Cyan)?;
+    write!(backend, "{}", date.label())?;
+    backend.set_fg(Color::Reset)?;
+    write!(backend, "\r\n")?;
+    backend.flush()
+}
@@ -0,0 +1,164 @@
+//! module for picking an entry on a calendar: a month at a time, with the
+//! days that have entries highlighted, moved around with the arrow keys (see
+//! [`choose()`])
+
+use std::io::stdout;
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use jrn::date::Date;
+use requestty::{
+    prompt::{
+        backend::{self, Backend},
+        events::{self, EventIterator, KeyCode, KeyModifiers},
+        style::{Attributes, Color},
+    },
+    ErrorKind,
+};
+
+use crate::{
+    browse::{clear, finish},
+    i18n::translate,
+    tr,
+};
+
+/// a column of 4 characters for every day of the week
+const WIDTH: u16 = 7 * 4;
+/// the question, the month, the names of the days, six weeks, and the keys
+const HEIGHT: u16 = 10;
+
+/// lets the user move around a calendar of `dates` with the arrow keys, and
+/// pick one of the days with entries with <Enter>. the day is returned
+/// without a time, even if the entries on it have one. `None` if the terminal
+/// is too small for the calendar, before anything is drawn.
+pub fn choose(dates: &[Date], message: &str) -> requestty::Result<Option<Date>> {
+    let stdout = stdout();
+    let mut backend = backend::get_backend(stdout.lock());
+    let size = backend.size()?;
+    if size.width < WIDTH || size.height <= HEIGHT {
+        return Ok(None);
+    }
+    let mut events = events::get_events();
+
+    backend.enable_raw_mode()?;
+    backend.hide_cursor()?;
+    let chosen = run(dates, translate(message), &mut backend, &mut events);
+    // the terminal is put back however it went
+    backend.show_cursor()?;
+    backend.disable_raw_mode()?;
+    chosen.map(Some)
+}
+
+fn run(dates: &[Date], message: &str, backend: &mut impl Backend, events: &mut impl EventIterator) -> requestty::Result<Date> {
+    let mut days = dates.iter().map(|date| date.without_time().as_naive()).collect::<Vec<_>>();
+    days.sort_unstable();
+    days.dedup();
+
+    // makes room first, so the terminal doesn't scroll while drawing
+    for _ in 0..HEIGHT {
+        backend.write_all(b"\r\n")?;
+    }
+    let top = backend.get_cursor_pos()?.1.saturating_sub(HEIGHT);
+
+    let today = Date::today().as_naive();
+    let mut selected = match days.binary_search(&today) {
+        Ok(_) => today,
+        Err(_) => days.last().copied().unwrap_or(today),
+    };
+    loop {
+        render(backend, top, message, &days, selected)?;
+
+        let key = events.next_event()?;
+        let moved = match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Interrupted);
+            }
+            KeyCode::Null => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Eof);
+            }
+            KeyCode::Esc => {
+                clear(backend, top)?;
+                return Err(ErrorKind::Aborted);
+            }
+            KeyCode::Enter => {
+                if days.binary_search(&selected).is_err() {
+                    continue;
+                }
+                let date = Date::from(selected);
+                clear(backend, top)?;
+                finish(backend, message, &date)?;
+                return Ok(date);
+            }
+            KeyCode::Left => selected.pred_opt(),
+            KeyCode::Right => selected.succ_opt(),
+            KeyCode::Up => selected.checked_sub_days(Days::new(7)),
+            KeyCode::Down => selected.checked_add_days(Days::new(7)),
+            KeyCode::PageUp => selected.checked_sub_months(Months::new(1)),
+            KeyCode::PageDown => selected.checked_add_months(Months::new(1)),
+            KeyCode::Tab => days.iter().find(|day| **day > selected).copied(),
+            KeyCode::BackTab => days.iter().rev().find(|day| **day < selected).copied(),
+            _ => None,
+        };
+        if let Some(day) = moved {
+            selected = day;
+        }
+    }
+}
+
+/// draws the question and the month of the selected day, with the days that
+/// have entries in color
+fn render(backend: &mut impl Backend, top: u16, message: &str, days: &[NaiveDate], selected: NaiveDate) -> std::io::Result<()> {
+    let width = backend.size()?.width as usize;
+    clear(backend, top)?;
+
+    backend.set_fg(Color::LightGreen)?;
+    write!(backend, "? ")?;
+    backend.set_fg(Color::Reset)?;
+    backend.set_attributes(Attributes::BOLD)?;
+    write!(backend, "{message}")?;
+    backend.set_attributes(Attributes::empty())?;
+
+    let first = selected.with_day(1).unwrap_or(selected);
+    let month = format!("{} {}", Date::from(first).month_name(), first.year());
+    backend.set_attributes(Attributes::BOLD)?;
+    write!(backend, "\r\n{month:^width$}", width = WIDTH as usize)?;
+    backend.set_attributes(Attributes::empty())?;
+
+    // any week will do for the names of its days
+    let monday = first - Days::new(first.weekday().num_days_from_monday().into());
+    write!(backend, "\r\n")?;
+    for day in monday.iter_days().take(7) {
+        let name = Date::from(day).weekday_name().chars().take(3).collect::<String>();
+        write!(backend, "{name:>3} ")?;
+    }
+
+    // always six weeks, so the calendar doesn't change height between months
+    for week in monday.iter_weeks().take(6) {
+        write!(backend, "\r\n")?;
+        for day in week.iter_days().take(7) {
+            if day.month() != first.month() {
+                write!(backend, "    ")?;
+                continue;
+            }
+            let has_entry = days.binary_search(&day).is_ok();
+            if has_entry {
+                backend.set_fg(Color::Cyan)?;
+            }
+            if day == selected {
+                backend.set_attributes(Attributes::REVERSED)?;
+            }
+            write!(backend, "{:>3}", day.day())?;
+            backend.set_attributes(Attributes::empty())?;
+            backend.set_fg(Color::Reset)?;
+            write!(backend, " ")?;
+        }
+    }
+
+    let keys = tr!("arrows move, <PgUp>/<PgDn> change the month, <Tab> jumps to the next entry");
+    backend.set_fg(Color::DarkGrey)?;
+    write!(backend, "\r\n{}", keys.chars().take(width.saturating_sub(1)).collect::<String>())?;
+    backend.set_fg(Color::Reset)?;
+
+    backend.flush()
+}
@@ -1,7 +1,11 @@
 //! module for command line arguments
-use argh::FromArgs;
+use std::sync::OnceLock;
 
-use crate::date::Date;
+use argh::{CommandInfo, DynamicSubCommand, EarlyExit, FromArgs};
+
+use jrn::{config::Config, date::{Age, Date, DateRange, GroupBy, Offset}, db::Field, export::ExportFormat, import::ImportFormat, mood::Mood, stats::StatsOutput};
+
+use crate::plugin::find_plugins;
 
 #[derive(FromArgs, PartialEq, Debug, Clone)]
 /// a journal app
@@ -10,6 +14,11 @@ pub struct Arguments {
     #[argh(switch, short='d')]
     pub dump_default_config: bool,
 
+    /// log how long loading, unlocking, and saving take to stderr (for more
+    /// detail, set `$JRN_LOG`, for example to `debug`)
+    #[argh(switch, short = 'v')]
+    pub verbose: bool,
+
     /// use toml file for config (otherwise looks for config file at `$JRN_CONFIG_PATH`,
     /// `$XDG_CONFIG_DIR/jrn/config.toml`, or `~/.config/jrn/config.toml`)
     #[argh(option, short = 'c')]
@@ -19,9 +28,20 @@ pub struct Arguments {
     #[argh(option, short = 'p')]
     pub password: Option<String>,
 
-    /// read password from file (recomended)
+    /// read password from file (recomended), or from the first line of stdin
+    /// with `-`
     #[argh(option, short = 'P')]
     pub password_file: Option<String>,
+
+    /// read password from an open file descriptor, like a pipe (for scripts,
+    /// so the password isn't in the arguments or a file)
+    #[argh(option)]
+    pub password_fd: Option<u32>,
+
+    /// how many times a typed password can be wrong before giving up
+    /// (default is 3)
+    #[argh(option)]
+    pub max_attempts: Option<u32>,
     
     /// force ui not to loop
     #[argh(switch, short = 'D')]
@@ -40,6 +60,17 @@ pub struct Arguments {
     #[argh(option, short = 'f')]
     pub file_path: Option<String>,
 
+    /// save entries larger than `max_entry_size` even with
+    /// `refuse_large_entries` set, and changes to entries before
+    /// `freeze_before`
+    #[argh(switch)]
+    pub force: bool,
+
+    /// ask questions with plain numbered lists and lines instead of
+    /// interactive menus, for screen readers and braille displays
+    #[argh(switch)]
+    pub accessible: bool,
+
     #[argh(subcommand)]
     /// action taken
     pub subcommand: Option<SubCommand>,
@@ -51,16 +82,119 @@ pub struct Arguments {
 pub enum SubCommand {
     /// Intent to change password as well as the new password (optional)
     ChangePassword(ChangePassword),
-    /// Intent to list entries (no options)
+    /// Intent to list entries as well as the range of dates to list (optional)
     List(List),
-    /// Intent to view entries as well as the index of the entry (optional)
+    /// Intent to find entries as well as the words, tags, and dates to search
+    /// by (all optional)
+    Search(Search),
+    /// Intent to rebuild the search index (no options)
+    Reindex(Reindex),
+    /// Intent to run the daemon that remembers keys, or to make it forget
+    /// them or stop, as well as how long to remember them (optional)
+    Daemon(Daemon),
+    /// Intent to turn the TOTP second factor on or off
+    Totp(TotpCommand),
+    /// The intent to find an entry to view or edit by typing part of it (no
+    /// options)
+    Browse(Browse),
+    /// Intent to view entries as well as the date of the entry or the range of dates to view (both optional)
     View(View),
     /// Intent to edit entries as well as the index of the entry to edit and the new content (both optional)
     Edit(Edit),
     /// The intent to view today's entry (no options)
     ViewToday(ViewToday),
     /// The intent to edit today's entry as well as the new content (optional) 
-    EditToday(EditToday)
+    EditToday(EditToday),
+    /// The intent to write today's entry in the editor right away (no
+    /// options)
+    Open(Open),
+    /// The intent to inspect the configuration file
+    Config(ConfigCommand),
+    /// The intent to add timestamped lines to today's entry, as well as a
+    /// line to add (optional)
+    Log(Log),
+    /// The intent to create a new journal (no options)
+    Init(Init),
+    /// The intent to print facts about the journal (no options)
+    Info(Info),
+    /// The intent to put a backup in place of the journal, as well as which
+    /// backup and whether to check it and ask first (all optional)
+    Restore(Restore),
+    /// The intent to check the journal, as well as whether to check its hash
+    /// chain (optional)
+    Verify(Verify),
+    /// The intent to show the audit log, as well as whether to show only
+    /// failed unlocks (optional)
+    Audit(Audit),
+    /// The intent to remove old entries, as well as how old, where to archive
+    /// them, and whether to ask first (all optional)
+    Prune(Prune),
+    /// The intent to write every year or month into a journal of its own, as
+    /// well as which and where to (both optional)
+    Split(Split),
+    /// The intent to add the entries of other journals, as well as the
+    /// journals and what to do about entries on the same dates
+    Merge(Merge),
+    /// The intent to add the entries of a file in another format, as well as
+    /// the file, its format, how to find the dates, and what to do about
+    /// entries on the same dates
+    Import(Import),
+    /// The intent to write some entries into a journal with a password of its
+    /// own, as well as which entries, where to, and the password (all
+    /// optional)
+    Share(Share),
+    /// The intent to bundle the journal and configuration into an encrypted
+    /// archive, unpack one, or check one
+    Archive(ArchiveCommand),
+    /// The intent to print statistics about the journal as well as the range
+    /// of dates and which statistics (all optional)
+    Stats(Stats),
+    /// The intent to show how much was written on every day of a year, as
+    /// well as the year (optional)
+    Heatmap(Heatmap),
+    /// The intent to list the tags and how many entries have each (no
+    /// options)
+    Tags(Tags),
+    /// The intent to rename or remove a tag in every entry, as well as the
+    /// tag, its new name, and whether to ask first (all optional)
+    Retag(Retag),
+    /// The intent to write entries for missed days one by one, as well as the
+    /// day to start from (optional)
+    Backfill(Backfill),
+    /// The intent to export the journal as well as the format and where to
+    /// (optional)
+    Export(Export),
+    /// The intent to move an entry to another date, as well as the dates and
+    /// what to do if there's already an entry there (all optional)
+    Move(Move),
+    /// The intent to copy an entry to another date, as well as the dates and
+    /// what to do if there's already an entry there (all optional)
+    Copy(CopyEntry),
+    /// The intent to move a range of entries by a number of days, as well as
+    /// the range and whether to ask first
+    Shift(Shift),
+    /// The intent to set the file type of entries as well as which ones
+    /// (optional)
+    FileType(FileType),
+    /// The intent to lock an entry as well as its date (optional)
+    Lock(Lock),
+    /// The intent to unlock an entry as well as its date and whether to ask
+    /// first (both optional)
+    Unlock(Unlock),
+    /// The intent to star an entry as well as its date (optional)
+    Star(Star),
+    /// The intent to unstar an entry as well as its date (optional)
+    Unstar(Unstar),
+    /// The intent to store an entry without encryption as well as its date
+    /// and whether to ask first (both optional)
+    Publish(Publish),
+    /// The intent to encrypt a public entry again as well as its date
+    /// (optional)
+    Unpublish(Unpublish),
+    /// The intent to run an external command (`jrn-<name>` on `$PATH`) as
+    /// well as the arguments to pass to it
+    #[argh(dynamic)]
+    Plugin(Plugin),
 }
 
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
@@ -79,38 +213,223 @@ pub struct ChangePassword {
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
 /// List by date
 #[argh(subcommand, name = "list")]
-pub struct List {}
+pub struct List {
+    #[argh(positional)]
+    /// only list entries in this range (like 2024-01-01..2024-03-31,
+    /// ..today-7, or a period like 2024-W23, 2024-06, or 2024)
+    pub range: Option<DateRange>,
+
+    /// only list starred entries
+    #[argh(switch, short = 's')]
+    pub starred: bool,
+
+    /// list the days without an entry instead (from the first entry, or the
+    /// start of the range, until today)
+    #[argh(switch, short = 'm')]
+    pub missing: bool,
+
+    /// only list from this date on (like today-30), instead of giving a range
+    #[argh(option)]
+    pub since: Option<Date>,
+
+    /// only list until this date, instead of giving a range
+    #[argh(option)]
+    pub until: Option<Date>,
+
+    /// group the list by month or year, under a heading with how many are in
+    /// each
+    #[argh(option)]
+    pub group_by: Option<GroupBy>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// keep the keys of unlocked journals in memory, so `jrn` doesn't ask for the
+/// password (or spend time checking it) every time. runs until stopped.
+#[argh(subcommand, name = "daemon")]
+pub struct Daemon {
+    /// forget keys that weren't used for this many minutes (default is 15)
+    #[argh(option, short = 't')]
+    pub timeout: Option<u64>,
+
+    /// make the running daemon forget every key, but keep running
+    #[argh(switch)]
+    pub forget: bool,
+
+    /// make the running daemon forget every key and stop
+    #[argh(switch)]
+    pub stop: bool,
+
+    #[argh(subcommand)]
+    /// the daemon action (optional)
+    pub subcommand: Option<DaemonSubCommand>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+#[argh(subcommand)]
+/// A daemon action
+pub enum DaemonSubCommand {
+    /// The intent to see whether a daemon is running and which journals it
+    /// has the keys of (no options)
+    Status(DaemonStatus),
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// print whether a daemon is running, and which journals it has the keys of
+#[argh(subcommand, name = "status")]
+pub struct DaemonStatus {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// ask for a code from an authenticator app (TOTP) after the password
+#[argh(subcommand, name = "totp")]
+pub struct TotpCommand {
+    #[argh(subcommand)]
+    /// the TOTP action
+    pub subcommand: TotpSubCommand,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+#[argh(subcommand)]
+/// A TOTP action
+pub enum TotpSubCommand {
+    /// The intent to make a new secret and ask for codes from it (no options)
+    Enable(TotpEnable),
+    /// The intent to stop asking for codes (no options)
+    Disable(TotpDisable),
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// make a new secret for an authenticator app, and ask for a code from it
+/// whenever the journal is unlocked
+#[argh(subcommand, name = "enable")]
+pub struct TotpEnable {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// stop asking for a code when the journal is unlocked
+#[argh(subcommand, name = "disable")]
+pub struct TotpDisable {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// rebuild the encrypted search index from every entry (creating it if
+/// there's none yet)
+#[argh(subcommand, name = "reindex")]
+pub struct Reindex {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// find entries containing some words, with some #tags, or between some
+/// dates (or all of these at once)
+#[argh(subcommand, name = "search")]
+pub struct Search {
+    /// the words to look for, in any order and ignoring case (asked for if
+    /// there's nothing else to search by)
+    #[argh(positional)]
+    pub text: Vec<String>,
+
+    /// only find entries with this #tag (can be given more than once)
+    #[argh(option, short = 't')]
+    pub tag: Vec<String>,
+
+    /// only search from this date on (like 2023-01-01 or today-30)
+    #[argh(option)]
+    pub since: Option<Date>,
+
+    /// only search until this date
+    #[argh(option)]
+    pub until: Option<Date>,
+
+    /// only find entries with this custom field, like weather=rain (or with
+    /// any value, like weather=). can be given more than once.
+    #[argh(option)]
+    pub meta: Vec<Field>,
+}
 
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
 /// Get entry
 #[argh(subcommand, name = "view")]
 pub struct View {
     #[argh(positional)]
-    /// the date of the entry (In YYYY-MM-DD format or today-n format)
-    pub date: Option<Date>
+    /// the dates of the entries (In YYYY-MM-DD format or today format, optionally followed by +n or -n days. With `time_of_day` set, YYYY-MM-DDTHH:MM and now work too)
+    pub dates: Vec<Date>,
+
+    /// view every entry in this range instead (like 2024-01-01..2024-03-31,
+    /// ..today-7, or a period like 2024-W23, 2024-06, or 2024)
+    #[argh(option, short = 'r')]
+    pub range: Option<DateRange>,
+
+    /// print the date, title, tags, mood, word count, and when the entry was
+    /// created and changed above it
+    #[argh(switch)]
+    pub header: bool,
+
+    /// show private sections (between `:::private` and `:::` lines), after
+    /// asking for their passphrase
+    #[argh(switch)]
+    pub show_private: bool,
 }
 
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
 /// Edit entry
 #[argh(subcommand, name = "edit")]
 pub struct Edit {
-    /// the date of the entry (In YYYY-MM-DD format or today-n format)
+    /// the dates of the entries, opened one after another (In YYYY-MM-DD format or today format, optionally followed by +n or -n days. With `time_of_day` set, YYYY-MM-DDTHH:MM and now work too)
     #[argh(positional)]
-    pub date: Option<Date>,
+    pub dates: Vec<Date>,
 
     /// the content to write, in string form
     #[argh(option, short =  'c')]
     pub content: Option<String>,
 
-    /// file path to write
+    /// file path to write, or `-` for stdin
     #[argh(option, short = 'C')]
     pub content_path: Option<String>,
+
+    /// how the day went, from 1 to 5 or awful, bad, okay, good, or great
+    #[argh(option, short = 'm')]
+    pub mood: Option<Mood>,
+
+    /// set a custom field of the entry, like weather=rain (an empty value,
+    /// like weather=, removes it). can be given more than once.
+    #[argh(option)]
+    pub meta: Vec<Field>,
+
+    /// where the entry was written, like "Lisbon" (an empty one removes it)
+    #[argh(option)]
+    pub location: Option<String>,
+
+    /// edit private sections (between `:::private` and `:::` lines) as text,
+    /// after asking for their passphrase
+    #[argh(switch)]
+    pub show_private: bool,
 }
 
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// pick an entry to view or edit from a list that narrows down to the entries
+/// containing what's typed
+#[argh(subcommand, name = "browse")]
+pub struct Browse {}
+
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
 /// view today's entry
 #[argh(subcommand, name="view-today")]
-pub struct ViewToday {}
+pub struct ViewToday {
+    /// print the date, title, tags, mood, word count, and when the entry was
+    /// created and changed above it
+    #[argh(switch)]
+    pub header: bool,
+
+    /// print nothing, and exit with 0 if there's an entry for today or 1 if
+    /// there isn't (without unlocking the journal)
+    #[argh(switch)]
+    pub exists: bool,
+
+    /// print only how many words today's entry has (0 without one)
+    #[argh(switch)]
+    pub words: bool,
+
+    /// show private sections (between `:::private` and `:::` lines), after
+    /// asking for their passphrase
+    #[argh(switch)]
+    pub show_private: bool,
+}
 
 #[derive(FromArgs, PartialEq, Debug, Clone, Default)]
 /// edit today's entry
@@ -120,7 +439,692 @@ pub struct EditToday {
     #[argh(option, short = 'c')]
     pub content: Option<String>,
 
-    /// file path to write
+    /// file path to write, or `-` for stdin
     #[argh(option, short = 'C')]
     pub content_path: Option<String>,
+
+    /// how the day went, from 1 to 5 or awful, bad, okay, good, or great
+    #[argh(option, short = 'm')]
+    pub mood: Option<Mood>,
+
+    /// set a custom field of the entry, like weather=rain (an empty value,
+    /// like weather=, removes it). can be given more than once.
+    #[argh(option)]
+    pub meta: Vec<Field>,
+
+    /// where the entry was written, like "Lisbon" (an empty one removes it)
+    #[argh(option)]
+    pub location: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// open today's entry in the editor right away, then save and exit (never
+/// loops)
+#[argh(subcommand, name = "open")]
+pub struct Open {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// create a new, empty journal at the journal path (`-f`), asking for its
+/// password. other commands refuse to run without one.
+#[argh(subcommand, name = "init")]
+pub struct Init {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// print facts about the journal (like its size and how many entries it has)
+/// without unlocking it
+#[argh(subcommand, name = "info")]
+pub struct Info {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// replace the journal with one of its backups (the replaced journal is kept
+/// as another backup)
+#[argh(subcommand, name = "restore")]
+pub struct Restore {
+    /// which backup to restore, counting from the newest (1). asks if neither
+    /// this nor --file is given
+    #[argh(option, short = 'b')]
+    pub backup: Option<usize>,
+
+    /// restore this file instead of one of the backups
+    #[argh(option, short = 'f')]
+    pub file: Option<String>,
+
+    /// don't check that the backup opens with the password
+    #[argh(switch)]
+    pub no_check: bool,
+
+    /// don't ask before restoring
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// check that every entry can be decrypted
+#[argh(subcommand, name = "verify")]
+pub struct Verify {
+    /// also check that no entries were changed or removed without the
+    /// password since the journal was last saved (needs `hash_chain`)
+    #[argh(switch)]
+    pub chain: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// show when the journal was unlocked, failed to unlock, and was changed
+/// (needs `audit_log`)
+#[argh(subcommand, name = "audit")]
+pub struct Audit {
+    /// only show failed attempts to unlock the journal
+    #[argh(switch)]
+    pub failed: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// remove (or archive) old entries, listing what was removed
+#[argh(subcommand, name = "prune")]
+pub struct Prune {
+    /// remove entries older than this (like 30d, 2w, 6m, or 5y). defaults to
+    /// `older_than` in the `[retention]` section of the config
+    #[argh(option)]
+    pub older_than: Option<Age>,
+
+    /// move the entries into this journal instead of deleting them (it uses
+    /// the same password). defaults to `archive` in `[retention]`
+    #[argh(option, short = 'a')]
+    pub archive: Option<String>,
+
+    /// don't ask before pruning
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+
+    /// only list the entries that would be pruned
+    #[argh(switch, short = 'n')]
+    pub dry_run: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// write the entries of every year (or month) into a journal of its own, with
+/// the same password, like jrn-2024.json. the journal itself isn't changed.
+#[argh(subcommand, name = "split")]
+pub struct Split {
+    /// year (the default) or month
+    #[argh(option)]
+    pub by: Option<GroupBy>,
+
+    /// the directory to write the journals into (default is the journal's)
+    #[argh(option, short = 'o')]
+    pub out_dir: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// add the entries of other journals (like ones made by `jrn split`) to this
+/// one. asks for their password if it isn't the same.
+#[argh(subcommand, name = "merge")]
+pub struct Merge {
+    /// the journals to merge in
+    #[argh(positional)]
+    pub files: Vec<String>,
+
+    /// replace entries on the same dates that are different
+    #[argh(switch, short = 'o')]
+    pub overwrite: bool,
+
+    /// add to the end of entries on the same dates that are different
+    #[argh(switch, short = 'a')]
+    pub append: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// add the entries of a journal kept in another format, like one big text
+/// file with a date heading before every entry
+#[argh(subcommand, name = "import")]
+pub struct Import {
+    /// the file to import
+    #[argh(positional)]
+    pub file: String,
+
+    /// the format of the file: plaintext (the default)
+    #[argh(option)]
+    pub from: Option<ImportFormat>,
+
+    /// a regular expression matching the date headings, with named groups
+    /// year, month, and day (the default matches lines starting with a date
+    /// like 2024-06-01)
+    #[argh(option, short = 'p')]
+    pub date_pattern: Option<String>,
+
+    /// only print what would be imported
+    #[argh(switch, short = 'n')]
+    pub dry_run: bool,
+
+    /// replace entries on the same dates that are different
+    #[argh(switch, short = 'o')]
+    pub overwrite: bool,
+
+    /// add to the end of entries on the same dates that are different
+    #[argh(switch, short = 'a')]
+    pub append: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// write some entries into a new journal with a password of its own, to give
+/// to someone without your password or the rest of the journal
+#[argh(subcommand, name = "share")]
+pub struct Share {
+    /// the dates of the entries to share (in the same formats as `view`).
+    /// asked for if there are none and no range
+    #[argh(positional)]
+    pub dates: Vec<Date>,
+
+    /// share every entry in this range too (like 2024-06-01..2024-06-14, or
+    /// a period like 2024-W23)
+    #[argh(option, short = 'r')]
+    pub range: Option<DateRange>,
+
+    /// the file to write the new journal to (default is "./jrn-share.json")
+    #[argh(option, short = 'o')]
+    pub output: Option<String>,
+
+    #[argh(option, short = 'n')]
+    /// the new journal's password in string form (vulnerable to shell history attacks, not recomended)
+    pub new_password: Option<String>,
+
+    #[argh(option, short = 'N')]
+    /// the new journal's password stored in a file
+    pub new_password_file: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// bundle the journal and the configuration file into one encrypted file
+/// (for backups, or moving to another machine), and unpack it again
+#[argh(subcommand, name = "archive")]
+pub struct ArchiveCommand {
+    #[argh(subcommand)]
+    /// the archive action
+    pub subcommand: ArchiveSubCommand,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+#[argh(subcommand)]
+/// An archive action
+pub enum ArchiveSubCommand {
+    /// The intent to write an archive, as well as where to
+    Create(ArchiveCreate),
+    /// The intent to write the files of an archive back, as well as the
+    /// archive and where to (optional)
+    Extract(ArchiveExtract),
+    /// The intent to check that an archive and the journal in it open, as
+    /// well as the archive
+    Verify(ArchiveVerify),
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// write the journal and the configuration file (if any) into an archive,
+/// encrypted with the journal's password
+#[argh(subcommand, name = "create")]
+pub struct ArchiveCreate {
+    /// the archive to write, like backup.jrnpack (it mustn't exist yet)
+    #[argh(positional)]
+    pub file: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// write the journal and the configuration file of an archive back, after
+/// checking that they open with the password
+#[argh(subcommand, name = "extract")]
+pub struct ArchiveExtract {
+    /// the archive to unpack
+    #[argh(positional)]
+    pub file: String,
+
+    /// the directory to write the files to (default is the current one).
+    /// files that are there already aren't replaced.
+    #[argh(option, short = 'o')]
+    pub out_dir: Option<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// check that an archive, and the journal in it, open with the password
+#[argh(subcommand, name = "verify")]
+pub struct ArchiveVerify {
+    /// the archive to check
+    #[argh(positional)]
+    pub file: String,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// print statistics about the journal (how many entries and words it has)
+#[argh(subcommand, name = "stats")]
+pub struct Stats {
+    #[argh(positional)]
+    /// only count entries in this range (like 2024-01-01..2024-03-31,
+    /// ..today-7, or a period like 2024-W23, 2024-06, or 2024)
+    pub range: Option<DateRange>,
+
+    /// show the average mood of every month instead
+    #[argh(switch)]
+    pub mood: bool,
+
+    /// show how much was written on every day of the week and at every hour
+    /// (for entries with a time) instead
+    #[argh(switch)]
+    pub habits: bool,
+
+    /// show the most used words (leaving out common ones like "the"),
+    /// overall and for every month, instead
+    #[argh(switch)]
+    pub words: bool,
+
+    /// how many words to show with --words (default is 10)
+    #[argh(option)]
+    pub top: Option<usize>,
+
+    /// with --words, print every word and how often it was used as JSON,
+    /// for making a word cloud
+    #[argh(switch)]
+    pub cloud: bool,
+
+    /// how to print the statistics: text (the default), or json or csv for
+    /// charting them in other tools
+    #[argh(option, short = 'o')]
+    pub output: Option<StatsOutput>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// show how much was written on every day of a year, as a grid of weeks
+#[argh(subcommand, name = "heatmap")]
+pub struct Heatmap {
+    /// the year to show (default is this year)
+    #[argh(positional)]
+    pub year: Option<i32>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// list every tag, and how many entries have it
+#[argh(subcommand, name = "tags")]
+pub struct Tags {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// rename a tag, or remove it, in every entry
+#[argh(subcommand, name = "retag")]
+pub struct Retag {
+    /// the tag to rename or remove (asks which, if not given)
+    #[argh(option)]
+    pub from: Option<String>,
+
+    /// the new name of the tag (asks for one if neither this nor --remove is
+    /// given)
+    #[argh(option)]
+    pub to: Option<String>,
+
+    /// remove the tag instead
+    #[argh(switch)]
+    pub remove: bool,
+
+    /// don't ask before changing the entries
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// add lines to today's entry, each starting with the current time. asks for
+/// one line after another until an empty line, unless a line is given.
+#[argh(subcommand, name = "log")]
+pub struct Log {
+    /// the line to add (without one, lines are asked for)
+    #[argh(positional)]
+    pub line: Vec<String>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// write entries for the days without one (see `list --missing`), one by
+/// one, oldest first
+#[argh(subcommand, name = "backfill")]
+pub struct Backfill {
+    /// start from this date (like today-30) instead of the first entry
+    #[argh(option)]
+    pub since: Option<Date>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// export the journal to another format
+#[argh(subcommand, name = "export")]
+pub struct Export {
+    /// the format: web (a static site that decrypts the entries in the
+    /// browser) or mdbook (the source of an mdBook, in plain text)
+    #[argh(option)]
+    pub format: ExportFormat,
+
+    /// only export entries in this range (like 2024-01-01..2024-03-31,
+    /// ..today-7, or a period like 2024-W23, 2024-06, or 2024)
+    #[argh(positional)]
+    pub range: Option<DateRange>,
+
+    /// the directory to export into (default is "./jrn-export")
+    #[argh(option, short = 'o')]
+    pub output: Option<String>,
+
+    /// only export entries from this date on, instead of giving a range
+    #[argh(option)]
+    pub since: Option<Date>,
+
+    /// only export entries until this date, instead of giving a range
+    #[argh(option)]
+    pub until: Option<Date>,
+
+    /// only export entries with this #tag (can be given more than once)
+    #[argh(option, short = 't')]
+    pub tag: Vec<String>,
+
+    /// leave out paragraphs with these tags (like "private" or
+    /// "private,health"), or what a pattern file lists: a #tag or a phrase to
+    /// mask on every line. can be given more than once.
+    #[argh(option)]
+    pub redact: Vec<String>,
+
+    /// include private sections (between `:::private` and `:::` lines),
+    /// after asking for their passphrase, instead of leaving them out
+    #[argh(switch)]
+    pub show_private: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// move an entry to another date (like one written under the wrong date)
+#[argh(subcommand, name = "move")]
+pub struct Move {
+    /// the date of the entry to move, then the date to move it to (in
+    /// the same formats as `view`)
+    #[argh(positional)]
+    pub dates: Vec<Date>,
+
+    /// replace an entry already on the new date instead of asking
+    #[argh(switch, short = 'o')]
+    pub overwrite: bool,
+
+    /// add to the end of an entry already on the new date instead of asking
+    #[argh(switch, short = 'a')]
+    pub append: bool,
+
+    /// merge with an entry already on the new date in $EDITOR instead of
+    /// asking
+    #[argh(switch, short = 'm')]
+    pub merge: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// copy an entry to another date
+#[argh(subcommand, name = "copy")]
+pub struct CopyEntry {
+    /// the date of the entry to copy, then the date to copy it to (in
+    /// the same formats as `view`)
+    #[argh(positional)]
+    pub dates: Vec<Date>,
+
+    /// replace an entry already on the new date instead of asking
+    #[argh(switch, short = 'o')]
+    pub overwrite: bool,
+
+    /// add to the end of an entry already on the new date instead of asking
+    #[argh(switch, short = 'a')]
+    pub append: bool,
+
+    /// merge with an entry already on the new date in $EDITOR instead of
+    /// asking
+    #[argh(switch, short = 'm')]
+    pub merge: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// move every entry in a range of dates by the same number of days (like
+/// entries imported under the wrong timezone). nothing is moved if an entry
+/// would land on one outside the range.
+#[argh(subcommand, name = "shift")]
+pub struct Shift {
+    #[argh(positional)]
+    /// only shift entries in this range (like 2024-01-01..2024-03-31, or a
+    /// period like 2024-W23, 2024-06, or 2024)
+    pub range: Option<DateRange>,
+
+    /// only shift entries from this date on, instead of giving a range
+    #[argh(option)]
+    pub since: Option<Date>,
+
+    /// only shift entries until this date, instead of giving a range
+    #[argh(option)]
+    pub until: Option<Date>,
+
+    /// how far to shift them (like +1d, -1d, or +1w)
+    #[argh(option)]
+    pub by: Offset,
+
+    /// don't ask before shifting
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// set the file type entries were written in (like .txt), so they're edited
+/// and exported as that. new entries get the `file_type` option's, so this is
+/// for entries written before jrn kept track.
+#[argh(subcommand, name = "file-type")]
+pub struct FileType {
+    /// the file type (like .txt, .md, or .org)
+    #[argh(positional)]
+    pub file_type: String,
+
+    #[argh(positional)]
+    /// only entries in this range (like ..2024-12-31, or a period like 2024-W23,
+    /// 2024-06, or 2024)
+    pub range: Option<DateRange>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// lock (finalize) an entry, so it can't be edited, moved, or overwritten
+/// until it's unlocked
+#[argh(subcommand, name = "lock")]
+pub struct Lock {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// unlock a locked entry, so it can be changed again
+#[argh(subcommand, name = "unlock")]
+pub struct Unlock {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+
+    /// don't ask before unlocking
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// star an entry, to find it again easily (see `list --starred`)
+#[argh(subcommand, name = "star")]
+pub struct Star {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// unstar an entry
+#[argh(subcommand, name = "unstar")]
+pub struct Unstar {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// make an entry public: store it without encryption, so it can be shared
+/// (see `list`)
+#[argh(subcommand, name = "publish")]
+pub struct Publish {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+
+    /// don't ask before publishing
+    #[argh(switch, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// make a public entry private, encrypting it again
+#[argh(subcommand, name = "unpublish")]
+pub struct Unpublish {
+    /// the date of the entry (in the same formats as `view`)
+    #[argh(positional)]
+    pub date: Option<Date>,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+/// inspect the configuration file
+#[argh(subcommand, name = "config")]
+pub struct ConfigCommand {
+    #[argh(subcommand)]
+    /// the configuration action
+    pub subcommand: ConfigSubCommand,
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone)]
+#[argh(subcommand)]
+/// A configuration action
+pub enum ConfigSubCommand {
+    /// The intent to lint the configuration file (no options)
+    Check(ConfigCheck),
+    /// The intent to print the configuration, as well as whether to merge it
+    /// with defaults, the environment, and flags (optional)
+    Show(ConfigShow),
+}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// check the configuration file for unknown keys, invalid values, and
+/// conflicting options
+#[argh(subcommand, name = "check")]
+pub struct ConfigCheck {}
+
+#[derive(FromArgs, PartialEq, Debug, Clone, Default)]
+/// print the configuration file
+#[argh(subcommand, name = "show")]
+pub struct ConfigShow {
+    /// print the configuration actually used (defaults, config file,
+    /// environment variables, and flags merged), noting where each value came
+    /// from
+    #[argh(switch, short = 'e')]
+    pub effective: bool,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+/// an external command, `jrn-<name>` on `$PATH` (see [`crate::plugin`])
+pub struct Plugin {
+    /// the name of the command, without `jrn-`
+    pub name: String,
+    /// the arguments to pass to it
+    pub args: Vec<String>,
+}
+
+impl DynamicSubCommand for Plugin {
+    fn commands() -> &'static [&'static CommandInfo] {
+        static COMMANDS: OnceLock<Vec<&'static CommandInfo>> = OnceLock::new();
+        COMMANDS.get_or_init(|| {
+            find_plugins()
+                .into_iter()
+                .map(|name| {
+                    let description = format!("run the external command jrn-{name}");
+                    &*Box::leak(Box::new(CommandInfo {
+                        name: name.leak(),
+                        description: description.leak(),
+                    }))
+                })
+                .collect()
+        })
+    }
+
+    fn try_redact_arg_values(
+        command_name: &[&str],
+        _args: &[&str],
+    ) -> Option<Result<Vec<String>, EarlyExit>> {
+        // nothing is known about a plugin's arguments, so they're all redacted
+        let name = command_name.last()?;
+        Self::commands()
+            .iter()
+            .any(|command| command.name == *name)
+            .then(|| Ok(vec![name.to_string()]))
+    }
+
+    fn try_from_args(command_name: &[&str], args: &[&str]) -> Option<Result<Self, EarlyExit>> {
+        let name = command_name.last()?;
+        Self::commands()
+            .iter()
+            .any(|command| command.name == *name)
+            .then(|| {
+                Ok(Plugin {
+                    name: name.to_string(),
+                    args: args.iter().map(|arg| arg.to_string()).collect(),
+                })
+            })
+    }
+}
+
+impl From<&Arguments> for Config {
+    /// the options set by flags, to override the configuration file with
+    /// (see [`Config::get_config()`])
+    fn from(value: &Arguments) -> Self {
+        let Arguments {
+            password,
+            password_file,
+            max_attempts,
+            dont_loop,
+            do_loop,
+            file_type,
+            file_path,
+            accessible,
+            ..
+        } = value.clone();
+        Self {
+            password,
+            password_file,
+            max_attempts,
+            dont_loop: dont_loop.then_some(true),
+            do_loop: do_loop.then_some(true),
+            file_type,
+            file_path,
+            accessible: accessible.then_some(true),
+            view_header: None,
+            autosave: None,
+            max_entry_size: None,
+            refuse_large_entries: None,
+            freeze_before: None,
+            editor_args: None,
+            edit_summary: None,
+            word_diff: None,
+            config_version: None,
+            timezone: None,
+            locale: None,
+            language: None,
+            day_rollover_hour: None,
+            relative_dates: None,
+            calendars: None,
+            time_of_day: None,
+            hash_chain: None,
+            search_index: None,
+            audit_log: None,
+            pre_save_hook: None,
+            post_save_hook: None,
+            post_save_webhook: None,
+            post_edit_hook: None,
+            location_hook: None,
+            lint: None,
+            lint_hook: None,
+            private_password_file: None,
+            theme: None,
+            backup: None,
+            retention: None,
+            templates: None,
+        }
+    }
 }
@@ -1,13 +1,42 @@
 //! module for file-based configuration
 
-use std::{env, path::Path};
+use std::{env, fmt::Display, path::Path};
 
-use crate::cli::Arguments;
+use crate::{
+    backup::BackupConfig,
+    date::{Calendar, Date},
+    hooks,
+    lint::Check,
+    retention::RetentionConfig,
+    template::TemplateRule,
+    theme::{Theme, ThemeConfig, ThemeError},
+};
+use chrono::Locale;
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+
+/// the current version of the configuration file format
+pub const CONFIG_VERSION: u32 = 1;
+
+/// the default [`Config::max_attempts`]
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// the default [`Config::max_entry_size`], in kilobytes
+pub const DEFAULT_MAX_ENTRY_SIZE: u64 = 1000;
+
+/// keys that have been renamed, as `(config_version of the rename, old key,
+/// new key)`. keys inside sections are written as `section.key`.
+const RENAMED_KEYS: &[(u32, &str, &str)] = &[];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 /// a configuration file
 pub struct Config {
+    /// the version of the configuration file format. files without one are
+    /// treated as version 0 and migrated (see [`Config::migrate()`])
+    pub config_version: Option<u32>,
     /// the password
     pub password: Option<String>,
     /// the file in which to find a password
@@ -15,78 +44,277 @@ pub struct Config {
     /// If [`Config::password`] and this option are both set, an error will be 
     /// thrown
     pub password_file: Option<String>,
+    /// how many times a typed password can be wrong before giving up
+    pub max_attempts: Option<u32>,
     /// whether to force no looping. NOTE: `None` and `Some(false)` will be 
     /// treated the same.
     pub dont_loop: Option<bool>,
     /// whether to force no looping. NOTE: `None` and `Some(false)` will be
     /// treated the same.
     pub do_loop: Option<bool>,
+    /// whether changes made while looping are saved on quitting without
+    /// asking. NOTE: `None` and `Some(true)` will be treated the same.
+    pub autosave: Option<bool>,
     /// the file type for `$EDITOR`
     pub file_type: Option<String>,
+    /// the size (in kilobytes) above which an entry is too large. every
+    /// save and load goes through the whole journal, so a huge entry (like a
+    /// pasted log) slows all of them down.
+    pub max_entry_size: Option<u64>,
+    /// whether to refuse to save an entry larger than `max_entry_size`
+    /// (unless `--force` is given), instead of only warning about it. NOTE:
+    /// `None` and `Some(false)` will be treated the same.
+    pub refuse_large_entries: Option<bool>,
+    /// entries before this date (like `"2023-01-01"`) can't be changed,
+    /// removed, or added unless `--force` is given
+    pub freeze_before: Option<String>,
+    /// extra arguments for `$EDITOR`, for example to turn off swap and backup
+    /// files so the entry isn't left in them (`["-n", "-i", "NONE"]` for vim)
+    pub editor_args: Option<Vec<String>>,
+    /// whether to print how many words and lines an edit in `$EDITOR`
+    /// changed, to catch deleting something by accident
+    pub edit_summary: Option<bool>,
+    /// whether to also print the changed lines with the removed and added
+    /// words marked, after an edit in `$EDITOR`. NOTE: `None` and
+    /// `Some(false)` will be treated the same.
+    pub word_diff: Option<bool>,
+    /// whether viewing an entry prints its date, title, tags, mood, word
+    /// count, and when it was created and changed above it (like `--header`).
+    /// NOTE: `None` and `Some(false)` will be treated the same.
+    pub view_header: Option<bool>,
     /// the path for the data file
     pub file_path: Option<String>,
-}
-
-impl From<Arguments> for Config {
-    fn from(value: Arguments) -> Self {
-        let Arguments {
-            password,
-            password_file,
-            dont_loop,
-            do_loop,
-            file_type,
-            file_path,
-            ..
-        } = value;
-        Self {
-            password,
-            password_file,
-            dont_loop: Some(dont_loop),
-            do_loop: Some(do_loop),
-            file_type,
-            file_path,
-        }
-    }
+    /// whether to ask questions with plain numbered lists and lines instead
+    /// of interactive menus, for screen readers. NOTE: `None` and
+    /// `Some(false)` will be treated the same.
+    pub accessible: Option<bool>,
+    /// the timezone used to work out what "today" is (for example
+    /// `"America/New_York"`). defaults to the machine's timezone.
+    pub timezone: Option<String>,
+    /// the locale used for names of days and months (for example `"en_US"`)
+    pub locale: Option<String>,
+    /// the language for prompts and messages (for example `"es"`). defaults
+    /// to the language from `$LANG`.
+    pub language: Option<String>,
+    /// the hour (0-23) at which a new day starts. with `4`, an entry written at
+    /// 1 a.m. counts as the previous day's.
+    pub day_rollover_hour: Option<u8>,
+    /// whether to describe dates relative to today (like "yesterday" or "last
+    /// March") in lists and views. NOTE: `None` and `Some(false)` will be
+    /// treated the same.
+    pub relative_dates: Option<bool>,
+    /// secondary calendars to show dates in, next to the Gregorian date:
+    /// `"hebrew"`, `"islamic"`, or `"japanese"`
+    pub calendars: Option<Vec<String>>,
+    /// whether dates can have a time of day (`YYYY-MM-DDTHH:MM`, or `now`),
+    /// so there can be more than one entry per day. NOTE: `None` and
+    /// `Some(false)` will be treated the same.
+    pub time_of_day: Option<bool>,
+    /// whether to link entries into a hash chain when saving, so entries
+    /// changed or removed without the password can be noticed (see `jrn
+    /// verify --chain`). NOTE: `None` and `Some(false)` will be treated the
+    /// same, and a journal that has a chain keeps it.
+    pub hash_chain: Option<bool>,
+    /// whether to keep an encrypted index of the words and tags in every
+    /// entry, so `jrn search` only decrypts the entries that might match.
+    /// NOTE: `None` and `Some(false)` will be treated the same, and a journal
+    /// that has an index keeps it (see `jrn reindex`).
+    pub search_index: Option<bool>,
+    /// whether to keep an encrypted log of when the journal was unlocked,
+    /// failed to unlock, and was changed (see `jrn audit`). NOTE: `None` and
+    /// `Some(false)` will be treated the same, and a journal that has a log
+    /// keeps it.
+    pub audit_log: Option<bool>,
+    /// a shell command run before the journal is saved. if it fails, the
+    /// journal isn't saved. see [`run_hook()`](crate::hooks::run_hook) for
+    /// the environment variables it can read.
+    pub pre_save_hook: Option<String>,
+    /// a shell command run after the journal is saved
+    pub post_save_hook: Option<String>,
+    /// an `http://` URL sent the changed dates and entry counts (never the
+    /// entries) after the journal is saved. the journal's path and the dates
+    /// are sent unencrypted, so anyone on the network between can read them.
+    pub post_save_webhook: Option<String>,
+    /// a shell command run after an entry is edited
+    pub post_edit_hook: Option<String>,
+    /// a shell command that prints where you are (like `Lisbon`), run for
+    /// new entries without `--location`. it gets the same environment
+    /// variables as the other hooks.
+    pub location_hook: Option<String>,
+    /// the built-in checks run on an entry after it's edited in `$EDITOR`:
+    /// `"todo"`, `"placeholders"`, or `"empty_headings"` (see
+    /// [`lint()`](crate::lint::lint))
+    pub lint: Option<Vec<String>>,
+    /// a shell command given an entry on stdin after it's edited in
+    /// `$EDITOR`. every line it prints is a problem with the entry.
+    pub lint_hook: Option<String>,
+    /// a file with the passphrase of private sections, instead of asking for
+    /// it (see [`private`](crate::private))
+    pub private_password_file: Option<String>,
+    /// the colors used for output
+    pub theme: Option<ThemeConfig>,
+    /// rotating backups of the journal file
+    pub backup: Option<BackupConfig>,
+    /// when to prune old entries
+    pub retention: Option<RetentionConfig>,
+    /// the templates new entries start from, and which dates each is for.
+    /// the first one for a date is used.
+    pub templates: Option<Vec<TemplateRule>>,
 }
 
 impl Default for Config {
     fn default() -> Self {
+        let config_version = Some(CONFIG_VERSION);
         let password = None;
         let password_file = None;
+        let max_attempts = Some(DEFAULT_MAX_ATTEMPTS);
         let dont_loop = Some(false);
         let do_loop = Some(false);
+        let autosave = Some(true);
+        let max_entry_size = Some(DEFAULT_MAX_ENTRY_SIZE);
+        let refuse_large_entries = Some(false);
+        let freeze_before = None;
         let file_type = Some(".md".into());
+        let editor_args = None;
+        let edit_summary = Some(true);
+        let word_diff = Some(false);
+        let view_header = Some(false);
         let file_path = Some("./jrn.json".into());
+        let accessible = Some(false);
+        let timezone = None;
+        let locale = None;
+        let language = None;
+        let day_rollover_hour = Some(0);
+        let relative_dates = Some(false);
+        let calendars = None;
+        let time_of_day = Some(false);
+        let hash_chain = Some(false);
+        let search_index = Some(false);
+        let audit_log = Some(false);
+        let pre_save_hook = None;
+        let post_save_hook = None;
+        let post_save_webhook = None;
+        let post_edit_hook = None;
+        let location_hook = None;
+        let lint = None;
+        let lint_hook = None;
+        let private_password_file = None;
+        let theme = None;
+        let backup = None;
+        let retention = None;
+        let templates = None;
         Self {
+            config_version,
             password,
             password_file,
+            max_attempts,
             dont_loop,
             do_loop,
+            autosave,
+            max_entry_size,
+            refuse_large_entries,
+            freeze_before,
             file_type,
+            editor_args,
+            edit_summary,
+            word_diff,
+            view_header,
             file_path,
+            accessible,
+            timezone,
+            locale,
+            language,
+            day_rollover_hour,
+            relative_dates,
+            calendars,
+            time_of_day,
+            hash_chain,
+            search_index,
+            audit_log,
+            pre_save_hook,
+            post_save_hook,
+            post_save_webhook,
+            post_edit_hook,
+            location_hook,
+            lint,
+            lint_hook,
+            private_password_file,
+            theme,
+            backup,
+            retention,
+            templates,
         }
     }
 }
 
-impl Config {
-    fn get_journal_path(default_config: &Self) -> String {
-       match env::var("JRN_JOURNAL") {
-            Ok(file) => file,
-            Err(_) => default_config.clone().file_path.expect("always Some(String) if returned by fn Self::get_default_config")
+fn take_key(table: &mut toml::Table, key: &str) -> Option<toml::Value> {
+    match key.split_once('.') {
+        None => table.remove(key),
+        Some((section, key)) => match table.get_mut(section) {
+            Some(toml::Value::Table(section)) => section.remove(key),
+            _ => None,
+        },
+    }
+}
+
+fn insert_key(table: &mut toml::Table, key: &str, value: toml::Value) {
+    match key.split_once('.') {
+        None => {
+            table.insert(key.into(), value);
+        }
+        Some((section, key)) => {
+            let section = table
+                .entry(section)
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+            if let toml::Value::Table(section) = section {
+                section.insert(key.into(), value);
+            }
         }
     }
-    fn get_config_path(args: &Arguments) -> Option<String> {
-        let args = args.clone();
+}
+
+#[derive(Debug, Error)]
+/// how reading, parsing, and validating a configuration file could go wrong
+pub enum ConfigError {
+    /// the file at the given path could not be read
+    #[error("couldn't read config file {0}: {1}")]
+    NotAccessible(String, #[source] std::io::Error),
+    /// the file at the given path is not valid toml, or contains unknown or
+    /// mistyped keys
+    #[error("invalid config file {0}:\n{1}")]
+    ParseError(String, #[source] toml::de::Error),
+    /// the configuration parsed, but contains options that contradict each
+    /// other
+    #[error("{0}")]
+    Conflict(&'static str),
+    /// the option with the given name has a value that isn't allowed
+    #[error("invalid value for `{0}`: {1:?}")]
+    InvalidValue(&'static str, String),
+    /// the `[theme]` section is invalid
+    #[error("invalid theme: {0}")]
+    InvalidTheme(#[from] ThemeError),
+    /// the file at the given path is for a newer version of `jrn`
+    #[error("config file {0} has config_version {1}, but this version of jrn only supports up to {CONFIG_VERSION}")]
+    UnsupportedVersion(String, u32),
+}
 
-        if let Some(config_file) = args.config_file {
-            return Some(config_file);
+impl Config {
+    /// finds the configuration file to use, checking `config_file` (for
+    /// example, from `--config-file`), `$JRN_CONFIG_FILE`,
+    /// `$XDG_CONFIG_DIR/jrn/config.toml`, and `~/.config/jrn/config.toml` in
+    /// that order
+    pub fn get_config_path(config_file: Option<&str>) -> Option<String> {
+        if let Some(config_file) = config_file {
+            debug!(config_file, "using config file from flag");
+            return Some(config_file.into());
         }
 
         if let Ok(config_file) = env::var("JRN_CONFIG_FILE") {
-            println!("Got it!");
             if Path::new(&config_file).exists() {
+                debug!(config_file, "using config file from $JRN_CONFIG_FILE");
                 return Some(config_file);
             }
+            debug!(config_file, "$JRN_CONFIG_FILE doesn't exist, ignoring it");
         }
 
         if let Ok(config_dir) = env::var("XDG_CONFIG_DIR") {
@@ -105,69 +333,402 @@ impl Config {
         None
     }
 
-    fn get_default_config(path: Option<&str>) -> Self {
-        if path.is_none() {
-            return Self::default();
+    /// reads and strictly parses the configuration file at the given path.
+    /// unknown keys and mistyped values are reported rather than ignored, and
+    /// deprecated keys are migrated, with a warning for each one returned
+    /// alongside the configuration.
+    pub fn read_config_file(path: &str) -> Result<(Self, Vec<String>), ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::NotAccessible(path.into(), e))?;
+
+        let mut table: toml::Table = toml::from_str(&contents)
+            .map_err(|e| ConfigError::ParseError(path.into(), e))?;
+
+        let version = match table.get("config_version") {
+            Some(toml::Value::Integer(version)) => u32::try_from(*version)
+                .map_err(|_| ConfigError::InvalidValue("config_version", version.to_string()))?,
+            Some(version) => return Err(ConfigError::InvalidValue("config_version", version.to_string())),
+            None => 0,
+        };
+        if version > CONFIG_VERSION {
+            return Err(ConfigError::UnsupportedVersion(path.into(), version));
         }
 
-        let path_contents = std::fs::read_to_string(path.unwrap());
+        let warnings = Self::migrate(&mut table, version);
+        if warnings.is_empty() {
+            // parse the original text so errors can point at the offending line
+            let config = toml::from_str(&contents)
+                .map_err(|e| ConfigError::ParseError(path.into(), e))?;
+            return Ok((config, warnings));
+        }
+
+        let config = table
+            .try_into()
+            .map_err(|e| ConfigError::ParseError(path.into(), e))?;
+        let warnings = warnings
+            .into_iter()
+            .map(|warning| format!("{path}: {warning}"))
+            .collect();
+
+        Ok((config, warnings))
+    }
+
+    /// renames deprecated keys (see `RENAMED_KEYS`) in a configuration file
+    /// written for the given version. returns a warning for every key that
+    /// was renamed.
+    pub fn migrate(table: &mut toml::Table, version: u32) -> Vec<String> {
+        Self::migrate_keys(table, version, RENAMED_KEYS)
+    }
 
-        if path_contents.is_err() {
-            return Self::default();
+    /// like [`Config::migrate()`], but with the given renamed keys, as
+    /// `(config_version of the rename, old key, new key)`. when both the old
+    /// and the new key are set, the new one is kept.
+    pub fn migrate_keys(table: &mut toml::Table, version: u32, renamed: &[(u32, &str, &str)]) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        for (renamed_in, old_key, new_key) in renamed {
+            if version >= *renamed_in {
+                continue;
+            }
+            let Some(value) = take_key(table, old_key) else {
+                continue;
+            };
+            match take_key(table, new_key) {
+                Some(new_value) => {
+                    warnings.push(format!(
+                        "`{old_key}` has been renamed to `{new_key}`, and both are set. using `{new_key}`"
+                    ));
+                    insert_key(table, new_key, new_value);
+                }
+                None => {
+                    warnings.push(format!(
+                        "`{old_key}` has been renamed to `{new_key}`. please update your config file"
+                    ));
+                    insert_key(table, new_key, value);
+                }
+            }
         }
 
-        let tml = toml::from_str(&path_contents.unwrap());
+        warnings
+    }
 
-        match tml {
-            Err(_) => Self::default(),
-            Ok(tml) => tml,
+    fn get_default_config(path: Option<&str>) -> Result<(Self, Vec<String>), ConfigError> {
+        match path {
+            None => Ok((Self::default(), Vec::new())),
+            Some(path) => Self::read_config_file(path),
         }
     }
-    
-    /// gets configuration to be used by first checking command line arguments,
-    /// then the configuration provided, and then finally the default config.
-    pub fn get_config(args: &Arguments) -> Self {
-        let args = args.clone();
-        let path = Self::get_config_path(&args);
-        let default_config: Config = Self::get_default_config(path.as_deref());
 
-        let password = match args.password {
-            Some(_) => args.password,
-            None => default_config.clone().password,
-        };
+    /// checks that options which contradict each other haven't been set
+    /// together
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.password.is_some() && self.password_file.is_some() {
+            return Err(ConfigError::Conflict("please give only one password"));
+        }
 
-        let password_file = match args.password_file {
-            Some(_) => args.password_file,
-            None => default_config.clone().password_file,
-        };
+        if let (Some(true), Some(true)) = (self.dont_loop, self.do_loop) {
+            return Err(ConfigError::Conflict("can't both loop and not loop"));
+        }
 
-        let do_loop = match args.do_loop {
-            true => Some(args.do_loop),
-            false => default_config.do_loop,
-        };
+        if let Some(hour) = self.day_rollover_hour {
+            if hour > 23 {
+                return Err(ConfigError::InvalidValue(
+                    "day_rollover_hour",
+                    hour.to_string(),
+                ));
+            }
+        }
 
-        let dont_loop = match args.dont_loop {
-            true => Some(args.dont_loop),
-            false => default_config.dont_loop,
-        };
+        if self.max_attempts == Some(0) {
+            return Err(ConfigError::InvalidValue("max_attempts", "0".into()));
+        }
+
+        self.get_timezone()?;
+        self.get_freeze_before()?;
+        self.get_locale()?;
+        self.get_calendars()?;
+        self.get_lint()?;
+        self.get_theme()?;
+
+        if let Some(retention) = &self.retention {
+            if retention.get_older_than().is_err() {
+                return Err(ConfigError::InvalidValue(
+                    "retention.older_than",
+                    retention.older_than.clone().unwrap_or_default(),
+                ));
+            }
+        }
+
+        if let Some(url) = &self.post_save_webhook {
+            if let Err(e) = hooks::check_webhook_url(url) {
+                return Err(ConfigError::InvalidValue("post_save_webhook", e.to_string()));
+            }
+        }
+
+        for rule in self.templates.iter().flatten() {
+            if let Err(e) = rule.check() {
+                return Err(ConfigError::InvalidValue("templates", e.to_string()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// parses [`Config::timezone`]
+    pub fn get_timezone(&self) -> Result<Option<Tz>, ConfigError> {
+        match &self.timezone {
+            None => Ok(None),
+            Some(timezone) => timezone
+                .parse()
+                .map(Some)
+                .map_err(|_| ConfigError::InvalidValue("timezone", timezone.clone())),
+        }
+    }
+
+    /// parses [`Config::freeze_before`]
+    pub fn get_freeze_before(&self) -> Result<Option<Date>, ConfigError> {
+        match &self.freeze_before {
+            None => Ok(None),
+            Some(date) => date
+                .parse()
+                .map(Some)
+                .map_err(|_| ConfigError::InvalidValue("freeze_before", date.clone())),
+        }
+    }
+
+    /// builds the theme described by [`Config::theme`]
+    pub fn get_theme(&self) -> Result<Option<Theme>, ConfigError> {
+        match &self.theme {
+            None => Ok(None),
+            Some(theme) => Theme::from_config(theme)
+                .map(Some)
+                .map_err(ConfigError::InvalidTheme),
+        }
+    }
+
+    /// parses [`Config::calendars`]
+    pub fn get_calendars(&self) -> Result<Vec<Calendar>, ConfigError> {
+        self.calendars
+            .iter()
+            .flatten()
+            .map(|calendar| {
+                calendar
+                    .parse()
+                    .map_err(|_| ConfigError::InvalidValue("calendars", calendar.clone()))
+            })
+            .collect()
+    }
+
+    /// parses [`Config::lint`]
+    pub fn get_lint(&self) -> Result<Vec<Check>, ConfigError> {
+        self.lint
+            .iter()
+            .flatten()
+            .map(|check| check.parse().map_err(|_| ConfigError::InvalidValue("lint", check.clone())))
+            .collect()
+    }
+
+    /// parses [`Config::locale`]
+    pub fn get_locale(&self) -> Result<Option<Locale>, ConfigError> {
+        match &self.locale {
+            None => Ok(None),
+            Some(locale) => Locale::try_from(locale.as_str())
+                .map(Some)
+                .map_err(|_| ConfigError::InvalidValue("locale", locale.clone())),
+        }
+    }
 
-        let file_type = match args.file_type {
-            Some(_) => args.file_type,
-            None => default_config.clone().file_type,
+    /// gets the configuration to be used by first checking `overrides` (for
+    /// example, from command line flags), then the configuration file (see
+    /// [`Config::get_config_path()`]), and then finally the default config.
+    /// also reports where the value of every option came from.
+    pub fn get_config(
+        config_file: Option<&str>,
+        overrides: &Config,
+    ) -> Result<ResolvedConfig, ConfigError> {
+        let overrides = overrides.clone();
+        let path = Self::get_config_path(config_file);
+        let (default_config, warnings) = Self::get_default_config(path.as_deref())?;
+        let file = match &path {
+            Some(path) => Source::File(path.clone()),
+            None => Source::Default,
         };
+        let mut sources = ConfigSources::new();
+        let defaults = Self::default();
 
-        let file_path = match args.file_path {
-            Some(_) => args.file_path,
-            None => Some(Self::get_journal_path(&default_config)),
+        let config_version = layer("config_version", overrides.config_version, default_config.config_version, defaults.config_version, &file, &mut sources);
+        let password = layer("password", overrides.password, default_config.password, defaults.password, &file, &mut sources);
+        let password_file = layer("password_file", overrides.password_file, default_config.password_file, defaults.password_file, &file, &mut sources);
+        let dont_loop = layer("dont_loop", overrides.dont_loop, default_config.dont_loop, defaults.dont_loop, &file, &mut sources);
+        let do_loop = layer("do_loop", overrides.do_loop, default_config.do_loop, defaults.do_loop, &file, &mut sources);
+        let autosave = layer("autosave", overrides.autosave, default_config.autosave, defaults.autosave, &file, &mut sources);
+        let max_entry_size = layer("max_entry_size", overrides.max_entry_size, default_config.max_entry_size, defaults.max_entry_size, &file, &mut sources);
+        let refuse_large_entries = layer("refuse_large_entries", overrides.refuse_large_entries, default_config.refuse_large_entries, defaults.refuse_large_entries, &file, &mut sources);
+        let freeze_before = layer("freeze_before", overrides.freeze_before, default_config.freeze_before, defaults.freeze_before, &file, &mut sources);
+        let file_type = layer("file_type", overrides.file_type, default_config.file_type, defaults.file_type, &file, &mut sources);
+        let editor_args = layer("editor_args", overrides.editor_args, default_config.editor_args, defaults.editor_args, &file, &mut sources);
+        let edit_summary = layer("edit_summary", overrides.edit_summary, default_config.edit_summary, defaults.edit_summary, &file, &mut sources);
+        let word_diff = layer("word_diff", overrides.word_diff, default_config.word_diff, defaults.word_diff, &file, &mut sources);
+        let view_header = layer("view_header", overrides.view_header, default_config.view_header, defaults.view_header, &file, &mut sources);
+
+        let file_path = match (overrides.file_path, env::var("JRN_JOURNAL")) {
+            (Some(file_path), _) => {
+                sources.push(("file_path", Source::Flag("file_path")));
+                Some(file_path)
+            }
+            (None, Ok(file_path)) => {
+                sources.push(("file_path", Source::Env("JRN_JOURNAL")));
+                Some(file_path)
+            }
+            (None, Err(_)) => match default_config.file_path {
+                Some(file_path) => {
+                    sources.push(("file_path", file.clone()));
+                    Some(file_path)
+                }
+                None => {
+                    sources.push(("file_path", Source::Default));
+                    defaults.file_path
+                }
+            },
         };
+        debug!(file_path, "resolved journal path");
 
-        Self {
+        let max_attempts = layer("max_attempts", overrides.max_attempts, default_config.max_attempts, defaults.max_attempts, &file, &mut sources);
+        let accessible = layer("accessible", overrides.accessible, default_config.accessible, defaults.accessible, &file, &mut sources);
+        let timezone = layer("timezone", overrides.timezone, default_config.timezone, defaults.timezone, &file, &mut sources);
+        let locale = layer("locale", overrides.locale, default_config.locale, defaults.locale, &file, &mut sources);
+        let language = layer("language", overrides.language, default_config.language, defaults.language, &file, &mut sources);
+        let day_rollover_hour = layer("day_rollover_hour", overrides.day_rollover_hour, default_config.day_rollover_hour, defaults.day_rollover_hour, &file, &mut sources);
+        let relative_dates = layer("relative_dates", overrides.relative_dates, default_config.relative_dates, defaults.relative_dates, &file, &mut sources);
+        let calendars = layer("calendars", overrides.calendars, default_config.calendars, defaults.calendars, &file, &mut sources);
+        let time_of_day = layer("time_of_day", overrides.time_of_day, default_config.time_of_day, defaults.time_of_day, &file, &mut sources);
+        let hash_chain = layer("hash_chain", overrides.hash_chain, default_config.hash_chain, defaults.hash_chain, &file, &mut sources);
+        let search_index = layer("search_index", overrides.search_index, default_config.search_index, defaults.search_index, &file, &mut sources);
+        let audit_log = layer("audit_log", overrides.audit_log, default_config.audit_log, defaults.audit_log, &file, &mut sources);
+        let pre_save_hook = layer("pre_save_hook", overrides.pre_save_hook, default_config.pre_save_hook, defaults.pre_save_hook, &file, &mut sources);
+        let post_save_hook = layer("post_save_hook", overrides.post_save_hook, default_config.post_save_hook, defaults.post_save_hook, &file, &mut sources);
+        let post_save_webhook = layer("post_save_webhook", overrides.post_save_webhook, default_config.post_save_webhook, defaults.post_save_webhook, &file, &mut sources);
+        let post_edit_hook = layer("post_edit_hook", overrides.post_edit_hook, default_config.post_edit_hook, defaults.post_edit_hook, &file, &mut sources);
+        let location_hook = layer("location_hook", overrides.location_hook, default_config.location_hook, defaults.location_hook, &file, &mut sources);
+        let lint = layer("lint", overrides.lint, default_config.lint, defaults.lint, &file, &mut sources);
+        let lint_hook = layer("lint_hook", overrides.lint_hook, default_config.lint_hook, defaults.lint_hook, &file, &mut sources);
+        let private_password_file = layer("private_password_file", overrides.private_password_file, default_config.private_password_file, defaults.private_password_file, &file, &mut sources);
+        let theme = layer("theme", overrides.theme, default_config.theme, defaults.theme, &file, &mut sources);
+        let backup = layer("backup", overrides.backup, default_config.backup, defaults.backup, &file, &mut sources);
+        let retention = layer("retention", overrides.retention, default_config.retention, defaults.retention, &file, &mut sources);
+        let templates = layer("templates", overrides.templates, default_config.templates, defaults.templates, &file, &mut sources);
+
+        let config = Self {
+            config_version,
             password,
             password_file,
+            max_attempts,
             do_loop,
             dont_loop,
+            autosave,
+            max_entry_size,
+            refuse_large_entries,
+            freeze_before,
             file_type,
+            editor_args,
+            edit_summary,
+            word_diff,
+            view_header,
             file_path,
+            accessible,
+            timezone,
+            locale,
+            language,
+            day_rollover_hour,
+            relative_dates,
+            calendars,
+            time_of_day,
+            hash_chain,
+            search_index,
+            audit_log,
+            pre_save_hook,
+            post_save_hook,
+            post_save_webhook,
+            post_edit_hook,
+            location_hook,
+            lint,
+            lint_hook,
+            private_password_file,
+            theme,
+            backup,
+            retention,
+            templates,
+        };
+
+        Ok(ResolvedConfig { config, sources, warnings })
+    }
+}
+
+/// picks the overriding value of an option if there is one, the value from
+/// the file if it sets one, and the default otherwise, and records which was
+/// used in `sources`
+fn layer<T>(
+    key: &'static str,
+    overriding: Option<T>,
+    from_file: Option<T>,
+    default: Option<T>,
+    file: &Source,
+    sources: &mut ConfigSources,
+) -> Option<T> {
+    match (overriding, from_file) {
+        (Some(value), _) => {
+            sources.push((key, Source::Flag(key)));
+            Some(value)
+        }
+        (None, Some(value)) => {
+            sources.push((key, file.clone()));
+            Some(value)
+        }
+        (None, None) => {
+            sources.push((key, Source::Default));
+            default
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// a configuration, along with where it came from
+pub struct ResolvedConfig {
+    /// the configuration to use
+    pub config: Config,
+    /// where the value of every option came from, in the order the options
+    /// are declared
+    pub sources: ConfigSources,
+    /// a warning for every deprecated key that was migrated (see
+    /// [`Config::migrate()`])
+    pub warnings: Vec<String>,
+}
+
+/// the name of every option, paired with where its value came from
+pub type ConfigSources = Vec<(&'static str, Source)>;
+
+#[derive(Debug, Clone, PartialEq)]
+/// where the value of an option came from
+pub enum Source {
+    /// the built-in default configuration (used for options that aren't
+    /// set anywhere else)
+    Default,
+    /// the configuration file at the given path
+    File(String),
+    /// the given environment variable
+    Env(&'static str),
+    /// an override of the option with the given name, usually from its
+    /// command line flag
+    Flag(&'static str),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "config file {path}"),
+            Self::Env(var) => write!(f, "${var}"),
+            Self::Flag(key) => write!(f, "--{}", key.replace('_', "-")),
         }
     }
 }
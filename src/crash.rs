@@ -0,0 +1,185 @@
+//! module for what happens when `jrn` panics. the default message (and a
+//! backtrace, with `$RUST_BACKTRACE`) can include decrypted entries, so it's
+//! replaced by one that only says where `jrn` crashed. entries that were
+//! edited but not saved yet are kept as drafts, encrypted like the journal,
+//! in `<journal>.recovery`, and offered back the next time the journal is
+//! unlocked (see [`recover()`]).
+
+use std::{
+    backtrace::Backtrace,
+    collections::BTreeMap,
+    env, fs,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    process,
+    sync::Mutex,
+};
+
+use jrn::{
+    date::Date,
+    db::{EncryptedEntry, State, StoredEntry},
+    encryptor::{Encryptor, Secure},
+    storage,
+};
+
+use crate::tr;
+
+/// where to report bugs
+const ISSUES: &str = "https://github.com/BenRaz123/jrn/issues";
+
+/// the unlocked journal, and the entries edited in it since it was saved
+struct Recovery {
+    /// where to keep the drafts
+    path: PathBuf,
+    /// the key to encrypt the drafts with
+    key: [u8; 32],
+    /// the entries that were edited but not saved yet
+    drafts: BTreeMap<Date, String>,
+}
+
+static RECOVERY: Mutex<Option<Recovery>> = Mutex::new(None);
+
+/// where the drafts for `journal` are kept
+fn recovery_path(journal: &str) -> PathBuf {
+    PathBuf::from(format!("{journal}.recovery"))
+}
+
+/// replaces the default panic message with one that doesn't include any
+/// details (unless `$JRN_BACKTRACE` is set), after saving the drafts
+pub fn install() {
+    panic::set_hook(Box::new(|info: &PanicHookInfo| {
+        // the panic might have happened while the lock was held
+        let saved = RECOVERY
+            .try_lock()
+            .ok()
+            .and_then(|recovery| recovery.as_ref().and_then(write_drafts));
+
+        // not a bug: what `jrn` was printing to went away, like the other
+        // end of `jrn ... | head`, or couldn't take any more
+        if let Some(error) = output_error(info) {
+            if let Some(path) = saved {
+                eprintln!("{}", tr!("the entries you edited were kept in {0}", path.display()));
+            }
+            if error.contains("Broken pipe") {
+                // like the `SIGPIPE` that stops other commands
+                process::exit(141);
+            }
+            eprintln!("{}", tr!("couldn't write the output: {0}", error));
+            process::exit(1);
+        }
+
+        eprintln!("{}", tr!("jrn crashed. this is a bug, please report it at {0}", ISSUES));
+        if let Some(location) = info.location() {
+            eprintln!("{}", tr!("it happened at {0}", location));
+        }
+        match env::var_os("JRN_BACKTRACE") {
+            // the message and backtrace can contain entries, so only print
+            // them when asked to, and don't paste them into a report as is
+            Some(_) => eprintln!("{info}\n{}", Backtrace::force_capture()),
+            None => eprintln!(
+                "{}",
+                tr!("details are left out, since they can contain your entries (set $JRN_BACKTRACE=1 to see them)")
+            ),
+        }
+        if let Some(path) = saved {
+            eprintln!("{}", tr!("the entries you edited were kept in {0}", path.display()));
+        }
+    }));
+}
+
+/// the error a panic of `print!()` or `println!()` failing to write to
+/// stdout or stderr was about, like `Broken pipe (os error 32)`
+fn output_error(info: &PanicHookInfo) -> Option<String> {
+    let message = match info.payload().downcast_ref::<String>() {
+        Some(message) => message.as_str(),
+        None => *info.payload().downcast_ref::<&str>()?,
+    };
+    ["failed printing to stdout: ", "failed printing to stderr: "]
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .map(String::from)
+}
+
+/// writes the drafts right away, like a crash does, when `jrn` is stopped
+/// before saving them (see [`signals`](crate::signals)). gives back where.
+pub fn keep_drafts() -> Option<PathBuf> {
+    RECOVERY
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(write_drafts)
+}
+
+/// encrypts and writes the drafts, if there are any. gives back where.
+fn write_drafts(recovery: &Recovery) -> Option<PathBuf> {
+    let Recovery { path, key, drafts } = recovery;
+    if drafts.is_empty() {
+        return None;
+    }
+
+    let stored = drafts
+        .iter()
+        .map(|(date, content)| Some(StoredEntry::from(Secure.encrypt_journal_entry(*key, content, date).ok()?)))
+        .collect::<Option<Vec<_>>>()?;
+    let json = serde_json::to_string(&stored).ok()?;
+    storage::write_private(path, json.as_bytes()).ok()?;
+    Some(path.clone())
+}
+
+/// remembers the unlocked journal, so drafts can be encrypted for it. a new
+/// journal doesn't have a key yet, so its drafts aren't kept.
+pub fn set_journal(journal: &str, state: &State) {
+    let recovery = state.keys.as_ref().map(|keys| Recovery {
+        path: recovery_path(journal),
+        key: keys.key,
+        drafts: BTreeMap::new(),
+    });
+    *RECOVERY.lock().unwrap_or_else(|e| e.into_inner()) = recovery;
+}
+
+/// keeps `content` as the draft of the entry for `date`, until the journal is
+/// saved with it (see [`saved()`])
+pub fn keep_draft(date: &Date, content: &str) {
+    if let Some(recovery) = RECOVERY.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        recovery.drafts.insert(*date, content.into());
+    }
+}
+
+/// forgets the drafts that were saved in `state`. drafts from a crash that
+/// weren't restored yet stay in `<journal>.recovery`.
+pub fn saved(journal: &str, state: &State) {
+    let mut recovery = RECOVERY.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(recovery) = recovery.as_mut() else {
+        return;
+    };
+
+    recovery
+        .drafts
+        .retain(|date, content| state.entries.get(date) != Some(content));
+    if write_drafts(recovery).is_none() {
+        discard(journal);
+    }
+}
+
+/// removes the drafts kept for `journal` after a crash, if any
+pub fn discard(journal: &str) {
+    let path = recovery_path(journal);
+    if path.exists() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// the drafts kept for `journal` after a crash, if `state`'s key decrypts
+/// them
+pub fn recover(journal: &str, state: &State) -> Option<Vec<(Date, String)>> {
+    let keys = state.keys.as_ref()?;
+    let json = fs::read_to_string(recovery_path(journal)).ok()?;
+    serde_json::from_str::<Vec<StoredEntry>>(&json)
+        .ok()?
+        .into_iter()
+        .map(|stored| {
+            let encrypted = EncryptedEntry::try_from(stored).ok()?;
+            Secure.decrypt_journal_entry(keys.key, &encrypted).ok()
+        })
+        .collect()
+}
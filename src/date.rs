@@ -3,33 +3,246 @@
 
 use chrono::DateTime;
 use chrono::Local;
+use chrono::Locale;
+use chrono::Months;
 use chrono::NaiveDate;
 use chrono::NaiveDateTime;
-use enum_display::EnumDisplay;
+use chrono::NaiveTime;
+use chrono::Timelike;
+use chrono::TimeDelta;
+use chrono::Utc;
+use chrono::Weekday;
+use chrono_tz::Tz;
+use icu_calendar::{hebrew::Hebrew, islamic::IslamicCivil, japanese::Japanese, Date as IcuDate};
 use chrono::Datelike;
 use serde::Deserialize;
 use serde::Serialize;
-use std::{fmt::Display, str::FromStr};
+use thiserror::Error;
+use std::{fmt::Display, ops::Range, str::FromStr, sync::{Mutex, OnceLock}};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
-/// A date with a year, month, and day value
+static TIMEZONE: OnceLock<Tz> = OnceLock::new();
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+static DAY_ROLLOVER_HOUR: OnceLock<u8> = OnceLock::new();
+static RELATIVE_DATES: OnceLock<bool> = OnceLock::new();
+static CALENDARS: OnceLock<Vec<Calendar>> = OnceLock::new();
+static TIME_OF_DAY: OnceLock<bool> = OnceLock::new();
+/// the names [`weekday_name()`] gives, and the locale they're in. formatting
+/// them is slow enough to show when listing thousands of entries.
+static WEEKDAY_NAMES: Mutex<Option<(Locale, [String; 7])>> = Mutex::new(None);
+
+/// the abbreviated name of `weekday`, in the configured locale (see
+/// [`set_locale()`])
+pub fn weekday_name(weekday: Weekday) -> String {
+    let locale = locale();
+    let mut cached = WEEKDAY_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    let names = match &*cached {
+        Some((cached_locale, names)) if *cached_locale == locale => names,
+        _ => {
+            let names = [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+                Weekday::Sat,
+                Weekday::Sun,
+            ]
+            .map(|weekday| {
+                NaiveDate::from_isoywd_opt(2024, 1, weekday)
+                    .expect("the first week of 2024 has every weekday")
+                    .format_localized("%a", locale)
+                    .to_string()
+            });
+            &cached.insert((locale, names)).1
+        }
+    };
+    names[weekday.num_days_from_monday() as usize].clone()
+}
+
+/// sets the timezone used to work out what "today" is, instead of the
+/// machine's local timezone. can only be set once.
+pub fn set_timezone(timezone: Tz) {
+    let _ = TIMEZONE.set(timezone);
+}
+
+/// sets the locale used for displaying names of days and months. can only be
+/// set once.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// sets the hour at which a new day starts. with an hour of `4`, anything
+/// written before 4 a.m. counts as the previous day. can only be set once.
+pub fn set_day_rollover_hour(hour: u8) {
+    let _ = DAY_ROLLOVER_HOUR.set(hour);
+}
+
+/// sets whether dates shown to the user are described relative to today (see
+/// [`Date::humanize()`]). can only be set once.
+pub fn set_relative_dates(relative_dates: bool) {
+    let _ = RELATIVE_DATES.set(relative_dates);
+}
+
+/// sets the secondary calendars dates are shown in, next to the Gregorian
+/// date (see [`Date::annotation()`]). can only be set once.
+pub fn set_calendars(calendars: Vec<Calendar>) {
+    let _ = CALENDARS.set(calendars);
+}
+
+/// sets whether dates given by the user can have a time of day
+/// (`YYYY-MM-DDTHH:MM` or `now`). until this is set, times are accepted.
+/// dates read from a journal can always have a time. can only be set once.
+pub fn set_time_of_day(time_of_day: bool) {
+    let _ = TIME_OF_DAY.set(time_of_day);
+}
+
+/// the locale set by [`set_locale()`], or `POSIX` if none has been set
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or(Locale::POSIX)
+}
+
+#[cfg(feature = "test-util")]
+thread_local! {
+    /// the time [`now()`] gives on this thread instead of the clock's (see
+    /// [`set_fake_now()`])
+    static FAKE_NOW: std::cell::Cell<Option<NaiveDateTime>> = const { std::cell::Cell::new(None) };
+}
+
+#[cfg(feature = "test-util")]
+/// makes [`now()`] (and so [`Date::today()`] and [`Date::now()`]) give `now`
+/// on this thread, instead of the time on the clock, until it's set back to
+/// `None`. only there with the `test-util` feature.
+pub fn set_fake_now(now: Option<NaiveDateTime>) {
+    FAKE_NOW.set(now);
+}
+
+#[cfg(feature = "test-util")]
+/// the time set with [`set_fake_now()`], or else `$JRN_FAKE_TODAY`: a date
+/// (like `2024-06-01`, taken as noon) or a date and time (like
+/// `2024-06-01T23:30`) in the configured timezone. a value that's neither is
+/// ignored.
+fn fake_now() -> Option<NaiveDateTime> {
+    static FAKE_TODAY: OnceLock<Option<NaiveDateTime>> = OnceLock::new();
+    FAKE_NOW.get().or_else(|| {
+        *FAKE_TODAY.get_or_init(|| {
+            let today = std::env::var("JRN_FAKE_TODAY").ok()?;
+            let today = today.trim();
+            NaiveDateTime::parse_from_str(today, "%Y-%m-%dT%H:%M")
+                .ok()
+                .or_else(|| NaiveDate::parse_from_str(today, "%Y-%m-%d").ok()?.and_hms_opt(12, 0, 0))
+        })
+    })
+}
+
+/// the current date and time in the configured timezone (see
+/// [`set_timezone()`]), falling back to the machine's local timezone. with
+/// the `test-util` feature, it can be faked (see [`set_fake_now()`]).
+pub fn now() -> NaiveDateTime {
+    #[cfg(feature = "test-util")]
+    if let Some(now) = fake_now() {
+        return now;
+    }
+    to_local(Utc::now())
+}
+
+/// `time` in the configured timezone (see [`set_timezone()`]), falling back
+/// to the machine's local timezone
+pub fn to_local(time: DateTime<Utc>) -> NaiveDateTime {
+    match TIMEZONE.get() {
+        Some(timezone) => time.with_timezone(timezone).naive_local(),
+        None => time.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// the current day, taking the day rollover hour (see
+/// [`set_day_rollover_hour()`]) into account
+fn current_day() -> NaiveDate {
+    let hour = DAY_ROLLOVER_HOUR.get().copied().unwrap_or(0);
+    (now() - TimeDelta::hours(hour as i64)).date()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+/// A date with a year, month, and day value, and optionally a time of day
+/// (to the minute). Always a real date on the proleptic Gregorian calendar.
+/// Dates without a time sort before dates on the same day with one.
 pub struct Date {
-    /// the year. can be negative for BC\[E\]
-    pub year: i32,
-    /// the month
-    pub month: u8,
-    /// the day
-    pub day: u8,
+    inner: NaiveDate,
+    time: Option<NaiveTime>,
 }
 
 impl Date {
-    /// returns today's date
+    /// returns today's date (see [`set_day_rollover_hour()`])
     pub fn today() -> Self {
-        let current_date = chrono::Local::now();
-        let year = current_date.year();
-        let month = current_date.month() as u8;
-        let day = current_date.day() as u8;
-        Self { year, month, day }
+        Self::from(current_day())
+    }
+
+    /// makes a date from a year, month, and day. `None` if the date doesn't
+    /// exist.
+    pub fn from_ymd_opt(year: i32, month: u8, day: u8) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32).map(Self::from)
+    }
+
+    /// the year. can be negative for BC\[E\]
+    pub fn year(&self) -> i32 {
+        self.inner.year()
+    }
+
+    /// the month, from 1 to 12
+    pub fn month(&self) -> u8 {
+        self.inner.month() as u8
+    }
+
+    /// the day of the month, from 1 to 31
+    pub fn day(&self) -> u8 {
+        self.inner.day() as u8
+    }
+
+    /// the current date and time, to the minute (see
+    /// [`set_day_rollover_hour()`])
+    pub fn now() -> Self {
+        let time = now().time();
+        Self::today().with_time(time)
+    }
+
+    /// the time of day, if there is one
+    pub fn time(&self) -> Option<NaiveTime> {
+        self.time
+    }
+
+    /// the same date at the given time of day (to the minute)
+    pub fn with_time(&self, time: NaiveTime) -> Self {
+        let time = NaiveTime::from_hms_opt(time.hour(), time.minute(), 0);
+        Self { inner: self.inner, time }
+    }
+
+    /// the same date without a time of day
+    pub fn without_time(&self) -> Self {
+        Self::from(self.inner)
+    }
+
+    /// the underlying [`chrono`] date
+    pub fn as_naive(&self) -> NaiveDate {
+        self.inner
+    }
+
+    /// the next day, at the same time of day. `None` at the end of the
+    /// supported range of dates.
+    pub fn succ(&self) -> Option<Self> {
+        let inner = self.inner.succ_opt()?;
+        Some(Self { inner, time: self.time })
+    }
+
+    /// the previous day, at the same time of day. `None` at the start of the
+    /// supported range of dates.
+    pub fn pred(&self) -> Option<Self> {
+        let inner = self.inner.pred_opt()?;
+        Some(Self { inner, time: self.time })
+    }
+
+    /// the number of days from `other` to this date (negative if `other` is
+    /// later)
+    pub fn diff_days(&self, other: &Self) -> i64 {
+        (self.inner - other.inner).num_days()
     }
 }
 
@@ -45,72 +258,250 @@ impl Serialize for Date {
 impl<'de> Deserialize<'de> for Date {
     fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let s = String::deserialize(d)?;
-        Self::from_str(&s).map_err(|_| serde::de::Error::custom("Could not parse into timestamp"))
+        Self::parse(&s, true).map_err(|_| serde::de::Error::custom("Could not parse into timestamp"))
     }
 }
 
 impl Display for Date {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}-{:02}-{:02}", self.year, self.month, self.day)
+        write!(f, "{}-{:02}-{:02}", self.year(), self.month(), self.day())?;
+        if let Some(time) = self.time {
+            write!(f, "T{}", time.format("%H:%M"))?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, EnumDisplay)]
+#[derive(Debug, Error)]
 /// The errors [`Date::from_str`] can return
 pub enum DateFromStrError {
-    /// dates can be constructed in the form of `today-<n>` where `<n>` is a
-    /// number of days. If the format is incorrect, this error will be returned
-    InvalidTodayMinusFormat,
+    /// dates can be offset by a number of days in the form of `today+<n>`,
+    /// `today-<n>`, `<date>+<n>`, or `<date>-<n>`. If the offset is
+    /// incorrect, this error will be returned
+    #[error("invalid offset (expected +<n> or -<n> days)")]
+    InvalidOffsetFormat,
     /// Does not have two hyphen-minus ('-') characters (YYYY-MM-DD format)
+    #[error("expected a date in YYYY-MM-DD format")]
     InvalidLength,
     /// Quantities are not numeric (YYYY-MM-DD format)
+    #[error("the year, month, and day must be numbers")]
     IsNotNumeric,
     /// The Date is invalid
+    #[error("no such date")]
     InvalidDate,
+    /// The time of day isn't in HH:MM format
+    #[error("expected a time of day in HH:MM format")]
+    InvalidTime,
+    /// A time of day was given, but times of day are turned off (see
+    /// [`set_time_of_day()`])
+    #[error("times of day are turned off (see the `time_of_day` option)")]
+    TimeOfDayDisabled,
+}
+
+/// splits an offset (`+<n>` or `-<n>`) off the end of a date, returning the
+/// rest of the date and the offset in days
+fn split_offset(s: &str) -> Result<(&str, i64), DateFromStrError> {
+    let (base, sign, days) = if let Some((base, days)) = s.rsplit_once('+') {
+        (base, 1, days)
+    } else if let Some((keyword, rest)) = ["today", "now"]
+        .into_iter()
+        .find_map(|keyword| Some((keyword, s.strip_prefix(keyword)?)))
+    {
+        match rest.trim().strip_prefix('-') {
+            Some(days) => (keyword, -1, days),
+            None if rest.trim().is_empty() => return Ok((keyword, 0)),
+            None => return Err(DateFromStrError::InvalidOffsetFormat),
+        }
+    } else if s.matches('-').count() == 3 {
+        let (base, days) = s.rsplit_once('-').expect("contains a hyphen-minus");
+        (base, -1, days)
+    } else {
+        return Ok((s, 0));
+    };
+
+    let days = days
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| DateFromStrError::InvalidOffsetFormat)?;
+
+    Ok((base.trim(), sign * days as i64))
 }
 
 impl FromStr for Date {
     type Err = DateFromStrError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, TIME_OF_DAY.get().copied().unwrap_or(true))
+    }
+}
+
+impl Date {
+    fn parse(s: &str, allow_time: bool) -> Result<Self, DateFromStrError> {
         let s = s.trim().to_lowercase();
 
-        if s.starts_with("today") {
-            let rest = &s[5..].trim();
-            if rest.is_empty() {
-                return Ok(Self::today());
-            }
+        let (base, offset) = split_offset(&s)?;
 
-            if !rest.starts_with("-") {
-                return Err(DateFromStrError::InvalidTodayMinusFormat);
-            }
+        let date = match base {
+            "today" => Self::today(),
+            "now" => Self::now(),
+            _ => match base.split_once('t') {
+                None => Self::from_ymd(base)?,
+                Some((date, time)) => {
+                    let time = NaiveTime::parse_from_str(time, "%H:%M")
+                        .map_err(|_| DateFromStrError::InvalidTime)?;
+                    Self::from_ymd(date)?.with_time(time)
+                }
+            },
+        };
 
-            let minus_days = &rest[1..].trim().parse::<u128>();
+        if date.time.is_some() && !allow_time {
+            return Err(DateFromStrError::TimeOfDayDisabled);
+        }
 
-            if minus_days.is_err() {
-                return Err(DateFromStrError::InvalidTodayMinusFormat);
-            }
-            
-            let delta = chrono::TimeDelta::try_days(minus_days.to_owned().unwrap() as i64);
+        date.add_days(offset).ok_or(DateFromStrError::InvalidDate)
+    }
+}
 
-            if delta.is_none() {
-                return Err(DateFromStrError::InvalidDate);
-            }
+impl Date {
+    /// the day of the week
+    pub fn weekday(&self) -> Weekday {
+        self.inner.weekday()
+    }
 
-            let delta = delta.unwrap();
+    /// the abbreviated name of the day of the week, in the configured locale
+    /// (see [`set_locale()`])
+    pub fn weekday_name(&self) -> String {
+        weekday_name(self.weekday())
+    }
 
-            let chrono_today: DateTime<Local> = Local::now();
-        
-            let chrono_with_delta = chrono_today.checked_sub_signed(delta);
+    /// the name of the month, in the configured locale (see [`set_locale()`])
+    pub fn month_name(&self) -> String {
+        self.inner.format_localized("%B", locale()).to_string()
+    }
 
-            if chrono_with_delta.is_none() {
-                return Err(DateFromStrError::InvalidDate);
+    /// describes the date relative to today, for example `"today"`,
+    /// `"yesterday"`, `"5 days ago"`, `"last March"`, or `"in 2 weeks"`
+    pub fn humanize(&self) -> String {
+        let today = Self::today();
+        let days = today.diff_days(self);
+        let months = (today.year() * 12 + today.month() as i32)
+            - (self.year() * 12 + self.month() as i32);
+
+        let past = days > 0;
+        let (days, months) = (days.abs(), months.abs());
+        let ago = |amount: i64, unit: &str| match past {
+            true => format!("{amount} {unit}s ago"),
+            false => format!("in {amount} {unit}s"),
+        };
+        let last_or_next = match past {
+            true => "last",
+            false => "next",
+        };
+
+        match (days, months) {
+            (0, _) => "today".into(),
+            (1, _) if past => "yesterday".into(),
+            (1, _) => "tomorrow".into(),
+            (2..=6, _) => ago(days, "day"),
+            (7..=13, _) => format!("{last_or_next} week"),
+            (_, 0) | (14..=27, _) => ago(days / 7, "week"),
+            (_, 1..=11) => {
+                let month = self.inner.format_localized("%B", locale());
+                format!("{last_or_next} {month}")
             }
+            (_, 12..=23) => format!("{last_or_next} year"),
+            (_, _) => ago(months as i64 / 12, "year"),
+        }
+    }
 
-            let chrono_with_delta = chrono_with_delta.unwrap();
+    /// what is shown in brackets after the date: the name of the day of the
+    /// week and, if turned on (see [`set_relative_dates()`]), the date
+    /// relative to today. for example `Sun` or `Sun, yesterday`
+    pub fn annotation(&self) -> String {
+        let mut parts = vec![self.weekday_name()];
+        if RELATIVE_DATES.get().copied().unwrap_or(false) {
+            parts.push(self.humanize());
+        }
+        for calendar in CALENDARS.get().into_iter().flatten() {
+            parts.extend(self.in_calendar(*calendar));
+        }
+        parts.join(", ")
+    }
 
-            return Ok(Self { year: chrono_with_delta.year(), month: chrono_with_delta.month() as u8, day: chrono_with_delta.day() as u8 })
+    /// the date in a secondary calendar, for example `14 Tishrei 5787`.
+    /// `None` if the calendar has no sensible way of showing the date (like
+    /// Japanese eras before Meiji).
+    pub fn in_calendar(&self, calendar: Calendar) -> Option<String> {
+        let iso = IcuDate::try_new_iso_date(self.year(), self.month(), self.day()).ok()?;
+        match calendar {
+            Calendar::Hebrew => {
+                let date = iso.to_calendar(Hebrew);
+                let month = match date.month().code.0.as_str() {
+                    "M01" => "Tishrei",
+                    "M02" => "Cheshvan",
+                    "M03" => "Kislev",
+                    "M04" => "Tevet",
+                    "M05" => "Shevat",
+                    "M05L" => "Adar I",
+                    "M06" if date.is_in_leap_year() => "Adar II",
+                    "M06" => "Adar",
+                    "M07" => "Nisan",
+                    "M08" => "Iyar",
+                    "M09" => "Sivan",
+                    "M10" => "Tammuz",
+                    "M11" => "Av",
+                    "M12" => "Elul",
+                    _ => return None,
+                };
+                Some(format!("{} {month} {}", date.day_of_month().0, date.year().number))
+            }
+            Calendar::Islamic => {
+                const MONTHS: [&str; 12] = [
+                    "Muharram",
+                    "Safar",
+                    "Rabi' al-Awwal",
+                    "Rabi' al-Thani",
+                    "Jumada al-Ula",
+                    "Jumada al-Akhirah",
+                    "Rajab",
+                    "Sha'ban",
+                    "Ramadan",
+                    "Shawwal",
+                    "Dhu al-Qa'dah",
+                    "Dhu al-Hijjah",
+                ];
+                let date = iso.to_calendar(IslamicCivil);
+                let month = MONTHS.get(date.month().ordinal as usize - 1)?;
+                Some(format!("{} {month} {} AH", date.day_of_month().0, date.year().number))
+            }
+            Calendar::Japanese => {
+                let date = iso.to_calendar(Japanese::new());
+                let era = match date.year().era.0.as_str() {
+                    "meiji" => "Meiji",
+                    "taisho" => "Taisho",
+                    "showa" => "Showa",
+                    "heisei" => "Heisei",
+                    "reiwa" => "Reiwa",
+                    _ => return None,
+                };
+                Some(format!("{era} {}", date.year().number))
+            }
         }
+    }
+
+    /// the date followed by its [annotation](`Date::annotation()`), for
+    /// example `2024-06-02 (Sun)`
+    pub fn label(&self) -> String {
+        format!("{self} ({})", self.annotation())
+    }
+
+    /// the date `days` days after this one (or before, if `days` is
+    /// negative). `None` if the result is out of range.
+    pub fn add_days(&self, days: i64) -> Option<Self> {
+        let inner = self.inner.checked_add_signed(TimeDelta::try_days(days)?)?;
+        Some(Self { inner, time: self.time })
+    }
 
+    fn from_ymd(s: &str) -> Result<Self, DateFromStrError> {
         let items = s.split('-').collect::<Vec<&str>>();
         if items.len() != 3 {
             return Err(DateFromStrError::InvalidLength);
@@ -122,13 +513,523 @@ impl FromStr for Date {
 
         match (year_result, month_result, day_result) {
             (Ok(year), Ok(month), Ok(day)) => {
-                let chrono_date = NaiveDate::from_ymd_opt(year, month as u32, day as u32);
-                match chrono_date {
-                    None => Err(DateFromStrError::InvalidDate),
-                    Some(_) => Ok(Self {year, month, day})
-                }
+                Self::from_ymd_opt(year, month, day).ok_or(DateFromStrError::InvalidDate)
             },
             _ => Err(DateFromStrError::IsNotNumeric),
         }
     }
 }
+
+/// finds the ISO dates (`YYYY-MM-DD`, optionally followed by `THH:MM`)
+/// mentioned in some text, along with where in the text they are
+pub fn mentioned_dates(text: &str) -> Vec<(Range<usize>, Date)> {
+    const DATE: &[u8] = b"dddd-dd-dd";
+    const TIME: &[u8] = b"Tdd:dd";
+
+    fn matches(bytes: &[u8], pattern: &[u8]) -> bool {
+        bytes.len() >= pattern.len()
+            && pattern.iter().zip(bytes).all(|(p, b)| match p {
+                b'd' => b.is_ascii_digit(),
+                _ => p == b,
+            })
+    }
+
+    let bytes = text.as_bytes();
+    let mut mentions = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let starts_word = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+        if !starts_word || !matches(&bytes[i..], DATE) {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i + DATE.len();
+        if matches(&bytes[end..], TIME) {
+            end += TIME.len();
+        }
+        if bytes.get(end).is_some_and(|b| b.is_ascii_alphanumeric()) {
+            i += 1;
+            continue;
+        }
+
+        match Date::parse(&text[i..end], true) {
+            Ok(date) => {
+                mentions.push((i..end, date));
+                i = end;
+            }
+            Err(_) => i += 1,
+        }
+    }
+
+    mentions
+}
+
+impl From<NaiveDate> for Date {
+    fn from(value: NaiveDate) -> Self {
+        Self { inner: value, time: None }
+    }
+}
+
+impl From<Date> for NaiveDate {
+    fn from(value: Date) -> Self {
+        value.inner
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A calendar dates can be shown in next to the Gregorian date. Only used for
+/// display; dates are always stored as Gregorian dates.
+pub enum Calendar {
+    /// the Hebrew calendar
+    Hebrew,
+    /// the tabular (civil) Islamic calendar
+    Islamic,
+    /// the Gregorian calendar with Japanese eras
+    Japanese,
+}
+
+impl FromStr for Calendar {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hebrew" => Ok(Self::Hebrew),
+            "islamic" => Ok(Self::Islamic),
+            "japanese" => Ok(Self::Japanese),
+            _ => Err(s.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A span of dates: an ISO week (`2024-W23`), a month (`2024-06`), or a year
+/// (`2024`)
+pub enum Period {
+    /// an ISO 8601 week, which starts on a Monday
+    Week {
+        /// the ISO week-numbering year (which can differ from the calendar
+        /// year near January 1st)
+        year: i32,
+        /// the week, from 1 to 52 or 53
+        week: u32,
+    },
+    /// a calendar month
+    Month {
+        /// the year
+        year: i32,
+        /// the month, from 1 to 12
+        month: u32,
+    },
+    /// a calendar year
+    Year(i32),
+}
+
+impl Period {
+    /// the first date in the period
+    pub fn first(&self) -> Date {
+        let date = match *self {
+            Self::Week { year, week } => NaiveDate::from_isoywd_opt(year, week, Weekday::Mon),
+            Self::Month { year, month } => NaiveDate::from_ymd_opt(year, month, 1),
+            Self::Year(year) => NaiveDate::from_ymd_opt(year, 1, 1),
+        };
+        Date::from(date.expect("validated when parsed"))
+    }
+
+    /// the last date in the period
+    pub fn last(&self) -> Date {
+        let date = match *self {
+            Self::Week { year, week } => NaiveDate::from_isoywd_opt(year, week, Weekday::Sun),
+            Self::Month { year, month } => match month {
+                12 => NaiveDate::from_ymd_opt(year + 1, 1, 1),
+                _ => NaiveDate::from_ymd_opt(year, month + 1, 1),
+            }
+            .and_then(|date| date.pred_opt()),
+            Self::Year(year) => NaiveDate::from_ymd_opt(year, 12, 31),
+        };
+        Date::from(date.expect("validated when parsed"))
+    }
+
+    /// whether the date is in the period
+    pub fn contains(&self, date: &Date) -> bool {
+        DateRange::from(*self).contains(date)
+    }
+
+    /// the period in words, in the configured locale (see [`set_locale()`]),
+    /// like `June 2024` or `2024`. weeks are like `2024-W23`.
+    pub fn name(&self) -> String {
+        match *self {
+            Self::Month { year, .. } => format!("{} {year}", self.first().month_name()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Week { year, week } => write!(f, "{year}-W{week:02}"),
+            Self::Month { year, month } => write!(f, "{year}-{month:02}"),
+            Self::Year(year) => write!(f, "{year}"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`Period::from_str`] can return
+pub enum PeriodFromStrError {
+    /// Not in YYYY-Www, YYYY-MM, or YYYY format
+    #[error("expected a period in YYYY-Www, YYYY-MM, or YYYY format")]
+    InvalidFormat,
+    /// Quantities are not numeric
+    #[error("the year, week, and month must be numbers")]
+    IsNotNumeric,
+    /// The week or month doesn't exist
+    #[error("no such week or month")]
+    InvalidPeriod,
+}
+
+impl FromStr for Period {
+    type Err = PeriodFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let items = s.split('-').collect::<Vec<&str>>();
+
+        let year = items[0]
+            .parse::<i32>()
+            .map_err(|_| PeriodFromStrError::IsNotNumeric)?;
+
+        let period = match items[1..] {
+            [] => Self::Year(year),
+            [week] if week.starts_with('w') => {
+                let week = week[1..]
+                    .parse::<u32>()
+                    .map_err(|_| PeriodFromStrError::IsNotNumeric)?;
+                Self::Week { year, week }
+            }
+            [month] => {
+                let month = month
+                    .parse::<u32>()
+                    .map_err(|_| PeriodFromStrError::IsNotNumeric)?;
+                Self::Month { year, month }
+            }
+            _ => return Err(PeriodFromStrError::InvalidFormat),
+        };
+
+        let valid = match period {
+            Self::Week { year, week } => NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).is_some(),
+            Self::Month { year, month } => NaiveDate::from_ymd_opt(year, month, 1).is_some(),
+            Self::Year(year) => NaiveDate::from_ymd_opt(year, 1, 1).is_some(),
+        };
+
+        match valid {
+            true => Ok(period),
+            false => Err(PeriodFromStrError::InvalidPeriod),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How dates are grouped when listing them: by month or by year
+pub enum GroupBy {
+    /// a group for every calendar month
+    Month,
+    /// a group for every calendar year
+    Year,
+}
+
+impl GroupBy {
+    /// the group the date is in
+    pub fn period_of(&self, date: &Date) -> Period {
+        match self {
+            Self::Month => Period::Month {
+                year: date.year(),
+                month: date.month().into(),
+            },
+            Self::Year => Period::Year(date.year()),
+        }
+    }
+}
+
+impl Display for GroupBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Month => write!(f, "month"),
+            Self::Year => write!(f, "year"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`GroupBy::from_str`] can return
+pub enum GroupByFromStrError {
+    /// Neither `month` nor `year`
+    #[error("expected month or year")]
+    Unknown,
+}
+
+impl FromStr for GroupBy {
+    type Err = GroupByFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "month" => Ok(Self::Month),
+            "year" => Ok(Self::Year),
+            _ => Err(GroupByFromStrError::Unknown),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// An inclusive range of dates, either end of which can be left open. Parsed
+/// from `<a>..<b>`, `..<b>`, `<a>..`, a [`Period`], or a single [`Date`]. Either
+/// end of `<a>..<b>` can also be a period, in which case the range starts at
+/// the start of `<a>` and ends at the end of `<b>`.
+pub struct DateRange {
+    /// the first date in the range, or `None` if it is open at the start
+    pub start: Option<Date>,
+    /// the last date in the range, or `None` if it is open at the end
+    pub end: Option<Date>,
+}
+
+impl DateRange {
+    /// the range containing every date
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// whether the date is in the range. an end without a time of day
+    /// includes every time on that day.
+    pub fn contains(&self, date: &Date) -> bool {
+        let after_start = self.start.as_ref().is_none_or(|start| start <= date);
+        let before_end = self.end.as_ref().is_none_or(|end| match end.time {
+            None => date.inner <= end.inner,
+            Some(_) => date <= end,
+        });
+        after_start && before_end
+    }
+
+    /// iterates over every date in the range, in order. `None` if the range
+    /// is open at either end.
+    pub fn dates(&self) -> Option<Dates> {
+        let next = self.start?;
+        let end = self.end?;
+        Some(Dates { next: Some(next), end })
+    }
+}
+
+impl From<Period> for DateRange {
+    fn from(value: Period) -> Self {
+        Self {
+            start: Some(value.first()),
+            end: Some(value.last()),
+        }
+    }
+}
+
+impl From<Date> for DateRange {
+    fn from(value: Date) -> Self {
+        Self {
+            start: Some(value),
+            end: Some(value),
+        }
+    }
+}
+
+impl Display for DateRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(start) = &self.start {
+            write!(f, "{start}")?;
+        }
+        write!(f, "..")?;
+        if let Some(end) = &self.end {
+            write!(f, "{end}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the dates in a [`DateRange`] (see [`DateRange::dates()`])
+pub struct Dates {
+    next: Option<Date>,
+    end: Date,
+}
+
+impl Iterator for Dates {
+    type Item = Date;
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        if current > self.end {
+            return None;
+        }
+        self.next = current.succ();
+        Some(current)
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`DateRange::from_str`] can return
+pub enum DateRangeFromStrError {
+    /// One end of the range is neither a date nor a period
+    #[error("{0:?} is neither a date nor a period")]
+    InvalidEnd(String),
+    /// The range ends before it starts
+    #[error("the range ends before it starts")]
+    EndsBeforeStart,
+}
+
+/// parses one end of a range, as a date or (using `pick` to choose which end
+/// of it) a period
+fn parse_range_end(
+    s: &str,
+    pick: fn(&Period) -> Date,
+) -> Result<Option<Date>, DateRangeFromStrError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(date) = Date::from_str(s) {
+        return Ok(Some(date));
+    }
+    match Period::from_str(s) {
+        Ok(period) => Ok(Some(pick(&period))),
+        Err(_) => Err(DateRangeFromStrError::InvalidEnd(s.into())),
+    }
+}
+
+impl FromStr for DateRange {
+    type Err = DateRangeFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once("..").unwrap_or((s, s));
+
+        let range = Self {
+            start: parse_range_end(start, Period::first)?,
+            end: parse_range_end(end, Period::last)?,
+        };
+
+        if let (Some(start), Some(end)) = (&range.start, &range.end) {
+            if start > end {
+                return Err(DateRangeFromStrError::EndsBeforeStart);
+            }
+        }
+
+        Ok(range)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How long ago something was, in days (`30d`), weeks (`2w`), months (`6m`),
+/// or years (`5y`)
+pub enum Age {
+    /// a number of days
+    Days(u32),
+    /// a number of weeks
+    Weeks(u32),
+    /// a number of calendar months
+    Months(u32),
+    /// a number of calendar years
+    Years(u32),
+}
+
+impl Age {
+    /// the day this long before `date`. months and years past the end of a
+    /// shorter month land on its last day.
+    pub fn before(&self, date: &Date) -> Option<Date> {
+        let inner = match *self {
+            Self::Days(days) => date.inner.checked_sub_days(chrono::Days::new(days.into())),
+            Self::Weeks(weeks) => date.inner.checked_sub_days(chrono::Days::new(weeks as u64 * 7)),
+            Self::Months(months) => date.inner.checked_sub_months(Months::new(months)),
+            Self::Years(years) => date.inner.checked_sub_months(Months::new(years.checked_mul(12)?)),
+        };
+        inner.map(Date::from)
+    }
+}
+
+impl Display for Age {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Days(days) => write!(f, "{days}d"),
+            Self::Weeks(weeks) => write!(f, "{weeks}w"),
+            Self::Months(months) => write!(f, "{months}m"),
+            Self::Years(years) => write!(f, "{years}y"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`Age::from_str`] can return
+pub enum AgeFromStrError {
+    /// Not a number followed by `d`, `w`, `m`, or `y`
+    #[error("expected a number followed by d, w, m, or y (like 30d or 5y)")]
+    InvalidFormat,
+}
+
+impl FromStr for Age {
+    type Err = AgeFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let Some(unit) = s.chars().last() else {
+            return Err(AgeFromStrError::InvalidFormat);
+        };
+        let amount = s[..s.len() - unit.len_utf8()]
+            .parse::<u32>()
+            .map_err(|_| AgeFromStrError::InvalidFormat)?;
+
+        match unit {
+            'd' => Ok(Self::Days(amount)),
+            'w' => Ok(Self::Weeks(amount)),
+            'm' => Ok(Self::Months(amount)),
+            'y' => Ok(Self::Years(amount)),
+            _ => Err(AgeFromStrError::InvalidFormat),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// A number of days to move dates by, forwards (`+1d`) or backwards (`-2w`),
+/// in days (`d`) or weeks (`w`). A number on its own is in days.
+pub struct Offset {
+    /// the number of days, negative if backwards
+    pub days: i64,
+}
+
+impl Offset {
+    /// `date` moved by this offset. `None` if the result is out of range.
+    pub fn apply(&self, date: &Date) -> Option<Date> {
+        date.add_days(self.days)
+    }
+}
+
+impl Display for Offset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+}d", self.days)
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`Offset::from_str`] can return
+pub enum OffsetFromStrError {
+    /// Not a number, optionally signed and followed by `d` or `w`
+    #[error("expected a number of days or weeks (like +1d, -2d, or +1w)")]
+    InvalidFormat,
+}
+
+impl FromStr for Offset {
+    type Err = OffsetFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (sign, s) = match s.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, s.strip_prefix('+').unwrap_or(&s)),
+        };
+        let (amount, per_unit) = match s.strip_suffix('w') {
+            Some(amount) => (amount, 7),
+            None => (s.strip_suffix('d').unwrap_or(s), 1),
+        };
+        let amount = amount
+            .parse::<u32>()
+            .map_err(|_| OffsetFromStrError::InvalidFormat)?;
+
+        Ok(Self {
+            days: sign * per_unit * amount as i64,
+        })
+    }
+}
@@ -1,18 +1,28 @@
 //! module for interacting with application state, and writing to/reading from a JSON file
 
 use std::{
-    collections::{HashMap, HashSet},
-    fs,
+    collections::{BTreeMap, HashMap, HashSet},
+    str::FromStr,
 };
 
 use base64::prelude::*;
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tracing::{info_span, instrument, Instrument};
 
 use crate::{
-    date::Date,
-    encryptor::{DecryptError, Encryptor},
+    audit::{AuditAction, AuditEvent},
+    date::{Date, DateRange, Offset},
+    encryptor::{encrypt_audit, DecryptError, EncryptError, Encryptor},
+    index::SearchIndex,
+    mood::Mood,
+    search::Query,
+    storage::{AsyncStorage, Storage},
 };
+#[cfg(feature = "fs")]
+use crate::storage::FileStorage;
 
 #[derive(Debug, Clone)]
 /// representation of an encrypted journal. middle stage between a 
@@ -24,17 +34,74 @@ pub struct EncryptedJournal {
     pub kdf_salt: [u8; 32],
     /// the set of encrypted entries
     pub entries: HashSet<EncryptedEntry>,
+    /// the head of the hash chain, if there is one (see [`State::hash_chain`])
+    pub chain: Option<[u8; 32]>,
+    /// the encrypted [search index](State::index), if there is one: a nonce
+    /// followed by the encrypted JSON
+    pub index: Option<Vec<u8>>,
+    /// the encrypted [TOTP secret](State::totp), if there is one: a nonce
+    /// followed by the encrypted secret
+    pub totp: Option<Vec<u8>>,
+    /// the encrypted [audit log](State::audit), if there is one: a nonce
+    /// followed by the encrypted JSON
+    pub audit: Option<Vec<u8>>,
+    /// when someone failed to unlock the journal since it was last unlocked,
+    /// in seconds since the unix epoch (see
+    /// [`record_failed_unlock()`](crate::audit::record_failed_unlock))
+    pub failed_unlocks: Vec<i64>,
+}
+
+/// the version of the journal format written by this version of `jrn`.
+/// version 2 stores digests shared by several entries once (see
+/// [`StoredJournal::blobs`]).
+pub const FORMAT_VERSION: u32 = 2;
+
+/// how many bytes of a digest's SHA-256 hash make up its name in
+/// [`StoredJournal::blobs`]
+const BLOB_ID_LEN: usize = 12;
+
+fn first_format_version() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// An encrypted journal, use for storing in a secure manner
 pub struct StoredJournal {
+    /// the version of the journal format (see [`FORMAT_VERSION`]). journals
+    /// written before it was recorded are version 1.
+    #[serde(default = "first_format_version")]
+    pub version: u32,
     /// Hash of the password
     pub password_hash: String,
     /// Salt for kdf (key is reused)
     pub kdf_salt: String,
     /// Set of [entries](`StoredEntry`)
     pub entries: HashSet<StoredEntry>,
+    /// the head of the hash chain in Base64, if there is one (see
+    /// [`State::hash_chain`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// the encrypted search index in Base64, if there is one (see
+    /// [`State::index`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// the encrypted TOTP secret in Base64, if there is one (see
+    /// [`State::totp`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<String>,
+    /// the encrypted audit log in Base64, if there is one (see
+    /// [`State::audit`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<String>,
+    /// failed unlocks that aren't in the audit log yet (see
+    /// [`EncryptedJournal::failed_unlocks`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_unlocks: Vec<i64>,
+    /// digests that more than one entry has (entries with the same content,
+    /// like template stubs), stored once in Base64 by the name the entries
+    /// refer to them with (see [`StoredEntry::blob`])
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub blobs: BTreeMap<String, String>,
 }
 
 #[derive(Hash, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -46,6 +113,16 @@ pub struct EncryptedEntry {
     pub nonce: [u8; 12],
     /// Encrypted digest of journal entry
     pub digest: Vec<u8>,
+    /// this entry's link in the hash chain, if there is one (see
+    /// [`State::hash_chain`])
+    pub chain: Option<[u8; 32]>,
+    /// the entry's encrypted [metadata](EntryMeta), if it has any: a nonce
+    /// followed by the encrypted JSON
+    pub meta: Option<Vec<u8>>,
+    /// whether the entry is stored without encryption (see
+    /// [`EntryMeta::public`]). the digest is then the entry's text, and the
+    /// nonce is all zeros.
+    pub public: bool,
 }
 
 #[derive(Hash, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -54,20 +131,126 @@ pub struct EncryptedEntry {
 pub struct StoredEntry {
     /// the date, represented as a timestamp
     pub date: Date,
-    /// the nonce for the encryption function, stored in Base64.
+    /// the nonce for the encryption function, stored in Base64. empty for
+    /// public entries.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub nonce: String,
-    /// the encrypted digest, stored in Base64.
+    /// the encrypted digest, stored in Base64. empty if it's in
+    /// [`StoredJournal::blobs`].
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub digest: String,
+    /// the name of the digest in [`StoredJournal::blobs`], if it's stored
+    /// there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+    /// the link in the hash chain, stored in Base64.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<String>,
+    /// the encrypted metadata, stored in Base64.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<String>,
+    /// the entry's text, if it's public (see [`EntryMeta::public`]), instead
+    /// of a nonce and a digest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// what's known about an entry besides its content. stored encrypted, next to
+/// the entry.
+pub struct EntryMeta {
+    /// whether the entry is finalized, so it shouldn't be changed until it's
+    /// unlocked
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// whether the entry is starred, to find it again easily
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub starred: bool,
+    /// how the day went
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mood: Option<Mood>,
+    /// where the entry was written, like `Lisbon`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// whether the entry is stored without encryption, so it can be shared.
+    /// its metadata is still encrypted.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub public: bool,
+    /// the file type the entry was written in (like `.md` or `.org`), so it's
+    /// edited and exported as one even if the default changed since
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_type: Option<String>,
+    /// when the entry was first saved, in seconds since the unix epoch.
+    /// unknown for entries saved before it was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<i64>,
+    /// when the entry was last saved with a change to its content, in seconds
+    /// since the unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<i64>,
+    /// custom fields (like `weather` → `rain`), for tracking things without
+    /// writing them into the entry a certain way. keys are in lowercase.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
 }
 
-#[derive(Debug)]
+impl EntryMeta {
+    /// whether there's nothing to store
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// a custom field of an entry (see [`EntryMeta::fields`]), parsed from
+/// `key=value`
+pub struct Field {
+    /// the name of the field, in lowercase
+    pub key: String,
+    /// the value, which can be empty
+    pub value: String,
+}
+
+#[derive(Debug, Error)]
+/// The errors [`Field::from_str`] can return
+pub enum FieldFromStrError {
+    /// There's no `=`
+    #[error("expected a field like key=value")]
+    MissingEquals,
+    /// There's nothing before the `=`
+    #[error("the key of a field can't be empty")]
+    EmptyKey,
+}
+
+impl FromStr for Field {
+    type Err = FieldFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s.split_once('=').ok_or(FieldFromStrError::MissingEquals)?;
+        let key = key.trim().to_lowercase();
+        if key.is_empty() {
+            return Err(FieldFromStrError::EmptyKey);
+        }
+        Ok(Self {
+            key,
+            value: value.trim().into(),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
 /// How getting a `[u8; N]`/`Vec<u8>` could go wrong
 pub enum FromBase64Error {
     /// the base64 string contains characters other than [the standard Base64
     /// alphabet](https://docs.rs/base64/latest/base64/alphabet/constant.STANDARD.html)
+    #[error("invalid base64")]
     NotValidBase64,
     /// the decoded Base64 is too long or too short
+    #[error("decoded base64 has the wrong length")]
     InvalidLength,
+    /// an entry's digest is supposed to be in [`StoredJournal::blobs`], but
+    /// isn't
+    #[error("an entry's digest is missing from the journal")]
+    MissingBlob,
 }
 
 impl TryFrom<StoredJournal> for EncryptedJournal {
@@ -76,13 +259,25 @@ impl TryFrom<StoredJournal> for EncryptedJournal {
         let password_hash = value.password_hash;
         let kdf_salt = try_b64_to_arr(&value.kdf_salt)?;
         let mut entries = HashSet::new();
-        for entry in value.entries {
+        for mut entry in value.entries {
+            if let Some(blob) = &entry.blob {
+                entry.digest = value.blobs.get(blob).ok_or(FromBase64Error::MissingBlob)?.clone();
+            }
             entries.insert(entry.try_into()?);
         }
+        let chain = value.chain.as_deref().map(try_b64_to_arr).transpose()?;
+        let index = value.index.as_deref().map(try_b64_to_vec).transpose()?;
+        let totp = value.totp.as_deref().map(try_b64_to_vec).transpose()?;
+        let audit = value.audit.as_deref().map(try_b64_to_vec).transpose()?;
         Ok(Self {
             password_hash,
             kdf_salt,
             entries,
+            chain,
+            index,
+            totp,
+            audit,
+            failed_unlocks: value.failed_unlocks,
         })
     }
 }
@@ -91,29 +286,75 @@ impl From<EncryptedJournal> for StoredJournal {
     fn from(value: EncryptedJournal) -> Self {
         let password_hash = value.password_hash;
         let kdf_salt = BASE64_STANDARD.encode(value.kdf_salt);
-        let entries = value
+        let mut entries = value
             .entries
-            .iter()
-            .map(|entry| StoredEntry::from(entry.clone()))
-            .collect();
+            .into_iter()
+            .map(StoredEntry::from)
+            .collect::<Vec<_>>();
+        let blobs = share_digests(&mut entries);
+        let chain = value.chain.map(|chain| BASE64_STANDARD.encode(chain));
+        let index = value.index.map(|index| BASE64_STANDARD.encode(index));
+        let totp = value.totp.map(|totp| BASE64_STANDARD.encode(totp));
+        let audit = value.audit.map(|audit| BASE64_STANDARD.encode(audit));
         Self {
+            version: FORMAT_VERSION,
             password_hash,
             kdf_salt,
-            entries,
+            entries: entries.into_iter().collect(),
+            chain,
+            index,
+            totp,
+            audit,
+            failed_unlocks: value.failed_unlocks,
+            blobs,
         }
     }
 }
 
+/// the name of `digest` (in Base64) in [`StoredJournal::blobs`]
+fn blob_id(digest: &str) -> String {
+    BASE64_STANDARD.encode(&Sha256::digest(digest.as_bytes())[..BLOB_ID_LEN])
+}
+
+/// moves the digests more than one entry has into a table of their own,
+/// leaving their names in the entries, and gives back the table. digests
+/// shorter than their name are left alone.
+fn share_digests(entries: &mut [StoredEntry]) -> BTreeMap<String, String> {
+    let mut counts = HashMap::<String, usize>::new();
+    for entry in entries.iter() {
+        *counts.entry(entry.digest.clone()).or_default() += 1;
+    }
+
+    let mut blobs = BTreeMap::new();
+    for entry in entries.iter_mut() {
+        let id = blob_id(&entry.digest);
+        if counts[&entry.digest] < 2 || entry.digest.len() <= id.len() {
+            continue;
+        }
+        blobs.insert(id.clone(), std::mem::take(&mut entry.digest));
+        entry.blob = Some(id);
+    }
+    blobs
+}
+
 impl TryFrom<StoredEntry> for EncryptedEntry {
     type Error = FromBase64Error;
     fn try_from(value: StoredEntry) -> Result<Self, Self::Error> {
         let date = value.date;
-        let nonce = try_b64_to_arr(&value.nonce)?;
-        let digest = try_b64_to_vec(&value.digest)?;
+        let public = value.text.is_some();
+        let (nonce, digest) = match value.text {
+            Some(text) => ([0; 12], text.into_bytes()),
+            None => (try_b64_to_arr(&value.nonce)?, try_b64_to_vec(&value.digest)?),
+        };
+        let chain = value.chain.as_deref().map(try_b64_to_arr).transpose()?;
+        let meta = value.meta.as_deref().map(try_b64_to_vec).transpose()?;
         Ok(Self {
             date,
             nonce,
             digest,
+            chain,
+            meta,
+            public,
         })
     }
 }
@@ -121,26 +362,32 @@ impl TryFrom<StoredEntry> for EncryptedEntry {
 impl From<EncryptedEntry> for StoredEntry {
     fn from(value: EncryptedEntry) -> Self {
         let date = value.date;
-        let nonce = BASE64_STANDARD.encode(value.nonce);
-        let digest = BASE64_STANDARD.encode(value.digest);
+        let chain = value.chain.map(|chain| BASE64_STANDARD.encode(chain));
+        let meta = value.meta.map(|meta| BASE64_STANDARD.encode(meta));
+        // a public entry's digest is its text, which came from a `String`
+        let (nonce, digest, text) = match value.public {
+            true => (String::new(), String::new(), Some(String::from_utf8_lossy(&value.digest).into_owned())),
+            false => (BASE64_STANDARD.encode(value.nonce), BASE64_STANDARD.encode(value.digest), None),
+        };
         Self {
             date,
             nonce,
             digest,
+            blob: None,
+            chain,
+            meta,
+            text,
         }
     }
 }
 
 fn try_b64_to_arr<const N: usize>(str: &str) -> Result<[u8; N], FromBase64Error> {
-    let attempted_decoded = BASE64_STANDARD.decode(str);
-    if attempted_decoded.is_err() {
-        return Err(FromBase64Error::NotValidBase64);
-    }
-    let decoded = attempted_decoded.unwrap();
-    match decoded.len() == N {
-        true => Ok(decoded.try_into().unwrap()),
-        false => Err(FromBase64Error::InvalidLength),
-    }
+    let decoded = BASE64_STANDARD
+        .decode(str)
+        .map_err(|_| FromBase64Error::NotValidBase64)?;
+    decoded
+        .try_into()
+        .map_err(|_| FromBase64Error::InvalidLength)
 }
 
 fn try_b64_to_vec(str: &str) -> Result<Vec<u8>, FromBase64Error> {
@@ -151,6 +398,28 @@ fn try_b64_to_vec(str: &str) -> Result<Vec<u8>, FromBase64Error> {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// what unlocking a journal gives (see [`Encryptor::unlock()`]): the password
+/// and the key derived from it, along with the password hash and salt they
+/// belong to. saving reuses them as long as the password is the same, so the
+/// password isn't hashed and a key isn't derived every time.
+pub struct Keys {
+    /// the password
+    pub password: String,
+    /// the hash of the password
+    pub password_hash: String,
+    /// the salt the key was derived with
+    pub kdf_salt: [u8; 32],
+    /// the key entries are encrypted with
+    pub key: [u8; 32],
+}
+
+impl std::fmt::Debug for Keys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keys").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone)]
 /// A journal. contains a password, and a set of entries.
 pub struct State {
@@ -158,28 +427,111 @@ pub struct State {
     pub password: String,
     /// a set of entries
     pub entries: HashMap<Date, String>,
+    /// whether to link the entries into a hash chain when saving, so that
+    /// changing or removing entries without the password can be noticed (see
+    /// [`Encryptor::verify_chain()`]). journals that have a chain keep it.
+    pub hash_chain: bool,
+    /// the metadata of the entries that have any
+    pub meta: HashMap<Date, EntryMeta>,
+    /// an index of the words and tags in every entry, saved (encrypted) with
+    /// the journal so searching doesn't have to decrypt every entry (see
+    /// [`State::load_matching_from()`]). it's brought up to date whenever the
+    /// journal is saved, and journals that have one keep it.
+    pub index: Option<SearchIndex>,
+    /// the secret for a second factor (see [`totp`](crate::totp)), saved
+    /// encrypted with the journal. `jrn` asks for a code from it after the
+    /// password. the secret is only as safe as the password, so it keeps
+    /// someone who learned the password from using `jrn`, not from
+    /// decrypting a copy of the journal.
+    pub totp: Option<String>,
+    /// when the journal was unlocked, failed to unlock, and was changed, saved
+    /// encrypted with the journal, if it has an audit log (see
+    /// [`audit`](crate::audit)). journals that have one keep it.
+    pub audit: Option<Vec<AuditEvent>>,
+    /// the keys the journal was unlocked with, if it was loaded (see
+    /// [`Keys`])
+    pub keys: Option<Keys>,
 }
 
 /// how loading, deserializing, and unencrypting a file could go wrong
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum LoadError {
     /// the file either has inadequate permissions or does not exist
-    NotAccessible,
+    #[error("couldn't read journal: {0}")]
+    NotAccessible(#[from] std::io::Error),
     /// the file could not be parsed
-    ParseError,
+    #[error("couldn't parse journal: {0}")]
+    ParseError(#[from] serde_json::Error),
     /// the Base64 was incorrect (see [`FromBase64Error`])
-    FromBase64Error(FromBase64Error),
+    #[error("corrupted journal: {0}")]
+    FromBase64Error(#[from] FromBase64Error),
     /// the password given was incorrect and the file could not be unencrypted
+    #[error("incorrect password")]
     IncorrectPassword,
+    /// the password was right, but the journal couldn't be decrypted (see
+    /// [`DecryptError`])
+    #[error("corrupted journal: {0}")]
+    DecryptError(DecryptError),
+}
+
+impl From<DecryptError> for LoadError {
+    fn from(value: DecryptError) -> Self {
+        match value {
+            DecryptError::IncorrectPassword => Self::IncorrectPassword,
+            e => Self::DecryptError(e),
+        }
+    }
 }
 
 /// how encrypting, serializing, and writing to a file could go wrong
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum SaveError {
+    /// the journal couldn't be encrypted (see [`EncryptError`])
+    #[error("couldn't encrypt journal: {0}")]
+    EncryptError(#[from] EncryptError),
     /// should never happen. for some reason, [`serde`] could not serialize
-    SerializationError,
+    #[error("couldn't serialize journal: {0}")]
+    SerializationError(#[from] serde_json::Error),
     /// file could not be written to
-    FileError,
+    #[error("couldn't write journal: {0}")]
+    FileError(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// what to do when moving or copying an entry onto a date that already has
+/// one (see [`State::copy_entry()`])
+pub enum Conflict {
+    /// replace the entry that's there
+    Overwrite,
+    /// add the entry to the end of the one that's there, after a blank line
+    Append,
+}
+
+#[derive(Debug, Error)]
+/// How shifting entries to other dates could fail (see
+/// [`State::shift_entries()`])
+pub enum ShiftError {
+    /// entries would be shifted onto these dates, which already have entries
+    /// that aren't shifted
+    #[error("there are already entries on {}", list_dates(.0))]
+    Collision(Vec<Date>),
+    /// the entry for this date would be shifted out of the range of dates
+    #[error("the entry for {0} can't be shifted that far")]
+    OutOfRange(Date),
+}
+
+#[derive(Debug, Error)]
+/// How merging another journal into this one could fail (see
+/// [`State::merge_from()`])
+pub enum MergeError {
+    /// both journals have different entries on these dates, and there's no
+    /// [`Conflict`] to resolve them with
+    #[error("both journals have different entries on {}", list_dates(.0))]
+    Collision(Vec<Date>),
+}
+
+fn list_dates(dates: &[Date]) -> String {
+    dates.iter().map(Date::to_string).collect::<Vec<_>>().join(", ")
 }
 
 impl State {
@@ -190,7 +542,277 @@ impl State {
 
     /// create or overide an entry at a given date
     pub fn set_entry(&mut self, date: &Date, content: &str) {
-        self.entries.insert(date.clone(), content.into());
+        self.entries.insert(*date, content.into());
+    }
+
+    /// removes the entry at a given date (and its metadata), returning it
+    pub fn remove_entry(&mut self, date: &Date) -> Option<String> {
+        self.meta.remove(date);
+        self.entries.remove(date)
+    }
+
+    /// whether the entry at a given date is locked (see [`EntryMeta::locked`])
+    pub fn is_locked(&self, date: &Date) -> bool {
+        self.meta.get(date).is_some_and(|meta| meta.locked)
+    }
+
+    /// locks or unlocks the entry at a given date
+    pub fn set_locked(&mut self, date: &Date, locked: bool) {
+        self.meta.entry(*date).or_default().locked = locked;
+    }
+
+    /// whether the entry at a given date is starred (see
+    /// [`EntryMeta::starred`])
+    pub fn is_starred(&self, date: &Date) -> bool {
+        self.meta.get(date).is_some_and(|meta| meta.starred)
+    }
+
+    /// stars or unstars the entry at a given date
+    pub fn set_starred(&mut self, date: &Date, starred: bool) {
+        self.meta.entry(*date).or_default().starred = starred;
+    }
+
+    /// whether the entry at a given date is stored without encryption (see
+    /// [`EntryMeta::public`])
+    pub fn is_public(&self, date: &Date) -> bool {
+        self.meta.get(date).is_some_and(|meta| meta.public)
+    }
+
+    /// makes the entry at a given date public (stored without encryption) or
+    /// private again
+    pub fn set_public(&mut self, date: &Date, public: bool) {
+        self.meta.entry(*date).or_default().public = public;
+    }
+
+    /// the file type the entry at a given date was written in (see
+    /// [`EntryMeta::file_type`])
+    pub fn file_type(&self, date: &Date) -> Option<&str> {
+        self.meta.get(date).and_then(|meta| meta.file_type.as_deref())
+    }
+
+    /// sets (or with `None`, clears) the file type of the entry at a given
+    /// date
+    pub fn set_file_type(&mut self, date: &Date, file_type: Option<&str>) {
+        self.meta.entry(*date).or_default().file_type = file_type.map(Into::into);
+    }
+
+    /// the mood of the entry at a given date (see [`EntryMeta::mood`])
+    pub fn mood(&self, date: &Date) -> Option<Mood> {
+        self.meta.get(date).and_then(|meta| meta.mood)
+    }
+
+    /// sets (or with `None`, clears) the mood of the entry at a given date
+    pub fn set_mood(&mut self, date: &Date, mood: Option<Mood>) {
+        self.meta.entry(*date).or_default().mood = mood;
+    }
+
+    /// the custom fields of the entry at a given date (see
+    /// [`EntryMeta::fields`])
+    pub fn fields(&self, date: &Date) -> Option<&BTreeMap<String, String>> {
+        self.meta.get(date).map(|meta| &meta.fields).filter(|fields| !fields.is_empty())
+    }
+
+    /// sets custom fields of the entry at a given date, removing the ones
+    /// with an empty value. returns whether any changed.
+    pub fn set_fields(&mut self, date: &Date, fields: &[Field]) -> bool {
+        let meta = self.meta.entry(*date).or_default();
+        let mut changed = false;
+        for field in fields {
+            let new = Some(&field.value).filter(|value| !value.is_empty());
+            let old = match new {
+                Some(value) => meta.fields.insert(field.key.clone(), value.clone()),
+                None => meta.fields.remove(&field.key),
+            };
+            changed |= old.as_ref() != new;
+        }
+        changed
+    }
+
+    /// where the entry at a given date was written (see
+    /// [`EntryMeta::location`])
+    pub fn location(&self, date: &Date) -> Option<&str> {
+        self.meta.get(date).and_then(|meta| meta.location.as_deref())
+    }
+
+    /// sets (or with `None`, clears) where the entry at a given date was
+    /// written
+    pub fn set_location(&mut self, date: &Date, location: Option<&str>) {
+        self.meta.entry(*date).or_default().location = location.map(Into::into);
+    }
+
+    /// when the entry at a given date was first saved (see
+    /// [`EntryMeta::created`])
+    pub fn created(&self, date: &Date) -> Option<i64> {
+        self.meta.get(date).and_then(|meta| meta.created)
+    }
+
+    /// when the entry at a given date last changed (see
+    /// [`EntryMeta::modified`])
+    pub fn modified(&self, date: &Date) -> Option<i64> {
+        self.meta.get(date).and_then(|meta| meta.modified)
+    }
+
+    /// notes that the entry at a given date changed at `time`, and was created
+    /// then too if it's `new`
+    pub fn touch(&mut self, date: &Date, time: i64, new: bool) {
+        let meta = self.meta.entry(*date).or_default();
+        if new {
+            meta.created = Some(time);
+        }
+        meta.modified = Some(time);
+    }
+
+    /// copies the entry at `from` to `to`, resolving an entry already at `to`
+    /// with `conflict`. returns `false` (changing nothing) if there's no entry
+    /// at `from`.
+    pub fn copy_entry(&mut self, from: &Date, to: &Date, conflict: Conflict) -> bool {
+        let Some(content) = self.get_entry(from) else {
+            return false;
+        };
+        if from == to {
+            return true;
+        }
+
+        let content = match (self.entries.get(to), conflict) {
+            (Some(existing), Conflict::Append) => format!("{existing}\n\n{content}"),
+            _ => {
+                // it's the copied entry now, so it's edited like it
+                let file_type = self.file_type(from).map(String::from);
+                self.set_file_type(to, file_type.as_deref());
+                content
+            }
+        };
+        self.set_entry(to, &content);
+        true
+    }
+
+    /// the entries on `dates` and their metadata, as a journal of their own.
+    /// it has the same password and second factor, and a hash chain, search
+    /// index, and audit log if this one does.
+    pub fn extract(&self, dates: &[Date]) -> State {
+        let mut part = State::new();
+        part.password = self.password.clone();
+        part.hash_chain = self.hash_chain;
+        part.totp = self.totp.clone();
+        part.index = self.index.as_ref().map(|_| SearchIndex::default());
+        part.audit = self.audit.as_ref().map(|_| Vec::new());
+        for date in dates {
+            if let Some(content) = self.entries.get(date) {
+                part.entries.insert(*date, content.clone());
+            }
+            if let Some(meta) = self.meta.get(date) {
+                part.meta.insert(*date, meta.clone());
+            }
+        }
+        part
+    }
+
+    /// adds the entries of `other` and their metadata, giving back the dates
+    /// that were added or changed, oldest first. entries that are the same in
+    /// both are left alone. other entries on dates that have one already are
+    /// resolved with `conflict` (keeping this one's metadata when appending),
+    /// and without one, nothing is merged.
+    pub fn merge_from(&mut self, other: &State, conflict: Option<Conflict>) -> Result<Vec<Date>, MergeError> {
+        let mut dates = other.entries.keys().copied().collect::<Vec<_>>();
+        dates.sort();
+        let collisions = dates
+            .iter()
+            .filter(|date| self.entries.get(date).is_some_and(|content| *content != other.entries[date]))
+            .copied()
+            .collect::<Vec<_>>();
+        let conflict = match conflict {
+            Some(conflict) => conflict,
+            None if collisions.is_empty() => Conflict::Overwrite,
+            None => return Err(MergeError::Collision(collisions)),
+        };
+
+        let mut merged = Vec::new();
+        for date in dates {
+            let content = &other.entries[&date];
+            let content = match (self.entries.get(&date), conflict) {
+                (Some(existing), _) if existing == content => continue,
+                (Some(existing), Conflict::Append) => format!("{existing}\n\n{content}"),
+                _ => {
+                    match other.meta.get(&date) {
+                        Some(meta) => self.meta.insert(date, meta.clone()),
+                        None => self.meta.remove(&date),
+                    };
+                    content.clone()
+                }
+            };
+            self.entries.insert(date, content);
+            merged.push(date);
+        }
+        Ok(merged)
+    }
+
+    /// like [`State::copy_entry()`], but removes the entry at `from`
+    /// afterwards. its metadata goes with it, unless it was appended to
+    /// another entry.
+    pub fn move_entry(&mut self, from: &Date, to: &Date, conflict: Conflict) -> bool {
+        let appended = conflict == Conflict::Append && self.entries.contains_key(to);
+        if !self.copy_entry(from, to, conflict) {
+            return false;
+        }
+        if from == to {
+            return true;
+        }
+
+        let meta = self.meta.remove(from);
+        if !appended {
+            match meta {
+                Some(meta) => self.meta.insert(*to, meta),
+                None => self.meta.remove(to),
+            };
+        }
+        self.remove_entry(from);
+        true
+    }
+
+    /// the entries in `range`, each with the date it would be shifted to by
+    /// `offset`, oldest first. fails (see [`ShiftError`]) if any of them would
+    /// land on an entry that isn't shifted along with them.
+    pub fn plan_shift(&self, range: &DateRange, offset: Offset) -> Result<Vec<(Date, Date)>, ShiftError> {
+        let shifts = self
+            .entries
+            .keys()
+            .filter(|date| range.contains(date))
+            .map(|date| Ok((*date, offset.apply(date).ok_or(ShiftError::OutOfRange(*date))?)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let collisions = shifts
+            .iter()
+            .map(|(_, to)| *to)
+            .filter(|to| self.entries.contains_key(to) && !range.contains(to))
+            .collect::<Vec<_>>();
+        match collisions.is_empty() {
+            true => Ok(shifts),
+            false => Err(ShiftError::Collision(collisions)),
+        }
+    }
+
+    /// moves every entry in `range` by `offset` (see [`State::plan_shift()`]),
+    /// along with its metadata. changes nothing if it fails. gives back the
+    /// old and new date of every entry.
+    pub fn shift_entries(&mut self, range: &DateRange, offset: Offset) -> Result<Vec<(Date, Date)>, ShiftError> {
+        let shifts = self.plan_shift(range, offset)?;
+
+        // take them all out first, since they can land on each other's dates
+        let moved = shifts
+            .iter()
+            .map(|(from, to)| (*to, self.meta.remove(from), self.entries.remove(from)))
+            .collect::<Vec<_>>();
+        for (to, meta, content) in moved {
+            match meta {
+                Some(meta) => self.meta.insert(to, meta),
+                None => self.meta.remove(&to),
+            };
+            if let Some(content) = content {
+                self.entries.insert(to, content);
+            }
+        }
+
+        Ok(shifts)
     }
 
     /// a convenience function for getting the value of today's entry
@@ -208,6 +830,12 @@ impl State {
         Self {
             password: "".into(),
             entries: HashMap::new(),
+            hash_chain: false,
+            meta: HashMap::new(),
+            index: None,
+            totp: None,
+            audit: None,
+            keys: None,
         }
     }
 
@@ -218,67 +846,199 @@ impl State {
 
     /// loads a file at the given name, deserializes the data, and unencrypts with the given
     /// password
+    #[cfg(feature = "fs")]
     pub fn load<E: Encryptor>(
         &mut self,
         file_name: &str,
         password: &str,
         e: &E,
     ) -> Result<(), LoadError> {
-        let json = fs::read(file_name);
-        if json.is_err() {
-            return Err(LoadError::NotAccessible);
-        }
-        let json = String::from_utf8(json.unwrap()).unwrap();
+        self.load_from(&FileStorage::new(file_name), password, e)
+    }
 
-        let stored_journal = serde_json::from_str::<StoredJournal>(&json);
+    /// like [`State::load()`], but reads from any [`Storage`]
+    #[instrument(name = "load", skip_all)]
+    pub fn load_from<E: Encryptor, S: Storage>(
+        &mut self,
+        storage: &S,
+        password: &str,
+        e: &E,
+    ) -> Result<(), LoadError> {
+        let encrypted_journal = read(storage)?;
 
-        if stored_journal.is_err() {
-            return Err(LoadError::ParseError);
-        }
+        *self = e.decrypt_journal(&encrypted_journal, password)?;
 
-        let stored_journal = stored_journal.unwrap();
+        Ok(())
+    }
 
-        let encrypted_journal = EncryptedJournal::try_from(stored_journal);
+    /// like [`State::load_from()`], but doesn't block while reading
+    #[instrument(name = "load", skip_all)]
+    pub async fn load_from_async<E: Encryptor, S: AsyncStorage>(
+        &mut self,
+        storage: &S,
+        password: &str,
+        e: &E,
+    ) -> Result<(), LoadError> {
+        let json = storage.read().instrument(info_span!("read")).await?;
+        self.load_json(&json, password, e)
+    }
 
-        if let Err(e) = encrypted_journal {
-            return Err(LoadError::FromBase64Error(e));
-        }
+    /// like [`State::load_from()`], but only decrypts the entries that might
+    /// match `query`, if the journal has a [search index](State::index). the
+    /// loaded journal can be missing other entries, so it should only be
+    /// searched, never saved.
+    #[instrument(name = "load", skip_all)]
+    pub fn load_matching_from<E: Encryptor, S: Storage>(
+        &mut self,
+        storage: &S,
+        password: &str,
+        e: &E,
+        query: &Query,
+    ) -> Result<(), LoadError> {
+        let encrypted_journal = read(storage)?;
+
+        *self = e.decrypt_matching(&encrypted_journal, password, query)?;
+
+        Ok(())
+    }
 
-        let encrypted_journal = encrypted_journal.unwrap();
+    /// like [`State::load_from()`], but with keys kept from unlocking the
+    /// journal before (see [`Encryptor::decrypt_with_keys()`]) instead of the
+    /// password. with a query, only the entries that might match are
+    /// decrypted, like [`State::load_matching_from()`].
+    #[instrument(name = "load", skip_all)]
+    pub fn load_with_keys_from<E: Encryptor, S: Storage>(
+        &mut self,
+        storage: &S,
+        keys: &Keys,
+        e: &E,
+        query: Option<&Query>,
+    ) -> Result<(), LoadError> {
+        let encrypted_journal = read(storage)?;
 
-        let state = e.decrypt_journal(&encrypted_journal, password);
+        *self = e.decrypt_with_keys(&encrypted_journal, keys, query)?;
 
-        if let Err(DecryptError::IncorrectPassword) = state {
-            return Err(LoadError::IncorrectPassword);
+        Ok(())
+    }
+
+    /// adds `action` to the audit log, happening now, if the journal has one
+    pub fn record(&mut self, action: AuditAction) {
+        if let Some(audit) = &mut self.audit {
+            audit.push(AuditEvent::now(action));
+        }
+    }
+
+    /// writes just the audit log to `storage`, so events are kept even if
+    /// nothing else is saved. failed unlocks (which are in the log once the
+    /// journal is unlocked) are taken out of the clear. does nothing without
+    /// an audit log or the keys, or if the stored journal has other keys.
+    pub fn save_audit_to<E: Encryptor, S: Storage>(&self, storage: &S, e: &E) -> Result<(), SaveError> {
+        let (Some(audit), Some(keys)) = (&self.audit, &self.keys) else {
+            return Ok(());
+        };
+        let mut stored = serde_json::from_reader::<_, StoredJournal>(storage.reader()?)?;
+        if stored.password_hash != keys.password_hash || stored.kdf_salt != BASE64_STANDARD.encode(keys.kdf_salt) {
+            return Ok(());
         }
 
-        *self = state.unwrap();
+        let encrypted = encrypt_audit(e, keys.key, audit)?;
+        stored.audit = Some(BASE64_STANDARD.encode(encrypted));
+        stored.failed_unlocks.clear();
+        storage.write_with(&mut |writer| Ok(serde_json::to_writer(writer, &stored)?))?;
+
+        Ok(())
+    }
+
+    /// deserializes a journal (as stored in a file) and unencrypts it with the
+    /// given password
+    pub fn load_json<E: Encryptor>(
+        &mut self,
+        json: &str,
+        password: &str,
+        e: &E,
+    ) -> Result<(), LoadError> {
+        let encrypted_journal = parse(json)?;
+
+        *self = e.decrypt_journal(&encrypted_journal, password)?;
 
         Ok(())
     }
 
     /// encrypts contents, serializes contents, and writes them to the given file
+    #[cfg(feature = "fs")]
     pub fn save<E: Encryptor>(&self, file_name: &str, e: &E) -> Result<(), SaveError> {
-        let encrypted_journal = e.encrypt_journal(self);
+        self.save_to(&FileStorage::new(file_name), e)
+    }
 
-        let saved_journal: StoredJournal = encrypted_journal.into();
+    /// like [`State::save()`], but writes to any [`Storage`]
+    pub fn save_to<E: Encryptor, S: Storage>(&self, storage: &S, e: &E) -> Result<(), SaveError> {
+        self.save_to_with_progress(storage, e, &mut |_, _| {})
+    }
 
-        let json = serde_json::to_string(&saved_journal);
+    /// like [`State::save_to()`], but calls `progress` while the entries are
+    /// encrypted (see [`Encryptor::encrypt_journal_with_progress()`])
+    #[instrument(name = "save", skip_all)]
+    pub fn save_to_with_progress<E: Encryptor, S: Storage>(
+        &self,
+        storage: &S,
+        e: &E,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<(), SaveError> {
+        let saved_journal: StoredJournal = e.encrypt_journal_with_progress(self, progress)?.into();
 
-        if json.is_err() {
-            return Err(SaveError::SerializationError);
-        }
+        // serialized straight into the storage, without the whole JSON in
+        // memory
+        info_span!("write").in_scope(|| {
+            storage.write_with(&mut |writer| Ok(serde_json::to_writer(writer, &saved_journal)?))
+        })?;
 
-        let json = json.unwrap();
+        Ok(())
+    }
 
-        let err = fs::write(file_name, json);
+    /// like [`State::save_to()`], but doesn't block while writing
+    #[instrument(name = "save", skip_all)]
+    pub async fn save_to_async<E: Encryptor, S: AsyncStorage>(
+        &self,
+        storage: &S,
+        e: &E,
+    ) -> Result<(), SaveError> {
+        let json = self.to_json(e)?;
 
-        if err.is_err() {
-            return Err(SaveError::FileError);
-        }
+        storage.write(&json).instrument(info_span!("write")).await?;
 
         Ok(())
     }
+
+    /// encrypts and serializes the journal, as it would be stored in a file
+    pub fn to_json<E: Encryptor>(&self, e: &E) -> Result<String, SaveError> {
+        let encrypted_journal = e.encrypt_journal(self)?;
+
+        let saved_journal: StoredJournal = encrypted_journal.into();
+
+        let json = info_span!("serialize").in_scope(|| serde_json::to_string(&saved_journal))?;
+
+        Ok(json)
+    }
+}
+
+/// reads and deserializes a journal from `storage` bit by bit, without the
+/// whole file in memory
+fn read<S: Storage>(storage: &S) -> Result<EncryptedJournal, LoadError> {
+    info_span!("read").in_scope(|| {
+        let stored_journal = serde_json::from_reader::<_, StoredJournal>(storage.reader()?).map_err(|e| match e.is_io() {
+            true => LoadError::NotAccessible(e.into()),
+            false => LoadError::ParseError(e),
+        })?;
+        Ok(EncryptedJournal::try_from(stored_journal)?)
+    })
+}
+
+/// deserializes a journal, as stored in a file
+fn parse(json: &str) -> Result<EncryptedJournal, LoadError> {
+    info_span!("parse").in_scope(|| {
+        let stored_journal = serde_json::from_str::<StoredJournal>(json)?;
+        Ok(EncryptedJournal::try_from(stored_journal)?)
+    })
 }
 
 impl Default for State {
@@ -0,0 +1,230 @@
+//! module for comparing two versions of an entry, to show what an edit
+//! changed. lines are compared first, and the words of the lines that
+//! changed after that (see [`diff()`]).
+
+use std::ops::Range;
+
+/// past this many comparisons, changed lines or words are taken as removed
+/// and added as a whole, instead of looking for what they share
+const MAX_COMPARISONS: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// a piece of text in a [`Hunk`], and whether an edit kept, removed, or
+/// added it
+pub enum Change<'a> {
+    /// in both versions
+    Same(&'a str),
+    /// only in the old version
+    Removed(&'a str),
+    /// only in the new version
+    Added(&'a str),
+}
+
+/// the lines that changed in one place, word by word
+pub type Hunk<'a> = Vec<Change<'a>>;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// what an edit changed
+pub struct EditDiff<'a> {
+    /// the changed lines, in order
+    pub hunks: Vec<Hunk<'a>>,
+    /// how many words were added
+    pub added_words: usize,
+    /// how many words were removed
+    pub removed_words: usize,
+    /// how many lines changed (a line replaced by another counts once)
+    pub changed_lines: usize,
+}
+
+impl EditDiff<'_> {
+    /// whether the edit didn't change anything
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Same,
+    Removed,
+    Added,
+}
+
+/// the shortest way (or close to it) to turn `old` into `new`: which items are
+/// kept, removed, and added, in order
+fn ops<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![Op::Same; prefix];
+    if old_middle.len() * new_middle.len() > MAX_COMPARISONS {
+        ops.extend(old_middle.iter().map(|_| Op::Removed));
+        ops.extend(new_middle.iter().map(|_| Op::Added));
+    } else {
+        ops.extend(longest_common(old_middle, new_middle));
+    }
+    ops.extend(vec![Op::Same; suffix]);
+    ops
+}
+
+/// [`ops()`] by the longest common subsequence
+fn longest_common<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Op> {
+    // lengths[i][j] is the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(Op::Same);
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            ops.push(Op::Removed);
+            i += 1;
+        } else {
+            ops.push(Op::Added);
+            j += 1;
+        }
+    }
+    ops.extend((i..old.len()).map(|_| Op::Removed));
+    ops.extend((j..new.len()).map(|_| Op::Added));
+    ops
+}
+
+/// the words of `text`, each with where it is in `text`, from its start to
+/// the start of the next word (so the whitespace after it goes with it)
+fn words(text: &str) -> Vec<(&str, Range<usize>)> {
+    let mut words = text
+        .split_whitespace()
+        .map(|word| {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            (word, start..start)
+        })
+        .collect::<Vec<_>>();
+    let starts = words.iter().skip(1).map(|(_, span)| span.start).chain([text.len()]).collect::<Vec<_>>();
+    for ((_, span), end) in words.iter_mut().zip(starts) {
+        span.end = end;
+    }
+    words
+}
+
+/// the words of `old` and `new`, marked as kept, removed, or added. only the
+/// words are compared, not the whitespace between them, and changes of the
+/// same kind next to each other are joined.
+fn word_changes<'a>(old: &'a str, new: &'a str) -> Hunk<'a> {
+    let old_words = words(old);
+    let new_words = words(new);
+    let compared = |words: &[(&'a str, Range<usize>)]| words.iter().map(|(word, _)| *word).collect::<Vec<_>>();
+
+    let mut spans = Vec::<(Op, Range<usize>)>::new();
+    let (mut i, mut j) = (0, 0);
+    for op in ops(&compared(&old_words), &compared(&new_words)) {
+        let span = match op {
+            Op::Same => {
+                i += 1;
+                j += 1;
+                new_words[j - 1].1.clone()
+            }
+            Op::Removed => {
+                i += 1;
+                old_words[i - 1].1.clone()
+            }
+            Op::Added => {
+                j += 1;
+                new_words[j - 1].1.clone()
+            }
+        };
+        match spans.last_mut() {
+            Some((last, last_span)) if *last == op && last_span.end == span.start => last_span.end = span.end,
+            _ => spans.push((op, span)),
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|(op, span)| match op {
+            Op::Same => Change::Same(&new[span]),
+            Op::Removed => Change::Removed(&old[span]),
+            Op::Added => Change::Added(&new[span]),
+        })
+        .collect()
+}
+
+/// compares `old` and `new` line by line, and then the changed lines word by
+/// word
+pub fn diff<'a>(old: &'a str, new: &'a str) -> EditDiff<'a> {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+
+    let mut diff = EditDiff::default();
+    let (mut i, mut j) = (0, 0);
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut ops = ops(&old_lines, &new_lines);
+    // so the last hunk is finished too
+    ops.push(Op::Same);
+    for op in ops {
+        match op {
+            Op::Removed => {
+                removed.push(old_lines[i]);
+                i += 1;
+            }
+            Op::Added => {
+                added.push(new_lines[j]);
+                j += 1;
+            }
+            Op::Same => {
+                if !removed.is_empty() || !added.is_empty() {
+                    diff.changed_lines += removed.len().max(added.len());
+                    let hunk = hunk_of(old, &removed, new, &added);
+                    for change in &hunk {
+                        match change {
+                            Change::Removed(text) => diff.removed_words += text.split_whitespace().count(),
+                            Change::Added(text) => diff.added_words += text.split_whitespace().count(),
+                            Change::Same(_) => {}
+                        }
+                    }
+                    diff.hunks.push(hunk);
+                    removed.clear();
+                    added.clear();
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    diff
+}
+
+/// the words of the `removed` lines of `old` against the `added` lines of
+/// `new`. the lines are next to each other in their text, so they're compared
+/// as one slice of it.
+fn hunk_of<'a>(old: &'a str, removed: &[&'a str], new: &'a str, added: &[&'a str]) -> Hunk<'a> {
+    word_changes(span(old, removed), span(new, added))
+}
+
+/// the part of `text` from the first of `lines` to the end of the last one
+fn span<'a>(text: &'a str, lines: &[&'a str]) -> &'a str {
+    let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+        return "";
+    };
+    let start = first.as_ptr() as usize - text.as_ptr() as usize;
+    let end = last.as_ptr() as usize - text.as_ptr() as usize + last.len();
+    &text[start..end]
+}
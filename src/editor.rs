@@ -0,0 +1,125 @@
+//! module for editing entries in `$VISUAL`/`$EDITOR`. the decrypted entry
+//! only ever touches a private temporary file, which is overwritten before
+//! it's removed.
+
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Mutex,
+};
+
+use crate::{tr, warn};
+
+/// the directory of the entry being edited, so it's cleaned up even if `jrn`
+/// is stopped while the editor is open (see [`abandon()`])
+static EDITING: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// a directory only the user can read, for the temporary file. in
+/// `$XDG_RUNTIME_DIR` (usually memory backed) when there is one, so the entry
+/// is never written to disk.
+fn private_dir() -> io::Result<PathBuf> {
+    let parent = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(env::temp_dir);
+    let dir = parent.join(format!("jrn-{}", hex::encode(rand::random::<[u8; 8]>())));
+
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+    Ok(dir)
+}
+
+/// overwrites the file with zeros before removing it. on copy-on-write
+/// filesystems and SSDs the old blocks may survive, which is why the file is
+/// kept in memory when possible (see [`private_dir()`]).
+fn shred(path: &Path) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let len = file.metadata()?.len();
+    file.rewind()?;
+    io::copy(&mut io::repeat(0).take(len), &mut file)?;
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)
+}
+
+/// the editor command, from `$VISUAL` or `$EDITOR` (which can include
+/// arguments, like `code --wait`), falling back to `vi`
+fn editor() -> Command {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.into());
+
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("vi"));
+    command.args(words);
+    command
+}
+
+/// overwrites and removes every file in `dir` (the entry, and any swap or
+/// backup files the editor left next to it), then `dir` itself. a file that
+/// can't be overwritten only gets a warning, so an edit isn't lost over it.
+fn clean_up(dir: &Path) {
+    match fs::read_dir(dir) {
+        Ok(files) => {
+            for file in files.filter_map(|file| file.ok()) {
+                if !file.file_type().is_ok_and(|file_type| file_type.is_file()) {
+                    continue;
+                }
+                if let Err(e) = shred(&file.path()) {
+                    let message = tr!("couldn't overwrite {0}: {1}", file.path().display(), e);
+                    warn!("{message}");
+                }
+            }
+        }
+        Err(e) => {
+            let message = tr!("couldn't overwrite {0}: {1}", dir.display(), e);
+            warn!("{message}");
+        }
+    }
+    let _ = fs::remove_dir_all(dir);
+}
+
+/// cleans up after an edit that's still going, for when `jrn` is stopped
+/// with the editor open (see [`signals`](crate::signals))
+pub fn abandon() {
+    let editing = EDITING.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(dir) = editing {
+        clean_up(&dir);
+    }
+}
+
+/// opens `content` in the editor, with `args` passed before the file (for
+/// example to turn off swap files), and returns what was saved. `extension`
+/// (like `.md`) lets the editor pick a syntax.
+pub fn edit(content: &str, extension: &str, args: &[String]) -> io::Result<String> {
+    let dir = private_dir()?;
+    let path = dir.join(format!("entry{extension}"));
+    *EDITING.lock().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+
+    let result = (|| {
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file: File = options.open(&path)?;
+        file.write_all(content.as_bytes())?;
+        drop(file);
+
+        let status = editor().args(args).arg(&path).status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("the editor exited with {status}")));
+        }
+
+        fs::read_to_string(&path)
+    })();
+
+    abandon();
+    result
+}
@@ -1,175 +1,660 @@
-//! module for the [`Encryptor`] trait. Contains [`ZeroSecurity`] and [`Secure`] Implementations.
+//! module for the [`Encryptor`] trait. Contains the [`Secure`] implementation, and
+//! `ZeroSecurity` for tests (with the `test-util` feature).
 
-use std::{
-    collections::{HashMap, HashSet},
-    process::exit,
-};
+use std::collections::HashMap;
 
 use aes_gcm_siv::{
     aead::{Aead, KeyInit},
     Aes256GcmSiv, Nonce,
 };
-use bcrypt::DEFAULT_COST;
+use bcrypt::{BcryptError, DEFAULT_COST};
+use hmac::{Hmac, Mac};
 use pbkdf2::pbkdf2_hmac;
 use rand::Rng;
 use sha2::Sha256;
+use thiserror::Error;
+use chrono::NaiveDate;
+use tracing::{debug, debug_span, info, info_span};
 
 use crate::{
+    audit::{AuditAction, AuditEvent},
     date::Date,
-    db::{EncryptedEntry, EncryptedJournal, State, StoredEntry, StoredJournal},
+    db::{EncryptedEntry, EncryptedJournal, EntryMeta, Keys, State},
+    index::SearchIndex,
+    search::Query,
 };
 
-#[derive(Debug)]
-/// the ways in which decrypting a [`StoredJournal`] can go wrong
+#[derive(Debug, Error)]
+/// the ways in which decrypting a [`StoredJournal`](crate::db::StoredJournal) can go wrong
 pub enum DecryptError {
     /// the password was incorrect (see [`Encryptor::verify_password()`])
+    #[error("incorrect password")]
     IncorrectPassword,
+    /// the stored password hash is malformed
+    #[error("invalid password hash: {0}")]
+    InvalidHash(#[from] BcryptError),
+    /// the entry for the given date couldn't be decrypted
+    #[error("couldn't decrypt the entry for {0}")]
+    CorruptedEntry(Date),
+    /// the search index couldn't be decrypted
+    #[error("couldn't decrypt the search index")]
+    CorruptedIndex,
+    /// the TOTP secret couldn't be decrypted
+    #[error("couldn't decrypt the TOTP secret")]
+    CorruptedTotp,
+    /// the audit log couldn't be decrypted
+    #[error("couldn't decrypt the audit log")]
+    CorruptedAudit,
+    /// the [keys](Keys) are from before the journal's password or salt
+    /// changed
+    #[error("the keys don't fit the journal anymore")]
+    StaleKeys,
+}
+
+#[derive(Debug, Error)]
+/// the ways in which encrypting a [`State`] can go wrong
+pub enum EncryptError {
+    /// the password couldn't be hashed
+    #[error("couldn't hash password: {0}")]
+    Hash(#[from] BcryptError),
+    /// the entry for the given date couldn't be encrypted
+    #[error("couldn't encrypt the entry for {0}")]
+    Entry(Date),
+    /// the search index couldn't be encrypted
+    #[error("couldn't encrypt the search index")]
+    Index,
+    /// the TOTP secret couldn't be encrypted
+    #[error("couldn't encrypt the TOTP secret")]
+    Totp,
+    /// the audit log couldn't be encrypted
+    #[error("couldn't encrypt the audit log")]
+    Audit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// the result of checking a journal's hash chain (see
+/// [`State::hash_chain`])
+pub enum ChainStatus {
+    /// the journal doesn't have a hash chain
+    Missing,
+    /// every entry is as it was when the journal was last saved
+    Intact,
+    /// the entry for the given date was changed, added, or removed (or the
+    /// one before it was removed) since the journal was last saved
+    BrokenAt(Date),
+    /// entries were removed from the end of the journal
+    Truncated,
+}
+
+fn new_mac(key: [u8; 32]) -> Hmac<Sha256> {
+    <Hmac<Sha256> as Mac>::new_from_slice(&key).expect("HMAC takes keys of any length")
+}
+
+/// a key for the hash chain, derived from the encryption key so it's never
+/// used for two things
+fn chain_key(key: [u8; 32]) -> [u8; 32] {
+    let mut mac = new_mac(key);
+    mac.update(b"jrn hash chain");
+    mac.finalize().into_bytes().into()
+}
+
+/// the link for `entry`: a MAC of the previous link and everything stored for
+/// the entry
+fn chain_link(chain_key: [u8; 32], previous: [u8; 32], entry: &EncryptedEntry) -> [u8; 32] {
+    let mut mac = new_mac(chain_key);
+    mac.update(&previous);
+    mac.update(entry.date.to_string().as_bytes());
+    mac.update(&entry.nonce);
+    mac.update(&entry.digest);
+    if let Some(meta) = &entry.meta {
+        mac.update(b"meta");
+        mac.update(meta);
+    }
+    if entry.public {
+        mac.update(b"public");
+    }
+    mac.finalize().into_bytes().into()
+}
+
+/// the head of the chain: a MAC of the last link and the number of entries, so
+/// removing entries from the end is noticed too
+fn chain_head(chain_key: [u8; 32], last: [u8; 32], count: usize) -> [u8; 32] {
+    let mut mac = new_mac(chain_key);
+    mac.update(b"head");
+    mac.update(&last);
+    mac.update(&(count as u64).to_le_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// links `entries` (sorted by date) into a hash chain, returning its head
+fn link_entries(key: [u8; 32], entries: &mut [EncryptedEntry]) -> [u8; 32] {
+    let chain_key = chain_key(key);
+    let mut previous = [0; 32];
+    for entry in entries.iter_mut() {
+        previous = chain_link(chain_key, previous, entry);
+        entry.chain = Some(previous);
+    }
+    chain_head(chain_key, previous, entries.len())
+}
+
+/// encrypts an entry's metadata as JSON, the same way as the entry, giving
+/// the nonce followed by the encrypted JSON
+fn encrypt_meta<E: Encryptor + ?Sized>(
+    e: &E,
+    key: [u8; 32],
+    meta: &EntryMeta,
+    date: &Date,
+) -> Result<Vec<u8>, EncryptError> {
+    let json = serde_json::to_string(meta).map_err(|_| EncryptError::Entry(*date))?;
+    let encrypted = e.encrypt_journal_entry(key, &json, date)?;
+    Ok([encrypted.nonce.as_slice(), &encrypted.digest].concat())
+}
+
+/// the reverse of [`encrypt_meta()`]
+fn decrypt_meta<E: Encryptor + ?Sized>(
+    e: &E,
+    key: [u8; 32],
+    meta: &[u8],
+    date: &Date,
+) -> Result<EntryMeta, DecryptError> {
+    let corrupted = || DecryptError::CorruptedEntry(*date);
+    let (nonce, digest) = meta.split_at_checked(12).ok_or_else(corrupted)?;
+    let encrypted = EncryptedEntry {
+        date: *date,
+        nonce: nonce.try_into().map_err(|_| corrupted())?,
+        digest: digest.to_vec(),
+        chain: None,
+        meta: None,
+        public: false,
+    };
+    let (_, json) = e.decrypt_journal_entry(key, &encrypted)?;
+    serde_json::from_str(&json).map_err(|_| corrupted())
+}
+
+/// the index, the TOTP secret, and the audit log aren't entries, but they're
+/// encrypted like one, under this date
+/// (which only ends up in errors, and those are replaced)
+fn index_date() -> Date {
+    NaiveDate::MIN.into()
+}
+
+/// brings the journal's search index up to date (see
+/// [`SearchIndex::update()`]) and encrypts it like [`encrypt_meta()`]
+fn encrypt_index<E: Encryptor + ?Sized>(
+    e: &E,
+    key: [u8; 32],
+    journal: &State,
+    index: &SearchIndex,
+) -> Result<Vec<u8>, EncryptError> {
+    let mut index = index.clone();
+    let updated = index.update(journal);
+    debug!(updated, words = index.words.len(), "updated search index");
+
+    let json = serde_json::to_string(&index).map_err(|_| EncryptError::Index)?;
+    let encrypted = e
+        .encrypt_journal_entry(key, &json, &index_date())
+        .map_err(|_| EncryptError::Index)?;
+    Ok([encrypted.nonce.as_slice(), &encrypted.digest].concat())
+}
+
+/// the reverse of [`encrypt_index()`]
+fn decrypt_index<E: Encryptor + ?Sized>(e: &E, key: [u8; 32], index: &[u8]) -> Result<SearchIndex, DecryptError> {
+    let (nonce, digest) = index.split_at_checked(12).ok_or(DecryptError::CorruptedIndex)?;
+    let encrypted = EncryptedEntry {
+        date: index_date(),
+        nonce: nonce.try_into().map_err(|_| DecryptError::CorruptedIndex)?,
+        digest: digest.to_vec(),
+        chain: None,
+        meta: None,
+        public: false,
+    };
+    let (_, json) = e
+        .decrypt_journal_entry(key, &encrypted)
+        .map_err(|_| DecryptError::CorruptedIndex)?;
+    serde_json::from_str(&json).map_err(|_| DecryptError::CorruptedIndex)
+}
+
+/// encrypts the journal's TOTP secret (see [`State::totp`]) like
+/// [`encrypt_index()`]
+fn encrypt_totp<E: Encryptor + ?Sized>(e: &E, key: [u8; 32], secret: &str) -> Result<Vec<u8>, EncryptError> {
+    let encrypted = e
+        .encrypt_journal_entry(key, secret, &index_date())
+        .map_err(|_| EncryptError::Totp)?;
+    Ok([encrypted.nonce.as_slice(), &encrypted.digest].concat())
+}
+
+/// the reverse of [`encrypt_totp()`]
+fn decrypt_totp<E: Encryptor + ?Sized>(e: &E, key: [u8; 32], totp: &[u8]) -> Result<String, DecryptError> {
+    let (nonce, digest) = totp.split_at_checked(12).ok_or(DecryptError::CorruptedTotp)?;
+    let encrypted = EncryptedEntry {
+        date: index_date(),
+        nonce: nonce.try_into().map_err(|_| DecryptError::CorruptedTotp)?,
+        digest: digest.to_vec(),
+        chain: None,
+        meta: None,
+        public: false,
+    };
+    let (_, secret) = e
+        .decrypt_journal_entry(key, &encrypted)
+        .map_err(|_| DecryptError::CorruptedTotp)?;
+    Ok(secret)
+}
+
+/// encrypts the journal's audit log (see [`State::audit`]) like
+/// [`encrypt_index()`]
+pub(crate) fn encrypt_audit<E: Encryptor + ?Sized>(
+    e: &E,
+    key: [u8; 32],
+    audit: &[AuditEvent],
+) -> Result<Vec<u8>, EncryptError> {
+    let json = serde_json::to_string(audit).map_err(|_| EncryptError::Audit)?;
+    let encrypted = e
+        .encrypt_journal_entry(key, &json, &index_date())
+        .map_err(|_| EncryptError::Audit)?;
+    Ok([encrypted.nonce.as_slice(), &encrypted.digest].concat())
+}
+
+/// the reverse of [`encrypt_audit()`]. failed unlocks that aren't in the log
+/// yet (see [`EncryptedJournal::failed_unlocks`]) are added to it.
+fn decrypt_audit<E: Encryptor + ?Sized>(
+    e: &E,
+    key: [u8; 32],
+    encrypted_journal: &EncryptedJournal,
+) -> Result<Option<Vec<AuditEvent>>, DecryptError> {
+    let Some(audit) = &encrypted_journal.audit else {
+        return Ok(None);
+    };
+    let (nonce, digest) = audit.split_at_checked(12).ok_or(DecryptError::CorruptedAudit)?;
+    let encrypted = EncryptedEntry {
+        date: index_date(),
+        nonce: nonce.try_into().map_err(|_| DecryptError::CorruptedAudit)?,
+        digest: digest.to_vec(),
+        chain: None,
+        meta: None,
+        public: false,
+    };
+    let (_, json) = e
+        .decrypt_journal_entry(key, &encrypted)
+        .map_err(|_| DecryptError::CorruptedAudit)?;
+    let mut audit = serde_json::from_str::<Vec<AuditEvent>>(&json).map_err(|_| DecryptError::CorruptedAudit)?;
+
+    audit.extend(encrypted_journal.failed_unlocks.iter().map(|&time| AuditEvent {
+        time,
+        action: AuditAction::FailedUnlock,
+    }));
+    audit.sort_by_key(|event| event.time);
+    Ok(Some(audit))
+}
+
+/// decrypts the entries on the dates `wanted` gives `true` for, along with
+/// their metadata
+fn decrypt_entries<E: Encryptor + ?Sized>(
+    e: &E,
+    encrypted_journal: &EncryptedJournal,
+    keys: &Keys,
+    index: Option<SearchIndex>,
+    wanted: impl Fn(&Date) -> bool,
+) -> Result<State, DecryptError> {
+    let key = keys.key;
+    let mut entries = HashMap::new();
+    let mut meta = HashMap::new();
+    for entry in encrypted_journal.entries.iter().filter(|entry| wanted(&entry.date)) {
+        let _span = debug_span!("decrypt_entry", date = %entry.date).entered();
+        let (date, content) = match entry.public {
+            true => {
+                let content = String::from_utf8(entry.digest.clone()).map_err(|_| DecryptError::CorruptedEntry(entry.date))?;
+                (entry.date, content)
+            }
+            false => e.decrypt_journal_entry(key, entry)?,
+        };
+        if let Some(encrypted_meta) = &entry.meta {
+            meta.insert(date, decrypt_meta(e, key, encrypted_meta, &date)?);
+        }
+        if entry.public {
+            meta.entry(date).or_insert_with(EntryMeta::default).public = true;
+        }
+        entries.insert(date, content);
+    }
+
+    let hash_chain = encrypted_journal.chain.is_some();
+    let totp = encrypted_journal
+        .totp
+        .as_deref()
+        .map(|totp| decrypt_totp(e, key, totp))
+        .transpose()?;
+    let audit = decrypt_audit(e, key, encrypted_journal)?;
+
+    Ok(State {
+        password: keys.password.clone(),
+        entries,
+        hash_chain,
+        meta,
+        index,
+        totp,
+        audit,
+        keys: Some(keys.clone()),
+    })
 }
 
 /// A implementation-agnostic abstraction over methods for encrypting, decrypting, and hashing.
 pub trait Encryptor {
     /// A 1->1 hash function
-    fn hash_password<'a>(&self, password: &'a str) -> String;
+    fn hash_password(&self, password: &str) -> Result<String, EncryptError>;
     /// Verify password using hashed password
-    fn verify_password<'a>(
+    fn verify_password(
         &self,
-        hashed_password: &'a str,
-        entered_password: &'a str,
-    ) -> bool;
+        hashed_password: &str,
+        entered_password: &str,
+    ) -> Result<bool, DecryptError>;
     /// Use password to encrypt a journal entry
-    fn encrypt_journal_entry<'a>(
+    fn encrypt_journal_entry(
         &self,
         key: [u8; 32],
-        entry: &'a str,
+        entry: &str,
         date: &Date,
-    ) -> EncryptedEntry;
+    ) -> Result<EncryptedEntry, EncryptError>;
     /// Use password to decrypt a journal entry
-    fn decrypt_journal_entry<'a>(
+    fn decrypt_journal_entry(
         &self,
         key: [u8; 32],
-        entry: &'a EncryptedEntry,
-    ) -> (Date, String);
+        entry: &EncryptedEntry,
+    ) -> Result<(Date, String), DecryptError>;
     /// make a salt for a key-derivation function
     fn make_kdf_salt(&self) -> [u8; 32];
     /// generate a key for encryption
-    fn gen_key<'a>(&self, password: &'a str, kdf_salt: [u8; 32]) -> [u8; 32];
+    fn gen_key(&self, password: &str, kdf_salt: [u8; 32]) -> [u8; 32];
     /// Provided. Encrypt journal state.
-    fn encrypt_journal<'a>(&self, journal: &'a State) -> EncryptedJournal {
-        let password_hash = self.hash_password(&journal.password);
-        let kdf_salt = self.make_kdf_salt();
-        let key = self.gen_key(&journal.password, kdf_salt);
+    fn encrypt_journal(&self, journal: &State) -> Result<EncryptedJournal, EncryptError> {
+        self.encrypt_journal_with_progress(journal, &mut |_, _| {})
+    }
+    /// Provided. like [`Encryptor::encrypt_journal()`], but calls `progress`
+    /// after every entry with how many were encrypted so far and how many
+    /// there are
+    fn encrypt_journal_with_progress(
+        &self,
+        journal: &State,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<EncryptedJournal, EncryptError> {
+        let _span = info_span!("encrypt", entries = journal.entries.len()).entered();
 
-        let entries: HashSet<EncryptedEntry> = journal
+        // the password and key from unlocking can be used again, unless the
+        // password changed since
+        let (password_hash, kdf_salt, key) = match journal.keys.as_ref().filter(|keys| keys.password == journal.password) {
+            Some(keys) => (keys.password_hash.clone(), keys.kdf_salt, keys.key),
+            None => {
+                let password_hash = info_span!("hash_password")
+                    .in_scope(|| self.hash_password(&journal.password))?;
+                let kdf_salt = self.make_kdf_salt();
+                let key = info_span!("kdf").in_scope(|| self.gen_key(&journal.password, kdf_salt));
+                (password_hash, kdf_salt, key)
+            }
+        };
+
+        // entries with the same content are encrypted once, so the digest can
+        // be stored once (see `StoredJournal::blobs`)
+        let mut encrypted_contents = HashMap::<&str, EncryptedEntry>::new();
+        let total = journal.entries.len();
+        let mut entries = journal
             .entries
             .iter()
-            .map(|(date, entry)| {
-                self.encrypt_journal_entry(key, entry, date)
+            .enumerate()
+            .map(|(done, (date, entry))| {
+                progress(done, total);
+                debug_span!("encrypt_entry", %date).in_scope(|| {
+                    let public = journal.is_public(date);
+                    let mut encrypted = match encrypted_contents.get(entry.as_str()) {
+                        // public entries are stored as they are
+                        _ if public => EncryptedEntry {
+                            date: *date,
+                            nonce: [0; 12],
+                            digest: entry.as_bytes().to_vec(),
+                            chain: None,
+                            meta: None,
+                            public,
+                        },
+                        Some(encrypted) => EncryptedEntry {
+                            date: *date,
+                            ..encrypted.clone()
+                        },
+                        None => {
+                            let encrypted = self.encrypt_journal_entry(key, entry, date)?;
+                            encrypted_contents.insert(entry, encrypted.clone());
+                            encrypted
+                        }
+                    };
+                    if let Some(meta) = journal.meta.get(date).filter(|meta| !meta.is_empty()) {
+                        encrypted.meta = Some(encrypt_meta(self, key, meta, date)?);
+                    }
+                    Ok::<_, EncryptError>(encrypted)
+                })
             })
-            .collect();
+            .collect::<Result<Vec<EncryptedEntry>, _>>()?;
+        progress(total, total);
+
+        let chain = journal.hash_chain.then(|| {
+            entries.sort_by_key(|entry| entry.date);
+            info_span!("hash_chain").in_scope(|| link_entries(key, &mut entries))
+        });
 
-        EncryptedJournal {
+        let index = journal
+            .index
+            .as_ref()
+            .map(|index| info_span!("index").in_scope(|| encrypt_index(self, key, journal, index)))
+            .transpose()?;
+        let totp = journal
+            .totp
+            .as_deref()
+            .map(|secret| encrypt_totp(self, key, secret))
+            .transpose()?;
+        let audit = journal
+            .audit
+            .as_deref()
+            .map(|audit| encrypt_audit(self, key, audit))
+            .transpose()?;
+
+        Ok(EncryptedJournal {
             password_hash,
             kdf_salt,
-            entries,
-        }
+            entries: entries.into_iter().collect(),
+            chain,
+            index,
+            totp,
+            audit,
+            failed_unlocks: Vec::new(),
+        })
     }
     /// Provided. Decrypts stored journal into application state
-    fn decrypt_journal<'a>(
+    fn decrypt_journal(
         &self,
-        encrypted_journal: &'a EncryptedJournal,
-        password: &'a str,
+        encrypted_journal: &EncryptedJournal,
+        password: &str,
     ) -> Result<State, DecryptError> {
-        let password = password.to_string();
-
-        if !(self.verify_password(&encrypted_journal.password_hash, &password))
-        {
+        let _span = info_span!("decrypt", entries = encrypted_journal.entries.len()).entered();
+        let keys = self.unlock(encrypted_journal, password)?;
+        self.decrypt_with_keys(encrypted_journal, &keys, None)
+    }
+    /// Provided. Like [`Encryptor::decrypt_journal()`], but if the journal
+    /// has a [search index](State::index), only decrypts the entries it finds
+    /// for `query` (see [`SearchIndex::candidates()`]). without one, or if it
+    /// doesn't cover every entry, every entry is decrypted. the other entries
+    /// are missing from the result, so it should never be saved.
+    fn decrypt_matching(
+        &self,
+        encrypted_journal: &EncryptedJournal,
+        password: &str,
+        query: &Query,
+    ) -> Result<State, DecryptError> {
+        let _span = info_span!("decrypt_matching", entries = encrypted_journal.entries.len()).entered();
+        let keys = self.unlock(encrypted_journal, password)?;
+        self.decrypt_with_keys(encrypted_journal, &keys, Some(query))
+    }
+    /// Provided. Checks the password and derives the journal's key from it
+    fn unlock(&self, encrypted_journal: &EncryptedJournal, password: &str) -> Result<Keys, DecryptError> {
+        let verified = info_span!("verify_password")
+            .in_scope(|| self.verify_password(&encrypted_journal.password_hash, password))?;
+        if !verified {
             return Err(DecryptError::IncorrectPassword);
         }
 
         let kdf_salt = encrypted_journal.kdf_salt;
-        let key = self.gen_key(&password, kdf_salt);
+        let key = info_span!("kdf").in_scope(|| self.gen_key(password, kdf_salt));
 
-        let entries: HashMap<Date, String> = encrypted_journal
-            .entries
-            .iter()
-            .map(|entry| self.decrypt_journal_entry(key, entry))
-            .collect();
+        Ok(Keys {
+            password: password.into(),
+            password_hash: encrypted_journal.password_hash.clone(),
+            kdf_salt,
+            key,
+        })
+    }
+    /// Provided. Decrypts the journal with keys from [`Encryptor::unlock()`],
+    /// which can be kept from an earlier time as long as the journal was
+    /// saved with them since, skipping the password check and key derivation.
+    /// with a query, only the entries that might match are decrypted (see
+    /// [`Encryptor::decrypt_matching()`]).
+    fn decrypt_with_keys(
+        &self,
+        encrypted_journal: &EncryptedJournal,
+        keys: &Keys,
+        query: Option<&Query>,
+    ) -> Result<State, DecryptError> {
+        if keys.kdf_salt != encrypted_journal.kdf_salt || keys.password_hash != encrypted_journal.password_hash {
+            return Err(DecryptError::StaleKeys);
+        }
+        let key = keys.key;
 
-        Ok(State { password, entries })
+        let index = encrypted_journal
+            .index
+            .as_deref()
+            .map(|index| info_span!("decrypt_index").in_scope(|| decrypt_index(self, key, index)))
+            .transpose()?;
+        let candidates = query.and_then(|query| {
+            let dates = encrypted_journal.entries.iter().map(|entry| &entry.date);
+            let candidates = index
+                .as_ref()
+                .filter(|index| index.covers(dates))
+                .map(|index| index.candidates(query));
+            info!(candidates = candidates.as_ref().map(|candidates| candidates.len()), "searched index");
+            candidates
+        });
+
+        decrypt_entries(self, encrypted_journal, keys, index, |date| {
+            candidates.as_ref().is_none_or(|candidates| candidates.contains(date))
+        })
+    }
+    /// Provided. Checks that the journal's hash chain (see
+    /// [`State::hash_chain`]) still matches its entries, which someone without
+    /// the password can't fake
+    fn verify_chain(
+        &self,
+        encrypted_journal: &EncryptedJournal,
+        password: &str,
+    ) -> Result<ChainStatus, DecryptError> {
+        let Some(head) = encrypted_journal.chain else {
+            return Ok(ChainStatus::Missing);
+        };
+
+        if !self.verify_password(&encrypted_journal.password_hash, password)? {
+            return Err(DecryptError::IncorrectPassword);
+        }
+        let key = info_span!("kdf").in_scope(|| self.gen_key(password, encrypted_journal.kdf_salt));
+        let chain_key = chain_key(key);
+
+        let mut entries = encrypted_journal.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.date);
+
+        let mut previous = [0; 32];
+        for entry in &entries {
+            previous = chain_link(chain_key, previous, entry);
+            if entry.chain != Some(previous) {
+                return Ok(ChainStatus::BrokenAt(entry.date));
+            }
+        }
+
+        match chain_head(chain_key, previous, entries.len()) == head {
+            true => Ok(ChainStatus::Intact),
+            false => Ok(ChainStatus::Truncated),
+        }
     }
 }
 
 /// Bare bones implementation that satisfies [`Encryptor`]. Does not employ any hashing or
-/// encryption. **DO NOT USE IN PRODUCTION!**
+/// encryption, so tests don't wait on `bcrypt` and `pbkdf2`. **DO NOT USE IN PRODUCTION!**
+#[cfg(feature = "test-util")]
 pub struct ZeroSecurity;
 
+#[cfg(feature = "test-util")]
 impl Encryptor for ZeroSecurity {
-    fn gen_key<'a>(&self, _password: &'a str, _kdf_salt: [u8; 32]) -> [u8; 32] {
+    fn gen_key(&self, _password: &str, _kdf_salt: [u8; 32]) -> [u8; 32] {
         Default::default()
     }
     fn make_kdf_salt(&self) -> [u8; 32] {
         Default::default()
     }
-    fn hash_password<'a>(&self, password: &'a str) -> String {
-        password.into()
+    fn hash_password(&self, password: &str) -> Result<String, EncryptError> {
+        Ok(password.into())
     }
-    fn verify_password<'a>(
+    fn verify_password(
         &self,
-        hashed_password: &'a str,
-        entered_password: &'a str,
-    ) -> bool {
-        hashed_password == entered_password
+        hashed_password: &str,
+        entered_password: &str,
+    ) -> Result<bool, DecryptError> {
+        Ok(hashed_password == entered_password)
     }
-    fn encrypt_journal_entry<'a>(
+    fn encrypt_journal_entry(
         &self,
         _key: [u8; 32],
-        entry: &'a str,
+        entry: &str,
         date: &Date,
-    ) -> EncryptedEntry {
-        EncryptedEntry {
-            date: date.clone(),
+    ) -> Result<EncryptedEntry, EncryptError> {
+        Ok(EncryptedEntry {
+            date: *date,
             nonce: Default::default(),
             digest: entry.bytes().collect(),
-        }
+            chain: None,
+            meta: None,
+            public: false,
+        })
     }
-    fn decrypt_journal_entry<'a>(
+    fn decrypt_journal_entry(
         &self,
         _key: [u8; 32],
-        entry: &'a EncryptedEntry,
-    ) -> (Date, String) {
-        (
-            entry.date.clone(),
-            String::from_utf8(entry.digest.clone()).unwrap(),
-        )
+        entry: &EncryptedEntry,
+    ) -> Result<(Date, String), DecryptError> {
+        let content = String::from_utf8(entry.digest.clone())
+            .map_err(|_| DecryptError::CorruptedEntry(entry.date))?;
+        Ok((entry.date, content))
     }
 }
 
 /// [`Encryptor`] implementation that uses
 /// - [bcrypt](https://wikipedia.org/wiki/Bcrypt) for password hashing and verification
 /// - [aes-gcm-siv](https://wikipedia.org/wiki/AES-GCM-SIV) for content encryption (256-bit
-/// keylength)
+///   keylength)
 ///     - 96-bit nonce
 /// - [pbkdf2](https://wikipedia.org/wiki/PBKDF2) for key derivation
 ///     - 256-bit salt
 pub struct Secure;
 
+/// the number of pbkdf2 rounds [`Secure`] derives keys with
+pub const PBKDF2_ROUNDS: u32 = 100_000;
+
 impl Encryptor for Secure {
-    fn hash_password<'a>(&self, password: &'a str) -> String {
-        bcrypt::hash(password, DEFAULT_COST).unwrap()
+    fn hash_password(&self, password: &str) -> Result<String, EncryptError> {
+        Ok(bcrypt::hash(password, DEFAULT_COST)?)
     }
-    fn verify_password<'a>(
+    fn verify_password(
         &self,
-        hashed_password: &'a str,
-        entered_password: &'a str,
-    ) -> bool {
-        bcrypt::verify(entered_password, hashed_password).unwrap()
+        hashed_password: &str,
+        entered_password: &str,
+    ) -> Result<bool, DecryptError> {
+        Ok(bcrypt::verify(entered_password, hashed_password)?)
     }
-    fn gen_key<'a>(&self, password: &'a str, kdf_salt: [u8; 32]) -> [u8; 32] {
+    fn gen_key(&self, password: &str, kdf_salt: [u8; 32]) -> [u8; 32] {
         let mut key: [u8; 32] = [0u8; 32];
         pbkdf2_hmac::<Sha256>(
             password.as_bytes(),
             &kdf_salt,
-            100_000,
+            PBKDF2_ROUNDS,
             &mut key,
         );
         key
@@ -178,35 +663,43 @@ impl Encryptor for Secure {
         let mut rng = rand::thread_rng();
         rng.gen()
     }
-    fn encrypt_journal_entry<'a>(
+    fn encrypt_journal_entry(
         &self,
         key: [u8; 32],
-        entry: &'a str,
+        entry: &str,
         date: &Date,
-    ) -> EncryptedEntry {
+    ) -> Result<EncryptedEntry, EncryptError> {
         let mut rng = rand::thread_rng();
         let nonce: [u8; 12] = rng.gen();
-        let digest = self.aes_encrypt(&key, &nonce, entry);
+        let digest = self
+            .aes_encrypt(&key, &nonce, entry)
+            .ok_or(EncryptError::Entry(*date))?;
 
-        EncryptedEntry {
-            date: date.clone(),
+        Ok(EncryptedEntry {
+            date: *date,
             nonce,
             digest,
-        }
+            chain: None,
+            meta: None,
+            public: false,
+        })
     }
-    fn decrypt_journal_entry<'a>(
+    fn decrypt_journal_entry(
         &self,
         key: [u8; 32],
-        entry: &'a EncryptedEntry,
-    ) -> (Date, String) {
+        entry: &EncryptedEntry,
+    ) -> Result<(Date, String), DecryptError> {
         let EncryptedEntry {
             date,
             nonce,
             digest,
+            ..
         } = entry;
 
-        let cleartext = self.aes_decrypt(&key, nonce, digest.clone());
-        (date.clone(), cleartext)
+        let cleartext = self
+            .aes_decrypt(&key, nonce, digest)
+            .ok_or(DecryptError::CorruptedEntry(*date))?;
+        Ok((*date, cleartext))
     }
 }
 
@@ -216,24 +709,23 @@ impl Secure {
         key: &[u8; 32],
         nonce: &[u8; 12],
         cleartext: &str,
-    ) -> Vec<u8> {
-        let cipher = Aes256GcmSiv::new_from_slice(key).expect("Key is 256 bit");
+    ) -> Option<Vec<u8>> {
+        let cipher = Aes256GcmSiv::new(key.into());
         let nonce = Nonce::from_slice(nonce);
 
-        let ciphertext = cipher.encrypt(nonce, cleartext.as_bytes());
-        ciphertext.unwrap()
+        cipher.encrypt(nonce, cleartext.as_bytes()).ok()
     }
     fn aes_decrypt(
         &self,
         key: &[u8; 32],
         nonce: &[u8; 12],
-        ciphertext: Vec<u8>,
-    ) -> String {
-        let cipher = Aes256GcmSiv::new_from_slice(key).unwrap();
+        ciphertext: &[u8],
+    ) -> Option<String> {
+        let cipher = Aes256GcmSiv::new(key.into());
         let nonce = Nonce::from_slice(nonce);
 
-        let cleartext = cipher.decrypt(nonce, ciphertext.as_slice()).unwrap();
+        let cleartext = cipher.decrypt(nonce, ciphertext).ok()?;
 
-        String::from_utf8(cleartext).unwrap()
+        String::from_utf8(cleartext).ok()
     }
 }
@@ -0,0 +1,103 @@
+//! module for the exit codes `jrn` uses, so scripts can tell failures apart
+
+use std::io;
+
+use jrn::{archive::ArchiveError, backup::RestoreError, config::ConfigError, db::LoadError, encryptor::ChainStatus, private::PrivateError};
+
+use crate::ui::UiError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// why `jrn` exited unsuccessfully
+pub enum ExitCode {
+    /// anything not covered by another code
+    Failure = 1,
+    /// the password was incorrect
+    IncorrectPassword = 2,
+    /// a file that was asked for (a configuration file, password file, or
+    /// content file) doesn't exist
+    NotFound = 3,
+    /// the journal or configuration file couldn't be parsed
+    ParseError = 4,
+    /// the journal parsed, but its contents are corrupted
+    Corrupted = 5,
+    /// the user aborted a prompt (with `Esc` or `Ctrl+C`)
+    Aborted = 6,
+}
+
+impl ExitCode {
+    /// the name of the code, for reporting errors as JSON
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Failure => "failure",
+            Self::IncorrectPassword => "incorrect_password",
+            Self::NotFound => "not_found",
+            Self::ParseError => "parse_error",
+            Self::Corrupted => "corrupted",
+            Self::Aborted => "aborted",
+        }
+    }
+}
+
+fn for_io(e: &io::Error) -> ExitCode {
+    match e.kind() {
+        io::ErrorKind::NotFound => ExitCode::NotFound,
+        _ => ExitCode::Failure,
+    }
+}
+
+impl From<&ConfigError> for ExitCode {
+    fn from(value: &ConfigError) -> Self {
+        match value {
+            ConfigError::NotAccessible(_, e) => for_io(e),
+            ConfigError::ParseError(..) => Self::ParseError,
+            _ => Self::Failure,
+        }
+    }
+}
+
+impl From<&LoadError> for ExitCode {
+    fn from(value: &LoadError) -> Self {
+        match value {
+            LoadError::NotAccessible(e) => for_io(e),
+            LoadError::ParseError(_) => Self::ParseError,
+            LoadError::FromBase64Error(_) | LoadError::DecryptError(_) => Self::Corrupted,
+            LoadError::IncorrectPassword => Self::IncorrectPassword,
+        }
+    }
+}
+
+impl From<&ArchiveError> for ExitCode {
+    fn from(value: &ArchiveError) -> Self {
+        match value {
+            ArchiveError::NotAnArchive(_) | ArchiveError::UnsupportedVersion(_) => Self::ParseError,
+            ArchiveError::IncorrectPassword => Self::IncorrectPassword,
+            ArchiveError::Corrupted => Self::Corrupted,
+            ArchiveError::Journal(e) => e.into(),
+            ArchiveError::Encrypt(_) => Self::Failure,
+        }
+    }
+}
+
+impl From<&UiError> for ExitCode {
+    fn from(value: &UiError) -> Self {
+        match value {
+            UiError::Prompt(requestty::ErrorKind::IoError(_)) => Self::Failure,
+            UiError::Prompt(_) => Self::Aborted,
+            UiError::ReadFile(_, e) => for_io(e),
+            UiError::Load(e) => e.into(),
+            UiError::NoJournal(_) => Self::NotFound,
+            UiError::NotAJournal(..) => Self::ParseError,
+            UiError::Restore(RestoreError::Read(_, e)) => for_io(e),
+            UiError::Restore(RestoreError::Invalid(..)) => Self::ParseError,
+            UiError::BackupPassword => Self::IncorrectPassword,
+            UiError::Archive(e) => e.into(),
+            UiError::Private(PrivateError::IncorrectPassphrase) => Self::IncorrectPassword,
+            UiError::Private(PrivateError::Corrupted(_)) => Self::Corrupted,
+            UiError::IncorrectCode | UiError::TooManyAttempts => Self::IncorrectPassword,
+            UiError::UnlockCancelled => Self::Aborted,
+            UiError::Config(e) | UiError::InvalidConfig(_, e) => e.as_ref().into(),
+            UiError::Chain(ChainStatus::BrokenAt(_) | ChainStatus::Truncated) => Self::Corrupted,
+            _ => Self::Failure,
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! module for exporting a journal to other formats (see [`ExportFormat`])
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{Display, Write},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde_json::json;
+use thiserror::Error;
+
+use crate::{
+    date::{self, Date},
+    db::{State, StoredJournal},
+    encryptor::{EncryptError, Encryptor, Secure, PBKDF2_ROUNDS},
+    render::{Renderers, Target},
+    search::{self, Query},
+    storage::write_private,
+};
+
+/// the page of a web export, with `{{JOURNAL}}` where the entries go
+const WEB_TEMPLATE: &str = include_str!("../assets/web-export.html");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what a journal can be exported as
+pub enum ExportFormat {
+    /// a static website (a single `index.html`) to host anywhere. the entries
+    /// stay encrypted, and are only decrypted in the browser, after entering
+    /// the password.
+    Web,
+    /// the source of an [mdBook](https://rust-lang.github.io/mdBook/), with a
+    /// chapter for every year and month. the entries are written in plain
+    /// text.
+    Mdbook,
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Web => write!(f, "web"),
+            Self::Mdbook => write!(f, "mdbook"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`ExportFormat::from_str`] can return
+pub enum ExportFormatFromStrError {
+    /// Not the name of a format
+    #[error("expected an export format: web or mdbook")]
+    Unknown,
+}
+
+impl FromStr for ExportFormat {
+    type Err = ExportFormatFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "web" => Ok(Self::Web),
+            "mdbook" => Ok(Self::Mdbook),
+            _ => Err(ExportFormatFromStrError::Unknown),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// how exporting a journal could go wrong
+pub enum ExportError {
+    /// the file or directory at the given path couldn't be written
+    #[error("couldn't write {0}: {1}")]
+    Write(String, #[source] io::Error),
+    /// the journal couldn't be encrypted (see [`EncryptError`])
+    #[error("couldn't encrypt journal: {0}")]
+    Encrypt(#[from] EncryptError),
+    /// should never happen. for some reason, [`serde`] could not serialize
+    #[error("couldn't serialize journal: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// a copy of `state` with only the entries `query` finds (see
+/// [`search::search()`]), to export part of a journal in any format
+pub fn select(state: &State, query: &Query) -> State {
+    let found = search::search(state, query)
+        .into_iter()
+        .map(|hit| hit.date)
+        .collect::<HashSet<_>>();
+
+    let mut selected = state.clone();
+    // the search index has every word of the entries left out
+    selected.index = None;
+    let left_out = selected
+        .entries
+        .keys()
+        .filter(|date| !found.contains(date))
+        .copied()
+        .collect::<Vec<_>>();
+    for date in left_out {
+        selected.remove_entry(&date);
+    }
+    selected
+}
+
+#[derive(Debug, Default)]
+/// what [`export_with_progress()`] wrote, and the entries it couldn't
+pub struct Exported {
+    /// the files written
+    pub files: Vec<PathBuf>,
+    /// the entries that couldn't be written, and why. the rest were written
+    /// anyway.
+    pub failed: Vec<(Date, ExportError)>,
+}
+
+/// writes `state` into the directory `output` (creating it if needed) as
+/// `format`, with the built-in [`Renderers`], returning the files written.
+/// fails on the first entry that can't be written.
+pub fn export(state: &State, format: ExportFormat, output: &Path) -> Result<Vec<PathBuf>, ExportError> {
+    let mut exported = export_with_progress(state, format, output, &Renderers::new(), &mut |_, _| {})?;
+    match exported.failed.is_empty() {
+        true => Ok(exported.files),
+        false => Err(exported.failed.remove(0).1),
+    }
+}
+
+/// like [`export()`], but renders entries with `renderers` (for formats
+/// that render them), calls `progress` after every entry with how many are
+/// done and how many there are, and carries on past entries that can't be
+/// written. only the files every entry needs (like `SUMMARY.md`) stop it.
+pub fn export_with_progress(
+    state: &State,
+    format: ExportFormat,
+    output: &Path,
+    renderers: &Renderers,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<Exported, ExportError> {
+    fs::create_dir_all(output).map_err(|e| ExportError::Write(output.display().to_string(), e))?;
+
+    match format {
+        ExportFormat::Web => web(state, output, progress),
+        ExportFormat::Mdbook => mdbook(state, output, renderers, progress),
+    }
+}
+
+fn write(path: PathBuf, contents: &str) -> Result<PathBuf, ExportError> {
+    fs::write(&path, contents).map_err(|e| ExportError::Write(path.display().to_string(), e))?;
+    Ok(path)
+}
+
+/// like [`write()`], for files with decrypted entries (see
+/// [`write_private()`])
+fn write_decrypted(path: PathBuf, contents: &str) -> Result<PathBuf, ExportError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| ExportError::Write(parent.display().to_string(), e))?;
+    }
+    write_private(&path, contents.as_bytes()).map_err(|e| ExportError::Write(path.display().to_string(), e))?;
+    Ok(path)
+}
+
+/// the entries are encrypted with [`Secure`], like in a journal file, and the
+/// page's script decrypts them the same way. the password hash is left out,
+/// so there's nothing to check guesses against but the entries themselves.
+fn web(state: &State, output: &Path, progress: &mut dyn FnMut(usize, usize)) -> Result<Exported, ExportError> {
+    // the page only shows the entries, so their locations go in them
+    let mut state = state.clone();
+    for (date, content) in &mut state.entries {
+        if let Some(location) = state.meta.get(date).and_then(|meta| meta.location.as_deref()) {
+            *content = with_location(content, location);
+        }
+    }
+    let state = &state;
+    let stored = StoredJournal::from(Secure.encrypt_journal_with_progress(state, progress)?);
+
+    let mut entries = stored.entries.into_iter().collect::<Vec<_>>();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.date));
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            // the page doesn't know about shared digests
+            let digest = entry.blob.as_ref().and_then(|blob| stored.blobs.get(blob)).unwrap_or(&entry.digest);
+            match &entry.text {
+                Some(text) => json!({ "date": entry.date, "text": text }),
+                None => json!({ "date": entry.date, "nonce": entry.nonce, "digest": digest }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let journal = json!({
+        "kdf_salt": stored.kdf_salt,
+        "rounds": PBKDF2_ROUNDS,
+        "entries": entries,
+    });
+    // `</script>` inside the JSON would end the script early
+    let journal = serde_json::to_string(&journal)?.replace("</", "<\\/");
+
+    let page = WEB_TEMPLATE.replace("{{JOURNAL}}", &journal);
+    Ok(Exported {
+        files: vec![write(output.join("index.html"), &page)?],
+        failed: Vec::new(),
+    })
+}
+
+/// `book.toml`, and in `src/`, `SUMMARY.md` and a page for every year, month,
+/// and entry (like `2024/06/2024-06-01.md`), oldest first
+fn mdbook(
+    state: &State,
+    output: &Path,
+    renderers: &Renderers,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<Exported, ExportError> {
+    let mut years = BTreeMap::<i32, BTreeMap<u8, Vec<Date>>>::new();
+    for date in state.entries.keys() {
+        years
+            .entry(date.year())
+            .or_default()
+            .entry(date.month())
+            .or_default()
+            .push(*date);
+    }
+
+    let src = output.join("src");
+    let mut files = vec![write(output.join("book.toml"), "[book]\ntitle = \"jrn\"\nsrc = \"src\"\n")?];
+    let mut summary = String::from("# Summary\n\n");
+    let mut failed = Vec::new();
+    let mut done = 0;
+
+    for (year, months) in years {
+        let entries = months.values().map(Vec::len).sum::<usize>();
+        let _ = writeln!(summary, "- [{year}]({year}/README.md)");
+        files.push(write_decrypted(
+            src.join(format!("{year}/README.md")),
+            &format!("# {year}\n\n{entries} entries\n"),
+        )?);
+
+        for (month, mut dates) in months {
+            dates.sort();
+            let name = dates[0].month_name();
+            let _ = writeln!(summary, "  - [{name}]({year}/{month:02}/README.md)");
+            files.push(write_decrypted(
+                src.join(format!("{year}/{month:02}/README.md")),
+                &format!("# {name} {year}\n\n{} entries\n", dates.len()),
+            )?);
+
+            for date in dates {
+                let page = page_path(&date);
+                // not `Date::label()`, which can be relative to today
+                let title = format!("{date} ({})", date.weekday_name());
+                match write_decrypted(src.join(&page), &format!("# {title}\n\n{}\n", page_of(state, &date, renderers))) {
+                    Ok(file) => {
+                        let _ = writeln!(summary, "    - [{title}]({page})");
+                        files.push(file);
+                    }
+                    Err(e) => failed.push((date, e)),
+                }
+                done += 1;
+                progress(done, state.entries.len());
+            }
+        }
+    }
+
+    files.insert(1, write_decrypted(src.join("SUMMARY.md"), &summary)?);
+    Ok(Exported { files, failed })
+}
+
+/// where the page of the entry for `date` goes in an mdbook export, like
+/// `2024/06/2024-06-01.md`
+fn page_path(date: &Date) -> String {
+    // `:` isn't allowed in file names everywhere
+    format!("{}/{:02}/{}.md", date.year(), date.month(), date.to_string().replace(':', "-"))
+}
+
+/// an entry rendered as markdown (see [`Renderers`]), with where it was
+/// written, and the dates it mentions that have an entry linking to its page
+fn page_of(state: &State, date: &Date, renderers: &Renderers) -> String {
+    let content = renderers.render(&state.entries[date], state.file_type(date), Target::Markdown);
+    // pages are two directories deep, like `2024/06/2024-06-01.md`
+    let content = link_dates(&content, |mentioned| {
+        (mentioned != date && state.entries.contains_key(mentioned)).then(|| format!("../../{}", page_path(mentioned)))
+    });
+    match state.location(date) {
+        Some(location) => with_location(&content, location),
+        None => content,
+    }
+}
+
+/// `content` with the dates it mentions (see [`date::mentioned_dates()`])
+/// made into markdown links to what `link` gives back for them, leaving
+/// fenced code blocks and dates `link` has nothing for as they are
+fn link_dates(content: &str, link: impl Fn(&Date) -> Option<String>) -> String {
+    let mut linked = String::new();
+    let mut fenced = false;
+    for line in content.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+        fenced ^= fence;
+        if fenced || fence {
+            linked.push_str(line);
+            continue;
+        }
+
+        let mut rest = 0;
+        for (range, mentioned) in date::mentioned_dates(line) {
+            if let Some(target) = link(&mentioned) {
+                let _ = write!(linked, "{}[{}]({target})", &line[rest..range.start], &line[range.clone()]);
+                rest = range.end;
+            }
+        }
+        linked.push_str(&line[rest..]);
+    }
+    linked
+}
+
+/// `content` with `location` on a line of its own before it
+fn with_location(content: &str, location: &str) -> String {
+    format!("📍 {location}\n\n{content}")
+}
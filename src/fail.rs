@@ -1,12 +1,83 @@
-//! crate for the `fail!` macro
+//! crate for the `fail!`, `error!`, and `warn!` macros
 
+use std::sync::OnceLock;
+
+use serde_json::json;
+
+use crate::{exit::ExitCode, tr};
+
+/// the command errors are reported as JSON for (see [`set_json()`])
+static JSON: OnceLock<&'static str> = OnceLock::new();
+
+/// reports errors and warnings as JSON from now on, for a wrapper running
+/// `command` with `--output json` to read (see [`report()`])
+pub fn set_json(command: &'static str) {
+    let _ = JSON.set(command);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// how bad a reported message is
+pub enum Level {
+    /// something failed
+    Error,
+    /// something might be wrong, but `jrn` carries on
+    Warning,
+}
+
+/// prints `message` on stderr: in color after `error:` or `warning:`, or as a
+/// line of JSON (with the level, the name and number of `code` if it's
+/// failing, the message, and the command) if [`set_json()`] was called
+pub fn report(level: Level, code: Option<ExitCode>, message: &str) {
+    if let Some(command) = JSON.get() {
+        let mut report = json!({
+            "level": match level {
+                Level::Error => "error",
+                Level::Warning => "warning",
+            },
+            "message": message,
+            "context": { "command": command },
+        });
+        if let Some(code) = code {
+            report["code"] = json!(code.name());
+            report["exit_code"] = json!(code as i32);
+        }
+        eprintln!("{report}");
+        return;
+    }
+
+    let theme = jrn::theme::current();
+    match level {
+        Level::Error => eprintln!("{} {message}", theme.error.paint(tr!("error:"))),
+        Level::Warning => eprintln!("{} {message}", theme.warning.paint(tr!("warning:"))),
+    }
+}
 
 #[macro_export]
-/// print an error message and exit with code 1
+/// print an error message and exit with code 1, or with the given
+/// [`ExitCode`](crate::exit::ExitCode)
 macro_rules! fail {
     ($msg:expr) => {
-        let err = format!($msg);
-        eprintln!("{}", color_print::cformat!("<red,bold>error:</> {err}"));
-        std::process::exit(1);
+        $crate::fail!($crate::exit::ExitCode::Failure, $msg);
+    };
+    ($code:expr, $msg:expr) => {
+        let code: $crate::exit::ExitCode = $code;
+        $crate::fail::report($crate::fail::Level::Error, Some(code), &format!($msg));
+        std::process::exit(code as i32);
+    };
+}
+
+#[macro_export]
+/// print an error message and carry on
+macro_rules! error {
+    ($msg:expr) => {
+        $crate::fail::report($crate::fail::Level::Error, None, &format!($msg));
+    };
+}
+
+#[macro_export]
+/// print a warning message and carry on
+macro_rules! warn {
+    ($msg:expr) => {
+        $crate::fail::report($crate::fail::Level::Warning, None, &format!($msg));
     };
 }
@@ -0,0 +1,252 @@
+//! module for running user-configured hook commands (see
+//! [`Config::pre_save_hook`](crate::config::Config::pre_save_hook)) and
+//! calling webhooks (see
+//! [`Config::post_save_webhook`](crate::config::Config::post_save_webhook))
+
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    process::{Command, ExitStatus, Stdio},
+    time::Duration,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::date::Date;
+
+#[derive(Debug, Error)]
+/// how running a hook could go wrong
+pub enum HookError {
+    /// the shell could not be started
+    #[error("couldn't run hook: {0}")]
+    CouldntRun(#[source] std::io::Error),
+    /// the hook exited unsuccessfully, with the given exit code
+    #[error("hook exited with code {0}")]
+    Failed(i32),
+    /// the hook was killed by a signal
+    #[error("hook was killed by a signal")]
+    Killed,
+}
+
+/// `command` run with `sh -c`, with the environment variables of a hook
+fn hook_command(name: &str, command: &str, journal_path: &str, dates: &[Date]) -> Command {
+    let dates = dates
+        .iter()
+        .map(|date| date.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut hook = Command::new("sh");
+    hook.arg("-c")
+        .arg(command)
+        .env("JRN_HOOK", name)
+        .env("JRN_JOURNAL", journal_path)
+        .env("JRN_DATES", dates);
+    hook
+}
+
+fn check_status(status: ExitStatus) -> Result<(), HookError> {
+    match status.success() {
+        true => Ok(()),
+        false => match status.code() {
+            Some(code) => Err(HookError::Failed(code)),
+            None => Err(HookError::Killed),
+        },
+    }
+}
+
+/// runs `command` with `sh -c`. the hook can read
+/// - `$JRN_HOOK`: the name of the hook (for example `pre_save_hook`)
+/// - `$JRN_JOURNAL`: the path of the journal file
+/// - `$JRN_DATES`: the changed dates, separated by spaces
+pub fn run_hook(
+    name: &str,
+    command: &str,
+    journal_path: &str,
+    dates: &[Date],
+) -> Result<(), HookError> {
+    let status = hook_command(name, command, journal_path, dates)
+        .status()
+        .map_err(HookError::CouldntRun)?;
+
+    check_status(status)
+}
+
+/// like [`run_hook()`], but gives back what the hook printed, trimmed (for
+/// hooks like [`Config::location_hook`](crate::config::Config::location_hook)
+/// that answer something). what it prints to stderr is shown as usual.
+pub fn run_hook_output(
+    name: &str,
+    command: &str,
+    journal_path: &str,
+    dates: &[Date],
+) -> Result<String, HookError> {
+    let output = hook_command(name, command, journal_path, dates)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(HookError::CouldntRun)?;
+
+    check_status(output.status)?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().into())
+}
+
+/// like [`run_hook_output()`], but with `input` on the hook's stdin, and
+/// giving back the lines it printed (for hooks like
+/// [`Config::lint_hook`](crate::config::Config::lint_hook) that report
+/// problems). a hook that fails after printing something isn't an error, only
+/// one that fails without printing anything.
+pub fn run_hook_lines(
+    name: &str,
+    command: &str,
+    journal_path: &str,
+    dates: &[Date],
+    input: &str,
+) -> Result<Vec<String>, HookError> {
+    let mut hook = hook_command(name, command, journal_path, dates)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(HookError::CouldntRun)?;
+    // written from another thread, so a hook that prints a lot before reading
+    // all of it doesn't get stuck. a hook that doesn't read it at all is fine.
+    let mut stdin = hook.stdin.take();
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        if let Some(stdin) = &mut stdin {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let output = hook.wait_with_output().map_err(HookError::CouldntRun)?;
+    let _ = writer.join();
+
+    let lines = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    match lines.is_empty() {
+        true => check_status(output.status).map(|()| lines),
+        false => Ok(lines),
+    }
+}
+
+/// how long a webhook gets to answer
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+/// what a webhook is sent after a save: which entries changed and how many
+/// there are, never what's in them
+pub struct WebhookPayload<'a> {
+    /// the name of the webhook (for example `post_save_webhook`)
+    pub hook: &'a str,
+    /// the path of the journal file
+    pub journal: &'a str,
+    /// the changed dates
+    pub dates: &'a [Date],
+    /// how many entries changed
+    pub changed: usize,
+    /// how many entries the journal has
+    pub entries: usize,
+}
+
+#[derive(Debug, Error)]
+/// how calling a webhook could go wrong
+pub enum WebhookError {
+    /// the URL isn't an `http://` URL
+    #[error("invalid webhook URL {0} (it should look like http://host:port/path)")]
+    InvalidUrl(String),
+    /// the webhook couldn't be reached, or didn't answer in time
+    #[error("couldn't reach webhook: {0}")]
+    Unreachable(#[source] io::Error),
+    /// the webhook answered with something other than success
+    #[error("webhook answered {0}")]
+    Failed(String),
+}
+
+/// a webhook URL split into where to connect and what to ask for
+struct WebhookUrl<'a> {
+    host: &'a str,
+    port: u16,
+    /// the path and query, like `/api/webhook?id=1`
+    path: String,
+}
+
+fn parse_url(url: &str) -> Result<WebhookUrl<'_>, WebhookError> {
+    let invalid = || WebhookError::InvalidUrl(url.into());
+    let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+    // the fragment isn't sent
+    let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest);
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(end) => rest.split_at(end),
+        None => (rest, ""),
+    };
+    let path = match path.starts_with('/') {
+        true => path.to_string(),
+        false => format!("/{path}"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        // not an IPv6 address without a port, like `[::1]`
+        Some((host, port)) if !port.ends_with(']') => (host, port.parse().map_err(|_| invalid())?),
+        _ => (authority, 80),
+    };
+    if host.is_empty() || host.contains('@') {
+        return Err(invalid());
+    }
+    Ok(WebhookUrl { host, port, path })
+}
+
+/// checks that `url` is a URL [`post_webhook()`] can send to. only plain
+/// `http://` URLs are supported.
+pub fn check_webhook_url(url: &str) -> Result<(), WebhookError> {
+    parse_url(url).map(|_| ())
+}
+
+/// POSTs `payload` as JSON to `url`, giving up after [`WEBHOOK_TIMEOUT`]. any
+/// `2xx` answer counts as success.
+pub fn post_webhook(url: &str, payload: &WebhookPayload) -> Result<(), WebhookError> {
+    let url = parse_url(url)?;
+    // serializing a few dates and numbers can't fail
+    let body = serde_json::to_string(payload).unwrap_or_default();
+
+    let address = (url.host.trim_start_matches('[').trim_end_matches(']'), url.port)
+        .to_socket_addrs()
+        .map_err(WebhookError::Unreachable)?
+        .next()
+        .ok_or_else(|| WebhookError::Unreachable(io::ErrorKind::NotFound.into()))?;
+    let mut stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT).map_err(WebhookError::Unreachable)?;
+    stream.set_read_timeout(Some(WEBHOOK_TIMEOUT)).map_err(WebhookError::Unreachable)?;
+    stream.set_write_timeout(Some(WEBHOOK_TIMEOUT)).map_err(WebhookError::Unreachable)?;
+
+    let host = match url.port {
+        80 => url.host.to_string(),
+        port => format!("{}:{port}", url.host),
+    };
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: jrn/{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        url.path,
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+    );
+    stream.write_all(request.as_bytes()).map_err(WebhookError::Unreachable)?;
+
+    // only the status line matters, like `HTTP/1.1 204 No Content`
+    let mut answer = Vec::new();
+    let mut buffer = [0; 256];
+    while !answer.contains(&b'\n') {
+        match stream.read(&mut buffer).map_err(WebhookError::Unreachable)? {
+            0 => break,
+            read => answer.extend_from_slice(&buffer[..read]),
+        }
+    }
+    let answer = String::from_utf8_lossy(&answer);
+    let status_line = answer.lines().next().unwrap_or_default().trim();
+    match status_line.split_once(' ') {
+        Some((_, status)) if status.starts_with('2') => Ok(()),
+        Some((_, status)) => Err(WebhookError::Failed(status.into())),
+        None if status_line.is_empty() => Err(WebhookError::Unreachable(io::ErrorKind::UnexpectedEof.into())),
+        None => Err(WebhookError::Failed(status_line.into())),
+    }
+}
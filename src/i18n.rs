@@ -0,0 +1,120 @@
+//! module for translating prompts and messages. translations are gettext
+//! style `.po` files in `po/`, keyed by the English message, so a message
+//! without a translation is shown in English. see the [`tr!`](crate::tr)
+//! macro.
+
+use std::{collections::HashMap, fmt::Display, sync::OnceLock};
+
+/// the built-in translations, by language code
+const CATALOGS: &[(&str, &str)] = &[
+    ("de", include_str!("../po/de.po")),
+    ("es", include_str!("../po/es.po")),
+];
+
+static CATALOG: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// sets the language used for all prompts and messages, like `"es"` or
+/// `"es_MX.UTF-8"` (only the language part is used). without a language,
+/// it's taken from `$LC_ALL`, `$LC_MESSAGES`, or `$LANG`. can only be set once.
+///
+/// returns `false` if there are no translations for the language.
+pub fn set_language(language: Option<&str>) -> bool {
+    let language = match language {
+        Some(language) => language.to_string(),
+        None => ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default(),
+    };
+    let code = language
+        .split(['_', '-', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if matches!(code.as_str(), "" | "c" | "posix" | "en") {
+        return true;
+    }
+
+    let Some((_, po)) = CATALOGS.iter().find(|(name, _)| *name == code) else {
+        return false;
+    };
+    let _ = CATALOG.set(parse_po(po));
+    true
+}
+
+/// the translation of `msgid` in the current language, or `msgid` itself
+pub fn translate(msgid: &str) -> &str {
+    CATALOG
+        .get()
+        .and_then(|catalog| catalog.get(msgid))
+        .map_or(msgid, String::as_str)
+}
+
+/// replaces `{0}`, `{1}`, and so on in `message` with the given arguments
+pub fn format(message: &str, args: &[&dyn Display]) -> String {
+    let mut formatted = message.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        formatted = formatted.replace(&format!("{{{i}}}"), &arg.to_string());
+    }
+    formatted
+}
+
+#[macro_export]
+/// translates a message (see [`translate()`](crate::i18n::translate)). with
+/// arguments, `{0}`, `{1}`, and so on are replaced by them, wherever the
+/// translation puts them.
+macro_rules! tr {
+    ($msgid:literal) => {
+        $crate::i18n::translate($msgid)
+    };
+    ($msgid:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format(
+            $crate::i18n::translate($msgid),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+/// reads the `msgid`/`msgstr` pairs of a `.po` file. entries without a
+/// translation are left out.
+fn parse_po(po: &str) -> HashMap<String, String> {
+    let mut catalog = HashMap::new();
+    let mut msgid = None;
+
+    for line in po.lines().map(str::trim) {
+        if let Some(id) = line.strip_prefix("msgid ") {
+            msgid = Some(unquote(id));
+        } else if let Some(translation) = line.strip_prefix("msgstr ") {
+            let translation = unquote(translation);
+            if let Some(id) = msgid.take().filter(|_| !translation.is_empty()) {
+                catalog.insert(id, translation);
+            }
+        }
+    }
+
+    catalog
+}
+
+fn unquote(string: &str) -> String {
+    let string = string.trim();
+    let string = string.strip_prefix('"').unwrap_or(string);
+    let string = string.strip_suffix('"').unwrap_or(string);
+
+    let mut unquoted = String::new();
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unquoted.push('\n'),
+            Some('t') => unquoted.push('\t'),
+            Some(c) => unquoted.push(c),
+            None => {}
+        }
+    }
+    unquoted
+}
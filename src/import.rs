@@ -0,0 +1,180 @@
+//! module for importing journals kept in other formats (see
+//! [`ImportFormat`]), like one big text file with a date heading before every
+//! entry
+
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use regex::{Captures, Regex};
+use thiserror::Error;
+
+use crate::{date::Date, db::State};
+
+/// the heading [`plaintext()`] looks for if no other is given: a line
+/// starting with a date like `2024-06-01`
+pub const DEFAULT_DATE_PATTERN: &str = r"^(?<year>\d{4})-(?<month>\d{1,2})-(?<day>\d{1,2})\b";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// what a journal can be imported from
+pub enum ImportFormat {
+    /// a single text file, with a heading with the date before every entry
+    /// (see [`plaintext()`])
+    #[default]
+    Plaintext,
+}
+
+impl Display for ImportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plaintext => write!(f, "plaintext"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`ImportFormat::from_str`] can return
+pub enum ImportFormatFromStrError {
+    /// Not the name of a format
+    #[error("expected an import format: plaintext")]
+    Unknown,
+}
+
+impl FromStr for ImportFormat {
+    type Err = ImportFormatFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "plaintext" | "text" | "txt" => Ok(Self::Plaintext),
+            _ => Err(ImportFormatFromStrError::Unknown),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// how a date pattern could be wrong (see [`date_pattern()`])
+pub enum DatePatternError {
+    /// it isn't a regular expression
+    #[error("invalid date pattern: {0}")]
+    Invalid(#[from] regex::Error),
+    /// it has some of the `year`, `month`, and `day` groups, but not all
+    #[error("the date pattern needs all of the groups year, month, and day, or none of them")]
+    MissingGroups,
+}
+
+/// compiles a pattern for the headings of [`plaintext()`]. each line is
+/// matched on its own, so `^` is the start of a line. with named groups
+/// `year`, `month`, and `day` (the month can be a number or an English name,
+/// like `June` or `Jun`), the date is put together from them. without them,
+/// the whole match is read like a date given to `jrn` (like `2024-06-01`).
+pub fn date_pattern(pattern: &str) -> Result<Regex, DatePatternError> {
+    let regex = Regex::new(pattern)?;
+    let groups = ["year", "month", "day"]
+        .iter()
+        .filter(|group| regex.capture_names().flatten().any(|name| name == **group))
+        .count();
+    match groups {
+        0 | 3 => Ok(regex),
+        _ => Err(DatePatternError::MissingGroups),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// the entries found in a text file by [`plaintext()`]
+pub struct Plaintext {
+    /// the entries, by date. entries with the same date are joined with a
+    /// blank line between them.
+    pub entries: BTreeMap<Date, String>,
+    /// the text before the first heading, if there's any
+    pub preamble: String,
+    /// the headings that matched but aren't a real date (like `2023-02-30`),
+    /// with their line number (from 1). they're kept in the entry before them.
+    pub invalid: Vec<(usize, String)>,
+}
+
+impl Plaintext {
+    /// a journal with the entries, to merge into another (see
+    /// [`State::merge_from()`])
+    pub fn to_state(&self) -> State {
+        let mut state = State::new();
+        for (date, content) in &self.entries {
+            state.set_entry(date, content);
+        }
+        state
+    }
+}
+
+/// the date of a heading matched by `pattern` (see [`date_pattern()`])
+fn date_of(captures: &Captures) -> Option<Date> {
+    let (Some(year), Some(month), Some(day)) = (captures.name("year"), captures.name("month"), captures.name("day")) else {
+        return captures[0].trim().parse().ok();
+    };
+    let month = match month.as_str().parse::<u8>() {
+        Ok(month) => month,
+        Err(_) => month_of(month.as_str())?,
+    };
+    Date::from_ymd_opt(year.as_str().parse().ok()?, month, day.as_str().parse().ok()?)
+}
+
+/// the number of an English month name, or of its first three letters
+fn month_of(name: &str) -> Option<u8> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let name = name.to_lowercase();
+    let position = MONTHS.iter().position(|month| name.get(..3) == Some(month))?;
+    Some(position as u8 + 1)
+}
+
+/// adds an entry found by [`plaintext()`], without the blank lines around it
+fn add(entry: Option<(Date, Vec<&str>)>, found: &mut Plaintext) {
+    let Some((date, lines)) = entry else {
+        return;
+    };
+    let start = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(lines.len());
+    let end = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(start, |end| end + 1);
+    let content = lines[start..end].join("\n");
+    found
+        .entries
+        .entry(date)
+        .and_modify(|existing| {
+            existing.push_str("\n\n");
+            existing.push_str(&content);
+        })
+        .or_insert(content);
+}
+
+/// splits `text` into entries, starting a new one at every line `pattern`
+/// matches (see [`date_pattern()`]). what's left of the heading line after
+/// the date (without separators like `-` or `:`) is the first line of the
+/// entry. blank lines around entries are trimmed.
+pub fn plaintext(text: &str, pattern: &Regex) -> Plaintext {
+    let mut found = Plaintext::default();
+    let mut current: Option<(Date, Vec<&str>)> = None;
+    let mut preamble = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let heading = pattern
+            .captures(line)
+            .map(|captures| (date_of(&captures), captures.get(0).map_or(0, |heading| heading.end())));
+        match heading {
+            Some((Some(date), end)) => {
+                add(current.take(), &mut found);
+                let rest = line[end..].trim_matches([' ', '\t', '-', ':', '|', '–', '—', ',', '.', '=', '*', '#']);
+                let lines = match rest {
+                    "" => Vec::new(),
+                    rest => vec![rest],
+                };
+                current = Some((date, lines));
+            }
+            heading => {
+                if heading.is_some() {
+                    found.invalid.push((number + 1, line.trim().into()));
+                }
+                match &mut current {
+                    Some((_, lines)) => lines.push(line),
+                    None => preamble.push(line),
+                }
+            }
+        }
+    }
+    add(current, &mut found);
+
+    found.preamble = preamble.join("\n").trim().into();
+    found
+}
@@ -0,0 +1,127 @@
+//! module for the [`SearchIndex`], an inverted index of the words and `#tags`
+//! in every entry. it's stored encrypted in the journal, next to the entries,
+//! so [`search`](crate::search) can decrypt only the entries that might match
+//! instead of all of them.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    date::Date,
+    db::State,
+    search::{self, Query},
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+/// which entries have which words and tags. kept up to date whenever the
+/// journal is saved (see [`SearchIndex::update()`]).
+pub struct SearchIndex {
+    /// the dates of the entries with each word (in lowercase) or tag (in
+    /// lowercase, after a `#`)
+    pub words: BTreeMap<String, BTreeSet<Date>>,
+    /// the indexed entries, with a fingerprint of their content when they
+    /// were indexed, to tell which ones changed since
+    pub entries: BTreeMap<Date, u64>,
+}
+
+/// the words of `text`: runs of letters and digits, in lowercase
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.chars().flat_map(char::to_lowercase).collect())
+}
+
+/// a fingerprint of an entry's content, to notice when it changes
+fn fingerprint(content: &str) -> u64 {
+    let digest = Sha256::digest(content.as_bytes());
+    u64::from_le_bytes(digest[..8].try_into().expect("a SHA-256 digest has 32 bytes"))
+}
+
+impl SearchIndex {
+    /// indexes every entry of `state`
+    pub fn build(state: &State) -> Self {
+        let mut index = Self::default();
+        index.update(state);
+        index
+    }
+
+    /// indexes the entries of `state` that were added or changed since they
+    /// were last indexed, and forgets the ones that were removed. gives back
+    /// how many entries were (re)indexed.
+    pub fn update(&mut self, state: &State) -> usize {
+        let changed = state
+            .entries
+            .iter()
+            .filter(|(date, content)| self.entries.get(date) != Some(&fingerprint(content)))
+            .map(|(date, _)| *date)
+            .collect::<HashSet<_>>();
+        let removed = self
+            .entries
+            .keys()
+            .filter(|date| !state.entries.contains_key(date))
+            .copied()
+            .collect::<HashSet<_>>();
+        if changed.is_empty() && removed.is_empty() {
+            return 0;
+        }
+
+        for dates in self.words.values_mut() {
+            dates.retain(|date| !changed.contains(date) && !removed.contains(date));
+        }
+        for date in &removed {
+            self.entries.remove(date);
+        }
+
+        for date in &changed {
+            let content = &state.entries[date];
+            let tags = search::tags(content).into_iter().map(|tag| format!("#{tag}"));
+            for word in words(content).chain(tags) {
+                self.words.entry(word).or_default().insert(*date);
+            }
+            self.entries.insert(*date, fingerprint(content));
+        }
+        self.words.retain(|_, dates| !dates.is_empty());
+
+        changed.len()
+    }
+
+    /// whether the index covers exactly the entries on `dates`, so it can be
+    /// trusted to find them
+    pub fn covers<'a>(&self, dates: impl IntoIterator<Item = &'a Date>) -> bool {
+        let dates = dates.into_iter().collect::<BTreeSet<_>>();
+        dates.len() == self.entries.len() && dates.into_iter().eq(self.entries.keys())
+    }
+
+    /// the dates of the entries that might match `query`: every entry that
+    /// matches is in there, but some of them might not (they still need to be
+    /// checked with [`Query::matches()`])
+    pub fn candidates(&self, query: &Query) -> BTreeSet<Date> {
+        let mut dates = self
+            .entries
+            .keys()
+            .filter(|date| query.range.contains(date))
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        for tag in query.tags() {
+            let tagged = self.words.get(&format!("#{tag}"));
+            dates.retain(|date| tagged.is_some_and(|tagged| tagged.contains(date)));
+        }
+
+        // the text matches anywhere in a word, so look for every part of the
+        // query's words inside the indexed words
+        for part in query.words().iter().flat_map(|word| words(word).collect::<Vec<_>>()) {
+            let with_part = self
+                .words
+                .iter()
+                .filter(|(word, _)| !word.starts_with('#') && word.contains(&part))
+                .flat_map(|(_, dates)| dates)
+                .collect::<HashSet<_>>();
+            dates.retain(|date| with_part.contains(date));
+        }
+
+        dates
+    }
+}
@@ -0,0 +1,134 @@
+//! module for [`Journal`], the way to read and write a journal without
+//! dealing with how it's stored or encrypted
+
+use std::path::Path;
+
+use crate::{
+    date::Date,
+    db::{LoadError, SaveError, State},
+    encryptor::{Encryptor, Secure},
+};
+
+/// an unlocked journal, tied to the file it was opened from. changes are only
+/// written by [`Journal::save()`].
+pub struct Journal<E: Encryptor = Secure> {
+    path: String,
+    state: State,
+    encryptor: E,
+}
+
+impl Journal {
+    /// unlocks the journal at `path` with `password`, using the [`Secure`]
+    /// encryptor. fails if there is no journal there yet (see
+    /// [`JournalBuilder::create()`]).
+    pub fn open(path: impl Into<String>, password: &str) -> Result<Self, LoadError> {
+        Self::builder(path).password(password).open()
+    }
+
+    /// starts building a journal for `path`, for when the defaults of
+    /// [`Journal::open()`] aren't enough
+    pub fn builder(path: impl Into<String>) -> JournalBuilder {
+        JournalBuilder {
+            path: path.into(),
+            password: String::new(),
+            encryptor: Secure,
+            create: false,
+        }
+    }
+}
+
+impl<E: Encryptor> Journal<E> {
+    /// the path of the journal file
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// every entry, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = (&Date, &str)> {
+        let mut entries = self
+            .state
+            .entries
+            .iter()
+            .map(|(date, entry)| (date, entry.as_str()))
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(date, _)| *date);
+        entries.into_iter()
+    }
+
+    /// the entry for the given date, if there is one
+    pub fn entry(&self, date: &Date) -> Option<&str> {
+        self.state.entries.get(date).map(String::as_str)
+    }
+
+    /// creates or overwrites the entry for the given date
+    pub fn set(&mut self, date: &Date, content: &str) {
+        self.state.set_entry(date, content);
+    }
+
+    /// changes the password the journal is encrypted with the next time it's
+    /// saved
+    pub fn change_password(&mut self, password: &str) {
+        self.state.change_password(password);
+    }
+
+    /// encrypts the journal and writes it to its file
+    pub fn save(&self) -> Result<(), SaveError> {
+        self.state.save(&self.path, &self.encryptor)
+    }
+
+    /// the unlocked journal, for lower level access
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+}
+
+/// builds a [`Journal`] (see [`Journal::builder()`])
+pub struct JournalBuilder<E: Encryptor = Secure> {
+    path: String,
+    password: String,
+    encryptor: E,
+    create: bool,
+}
+
+impl<E: Encryptor> JournalBuilder<E> {
+    /// the password to unlock the journal with (or to encrypt a new journal
+    /// with)
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = password.into();
+        self
+    }
+
+    /// the [`Encryptor`] to use instead of [`Secure`]
+    pub fn encryptor<F: Encryptor>(self, encryptor: F) -> JournalBuilder<F> {
+        JournalBuilder {
+            path: self.path,
+            password: self.password,
+            encryptor,
+            create: self.create,
+        }
+    }
+
+    /// whether to start an empty journal if there isn't one at the path yet.
+    /// nothing is written until [`Journal::save()`].
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// unlocks (or creates) the journal
+    pub fn open(self) -> Result<Journal<E>, LoadError> {
+        let mut state = State::new();
+
+        if self.create && !Path::new(&self.path).exists() {
+            state.change_password(&self.password);
+        } else {
+            state.load(&self.path, &self.password, &self.encryptor)?;
+        }
+
+        Ok(Journal {
+            path: self.path,
+            state,
+            encryptor: self.encryptor,
+        })
+    }
+}
@@ -0,0 +1,50 @@
+//! a journal app. this library reads and writes encrypted journals, and
+//! resolves `jrn`'s configuration, without ever prompting or exiting, so it
+//! can be embedded in other tools. the `jrn` binary is the interactive front
+//! end.
+//!
+//! everything that touches the filesystem or runs commands is behind the `fs`
+//! feature (on by default). without it, the core (dates, the journal format,
+//! and encryption) compiles to `wasm32-unknown-unknown`, for example to read
+//! an exported journal in a browser with [`db::State::load_json()`].
+//!
+//! the `test-util` feature adds helpers for testing code that uses journals
+//! without touching the filesystem or waiting on real encryption (see
+//! `test_util`), and for faking the time (see `date::set_fake_now()`).
+#![warn(missing_docs)]
+
+pub mod archive;
+pub mod audit;
+#[cfg(feature = "fs")]
+pub mod backup;
+#[cfg(feature = "fs")]
+pub mod config;
+pub mod date;
+pub mod db;
+pub mod diff;
+pub mod encryptor;
+#[cfg(feature = "fs")]
+pub mod export;
+#[cfg(feature = "fs")]
+pub mod hooks;
+#[cfg(feature = "fs")]
+pub mod journal;
+pub mod import;
+pub mod index;
+pub mod links;
+pub mod lint;
+pub mod merge;
+pub mod mood;
+pub mod private;
+pub mod redact;
+pub mod render;
+pub mod retention;
+pub mod search;
+pub mod stats;
+pub mod storage;
+pub mod tags;
+pub mod template;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod theme;
+pub mod totp;
@@ -0,0 +1,84 @@
+//! module for links between entries, which turn the journal into a small
+//! wiki. `[[2024-05-01]]` in an entry links to the entry for that date (any
+//! date in an entry is already highlighted, but only links count here), and
+//! the entries linking to an entry are its backlinks (see [`Backlinks`]).
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use crate::{
+    date::{self, Date},
+    db::State,
+};
+
+/// the `[[YYYY-MM-DD]]` links (optionally with `THH:MM`) in `text`, along
+/// with where in the text they are, brackets included
+pub fn links(text: &str) -> Vec<(Range<usize>, Date)> {
+    let mut links = Vec::new();
+    let mut i = 0;
+    while let Some(found) = text[i..].find("[[") {
+        let start = i + found;
+        let Some(length) = text[start + 2..].find("]]") else {
+            break;
+        };
+        let inner = &text[start + 2..start + 2 + length];
+        match date::mentioned_dates(inner.trim()).as_slice() {
+            [(range, date)] if range.len() == inner.trim().len() => {
+                let end = start + 2 + length + 2;
+                links.push((start..end, *date));
+                i = end;
+            }
+            // `[[[2024-05-01]]` still has a link, one bracket in
+            _ => i = start + 1,
+        }
+    }
+    links
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// which entries link to which, to look up an entry's backlinks without going
+/// through every entry each time
+pub struct Backlinks {
+    /// the entries linking to every date that's linked to
+    linked_from: BTreeMap<Date, BTreeSet<Date>>,
+}
+
+impl Backlinks {
+    /// the backlinks of every entry in `state`
+    pub fn new(state: &State) -> Self {
+        let mut backlinks = Self::default();
+        for (date, content) in &state.entries {
+            backlinks.insert(date, content);
+        }
+        backlinks
+    }
+
+    /// adds the links in `content`, the entry for `from`. an entry linking to
+    /// itself doesn't count.
+    pub fn insert(&mut self, from: &Date, content: &str) {
+        for (_, to) in links(content) {
+            if to != *from {
+                self.linked_from.entry(to).or_default().insert(*from);
+            }
+        }
+    }
+
+    /// forgets the links from the entry for `from`, for example before
+    /// inserting it again after it was edited
+    pub fn remove(&mut self, from: &Date) {
+        self.linked_from.retain(|_, linked_from| {
+            linked_from.remove(from);
+            !linked_from.is_empty()
+        });
+    }
+
+    /// the entries linking to the entry for `date`, oldest first
+    pub fn to(&self, date: &Date) -> Vec<Date> {
+        self.linked_from
+            .get(date)
+            .map(|linked_from| linked_from.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,110 @@
+//! module for checking an entry for things left unfinished, like a `TODO` or
+//! a template placeholder nobody filled in, after it's edited and before it's
+//! saved (see [`lint()`])
+
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// a built-in check, turned on by listing it in `lint`
+pub enum Check {
+    /// lines with `TODO` or `FIXME` in them
+    Todo,
+    /// placeholders like `{{mood}}` left in, from a template
+    Placeholders,
+    /// markdown headings (`#`) with nothing under them
+    EmptyHeadings,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Todo => write!(f, "todo"),
+            Self::Placeholders => write!(f, "placeholders"),
+            Self::EmptyHeadings => write!(f, "empty_headings"),
+        }
+    }
+}
+
+impl FromStr for Check {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "todo" => Ok(Self::Todo),
+            "placeholders" => Ok(Self::Placeholders),
+            "empty_headings" => Ok(Self::EmptyHeadings),
+            _ => Err(s.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// something [`lint()`] found, with its line number (from 1) and the line,
+/// trimmed
+pub enum Finding {
+    /// a `TODO` or `FIXME`
+    Todo(usize, String),
+    /// a placeholder like `{{mood}}`
+    Placeholder(usize, String),
+    /// a heading with nothing under it
+    EmptyHeading(usize, String),
+    /// a line printed by `lint_hook`
+    Hook(String),
+}
+
+/// whether `word` is in `line` on its own, not as part of a longer word
+fn has_word(line: &str, word: &str) -> bool {
+    line.match_indices(word).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// the first placeholder in `line`, like `{{mood}}`
+fn placeholder(line: &str) -> Option<&str> {
+    let start = line.find("{{")?;
+    let end = line[start..].find("}}")? + start + 2;
+    Some(&line[start..end])
+}
+
+/// the level of a markdown heading, if `line` is one
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match (level, line[level..].chars().next()) {
+        (1..=6, None | Some(' ' | '\t')) => Some(level),
+        _ => None,
+    }
+}
+
+/// runs `checks` on `content`, giving back what they found in the order of
+/// the lines. a heading only counts as empty if there's nothing under it
+/// before the next heading of the same or a higher level.
+pub fn lint(content: &str, checks: &[Check]) -> Vec<Finding> {
+    let lines = content.lines().collect::<Vec<_>>();
+    let mut findings = Vec::new();
+
+    for (number, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        if checks.contains(&Check::Todo) && (has_word(line, "TODO") || has_word(line, "FIXME")) {
+            findings.push(Finding::Todo(number + 1, trimmed.into()));
+        }
+        if checks.contains(&Check::Placeholders) && placeholder(line).is_some() {
+            findings.push(Finding::Placeholder(number + 1, trimmed.into()));
+        }
+        if !checks.contains(&Check::EmptyHeadings) {
+            continue;
+        }
+        let Some(level) = heading_level(line) else {
+            continue;
+        };
+        let empty = lines[number + 1..]
+            .iter()
+            .take_while(|next| heading_level(next).is_none_or(|next| next > level))
+            .all(|next| next.trim().is_empty() || heading_level(next).is_some());
+        if empty {
+            findings.push(Finding::EmptyHeading(number + 1, trimmed.into()));
+        }
+    }
+
+    findings
+}
@@ -0,0 +1,87 @@
+//! module for keeping a journal to one `jrn` at a time: from when it's
+//! unlocked until it's saved, a `jrn` holds a lock on `<journal>.lock`, with
+//! its process id in it, so another one can't load the same entries, change
+//! them, and save over the first one's changes (see [`lock()`]). the lock is
+//! let go of when `jrn` exits, however it exits.
+
+use std::{
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{self, Write},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+use thiserror::Error;
+
+use crate::tr;
+
+#[derive(Debug, Error)]
+/// how locking a journal could go wrong
+pub enum LockError {
+    /// another `jrn`, with the given process id (if it's known), has the
+    /// journal open
+    #[error("{}", match .1 {
+        Some(pid) => tr!("{0} is open in another jrn (process {1}), try again when it's done", .0, pid),
+        None => tr!("{0} is open in another jrn, try again when it's done", .0),
+    })]
+    Held(String, Option<u32>),
+    /// the lock file couldn't be opened or locked
+    #[error("{}", tr!("couldn't lock {0}: {1}", .0, .1))]
+    Io(String, #[source] io::Error),
+}
+
+/// the lock on a journal, held until it's dropped
+pub struct JournalLock {
+    _file: File,
+}
+
+/// the file next to `journal` that's locked while it's open
+fn lock_path(journal: &str) -> PathBuf {
+    format!("{journal}.lock").into()
+}
+
+/// the process id written in `journal`'s lock file, if there is one
+fn read_pid(journal: &str) -> Option<u32> {
+    fs::read_to_string(lock_path(journal)).ok()?.trim().parse().ok()
+}
+
+/// locks `journal` for this `jrn`, or says which one has it
+pub fn lock(journal: &str) -> Result<JournalLock, LockError> {
+    let io_error = |e| LockError::Io(journal.into(), e);
+
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(lock_path(journal)).map_err(io_error)?;
+
+    // `jrn daemon status` takes the lock for a moment to see if it's held
+    let mut tries = 0;
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) if tries < 3 => {
+                tries += 1;
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(TryLockError::WouldBlock) => return Err(LockError::Held(journal.into(), read_pid(journal))),
+            Err(TryLockError::Error(e)) => return Err(io_error(e)),
+        }
+    }
+
+    file.set_len(0).map_err(io_error)?;
+    write!(file, "{}", std::process::id()).map_err(io_error)?;
+    Ok(JournalLock { _file: file })
+}
+
+/// the process id of the `jrn` that has `journal` open, if one does
+pub fn holder(journal: &str) -> Option<u32> {
+    let pid = read_pid(journal)?;
+    // the id is left behind when `jrn` exits, but the lock isn't
+    let file = File::open(lock_path(journal)).ok()?;
+    match file.try_lock_shared() {
+        Err(TryLockError::WouldBlock) => Some(pid),
+        _ => None,
+    }
+}
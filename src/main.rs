@@ -1,25 +1,58 @@
 //! a journal app
 #![warn(missing_docs)]
 
-use std::process::exit;
+use std::{io::IsTerminal, path::Path, process::exit};
 
-use cli::Arguments;
-use config::Config;
-use db::State;
-use encryptor::{Secure, ZeroSecurity};
-use ui::{app, AppResult};
+use cli::{Arguments, SubCommand};
+use jrn::{audit::AuditAction, backup, config::Config, date::{self, Date}, encryptor::Secure, hooks::{post_webhook, run_hook, WebhookPayload}, index::SearchIndex, stats::StatsOutput, storage, theme};
+use exit::ExitCode;
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+use ui::{app, config_command, AppResult};
 
-pub mod date;
-pub mod db;
-pub mod encryptor;
+pub mod accessible;
+pub mod agent;
+pub mod browse;
+pub mod calendar;
+pub mod crash;
+pub mod exit;
+pub mod editor;
 pub mod fail;
+pub mod i18n;
+pub mod lock;
+pub mod plugin;
+pub mod progress;
+pub mod signals;
 pub mod ui;
 pub mod cli;
-pub mod config;
+
+/// sets up logging to stderr. `$JRN_LOG` (an [`EnvFilter`] directive, like
+/// `debug` or `jrn::encryptor=trace`) wins over `--verbose`. every span logs
+/// how long it took when it closes.
+fn init_logging(verbose: bool) {
+    let filter = match EnvFilter::try_from_env("JRN_LOG") {
+        Ok(filter) => filter,
+        Err(_) if verbose => EnvFilter::new("jrn=info"),
+        Err(_) => EnvFilter::new("warn"),
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_writer(std::io::stderr)
+        .init();
+}
 
 fn main() {
+    crash::install();
     let args: Arguments = argh::from_env();
-    let config: Config = Config::get_config(&args);
+    init_logging(args.verbose);
+    // wrappers reading JSON get the errors as JSON too
+    if let Some(SubCommand::Stats(opts)) = &args.subcommand {
+        if opts.output == Some(StatsOutput::Json) {
+            fail::set_json("stats");
+        }
+    }
     if args.dump_default_config {
         let to_print = toml::to_string_pretty(&Config::default());
         if to_print.is_err() {
@@ -28,25 +61,285 @@ fn main() {
         println!("{}", to_print.unwrap());
         exit(0);
     }
-    let file = config.clone().file_path.unwrap_or("./jrn.json".into());
 
-    if let (Some(dont_loop), Some(do_loop)) = (config.dont_loop, config.do_loop) {
-        if dont_loop && do_loop {
-            fail!("can't both loop and not loop");
+    if let Some(SubCommand::Daemon(opts)) = &args.subcommand {
+        if let Err(e) = agent::run(opts) {
+            fail!("{e}");
+        }
+        exit(0);
+    }
+
+    signals::install();
+
+    if let Some(SubCommand::Config(opts)) = &args.subcommand {
+        if let Err(e) = config_command(&args, opts) {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+        exit(0);
+    }
+
+    let mut config = match ui::get_config(&args) {
+        Ok(resolved) => resolved.config,
+        Err(e) => {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+    };
+    if let Err(e) = ui::read_password_source(&args, &mut config) {
+        fail!(ExitCode::from(&e), "{e}");
+    }
+    let file = config.file_path.clone().unwrap_or("./jrn.json".into());
+
+    if let Err(e) = config.validate() {
+        fail!(ExitCode::from(&e), "{e}");
+    }
+
+    if !i18n::set_language(config.language.as_deref()) {
+        if let Some(language) = &config.language {
+            let message = tr!("no translations for {0}, using English", language);
+            warn!("{message}");
         }
     }
 
-    if config.password.is_some() && config.password_file.is_some() {
-        fail!("please give only one password");
+    if let Ok(Some(timezone)) = config.get_timezone() {
+        date::set_timezone(timezone);
     }
+    if let Ok(Some(locale)) = config.get_locale() {
+        date::set_locale(locale);
+    }
+    if let Some(hour) = config.day_rollover_hour {
+        date::set_day_rollover_hour(hour);
+    }
+    if let Some(relative_dates) = config.relative_dates {
+        date::set_relative_dates(relative_dates);
+    }
+    if let Ok(calendars) = config.get_calendars() {
+        date::set_calendars(calendars);
+    }
+    date::set_time_of_day(config.time_of_day.unwrap_or(false));
+    accessible::set_accessible(config.accessible == Some(true));
+    // `$NO_COLOR` wins over any configured theme
+    if std::env::var_os("NO_COLOR").is_none() {
+        if let Ok(Some(theme)) = config.get_theme() {
+            theme::set_theme(theme);
+        }
+    }
+
+    // relative dates (`today-1`) are resolved while parsing, which happens
+    // before the configured timezone is known, so parse them again
+    let args: Arguments = argh::from_env();
 
-    let mut state = ui::init(&config, &Secure);
+    // like ssh, complain about secrets other users can read
+    for path in [Some(file.as_str()), config.password_file.as_deref()].into_iter().flatten() {
+        if let Some(mode) = storage::shared_permissions(Path::new(path)) {
+            let message = tr!(
+                "{0} can be read by other users (permissions {1}), run `chmod 600 {0}`",
+                path,
+                format!("{mode:03o}")
+            );
+            warn!("{message}");
+        }
+    }
 
-    let app_result = app(&config, args.subcommand, &mut state);
-    if let AppResult::ChangedState = app_result {
-        let save = state.save(&file, &Secure);
+    if let Some(SubCommand::Init(_)) = &args.subcommand {
+        if let Err(e) = ui::init_journal(&config, &Secure) {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+        exit(0);
+    }
+
+    if let Some(SubCommand::Info(_)) = &args.subcommand {
+        if let Err(e) = ui::info(&config) {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+        exit(0);
+    }
+
+    // so a shell prompt can check for today's entry without unlocking the
+    // journal (or being asked for the password)
+    if let Some(SubCommand::ViewToday(opts)) = &args.subcommand {
+        if opts.exists {
+            match ui::today_exists(&config, opts) {
+                Ok(exists) => exit(if exists { 0 } else { 1 }),
+                Err(e) => {
+                    fail!(ExitCode::from(&e), "{e}");
+                }
+            }
+        }
+    }
+
+    // restoring replaces the journal without unlocking it, so it works even if
+    // the journal is broken
+    if let Some(SubCommand::Restore(opts)) = &args.subcommand {
+        if let Err(e) = ui::restore(&config, opts, &Secure) {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+        exit(0);
+    }
+
+    // archives hold the journal file as it is, so it isn't unlocked
+    if let Some(SubCommand::Archive(opts)) = &args.subcommand {
+        if let Err(e) = ui::archive(&config, &args, opts, &Secure) {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+        exit(0);
+    }
+
+    // a search only needs the entries the search index finds, so it doesn't
+    // have to decrypt the whole journal
+    if let Some(SubCommand::Search(opts)) = &args.subcommand {
+        if !ui::should_loop(&config, &args.subcommand) {
+            if let Err(e) = ui::quick_search(&config, opts, &Secure) {
+                fail!(ExitCode::from(&e), "{e}");
+            }
+            exit(0);
+        }
+    }
+
+    // held until `jrn` exits, so nothing else saves over what's saved here. a
+    // journal somewhere read-only can still be read without one.
+    let _lock = match Path::new(&file).exists().then(|| lock::lock(&file)) {
+        Some(Err(e @ lock::LockError::Held(..))) => {
+            fail!("{e}");
+        }
+        Some(Err(e)) => {
+            warn!("{e}");
+            None
+        }
+        Some(Ok(lock)) => Some(lock),
+        None => None,
+    };
+    let mut state = match ui::init(&config, &Secure) {
+        Ok(state) => state,
+        Err(e) => {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+    };
+    let old_entries = state.entries.clone();
+    let old_password = state.password.clone();
+    // the chain is added the next time the journal is saved
+    if config.hash_chain == Some(true) {
+        state.hash_chain = true;
+    }
+    // the index is built the next time the journal is saved
+    if config.search_index == Some(true) && state.index.is_none() {
+        state.index = Some(SearchIndex::default());
+    }
+
+    crash::set_journal(&file, &state);
+    let recovered = ui::recover_drafts(&file, &mut state);
+
+    let pruned = match &args.subcommand {
+        Some(SubCommand::Prune(_)) => false,
+        _ => ui::auto_prune(&config, &mut state, args.force).unwrap_or_else(|e| {
+            error!("{e}");
+            false
+        }),
+    };
+
+    let app_result = match app(&config, args.subcommand, &mut state, args.force) {
+        Ok(app_result) => app_result,
+        Err(e) => {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+    };
+    // the user can choose not to save what was changed while looping
+    let discarded = matches!(app_result, AppResult::DiscardedChanges);
+    if !discarded && (pruned || recovered || matches!(app_result, AppResult::ChangedState)) {
+        // changed, added, and removed entries
+        let mut changed_dates = state
+            .entries
+            .iter()
+            .filter(|(date, content)| old_entries.get(date) != Some(content))
+            .map(|(date, _)| *date)
+            .chain(old_entries.keys().filter(|date| !state.entries.contains_key(date)).copied())
+            .collect::<Vec<Date>>();
+        changed_dates.sort();
+
+        // new entries remember the file type they were written in, in case
+        // the default changes later, and every changed entry remembers when
+        let file_type = config.file_type.as_deref().unwrap_or(".md");
+        let now = chrono::Utc::now().timestamp();
+        for date in &changed_dates {
+            let new = !old_entries.contains_key(date);
+            if new && state.entries.contains_key(date) && state.file_type(date).is_none() {
+                state.set_file_type(date, Some(file_type));
+            }
+            if state.entries.contains_key(date) {
+                state.touch(date, now, new);
+            }
+        }
+
+        let checked = ui::check_frozen(&config, &changed_dates, args.force)
+            .and_then(|()| ui::seal_private(&config, &mut state, &changed_dates));
+        let checked = checked.and_then(|()| ui::check_entry_sizes(&config, &state, &changed_dates, args.force));
+        if let Err(e) = checked {
+            // what was written isn't lost
+            if let Some(path) = crash::keep_drafts() {
+                let message = tr!("the entries you edited were kept in {0}", path.display());
+                warn!("{message}");
+            }
+            fail!(ExitCode::from(&e), "{e}");
+        }
+
+        if let Some(hook) = &config.pre_save_hook {
+            if let Err(e) = run_hook("pre_save_hook", hook, &file, &changed_dates) {
+                let message = tr!("{0} failed, not saving: {1}", "pre_save_hook", e);
+                fail!("{message}");
+            }
+        }
+
+        if let Some(backup_config) = &config.backup {
+            if backup_config.is_enabled() {
+                if let Err(e) = backup::backup(&file, backup_config) {
+                    let message = tr!("{0} failed, not saving: {1}", "backup", e);
+                    fail!("{message}");
+                }
+            }
+        }
+
+        // before the journal, so a failed save leaves pruned entries in both
+        // journals rather than in neither
+        if let Err(e) = ui::save_archives() {
+            fail!(ExitCode::from(&e), "{e}");
+        }
+
+        state.record(AuditAction::Modified { dates: changed_dates.clone() });
+        let saving = signals::saving();
+        let changed_password = state.password != old_password;
+        let save = match changed_password {
+            true => ui::save_with_new_password(&file, &state),
+            false => state.save(&file, &Secure),
+        };
         if let Err(e) = save {
-            fail!("error saving: {e:?}");
+            let message = tr!("error saving: {0}", e);
+            fail!("{message}");
+        }
+        crash::saved(&file, &state);
+        drop(saving);
+        if changed_password {
+            if let Err(e) = ui::check_new_password(&file, &state) {
+                fail!(ExitCode::from(&e), "{e}");
+            }
+        }
+
+        if let Some(hook) = &config.post_save_hook {
+            if let Err(e) = run_hook("post_save_hook", hook, &file, &changed_dates) {
+                let message = tr!("{0} failed: {1}", "post_save_hook", e);
+                warn!("{message}");
+            }
+        }
+        if let Some(url) = &config.post_save_webhook {
+            let payload = WebhookPayload {
+                hook: "post_save_webhook",
+                journal: &file,
+                dates: &changed_dates,
+                changed: changed_dates.len(),
+                entries: state.entries.len(),
+            };
+            if let Err(e) = post_webhook(url, &payload) {
+                let message = tr!("{0} failed: {1}", "post_save_webhook", e);
+                warn!("{message}");
+            }
         }
     }
 }
@@ -0,0 +1,70 @@
+//! module for merging two versions of an entry by hand. the lines they share
+//! at the start and end are kept as they are, and the lines in between are
+//! put between conflict markers, the way `git` does:
+//!
+//! ```text
+//! <<<<<<< 2024-06-02
+//! (the first version)
+//! =======
+//! (the second version)
+//! >>>>>>> 2024-06-01
+//! ```
+//!
+//! the result is edited until the markers are gone (see [`has_markers()`]).
+
+/// the line before the first version
+pub const START: &str = "<<<<<<<";
+
+/// the line between the two versions
+pub const MIDDLE: &str = "=======";
+
+/// the line after the second version
+pub const END: &str = ">>>>>>>";
+
+/// `ours` and `theirs` in one text, with the lines that differ between
+/// conflict markers labelled `ours_label` and `theirs_label`. gives back
+/// `ours` if they have the same lines.
+pub fn with_markers(ours: &str, ours_label: &str, theirs: &str, theirs_label: &str) -> String {
+    let ours_lines = ours.lines().collect::<Vec<_>>();
+    let theirs_lines = theirs.lines().collect::<Vec<_>>();
+    if ours_lines == theirs_lines {
+        return ours.into();
+    }
+
+    let shortest = ours_lines.len().min(theirs_lines.len());
+    let prefix = ours_lines
+        .iter()
+        .zip(&theirs_lines)
+        .take_while(|(ours, theirs)| ours == theirs)
+        .count();
+    let suffix = ours_lines
+        .iter()
+        .rev()
+        .zip(theirs_lines.iter().rev())
+        .take(shortest - prefix)
+        .take_while(|(ours, theirs)| ours == theirs)
+        .count();
+
+    let mut lines = ours_lines[..prefix].to_vec();
+    let start = format!("{START} {ours_label}");
+    let end = format!("{END} {theirs_label}");
+    lines.push(&start);
+    lines.extend(&ours_lines[prefix..ours_lines.len() - suffix]);
+    lines.push(MIDDLE);
+    lines.extend(&theirs_lines[prefix..theirs_lines.len() - suffix]);
+    lines.push(&end);
+    lines.extend(&ours_lines[ours_lines.len() - suffix..]);
+
+    let mut merged = lines.join("\n");
+    merged.push('\n');
+    merged
+}
+
+/// whether `text` still has a line that's a conflict marker
+pub fn has_markers(text: &str) -> bool {
+    text.lines().any(|line| {
+        [START, MIDDLE, END]
+            .iter()
+            .any(|marker| line.strip_prefix(marker).is_some_and(|rest| rest.is_empty() || rest.starts_with(' ')))
+    })
+}
@@ -0,0 +1,81 @@
+//! module for the [`Mood`] an entry can be tagged with
+
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// the names of the moods, from 1 to 5
+const NAMES: [&str; 5] = ["awful", "bad", "okay", "good", "great"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+/// how the day went, from 1 (awful) to 5 (great). parsed from the number or
+/// its name (`awful`, `bad`, `okay`, `good`, or `great`).
+pub struct Mood(u8);
+
+impl Mood {
+    /// the lowest mood
+    pub const MIN: u8 = 1;
+    /// the highest mood
+    pub const MAX: u8 = 5;
+
+    /// the mood with the given value, if it's from 1 to 5
+    pub fn new(value: u8) -> Option<Self> {
+        (Self::MIN..=Self::MAX).contains(&value).then_some(Self(value))
+    }
+
+    /// the mood as a number from 1 to 5
+    pub fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// the name of the mood, like `good`
+    pub fn name(&self) -> &'static str {
+        NAMES[usize::from(self.0 - Self::MIN)]
+    }
+}
+
+impl Display for Mood {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl From<Mood> for u8 {
+    fn from(value: Mood) -> Self {
+        value.0
+    }
+}
+
+impl TryFrom<u8> for Mood {
+    type Error = MoodFromStrError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::new(value).ok_or(MoodFromStrError::Invalid)
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`Mood::from_str`] can return
+pub enum MoodFromStrError {
+    /// Not a number from 1 to 5 or the name of a mood
+    #[error("expected a mood from 1 to 5, or awful, bad, okay, good, or great")]
+    Invalid,
+}
+
+impl FromStr for Mood {
+    type Err = MoodFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if let Ok(value) = s.parse::<u8>() {
+            return value.try_into();
+        }
+        let s = if s == "ok" { "okay" } else { s.as_str() };
+
+        NAMES
+            .iter()
+            .position(|name| *name == s)
+            .and_then(|i| Self::new(i as u8 + Self::MIN))
+            .ok_or(MoodFromStrError::Invalid)
+    }
+}
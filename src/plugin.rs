@@ -0,0 +1,312 @@
+//! module for external commands. like git, `jrn <name>` runs `jrn-<name>`
+//! from `$PATH`, so other programs can extend `jrn` without changing it.
+//!
+//! the journal is unlocked before the command runs. the command can read
+//! - `$JRN_JOURNAL`: the path of the journal file
+//! - `$JRN_SOCKET`: a unix socket to read and write the unlocked journal through
+//! - `$JRN_TOKEN`: the token to send before any requests on the socket
+//!
+//! the socket takes one JSON request per line and answers each with one JSON
+//! line, either `{"ok": ...}` or `{"error": "..."}`:
+//! - `{"method": "list"}` gives every date with an entry, oldest first
+//! - `{"method": "get", "date": "2024-06-01"}` gives the entry, or `null`
+//! - `{"method": "set", "date": "2024-06-01", "content": "..."}` writes the entry
+//!
+//! entries that were set are saved once the command exits successfully. the
+//! socket only exists while the command runs, in a directory only the user can
+//! read.
+//!
+//! commands named `jrn-render-<file type>` aren't run by name: they render
+//! entries of that file type instead (see [`renderers()`]).
+
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    process::{Command, ExitStatus, Stdio},
+    sync::OnceLock,
+    thread,
+};
+
+use jrn::{
+    config::Config,
+    date::Date,
+    db::State,
+    render::{Renderer, Renderers, Target},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{cli::Plugin, tr, ui::AppResult, warn};
+
+#[derive(Debug, Error)]
+/// how running an external command could go wrong
+pub enum PluginError {
+    /// the command could not be started
+    #[error("{}", tr!("couldn't run jrn-{0}: {1}", .0, .1))]
+    CouldntRun(String, #[source] io::Error),
+    /// the socket for the command could not be set up
+    #[error("{}", tr!("couldn't set up the socket for jrn-{0}: {1}", .0, .1))]
+    Socket(String, #[source] io::Error),
+    /// the command exited unsuccessfully, with the given exit code
+    #[error("{}", tr!("jrn-{0} exited with code {1}", .0, .1))]
+    Failed(String, i32),
+    /// the command was killed by a signal
+    #[error("{}", tr!("jrn-{0} was killed by a signal", .0))]
+    Killed(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", rename_all = "lowercase")]
+/// a request sent over the socket
+enum Request {
+    List,
+    Get { date: Date },
+    Set { date: Date, content: String },
+}
+
+/// the names (without `jrn-`) of the external commands on `$PATH`, sorted.
+/// renderers (see [`renderers()`]) aren't commands of their own.
+pub fn find_plugins() -> Vec<String> {
+    let mut plugins = find_executables();
+    plugins.retain(|name| !name.starts_with(RENDERER_PREFIX));
+    plugins
+}
+
+/// the names (without `jrn-`) of the executables on `$PATH` starting with
+/// `jrn-`, sorted
+fn find_executables() -> Vec<String> {
+    let Some(path) = env::var_os("PATH") else {
+        return Vec::new();
+    };
+
+    let mut plugins = env::split_paths(&path)
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let file_name = entry.file_name().into_string().ok()?;
+            let name = file_name.strip_prefix("jrn-")?;
+            let name = name.strip_suffix(env::consts::EXE_SUFFIX).unwrap_or(name);
+            is_executable(&entry.metadata().ok()?).then(|| name.to_string())
+        })
+        .collect::<Vec<_>>();
+    plugins.sort();
+    plugins.dedup();
+    plugins
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    metadata.is_file()
+}
+
+/// what external renderers are called after `jrn-`, before the file type
+const RENDERER_PREFIX: &str = "render-";
+
+/// a renderer run as an external command, `jrn-render-<file type>` (see
+/// [`renderers()`])
+struct ExternalRenderer {
+    name: String,
+}
+
+impl Renderer for ExternalRenderer {
+    fn render(&self, content: &str, target: Target) -> Option<String> {
+        match run_renderer(&self.name, content, target) {
+            Ok(rendered) => Some(rendered),
+            Err(e) => {
+                warn!("{e}");
+                None
+            }
+        }
+    }
+}
+
+/// runs `jrn-<name>` with `content` on its stdin, giving back what it printed
+fn run_renderer(name: &str, content: &str, target: Target) -> Result<String, PluginError> {
+    let mut renderer = Command::new(format!("jrn-{name}"))
+        .env("JRN_RENDER_TARGET", target.to_string())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| PluginError::CouldntRun(name.into(), e))?;
+    // written from another thread, so a renderer that prints before reading
+    // all of it doesn't get stuck
+    let mut stdin = renderer.stdin.take();
+    let content = content.to_string();
+    let writer = thread::spawn(move || {
+        if let Some(stdin) = &mut stdin {
+            let _ = stdin.write_all(content.as_bytes());
+        }
+    });
+    let output = renderer
+        .wait_with_output()
+        .map_err(|e| PluginError::CouldntRun(name.into(), e))?;
+    let _ = writer.join();
+
+    match (output.status.success(), output.status.code()) {
+        (true, _) => Ok(String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').into()),
+        (false, Some(code)) => Err(PluginError::Failed(name.into(), code)),
+        (false, None) => Err(PluginError::Killed(name.into())),
+    }
+}
+
+/// the built-in renderers (see [`Renderers`]), and for every
+/// `jrn-render-<file type>` on `$PATH`, a renderer for that file type. it
+/// gets the entry on stdin and `$JRN_RENDER_TARGET` (`terminal` or
+/// `markdown`), and prints the entry rendered. if it fails, the built-in
+/// renderer is used. they're looked for once a run.
+pub fn renderers() -> &'static Renderers {
+    static RENDERERS: OnceLock<Renderers> = OnceLock::new();
+    RENDERERS.get_or_init(|| {
+        let mut renderers = Renderers::new();
+        for name in find_executables() {
+            if let Some(file_type) = name.strip_prefix(RENDERER_PREFIX) {
+                renderers.register(file_type, ExternalRenderer { name: name.clone() });
+            }
+        }
+        renderers
+    })
+}
+
+/// runs the external command, answering requests on its socket until it
+/// exits
+pub fn run(config: &Config, plugin: &Plugin, state: &mut State) -> Result<AppResult, PluginError> {
+    let journal = config.file_path.as_deref().unwrap_or("./jrn.json");
+    let mut command = Command::new(format!("jrn-{}", plugin.name));
+    command.args(&plugin.args).env("JRN_JOURNAL", journal);
+
+    let (status, changed) = serve(&plugin.name, &mut command, state)?;
+
+    match (status.success(), status.code()) {
+        (true, _) if changed => Ok(AppResult::ChangedState),
+        (true, _) => Ok(AppResult::DidntChangeState),
+        (false, Some(code)) => Err(PluginError::Failed(plugin.name.clone(), code)),
+        (false, None) => Err(PluginError::Killed(plugin.name.clone())),
+    }
+}
+
+/// runs `command` with a socket for it to reach `state` through. gives back
+/// how it exited and whether it set any entries.
+#[cfg(unix)]
+fn serve(name: &str, command: &mut Command, state: &mut State) -> Result<(ExitStatus, bool), PluginError> {
+    use std::{
+        os::unix::{
+            fs::DirBuilderExt,
+            net::{UnixListener, UnixStream},
+        },
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+    };
+
+    let socket_error = |e| PluginError::Socket(name.into(), e);
+
+    let dir = env::temp_dir().join(format!("jrn-{}", hex::encode(rand::random::<[u8; 8]>())));
+    fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&dir)
+        .map_err(socket_error)?;
+    let socket = dir.join("api.sock");
+    let token = hex::encode(rand::random::<[u8; 32]>());
+
+    let listener = match UnixListener::bind(&socket) {
+        Ok(listener) => listener,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(socket_error(e));
+        }
+    };
+    command.env("JRN_SOCKET", &socket).env("JRN_TOKEN", &token);
+
+    let done = AtomicBool::new(false);
+    let changed = AtomicBool::new(false);
+    let state = Mutex::new(state);
+
+    let status = thread::scope(|scope| {
+        scope.spawn(|| {
+            // one connection at a time, until woken up after the command exits
+            for stream in listener.incoming() {
+                if done.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+                let mut set = false;
+                let _ = handle(&stream, &token, &mut state, &mut set);
+                if set {
+                    changed.store(true, Ordering::SeqCst);
+                }
+            }
+        });
+
+        let status = command.status();
+        done.store(true, Ordering::SeqCst);
+        let _ = UnixStream::connect(&socket);
+        status
+    });
+
+    let _ = fs::remove_dir_all(&dir);
+    let status = status.map_err(|e| PluginError::CouldntRun(name.into(), e))?;
+    Ok((status, changed.into_inner()))
+}
+
+/// runs `command`. without unix sockets, it only gets `$JRN_JOURNAL`.
+#[cfg(not(unix))]
+fn serve(name: &str, command: &mut Command, _state: &mut State) -> Result<(ExitStatus, bool), PluginError> {
+    let status = command
+        .status()
+        .map_err(|e| PluginError::CouldntRun(name.into(), e))?;
+    Ok((status, false))
+}
+
+/// answers the requests on one connection, setting `changed` if any entries
+/// were set
+fn handle<S>(stream: S, token: &str, state: &mut State, changed: &mut bool) -> io::Result<()>
+where
+    S: io::Read + Write + Copy,
+{
+    let mut lines = BufReader::new(stream).lines();
+    let mut stream = stream;
+
+    match lines.next() {
+        Some(Ok(line)) if line.trim() == token => {}
+        _ => {
+            writeln!(stream, "{}", json!({ "error": "incorrect token" }))?;
+            return Ok(());
+        }
+    }
+
+    for line in lines {
+        let response = match serde_json::from_str::<Request>(&line?) {
+            Ok(Request::List) => {
+                let mut dates = state.entries.keys().collect::<Vec<_>>();
+                dates.sort();
+                json!({ "ok": dates })
+            }
+            Ok(Request::Get { date }) => json!({ "ok": state.get_entry(&date) }),
+            Ok(Request::Set { date, .. }) if state.is_locked(&date) => {
+                json!({ "error": tr!("the entry for {0} is locked (run `jrn unlock {0}` to change it)", date) })
+            }
+            Ok(Request::Set { date, content }) => {
+                state.set_entry(&date, &content);
+                *changed = true;
+                json!({ "ok": Value::Null })
+            }
+            Err(e) => json!({ "error": e.to_string() }),
+        };
+        writeln!(stream, "{response}")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,219 @@
+//! module for private sections: parts of an entry between a `:::private`
+//! line and a `:::` line, encrypted again with a passphrase of their own, so
+//! an entry can be shown or exported without them (see [`Passphrase`] and
+//! [`hide()`]). a sealed section is kept in the entry as one line, like
+//! `jrn-private:<salt>:<nonce>:<ciphertext>`, between the same fences.
+
+use std::collections::HashMap;
+
+use base64::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    date::Date,
+    db::{EncryptedEntry, State},
+    encryptor::{EncryptError, Encryptor},
+};
+
+/// the line a private section starts with
+pub const OPEN: &str = ":::private";
+/// the line a private section ends with
+pub const CLOSE: &str = ":::";
+/// what a private section is replaced with by [`hide()`]
+pub const MASK: &str = "[private]";
+/// what the line of a sealed section starts with
+const SEALED: &str = "jrn-private:";
+
+#[derive(Debug, Error)]
+/// how sealing or revealing a private section could go wrong
+pub enum PrivateError {
+    /// the passphrase doesn't open the section
+    #[error("incorrect private passphrase")]
+    IncorrectPassphrase,
+    /// the line of a sealed section isn't one
+    #[error("a private section in the entry for {0} is corrupted")]
+    Corrupted(Date),
+    /// the section couldn't be encrypted (see [`EncryptError`])
+    #[error("couldn't encrypt a private section: {0}")]
+    Encrypt(#[from] EncryptError),
+}
+
+/// whether `body` (the lines between the fences) is a sealed section
+fn is_sealed(body: &[&str]) -> bool {
+    matches!(body, [line] if line.trim().starts_with(SEALED))
+}
+
+/// `content` with every private section replaced by what `replace` makes of
+/// its body. a section without a `:::` line runs to the end of the entry.
+fn map_sections<E>(content: &str, mut replace: impl FnMut(&[&str]) -> Result<String, E>) -> Result<String, E> {
+    let mut lines = content.lines();
+    let mut kept = Vec::new();
+    while let Some(line) = lines.next() {
+        if line.trim() != OPEN {
+            kept.push(line.to_string());
+            continue;
+        }
+        let body = lines.by_ref().take_while(|line| line.trim() != CLOSE).collect::<Vec<_>>();
+        kept.push(replace(&body)?);
+    }
+
+    let mut mapped = kept.join("\n");
+    if content.ends_with('\n') {
+        mapped.push('\n');
+    }
+    Ok(mapped)
+}
+
+/// the bodies of the private sections in `content`
+fn sections(content: &str) -> Vec<Vec<&str>> {
+    let mut sections = Vec::new();
+    let mut lines = content.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == OPEN {
+            sections.push(lines.by_ref().take_while(|line| line.trim() != CLOSE).collect());
+        }
+    }
+    sections
+}
+
+/// whether `content` has private sections that aren't sealed yet
+pub fn has_unsealed(content: &str) -> bool {
+    sections(content).iter().any(|body| !is_sealed(body))
+}
+
+/// whether `content` has sealed private sections
+pub fn has_sealed(content: &str) -> bool {
+    sections(content).iter().any(|body| is_sealed(body))
+}
+
+/// `content` with every private section, sealed or not, replaced by [`MASK`]
+pub fn hide(content: &str) -> String {
+    let hidden: Result<_, ()> = map_sections(content, |_| Ok(MASK.into()));
+    hidden.unwrap_or_default()
+}
+
+/// a copy of `state` with the private sections of every entry hidden (see
+/// [`hide()`])
+pub fn hide_state(state: &State) -> State {
+    let mut hidden = state.clone();
+    for content in hidden.entries.values_mut() {
+        *content = hide(content);
+    }
+    hidden
+}
+
+/// a passphrase for private sections, and the keys made from it so far
+/// (making one takes a while on purpose)
+pub struct Passphrase {
+    passphrase: String,
+    /// the salt and key new sections are sealed with
+    sealing: Option<([u8; 32], [u8; 32])>,
+    /// the keys for the salts of the sections revealed so far
+    keys: HashMap<[u8; 32], [u8; 32]>,
+}
+
+impl Passphrase {
+    /// a passphrase, without any keys made from it yet
+    pub fn new(passphrase: &str) -> Self {
+        Self {
+            passphrase: passphrase.into(),
+            sealing: None,
+            keys: HashMap::new(),
+        }
+    }
+
+    fn key<E: Encryptor>(&mut self, kdf_salt: [u8; 32], e: &E) -> [u8; 32] {
+        *self
+            .keys
+            .entry(kdf_salt)
+            .or_insert_with(|| e.gen_key(&self.passphrase, kdf_salt))
+    }
+
+    /// `content` with the private sections that aren't sealed yet encrypted
+    /// with the passphrase. sealed ones are left as they are.
+    pub fn seal<E: Encryptor>(&mut self, content: &str, date: &Date, e: &E) -> Result<String, PrivateError> {
+        map_sections(content, |body| {
+            if is_sealed(body) {
+                return Ok(format!("{OPEN}\n{}\n{CLOSE}", body[0].trim()));
+            }
+            let (kdf_salt, key) = match self.sealing {
+                Some(sealing) => sealing,
+                None => {
+                    let kdf_salt = e.make_kdf_salt();
+                    let sealing = (kdf_salt, self.key(kdf_salt, e));
+                    *self.sealing.insert(sealing)
+                }
+            };
+            let encrypted = e.encrypt_journal_entry(key, &body.join("\n"), date)?;
+            Ok(format!(
+                "{OPEN}\n{SEALED}{}:{}:{}\n{CLOSE}",
+                BASE64_STANDARD.encode(kdf_salt),
+                BASE64_STANDARD.encode(encrypted.nonce),
+                BASE64_STANDARD.encode(encrypted.digest)
+            ))
+        })
+    }
+
+    /// the text of a sealed section's line
+    fn open<E: Encryptor>(&mut self, sealed: &str, date: &Date, e: &E) -> Result<String, PrivateError> {
+        let corrupted = || PrivateError::Corrupted(*date);
+        let parts = sealed
+            .trim()
+            .trim_start_matches(SEALED)
+            .split(':')
+            .map(|part| BASE64_STANDARD.decode(part).map_err(|_| corrupted()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [kdf_salt, nonce, digest] = <[Vec<u8>; 3]>::try_from(parts).map_err(|_| corrupted())?;
+        let encrypted = EncryptedEntry {
+            date: *date,
+            nonce: nonce.try_into().map_err(|_| corrupted())?,
+            digest,
+            chain: None,
+            meta: None,
+            public: false,
+        };
+
+        let key = self.key(kdf_salt.try_into().map_err(|_| corrupted())?, e);
+        let (_, text) = e
+            .decrypt_journal_entry(key, &encrypted)
+            .map_err(|_| PrivateError::IncorrectPassphrase)?;
+        Ok(text)
+    }
+
+    /// `content` with the sealed private sections decrypted, still between
+    /// their fences, so sealing it again gives the same text
+    pub fn reveal<E: Encryptor>(&mut self, content: &str, date: &Date, e: &E) -> Result<String, PrivateError> {
+        map_sections(content, |body| {
+            let text = match is_sealed(body) {
+                true => self.open(body[0], date, e)?,
+                false => body.join("\n"),
+            };
+            Ok(format!("{OPEN}\n{text}\n{CLOSE}"))
+        })
+    }
+
+    /// a copy of `state` with the private sections of every entry revealed
+    /// (see [`Passphrase::reveal()`])
+    pub fn reveal_state<E: Encryptor>(&mut self, state: &State, e: &E) -> Result<State, PrivateError> {
+        let mut revealed = state.clone();
+        for (date, content) in &mut revealed.entries {
+            *content = self.reveal(content, date, e)?;
+        }
+        Ok(revealed)
+    }
+
+    /// checks the passphrase against the first sealed section in `state`.
+    /// gives back whether there was one to check against.
+    pub fn check<E: Encryptor>(&mut self, state: &State, e: &E) -> Result<bool, PrivateError> {
+        let sealed = state.entries.iter().find_map(|(date, content)| {
+            sections(content)
+                .into_iter()
+                .find(|body| is_sealed(body))
+                .map(|body| (*date, body[0]))
+        });
+        match sealed {
+            Some((date, sealed)) => self.open(sealed, &date, e).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
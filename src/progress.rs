@@ -0,0 +1,94 @@
+//! module for showing how far along a long operation is (like exporting or
+//! re-encrypting every entry), and for collecting what failed along the way,
+//! so one bad entry or file doesn't stop the rest (see [`Progress`])
+
+use std::{
+    fmt::Display,
+    io::{stderr, IsTerminal, Write},
+};
+
+use crate::error;
+
+/// how many characters wide the bar is
+const WIDTH: usize = 24;
+
+/// a progress bar on stderr (only if it's a terminal), and the failures so far
+pub struct Progress {
+    message: String,
+    done: usize,
+    total: usize,
+    /// the percentage last drawn, so the bar is only drawn again once it moves
+    drawn: Option<usize>,
+    show: bool,
+    failures: Vec<(String, String)>,
+}
+
+impl Progress {
+    /// a bar for `total` items, with `message` (translated already) before it
+    pub fn new(message: &str, total: usize) -> Self {
+        Self {
+            message: message.into(),
+            done: 0,
+            total,
+            drawn: None,
+            show: stderr().is_terminal(),
+            failures: Vec::new(),
+        }
+    }
+
+    /// sets how many of how many items are done, and draws the bar if it
+    /// moved
+    pub fn set(&mut self, done: usize, total: usize) {
+        self.total = total;
+        self.done = done.min(total);
+        let percent = match total {
+            0 => 100,
+            total => self.done * 100 / total,
+        };
+        if !self.show || self.drawn == Some(percent) {
+            return;
+        }
+        self.drawn = Some(percent);
+        let filled = WIDTH * percent / 100;
+        eprint!(
+            "\r{} [{}{}] {}/{}",
+            self.message,
+            "#".repeat(filled),
+            "-".repeat(WIDTH - filled),
+            self.done,
+            self.total
+        );
+        let _ = stderr().flush();
+    }
+
+    /// one more item is done
+    pub fn advance(&mut self) {
+        self.set(self.done + 1, self.total);
+    }
+
+    /// notes that `item` failed because of `error`. it still counts as done.
+    pub fn fail(&mut self, item: impl Display, error: impl Display) {
+        self.failures.push((item.to_string(), error.to_string()));
+        self.advance();
+    }
+
+    /// takes the bar off its line, so something else can be printed. it's
+    /// drawn again on the next update.
+    pub fn clear(&mut self) {
+        if self.show && self.drawn.take().is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+
+    /// leaves the bar where it is and prints what failed, if anything, one
+    /// item a line. returns how many items failed.
+    pub fn finish(self) -> usize {
+        if self.show && self.drawn.is_some() {
+            eprintln!();
+        }
+        for (item, e) in &self.failures {
+            error!("{item}: {e}");
+        }
+        self.failures.len()
+    }
+}
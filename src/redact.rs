@@ -0,0 +1,115 @@
+//! module for redacting a journal before sharing it, like a travel journal
+//! without the personal parts (see [`Redaction`]). entries are split into
+//! paragraphs at blank lines, and paragraphs with a redacted `#tag` are
+//! masked. entries with nothing left are left out.
+
+use crate::{db::State, search};
+
+/// what masked text is replaced with
+pub const MASK: &str = "[redacted]";
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// what to take out of the entries
+pub struct Redaction {
+    /// paragraphs with any of these tags (in lowercase, without the `#`) are
+    /// masked
+    pub tags: Vec<String>,
+    /// these phrases are masked wherever they appear, ignoring case
+    pub phrases: Vec<String>,
+}
+
+impl Redaction {
+    /// a redaction of paragraphs with any of `tags` (with or without the `#`)
+    pub fn tags<'a>(tags: impl IntoIterator<Item = &'a str>) -> Self {
+        let tags = tags
+            .into_iter()
+            .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        Self {
+            tags,
+            ..Self::default()
+        }
+    }
+
+    /// reads a pattern file: a `#tag` or a phrase on every line. blank lines
+    /// are skipped.
+    pub fn parse(patterns: &str) -> Self {
+        let mut redaction = Self::default();
+        for line in patterns.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            match line.strip_prefix('#') {
+                Some(tag) => redaction.tags.extend(Self::tags([tag]).tags),
+                None => redaction.phrases.push(line.to_lowercase()),
+            }
+        }
+        redaction
+    }
+
+    /// adds the tags and phrases of `other`
+    pub fn extend(&mut self, other: Self) {
+        self.tags.extend(other.tags);
+        self.phrases.extend(other.phrases);
+    }
+
+    /// whether there's nothing to redact
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.phrases.is_empty()
+    }
+
+    /// `content` with the paragraphs with a redacted tag replaced by
+    /// [`MASK`], and the phrases masked in the rest. `None` if every
+    /// paragraph was masked.
+    pub fn redact(&self, content: &str) -> Option<String> {
+        let mut kept = false;
+        let paragraphs = content
+            .split("\n\n")
+            .map(|paragraph| {
+                let tags = search::tags(paragraph);
+                if self.tags.iter().any(|tag| tags.contains(tag)) {
+                    return MASK.to_string();
+                }
+                kept |= !paragraph.trim().is_empty();
+                self.mask_phrases(paragraph)
+            })
+            .collect::<Vec<_>>();
+
+        kept.then(|| paragraphs.join("\n\n"))
+    }
+
+    /// `text` with every phrase replaced by [`MASK`]
+    fn mask_phrases(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for phrase in self.phrases.iter().filter(|phrase| !phrase.is_empty()) {
+            let mut masked = String::new();
+            let mut rest = text.as_str();
+            while let Some(found) = search::find_ignore_case(rest, phrase) {
+                masked.push_str(&rest[..found.start]);
+                masked.push_str(MASK);
+                rest = &rest[found.end..];
+            }
+            masked.push_str(rest);
+            text = masked;
+        }
+        text
+    }
+
+    /// a copy of `state` with its entries redacted. entries with nothing left
+    /// are left out, along with their metadata.
+    pub fn apply(&self, state: &State) -> State {
+        let mut redacted = state.clone();
+        // the search index has every word of the entries
+        redacted.index = None;
+
+        let mut removed = Vec::new();
+        for (date, content) in &mut redacted.entries {
+            match self.redact(content) {
+                Some(kept) => *content = kept,
+                None => removed.push(*date),
+            }
+        }
+        for date in removed {
+            redacted.remove_entry(&date);
+        }
+        redacted
+    }
+}
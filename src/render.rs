@@ -0,0 +1,370 @@
+//! module for rendering entries by the file type they were written in (see
+//! [`State::file_type()`](crate::db::State::file_type)): markdown, org, and
+//! plain text are built in, and more can be registered (see [`Renderers`]).
+//! an entry can be rendered for a terminal, or as markdown for an export (see
+//! [`Target`]).
+
+use std::{collections::BTreeMap, fmt::Display, sync::OnceLock};
+
+use regex::{Captures, Regex};
+
+use crate::theme::{self, Style};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// what an entry is rendered for
+pub enum Target {
+    /// text with escape codes, for `jrn view` on a terminal
+    Terminal,
+    /// markdown, for exports like the mdBook
+    Markdown,
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Terminal => write!(f, "terminal"),
+            Self::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+/// something that renders entries of a file type
+pub trait Renderer: Send + Sync {
+    /// `content` rendered for `target`, or `None` if it can't be (the
+    /// renderer registered before it is tried next)
+    fn render(&self, content: &str, target: Target) -> Option<String>;
+}
+
+/// renders markdown: as it is for exports, and with headings, emphasis,
+/// lists, and quotes styled for a terminal
+pub struct Markdown;
+
+/// renders org: converted to markdown for exports, and with headings,
+/// emphasis, lists, and links styled for a terminal
+pub struct Org;
+
+/// renders plain text: as it is for a terminal, and in a code block for
+/// exports, so it's shown as it was written
+pub struct Plain;
+
+/// the renderers for every file type. file types are given like
+/// [`State::file_type()`](crate::db::State::file_type) has them (like `.md`
+/// or `md`), and ones without a renderer are rendered as [`Plain`] text.
+pub struct Renderers {
+    renderers: BTreeMap<String, Vec<Box<dyn Renderer>>>,
+}
+
+/// `file_type` without the `.`, in lowercase
+fn extension(file_type: &str) -> String {
+    file_type.trim().trim_start_matches('.').to_lowercase()
+}
+
+impl Renderers {
+    /// the built-in renderers: [`Markdown`] for `md` and `markdown`, [`Org`]
+    /// for `org`, and [`Plain`] for `txt`
+    pub fn new() -> Self {
+        let mut renderers = Self { renderers: BTreeMap::new() };
+        renderers.register("md", Markdown);
+        renderers.register("markdown", Markdown);
+        renderers.register("org", Org);
+        renderers.register("txt", Plain);
+        renderers
+    }
+
+    /// renders `file_type` with `renderer` from now on. if it can't render
+    /// something, the renderer registered before it for `file_type` is tried.
+    pub fn register(&mut self, file_type: &str, renderer: impl Renderer + 'static) {
+        self.renderers
+            .entry(extension(file_type))
+            .or_default()
+            .push(Box::new(renderer));
+    }
+
+    /// the file types with a renderer, without the `.`
+    pub fn file_types(&self) -> impl Iterator<Item = &str> {
+        self.renderers.keys().map(String::as_str)
+    }
+
+    /// `content`, written as `file_type` (markdown if there isn't one),
+    /// rendered for `target`
+    pub fn render(&self, content: &str, file_type: Option<&str>, target: Target) -> String {
+        let extension = extension(file_type.unwrap_or(".md"));
+        self.renderers
+            .get(&extension)
+            .into_iter()
+            .flatten()
+            .rev()
+            .find_map(|renderer| renderer.render(content, target))
+            .unwrap_or_else(|| plain(content, &extension, target))
+    }
+}
+
+impl Default for Renderers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Renderer for Markdown {
+    fn render(&self, content: &str, target: Target) -> Option<String> {
+        match target {
+            Target::Markdown => Some(content.into()),
+            Target::Terminal => Some(markdown_for_terminal(content)),
+        }
+    }
+}
+
+impl Renderer for Org {
+    fn render(&self, content: &str, target: Target) -> Option<String> {
+        match target {
+            Target::Markdown => Some(org_to_markdown(content)),
+            Target::Terminal => Some(org_for_terminal(content)),
+        }
+    }
+}
+
+impl Renderer for Plain {
+    fn render(&self, content: &str, target: Target) -> Option<String> {
+        Some(plain(content, "txt", target))
+    }
+}
+
+/// `content` as it is for a terminal, or in a code block marked as
+/// `extension` for markdown
+fn plain(content: &str, extension: &str, target: Target) -> String {
+    if target == Target::Terminal {
+        return content.into();
+    }
+    // longer than any run of backticks in the entry, so it doesn't end early
+    let longest = content.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    let language = match extension {
+        "" | "txt" => "text",
+        extension => extension,
+    };
+    format!("{fence}{language}\n{content}\n{fence}")
+}
+
+/// compiles `pattern` the first time it's needed
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("built-in pattern"))
+}
+
+/// text between `marker`s (which can't be right inside them), after the start
+/// of a line, a space, or an opening bracket or quote. the marker is given
+/// escaped, like `\*`.
+fn emphasis(marker: &str) -> String {
+    format!(r#"(^|[\s(\["']){marker}([^\s{marker}](?:[^{marker}]*[^\s{marker}])?){marker}"#)
+}
+
+/// `line` with every match of `regex` replaced by its second group, wrapped
+/// by `wrap`. the first group (what's before the marker) is kept, and a match
+/// right before a letter or a digit (like in `/usr/bin`) isn't one.
+fn replace_emphasis(line: &str, regex: &Regex, wrap: impl Fn(&str) -> String) -> String {
+    regex
+        .replace_all(line, |captures: &Captures| {
+            let whole = captures.get(0).expect("always matches");
+            match line[whole.end()..].chars().next().is_some_and(char::is_alphanumeric) {
+                true => whole.as_str().to_string(),
+                false => format!("{}{}", &captures[1], wrap(&captures[2])),
+            }
+        })
+        .into_owned()
+}
+
+/// the level and the text of a heading starting with `marker`s and a space
+fn heading(line: &str, marker: char) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == marker).count();
+    let text = line[level..].strip_prefix([' ', '\t'])?;
+    (level > 0).then(|| (level, text.trim()))
+}
+
+/// `line` with a `-`, `*`, or `+` before a list item replaced by a bullet
+fn bullet(line: &str, markers: &[char]) -> Option<String> {
+    let text = line.trim_start();
+    let indent = &line[..line.len() - text.len()];
+    let rest = text.strip_prefix(markers)?.strip_prefix(' ')?;
+    Some(format!("{indent}• {rest}"))
+}
+
+fn markdown_for_terminal(content: &str) -> String {
+    static BOLD: OnceLock<Regex> = OnceLock::new();
+    static ITALIC: OnceLock<Regex> = OnceLock::new();
+    let theme = theme::current();
+    let (bold, italic, dim) = (style("bold"), style("italic"), style("dim"));
+
+    let mut in_code = false;
+    let mut rendered = Vec::new();
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            rendered.push(dim.paint(line));
+            continue;
+        }
+        if in_code {
+            rendered.push(line.to_string());
+            continue;
+        }
+        if let Some((_, text)) = heading(line, '#').filter(|(level, _)| *level <= 6) {
+            rendered.push(theme.heading.paint(text));
+            continue;
+        }
+
+        let line = match line.trim_start().strip_prefix('>') {
+            Some(quoted) => format!("{} {}", dim.paint("│"), quoted.trim_start()),
+            None => bullet(line, &['-', '*', '+']).unwrap_or_else(|| line.into()),
+        };
+        let line = replace_emphasis(&line, regex(&BOLD, &emphasis(r"\*\*")), |text| bold.paint(text));
+        let line = replace_emphasis(&line, regex(&ITALIC, &emphasis(r"\*")), |text| italic.paint(text));
+        rendered.push(line);
+    }
+    rejoin(content, rendered)
+}
+
+/// an org block (`#+BEGIN_SRC` to `#+END_SRC`, and the like) starting or
+/// ending on `line`: whether it's the start, the name of the block in
+/// lowercase (like `src` or `quote`), and what's after it (like the language)
+fn org_block(line: &str) -> Option<(bool, String, &str)> {
+    let rest = line.trim().strip_prefix("#+")?;
+    let (keyword, arguments) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let keyword = keyword.to_lowercase();
+    if let Some(name) = keyword.strip_prefix("begin_") {
+        return Some((true, name.into(), arguments.trim()));
+    }
+    keyword.strip_prefix("end_").map(|name| (false, name.into(), ""))
+}
+
+/// the value of an org keyword line like `#+TITLE: Hiking`
+fn org_keyword(line: &str) -> Option<(String, &str)> {
+    let rest = line.trim().strip_prefix("#+")?;
+    let (keyword, value) = rest.split_once(':')?;
+    Some((keyword.to_lowercase(), value.trim()))
+}
+
+fn org_to_markdown(content: &str) -> String {
+    static BOLD: OnceLock<Regex> = OnceLock::new();
+    static ITALIC: OnceLock<Regex> = OnceLock::new();
+    static VERBATIM: OnceLock<Regex> = OnceLock::new();
+    static CODE: OnceLock<Regex> = OnceLock::new();
+    static STRIKE: OnceLock<Regex> = OnceLock::new();
+    static LINK: OnceLock<Regex> = OnceLock::new();
+
+    let mut block = None;
+    let mut rendered = Vec::new();
+    for line in content.lines() {
+        if let Some((start, name, language)) = org_block(line) {
+            match (start, name.as_str()) {
+                (true, "quote") => block = Some(true),
+                (true, _) => {
+                    block = Some(false);
+                    rendered.push(format!("```{language}"));
+                }
+                (false, _) => {
+                    if block == Some(false) {
+                        rendered.push("```".into());
+                    }
+                    block = None;
+                }
+            }
+            continue;
+        }
+        match block {
+            Some(false) => {
+                rendered.push(line.into());
+                continue;
+            }
+            Some(true) => {
+                rendered.push(format!("> {line}"));
+                continue;
+            }
+            None => {}
+        }
+        if let Some((keyword, value)) = org_keyword(line) {
+            if keyword == "title" {
+                rendered.push(format!("# {value}"));
+            }
+            continue;
+        }
+
+        let line = match heading(line, '*') {
+            Some((level, text)) => format!("{} {text}", "#".repeat(level.min(6))),
+            None => match line.trim_start().strip_prefix("+ ") {
+                Some(item) => format!("{}- {item}", &line[..line.len() - line.trim_start().len()]),
+                None => line.into(),
+            },
+        };
+        let line = regex(&LINK, r"\[\[([^\]]+)\](?:\[([^\]]+)\])?\]").replace_all(&line, |captures: &Captures| {
+            match captures.get(2) {
+                Some(description) => format!("[{}]({})", description.as_str(), &captures[1]),
+                None => format!("<{}>", &captures[1]),
+            }
+        });
+        let line = replace_emphasis(&line, regex(&BOLD, &emphasis(r"\*")), |text| format!("**{text}**"));
+        let line = replace_emphasis(&line, regex(&ITALIC, &emphasis("/")), |text| format!("*{text}*"));
+        let line = replace_emphasis(&line, regex(&VERBATIM, &emphasis("=")), |text| format!("`{text}`"));
+        let line = replace_emphasis(&line, regex(&CODE, &emphasis("~")), |text| format!("`{text}`"));
+        let line = replace_emphasis(&line, regex(&STRIKE, &emphasis(r"\+")), |text| format!("~~{text}~~"));
+        rendered.push(line);
+    }
+    rejoin(content, rendered)
+}
+
+fn org_for_terminal(content: &str) -> String {
+    static BOLD: OnceLock<Regex> = OnceLock::new();
+    static ITALIC: OnceLock<Regex> = OnceLock::new();
+    static LINK: OnceLock<Regex> = OnceLock::new();
+    let theme = theme::current();
+    let (bold, italic, dim, underline) = (style("bold"), style("italic"), style("dim"), style("underline"));
+
+    let mut in_block = false;
+    let mut rendered = Vec::new();
+    for line in content.lines() {
+        if let Some((start, _, _)) = org_block(line) {
+            in_block = start;
+            rendered.push(dim.paint(line));
+            continue;
+        }
+        if in_block {
+            rendered.push(line.to_string());
+            continue;
+        }
+        if let Some((keyword, value)) = org_keyword(line) {
+            rendered.push(match keyword.as_str() {
+                "title" => theme.heading.paint(value),
+                _ => dim.paint(line),
+            });
+            continue;
+        }
+        if let Some((_, text)) = heading(line, '*') {
+            rendered.push(theme.heading.paint(text));
+            continue;
+        }
+
+        let line = bullet(line, &['-', '+']).unwrap_or_else(|| line.into());
+        let line = regex(&LINK, r"\[\[([^\]]+)\](?:\[([^\]]+)\])?\]").replace_all(&line, |captures: &Captures| {
+            underline.paint(captures.get(2).unwrap_or_else(|| captures.get(1).expect("always matches")).as_str())
+        });
+        let line = replace_emphasis(&line, regex(&BOLD, &emphasis(r"\*")), |text| bold.paint(text));
+        let line = replace_emphasis(&line, regex(&ITALIC, &emphasis("/")), |text| italic.paint(text));
+        rendered.push(line);
+    }
+    rejoin(content, rendered)
+}
+
+/// a built-in style, left out if the theme has no colors (like with
+/// `$NO_COLOR`)
+fn style(style: &str) -> Style {
+    match theme::current().heading == Style::default() {
+        true => Style::default(),
+        false => style.parse().expect("built-in style"),
+    }
+}
+
+/// the rendered lines of `content`, with its last newline if it had one
+fn rejoin(content: &str, lines: Vec<String>) -> String {
+    let mut rendered = lines.join("\n");
+    if content.ends_with('\n') {
+        rendered.push('\n');
+    }
+    rendered
+}
@@ -0,0 +1,45 @@
+//! module for retention policies, which remove (or archive) entries once
+//! they're old enough
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    date::{Age, AgeFromStrError, Date},
+    db::State,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// the `[retention]` section of a configuration file. entries past the
+/// policy are only removed after asking.
+pub struct RetentionConfig {
+    /// how old entries can get before they're pruned (like `"5y"`, see
+    /// [`Age`])
+    pub older_than: Option<String>,
+    /// a journal file to move pruned entries into, instead of deleting them.
+    /// it uses the same password.
+    pub archive: Option<String>,
+}
+
+impl RetentionConfig {
+    /// parses [`RetentionConfig::older_than`]
+    pub fn get_older_than(&self) -> Result<Option<Age>, AgeFromStrError> {
+        self.older_than.as_deref().map(str::parse).transpose()
+    }
+}
+
+/// the dates of the entries older than `age`, counting back from `today`,
+/// oldest first. locked entries never expire (see [`State::is_locked()`]).
+pub fn expired(state: &State, age: &Age, today: &Date) -> Vec<Date> {
+    let Some(cutoff) = age.before(today) else {
+        return Vec::new();
+    };
+    let mut dates = state
+        .entries
+        .keys()
+        .filter(|date| date.without_time() < cutoff && !state.is_locked(date))
+        .copied()
+        .collect::<Vec<_>>();
+    dates.sort();
+    dates
+}
@@ -0,0 +1,203 @@
+//! module for finding entries by their text, `#tags`, and dates. the filters
+//! of a [`Query`] are combined, so an entry has to match all of them.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{
+    date::{Date, DateRange},
+    db::{Field, State},
+};
+
+/// how many characters of context to keep on either side of a match in an
+/// [excerpt](Hit::excerpt)
+const CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+/// what to search for. an entry matches if it's in the range, has every tag
+/// and custom field, and contains every word of the text (ignoring case).
+pub struct Query {
+    /// words the entry has to contain, in any order
+    pub text: Option<String>,
+    /// tags (with or without the `#`) the entry has to have
+    pub tags: Vec<String>,
+    /// the dates to search
+    pub range: DateRange,
+    /// custom fields the entry has to have (see
+    /// [`EntryMeta::fields`](crate::db::EntryMeta::fields)). a field without a
+    /// value only has to be there.
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// an entry that matched a [`Query`]
+pub struct Hit {
+    /// the date of the entry
+    pub date: Date,
+    /// the line with the first match, shortened around it (or the first line,
+    /// without any text to search for)
+    pub excerpt: String,
+    /// where the match is in the excerpt
+    pub highlight: Option<Range<usize>>,
+}
+
+impl Query {
+    /// the words of the text, in lowercase
+    pub(crate) fn words(&self) -> Vec<String> {
+        self.text
+            .iter()
+            .flat_map(|text| text.split_whitespace())
+            .map(|word| word.chars().flat_map(char::to_lowercase).collect())
+            .collect()
+    }
+
+    /// the tags, in lowercase and without the `#`
+    pub(crate) fn tags(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|tag| tag.trim_start_matches('#').to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    /// whether the entry matches. the cheap filters go first: the date, then
+    /// the tags, and only then the text.
+    pub fn matches(&self, date: &Date, content: &str) -> bool {
+        if !self.range.contains(date) {
+            return false;
+        }
+
+        let wanted = self.tags();
+        if !wanted.is_empty() {
+            let tags = tags(content);
+            if !wanted.iter().all(|tag| tags.contains(tag)) {
+                return false;
+            }
+        }
+
+        self.words()
+            .iter()
+            .all(|word| find_ignore_case(content, word).is_some())
+    }
+
+    /// whether the entry's custom fields match, with values compared
+    /// ignoring case
+    pub fn matches_fields(&self, fields: Option<&BTreeMap<String, String>>) -> bool {
+        self.fields.iter().all(|wanted| {
+            let value = fields.and_then(|fields| fields.get(&wanted.key));
+            value.is_some_and(|value| wanted.value.is_empty() || value.to_lowercase() == wanted.value.to_lowercase())
+        })
+    }
+}
+
+/// the `#tags` in `text`, in lowercase and without the `#`, in the order they
+/// first appear. a tag is made of letters, digits, `-`, and `_`, has at least
+/// one letter, and can't follow a letter or digit (so `page#top` isn't one).
+pub fn tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::<String>::new();
+    for (_, tag) in tag_spans(text) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// like [`tags()`], but every time a tag appears, with where it is in `text`
+/// (including the `#`)
+pub fn tag_spans(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut spans = Vec::new();
+    let mut previous = None;
+
+    for (i, c) in text.char_indices() {
+        let starts_tag = c == '#' && previous.is_none_or(|previous: char| !previous.is_alphanumeric() && previous != '&');
+        previous = Some(c);
+        if !starts_tag {
+            continue;
+        }
+
+        let rest = &text[i + 1..];
+        let end = rest
+            .find(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+            .unwrap_or(rest.len());
+        let tag = rest[..end].trim_end_matches(['-', '_']);
+        if tag.chars().any(char::is_alphabetic) {
+            spans.push((i..i + 1 + tag.len(), tag.to_lowercase()));
+        }
+    }
+
+    spans
+}
+
+/// the entries matching `query`, oldest first
+pub fn search(state: &State, query: &Query) -> Vec<Hit> {
+    let words = query.words();
+    let mut dates = state
+        .entries
+        .iter()
+        .filter(|(date, content)| query.matches(date, content) && query.matches_fields(state.fields(date)))
+        .map(|(date, _)| *date)
+        .collect::<Vec<_>>();
+    dates.sort();
+
+    dates
+        .into_iter()
+        .map(|date| {
+            let content = &state.entries[&date];
+            let (excerpt, highlight) = match words.first() {
+                Some(word) => excerpt(content, word),
+                None => (content.lines().next().unwrap_or_default().trim().to_string(), None),
+            };
+            Hit {
+                date,
+                excerpt,
+                highlight,
+            }
+        })
+        .collect()
+}
+
+/// the line of `content` with the first match of `word`, shortened to
+/// [`CONTEXT`] characters on either side, and where the match is in it
+fn excerpt(content: &str, word: &str) -> (String, Option<Range<usize>>) {
+    let Some(found) = find_ignore_case(content, word) else {
+        return (String::new(), None);
+    };
+
+    let line_start = content[..found.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[found.end..].find('\n').map_or(content.len(), |i| found.end + i);
+    let start = content[line_start..found.start]
+        .char_indices()
+        .rev()
+        .nth(CONTEXT - 1)
+        .map_or(line_start, |(i, _)| line_start + i);
+    let end = content[found.end..line_end]
+        .char_indices()
+        .nth(CONTEXT)
+        .map_or(line_end, |(i, _)| found.end + i);
+
+    let before = if start > line_start { "…" } else { "" };
+    let after = if end < line_end { "…" } else { "" };
+    let excerpt = format!("{before}{}{after}", &content[start..end]);
+    let highlight_start = before.len() + found.start - start;
+
+    (excerpt, Some(highlight_start..highlight_start + found.len()))
+}
+
+/// where `needle` (in lowercase) first appears in `haystack`, ignoring case
+pub(crate) fn find_ignore_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    'starts: for (start, _) in haystack.char_indices() {
+        let mut lowered = haystack[start..]
+            .char_indices()
+            .flat_map(|(i, c)| c.to_lowercase().map(move |lower| (start + i + c.len_utf8(), lower)));
+        let mut end = start;
+        for wanted in needle.chars() {
+            match lowered.next() {
+                Some((next_end, lower)) if lower == wanted => end = next_end,
+                _ => continue 'starts,
+            }
+        }
+        return Some(start..end);
+    }
+
+    None
+}
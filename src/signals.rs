@@ -0,0 +1,92 @@
+//! module for what happens when `jrn` is told to stop: Ctrl-C outside of a
+//! prompt, `kill`, or closing the terminal. a save that already started is
+//! finished first, so the journal isn't left half written, the terminal is
+//! put back the way it was (prompts hide the cursor and turn off echo), and
+//! entries edited but not saved yet are kept as drafts, like when `jrn`
+//! crashes (see [`crash`]). an entry open in the editor has its temporary
+//! file overwritten and removed (see [`editor::abandon()`]).
+
+use std::{
+    io::stderr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use requestty::prompt::backend::{self, Backend};
+
+use crate::{crash, editor, tr};
+
+/// held while the journal is being saved (see [`saving()`])
+static SAVING: Mutex<()> = Mutex::new(());
+
+/// whether Ctrl-C is caught instead of stopping `jrn` (see
+/// [`catch_interrupts()`])
+static CATCHING: AtomicBool = AtomicBool::new(false);
+
+/// set by Ctrl-C while it's caught
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// starts handling `SIGINT`, `SIGTERM`, and `SIGHUP` on a thread of their own
+#[cfg(unix)]
+pub fn install() {
+    use signal_hook::{
+        consts::{SIGHUP, SIGINT, SIGTERM},
+        iterator::Signals,
+    };
+
+    let Ok(mut signals) = Signals::new([SIGINT, SIGTERM, SIGHUP]) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            if signal == SIGINT && CATCHING.load(Ordering::Relaxed) {
+                INTERRUPTED.store(true, Ordering::Relaxed);
+                continue;
+            }
+            stop(signal);
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install() {}
+
+/// waits for a save to finish, puts the terminal back, keeps the drafts, and
+/// exits like the signal would have
+fn stop(signal: i32) -> ! {
+    let _saving = SAVING.lock().unwrap_or_else(|e| e.into_inner());
+
+    // a prompt can be holding on to stdout
+    let mut backend = backend::get_backend(stderr());
+    let _ = backend.show_cursor();
+    let _ = backend.disable_raw_mode();
+
+    // the entry open in the editor isn't left behind in its temporary file
+    editor::abandon();
+
+    eprintln!();
+    eprintln!("{}", tr!("jrn was stopped"));
+    if let Some(path) = crash::keep_drafts() {
+        eprintln!("{}", tr!("the entries you edited were kept in {0}", path.display()));
+    }
+    std::process::exit(128 + signal);
+}
+
+/// keeps `jrn` from being stopped until the returned guard is dropped, so the
+/// journal is saved all the way
+pub fn saving() -> MutexGuard<'static, ()> {
+    SAVING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// whether Ctrl-C should be caught (and noticed with [`interrupted()`])
+/// instead of stopping `jrn`. gives back whether it was caught before.
+pub fn catch_interrupts(catch: bool) -> bool {
+    CATCHING.swap(catch, Ordering::Relaxed)
+}
+
+/// whether Ctrl-C was caught since this was last asked
+pub fn interrupted() -> bool {
+    INTERRUPTED.swap(false, Ordering::Relaxed)
+}
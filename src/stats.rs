@@ -0,0 +1,493 @@
+//! module for statistics about a journal, like how much was written, when,
+//! how moods changed over time, and which words came up most. these only
+//! gather the numbers; showing them is up to the caller, though they can be
+//! put in a [`Table`] to write as JSON or CSV.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use chrono::{Timelike, Weekday};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    date::{Date, DateRange, Period},
+    db::State,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// how much was written
+pub struct Summary {
+    /// the number of entries
+    pub entries: usize,
+    /// the number of words in all of them
+    pub words: usize,
+    /// the date of the first entry
+    pub first: Option<Date>,
+    /// the date of the last entry
+    pub last: Option<Date>,
+}
+
+impl Summary {
+    /// the average number of words in an entry
+    pub fn average_words(&self) -> f64 {
+        match self.entries {
+            0 => 0.0,
+            entries => self.words as f64 / entries as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// the average mood over a period
+pub struct MoodTrend {
+    /// the period (a month, in [`mood_by_month()`])
+    pub period: Period,
+    /// the average mood, from 1 to 5
+    pub average: f64,
+    /// the number of entries with a mood in the period
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+/// how often a word was used
+pub struct WordCount {
+    /// the word, in lowercase
+    pub word: String,
+    /// how many times it was used
+    pub count: usize,
+}
+
+/// common English words that say little about what an entry is about, left
+/// out of [`word_counts()`]
+pub const STOPWORDS: &[&str] = &[
+    "about", "above", "after", "again", "against", "all", "also", "and", "any", "are", "aren't",
+    "because", "been", "before", "being", "below", "between", "both", "but", "can", "can't",
+    "cannot", "could", "couldn't", "did", "didn't", "does", "doesn't", "doing", "don't", "down",
+    "during", "each", "even", "few", "for", "from", "further", "get", "got", "had", "hadn't",
+    "has", "hasn't", "have", "haven't", "having", "her", "here", "hers", "herself", "him",
+    "himself", "his", "how", "i'd", "i'll", "i'm", "i've", "into", "isn't", "it's", "its",
+    "itself", "just", "let's", "like", "more", "most", "much", "mustn't", "myself", "nor", "not",
+    "now", "off", "once", "only", "other", "ought", "our", "ours", "ourselves", "out", "over",
+    "own", "really", "same", "she", "she'd", "she'll", "she's", "should", "shouldn't", "some",
+    "still", "such", "than", "that", "that's", "the", "their", "theirs", "them", "themselves",
+    "then", "there", "there's", "these", "they", "they'd", "they'll", "they're", "they've",
+    "this", "those", "through", "too", "under", "until", "very", "was", "wasn't", "we'd",
+    "we'll", "we're", "we've", "well", "were", "weren't", "what", "what's", "when", "when's",
+    "where", "where's", "which", "while", "who", "who's", "whom", "why", "why's", "will", "with",
+    "won't", "would", "wouldn't", "you", "you'd", "you'll", "you're", "you've", "your", "yours",
+    "yourself", "yourselves",
+];
+
+/// the words of `text` worth counting: lowercase, at least three letters,
+/// and not a [stopword](STOPWORDS)
+fn meaningful_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphabetic() && c != '\'' && c != '’')
+        .map(|word| word.trim_matches(['\'', '’']).replace('’', "'").to_lowercase())
+        .filter(|word| word.chars().count() >= 3 && !STOPWORDS.contains(&word.as_str()))
+}
+
+/// counts the words, most used first (then alphabetically)
+fn count<'a>(texts: impl IntoIterator<Item = &'a String>) -> Vec<WordCount> {
+    let mut counts = HashMap::<String, usize>::new();
+    for word in texts.into_iter().flat_map(|text| meaningful_words(text)) {
+        *counts.entry(word).or_default() += 1;
+    }
+
+    let mut counts = counts
+        .into_iter()
+        .map(|(word, count)| WordCount { word, count })
+        .collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(&b.word)));
+    counts
+}
+
+/// whether the entry for `date` was written ahead, for a day after `today`.
+/// it's kept like any other entry, but doesn't count in statistics until its
+/// day comes (see [`until()`]).
+pub fn is_scheduled(date: &Date, today: &Date) -> bool {
+    date.without_time() > today.without_time()
+}
+
+/// `range`, ending at `today` at the latest, so scheduled entries (see
+/// [`is_scheduled()`]) are left out
+pub fn until(range: &DateRange, today: &Date) -> DateRange {
+    let end = match range.end {
+        Some(end) if !is_scheduled(&end, today) => end,
+        _ => today.without_time(),
+    };
+    DateRange {
+        start: range.start,
+        end: Some(end),
+    }
+}
+
+/// the dates of the entries in `range`, oldest first
+fn dates_in(state: &State, range: &DateRange) -> Vec<Date> {
+    let mut dates = state
+        .entries
+        .keys()
+        .filter(|date| range.contains(date))
+        .copied()
+        .collect::<Vec<_>>();
+    dates.sort();
+    dates
+}
+
+/// how much was written on `dates` (oldest first)
+fn summarize(state: &State, dates: &[Date]) -> Summary {
+    let words = dates
+        .iter()
+        .map(|date| state.entries[date].split_whitespace().count())
+        .sum();
+
+    Summary {
+        entries: dates.len(),
+        words,
+        first: dates.first().copied(),
+        last: dates.last().copied(),
+    }
+}
+
+/// how much was written in `range`
+pub fn summary(state: &State, range: &DateRange) -> Summary {
+    summarize(state, &dates_in(state, range))
+}
+
+/// how much was written in `range` on every day of the week, from Monday to
+/// Sunday (including days without any entries)
+pub fn by_weekday(state: &State, range: &DateRange) -> Vec<(Weekday, Summary)> {
+    let dates = dates_in(state, range);
+    [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .into_iter()
+    .map(|weekday| {
+        let dates = dates
+            .iter()
+            .filter(|date| date.weekday() == weekday)
+            .copied()
+            .collect::<Vec<_>>();
+        (weekday, summarize(state, &dates))
+    })
+    .collect()
+}
+
+/// how much was written in `range` at every hour of the day (from 0 to 23)
+/// with an entry. only entries with a time count, so this is empty for a
+/// journal without times.
+pub fn by_hour(state: &State, range: &DateRange) -> Vec<(u32, Summary)> {
+    let mut hours = BTreeMap::<u32, Vec<Date>>::new();
+    for date in dates_in(state, range) {
+        if let Some(time) = date.time() {
+            hours.entry(time.hour()).or_default().push(date);
+        }
+    }
+
+    hours
+        .into_iter()
+        .map(|(hour, dates)| (hour, summarize(state, &dates)))
+        .collect()
+}
+
+/// the average mood of every month in `range` with at least one mood, oldest
+/// first
+pub fn mood_by_month(state: &State, range: &DateRange) -> Vec<MoodTrend> {
+    let mut months = BTreeMap::<Period, (u32, usize)>::new();
+    for date in dates_in(state, range) {
+        let Some(mood) = state.mood(&date) else {
+            continue;
+        };
+        let period = Period::Month {
+            year: date.year(),
+            month: date.month().into(),
+        };
+        let (sum, entries) = months.entry(period).or_default();
+        *sum += u32::from(mood.value());
+        *entries += 1;
+    }
+
+    months
+        .into_iter()
+        .map(|(period, (sum, entries))| MoodTrend {
+            period,
+            average: f64::from(sum) / entries as f64,
+            entries,
+        })
+        .collect()
+}
+
+/// the days in `range` without an entry, oldest first. an open start begins
+/// at the first entry, and an open end stops at `today`.
+pub fn missing_days(state: &State, range: &DateRange, today: &Date) -> Vec<Date> {
+    let written = state
+        .entries
+        .keys()
+        .map(Date::without_time)
+        .collect::<HashSet<_>>();
+    let Some(first) = range.start.or_else(|| written.iter().min().copied()) else {
+        return Vec::new();
+    };
+    let days = DateRange {
+        start: Some(first.without_time()),
+        end: Some(range.end.unwrap_or(*today).without_time()),
+    };
+
+    days.dates()
+        .into_iter()
+        .flatten()
+        .filter(|day| !written.contains(day))
+        .collect()
+}
+
+/// the words written on every day of `year`, in weeks from Monday to Sunday
+/// like a calendar. days outside the year, and after `today`, are `None`.
+pub fn heatmap(state: &State, year: i32, today: &Date) -> Vec<[Option<usize>; 7]> {
+    let (Ok(first), Ok(last)) = (format!("{year}-01-01").parse::<Date>(), format!("{year}-12-31").parse::<Date>()) else {
+        return Vec::new();
+    };
+    let mut words = HashMap::<Date, usize>::new();
+    for date in dates_in(state, &DateRange::from(Period::Year(year))) {
+        *words.entry(date.without_time()).or_default() += state.entries[&date].split_whitespace().count();
+    }
+
+    let mut weeks = Vec::new();
+    let mut week = [None; 7];
+    let days = DateRange { start: Some(first), end: Some(last) };
+    for day in days.dates().into_iter().flatten() {
+        let weekday = day.weekday().num_days_from_monday() as usize;
+        if day <= *today {
+            week[weekday] = Some(words.get(&day).copied().unwrap_or(0));
+        }
+        if weekday == 6 {
+            weeks.push(week);
+            week = [None; 7];
+        }
+    }
+    if week.iter().any(Option::is_some) || last.weekday() != Weekday::Sun {
+        weeks.push(week);
+    }
+    weeks
+}
+
+/// how dark a day of a [`heatmap()`] is: 0 without any words, otherwise from
+/// 1 to 4 by its share of `most`, the most words written on a day
+pub fn heatmap_level(words: usize, most: usize) -> usize {
+    match words {
+        0 => 0,
+        words => (words * 4).div_ceil(most.max(words)).clamp(1, 4),
+    }
+}
+
+/// how often every meaningful word (see [`STOPWORDS`]) was used in `range`,
+/// most used first
+pub fn word_counts(state: &State, range: &DateRange) -> Vec<WordCount> {
+    count(dates_in(state, range).iter().map(|date| &state.entries[date]))
+}
+
+/// like [`word_counts()`], for every month in `range` with an entry, oldest
+/// first
+pub fn word_counts_by_month(state: &State, range: &DateRange) -> Vec<(Period, Vec<WordCount>)> {
+    let mut months = BTreeMap::<Period, Vec<&String>>::new();
+    for date in dates_in(state, range) {
+        let period = Period::Month {
+            year: date.year(),
+            month: date.month().into(),
+        };
+        months.entry(period).or_default().push(&state.entries[&date]);
+    }
+
+    months
+        .into_iter()
+        .map(|(period, texts)| (period, count(texts)))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// how statistics are written
+pub enum StatsOutput {
+    /// a report for reading in a terminal
+    #[default]
+    Text,
+    /// a JSON array with an object for every row of the [`Table`]
+    Json,
+    /// the [`Table`] as CSV, with a header
+    Csv,
+}
+
+impl Display for StatsOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Json => write!(f, "json"),
+            Self::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+/// The errors [`StatsOutput::from_str`] can return
+pub enum StatsOutputFromStrError {
+    /// Not the name of an output
+    #[error("expected an output: text, json, or csv")]
+    Unknown,
+}
+
+impl FromStr for StatsOutput {
+    type Err = StatsOutputFromStrError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(StatsOutputFromStrError::Unknown),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+/// statistics as rows of named columns, for charting in other tools (see
+/// [`Table::to_json()`] and [`Table::to_csv()`])
+pub struct Table {
+    /// the names of the columns
+    pub columns: Vec<&'static str>,
+    /// the rows, with a value for every column
+    pub rows: Vec<Vec<Value>>,
+}
+
+/// `value` rounded to two decimals, which is plenty for averages
+fn rounded(value: f64) -> Value {
+    Value::from((value * 100.0).round() / 100.0)
+}
+
+fn date_value(date: Option<&Date>) -> Value {
+    date.map_or(Value::Null, |date| Value::from(date.to_string()))
+}
+
+impl Table {
+    /// every row as an object, keyed by column
+    pub fn to_json(&self) -> Value {
+        self.rows
+            .iter()
+            .map(|row| {
+                let object = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| (column.to_string(), value.clone()))
+                    .collect::<Map<_, _>>();
+                Value::Object(object)
+            })
+            .collect()
+    }
+
+    /// a header with the columns, then every row. `null`s are left empty,
+    /// and values with commas, quotes, or line breaks are quoted.
+    pub fn to_csv(&self) -> String {
+        let line = |fields: Vec<String>| {
+            let fields = fields
+                .into_iter()
+                .map(|field| match field.contains([',', '"', '\n', '\r']) {
+                    true => format!("\"{}\"", field.replace('"', "\"\"")),
+                    false => field,
+                })
+                .collect::<Vec<_>>();
+            fields.join(",") + "\n"
+        };
+
+        let mut csv = line(self.columns.iter().map(|column| column.to_string()).collect());
+        for row in &self.rows {
+            csv += &line(
+                row.iter()
+                    .map(|value| match value {
+                        Value::Null => String::new(),
+                        Value::String(text) => text.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect(),
+            );
+        }
+        csv
+    }
+
+    /// a [`Summary`] as a single row
+    pub fn summary(summary: &Summary) -> Self {
+        Self {
+            columns: vec!["entries", "words", "words_per_entry", "first", "last"],
+            rows: vec![vec![
+                summary.entries.into(),
+                summary.words.into(),
+                rounded(summary.average_words()),
+                date_value(summary.first.as_ref()),
+                date_value(summary.last.as_ref()),
+            ]],
+        }
+    }
+
+    /// a row for every month of [`mood_by_month()`]
+    pub fn moods(trends: &[MoodTrend]) -> Self {
+        Self {
+            columns: vec!["period", "average_mood", "entries"],
+            rows: trends
+                .iter()
+                .map(|trend| vec![trend.period.to_string().into(), rounded(trend.average), trend.entries.into()])
+                .collect(),
+        }
+    }
+
+    /// a row for every day of the week of [`by_weekday()`] (`Mon` to `Sun`),
+    /// then for every hour of [`by_hour()`] (`0` to `23`)
+    pub fn habits(weekdays: &[(Weekday, Summary)], hours: &[(u32, Summary)]) -> Self {
+        let row = |group: &str, name: Value, summary: &Summary| {
+            vec![
+                group.into(),
+                name,
+                summary.entries.into(),
+                summary.words.into(),
+                rounded(summary.average_words()),
+            ]
+        };
+        let weekdays = weekdays
+            .iter()
+            .map(|(weekday, summary)| row("weekday", weekday.to_string().into(), summary));
+        let hours = hours
+            .iter()
+            .map(|(hour, summary)| row("hour", (*hour).into(), summary));
+
+        Self {
+            columns: vec!["group", "name", "entries", "words", "words_per_entry"],
+            rows: weekdays.chain(hours).collect(),
+        }
+    }
+
+    /// a row for each of the `top` words overall (with an empty period), then
+    /// for each of the `top` words of every month of
+    /// [`word_counts_by_month()`]
+    pub fn words(overall: &[WordCount], by_month: &[(Period, Vec<WordCount>)], top: usize) -> Self {
+        let overall = overall.iter().take(top).map(|count| (Value::Null, count));
+        let by_month = by_month.iter().flat_map(|(period, counts)| {
+            counts
+                .iter()
+                .take(top)
+                .map(move |count| (Value::from(period.to_string()), count))
+        });
+
+        Self {
+            columns: vec!["period", "word", "count"],
+            rows: overall
+                .chain(by_month)
+                .map(|(period, count)| vec![period, count.word.clone().into(), count.count.into()])
+                .collect(),
+        }
+    }
+}
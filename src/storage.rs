@@ -0,0 +1,228 @@
+//! module for where journals are kept. `FileStorage` is a file on disk.
+//! other backends (like WebDAV, S3, or a server) implement [`Storage`], or
+//! [`AsyncStorage`] so they don't block while talking to the network.
+//! `InMemoryStorage` (with the `test-util` feature) never touches the disk.
+
+use std::{
+    future::{ready, Future},
+    io::{self, Read, Write},
+};
+#[cfg(feature = "test-util")]
+use std::sync::Mutex;
+#[cfg(feature = "fs")]
+use std::{
+    fs,
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+};
+
+/// somewhere a journal can be read from and written to, a whole journal at a
+/// time. backends that can stream it (like files) also implement
+/// [`Storage::reader()`] and [`Storage::write_with()`], so a large journal
+/// doesn't have to be in memory as one string.
+pub trait Storage {
+    /// reads the stored journal
+    fn read(&self) -> io::Result<String>;
+    /// replaces the stored journal
+    fn write(&self, contents: &str) -> io::Result<()>;
+    /// whether a journal has been stored yet
+    fn exists(&self) -> bool;
+    /// Provided. reads the stored journal bit by bit. by default, it's read
+    /// all at once with [`Storage::read()`].
+    fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        Ok(Box::new(io::Cursor::new(self.read()?)))
+    }
+    /// Provided. replaces the stored journal with what `write` writes, bit by
+    /// bit. by default, it's collected and written all at once with
+    /// [`Storage::write()`].
+    fn write_with(&self, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        let mut contents = Vec::new();
+        write(&mut contents)?;
+        let contents = String::from_utf8(contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.write(&contents)
+    }
+}
+
+/// like [`Storage`], but without blocking. see [`State::load_from_async()`]
+/// and [`State::save_to_async()`].
+///
+/// [`State::load_from_async()`]: crate::db::State::load_from_async
+/// [`State::save_to_async()`]: crate::db::State::save_to_async
+pub trait AsyncStorage {
+    /// reads the stored journal
+    fn read(&self) -> impl Future<Output = io::Result<String>> + Send;
+    /// replaces the stored journal
+    fn write(&self, contents: &str) -> impl Future<Output = io::Result<()>> + Send;
+    /// whether a journal has been stored yet
+    fn exists(&self) -> impl Future<Output = bool> + Send;
+}
+
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// a journal file on disk
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+#[cfg(feature = "fs")]
+impl FileStorage {
+    /// the journal file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// the path of the journal file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Storage for FileStorage {
+    fn read(&self) -> io::Result<String> {
+        fs::read_to_string(&self.path)
+    }
+    fn write(&self, contents: &str) -> io::Result<()> {
+        write_private(&self.path, contents.as_bytes())
+    }
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+    fn reader(&self) -> io::Result<Box<dyn Read + '_>> {
+        Ok(Box::new(BufReader::new(fs::File::open(&self.path)?)))
+    }
+    fn write_with(&self, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        replace_private(&self.path, write)
+    }
+}
+
+/// writes `contents` to the file at `path`. a new file is created so only its
+/// owner can read and write it (`0600`), and an existing file keeps its
+/// permissions. the file is replaced all at once (see [`replace_private()`]),
+/// so it's never left half written.
+#[cfg(feature = "fs")]
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    replace_private(path, &mut |file| file.write_all(contents))
+}
+
+/// writes a temporary file next to the file at `path` with `write`, then
+/// moves it over the file. if `jrn` is stopped (or fails) before then, the
+/// file is left as it was.
+#[cfg(feature = "fs")]
+fn replace_private(path: &Path, write: &mut dyn FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+    // a symlink to the file stays a symlink
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let temporary = temporary_path(&path);
+    let _ = fs::remove_file(&temporary);
+
+    let replaced = (|| {
+        let mut file = BufWriter::new(open_private(&temporary)?);
+        write(&mut file)?;
+        let file = file.into_inner().map_err(|e| e.into_error())?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    })();
+    if replaced.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    replaced
+}
+
+/// where the file at `path` is written before it's replaced
+#[cfg(feature = "fs")]
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// opens the file at `path` for writing, emptied, with the permissions
+/// [`write_private()`] gives it
+#[cfg(feature = "fs")]
+fn open_private(path: &Path) -> io::Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+/// the permissions of the file at `path` (like `0o644`) if users other than
+/// its owner can read or write it. always `None` where there are no unix
+/// permissions.
+#[cfg(feature = "fs")]
+pub fn shared_permissions(path: &Path) -> Option<u32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path).ok()?.permissions().mode() & 0o777;
+        (mode & 0o077 != 0).then_some(mode)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+#[cfg(feature = "test-util")]
+#[derive(Debug, Default)]
+/// a journal kept in memory, for tests
+pub struct InMemoryStorage {
+    contents: Mutex<Option<String>>,
+}
+
+#[cfg(feature = "test-util")]
+impl InMemoryStorage {
+    /// an empty storage, with no journal stored yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a storage that already holds `contents`, as a journal file would
+    pub fn with_contents(contents: impl Into<String>) -> Self {
+        Self {
+            contents: Mutex::new(Some(contents.into())),
+        }
+    }
+
+    /// what was last written, if anything
+    pub fn contents(&self) -> Option<String> {
+        self.contents.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(feature = "test-util")]
+impl Storage for InMemoryStorage {
+    fn read(&self) -> io::Result<String> {
+        self.contents()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no journal stored"))
+    }
+    fn write(&self, contents: &str) -> io::Result<()> {
+        *self.contents.lock().unwrap_or_else(|e| e.into_inner()) = Some(contents.into());
+        Ok(())
+    }
+    fn exists(&self) -> bool {
+        self.contents().is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// lets any [`Storage`] be used as an [`AsyncStorage`]. reading and writing
+/// still block, so this is for backends that are fast anyway, like files.
+pub struct Blocking<S>(pub S);
+
+impl<S: Storage + Sync> AsyncStorage for Blocking<S> {
+    fn read(&self) -> impl Future<Output = io::Result<String>> + Send {
+        ready(self.0.read())
+    }
+    fn write(&self, contents: &str) -> impl Future<Output = io::Result<()>> + Send {
+        ready(self.0.write(contents))
+    }
+    fn exists(&self) -> impl Future<Output = bool> + Send {
+        ready(self.0.exists())
+    }
+}
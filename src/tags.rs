@@ -0,0 +1,95 @@
+//! module for looking after the `#tags` of a journal as a whole: counting
+//! them, and renaming or removing one in every entry (see [`retag()`]). tags
+//! only live in the text of the entries, so that's what's changed.
+
+use std::collections::HashMap;
+
+use crate::{date::Date, db::State, search};
+
+/// how many entries have each tag, most used first (then alphabetically)
+pub fn tag_counts(state: &State) -> Vec<(String, usize)> {
+    let mut counts = HashMap::<String, usize>::new();
+    for content in state.entries.values() {
+        for tag in search::tags(content) {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// `tag` without the `#`, if it's a valid tag on its own (see
+/// [`search::tags()`])
+pub fn parse_tag(tag: &str) -> Option<&str> {
+    let tag = tag.trim().trim_start_matches('#');
+    let spans = search::tag_spans(&format!("#{tag}"));
+    match &spans[..] {
+        [(span, _)] if span.len() == tag.len() + 1 => Some(tag),
+        _ => None,
+    }
+}
+
+/// `text` with every `#from` (ignoring case) replaced by `#to`, or removed
+/// without `to`. a line left empty by removing its tags is removed too.
+/// `None` if `text` doesn't have the tag.
+pub fn retag(text: &str, from: &str, to: Option<&str>) -> Option<String> {
+    let from = from.trim_start_matches('#').to_lowercase();
+    let mut changed = false;
+    let mut retagged = String::with_capacity(text.len());
+
+    for line in text.split_inclusive('\n') {
+        let spans = search::tag_spans(line)
+            .into_iter()
+            .filter(|(_, tag)| *tag == from)
+            .map(|(span, _)| span)
+            .collect::<Vec<_>>();
+        if spans.is_empty() {
+            retagged.push_str(line);
+            continue;
+        }
+        changed = true;
+
+        let mut new_line = String::with_capacity(line.len());
+        let mut rest = 0;
+        for span in spans {
+            new_line.push_str(&line[rest..span.start]);
+            rest = span.end;
+            match to {
+                Some(to) => {
+                    new_line.push('#');
+                    new_line.push_str(to);
+                }
+                // a space next to the tag goes with it, so no gap is left
+                // (and none before punctuation or the end of the line)
+                None => {
+                    let at_start = new_line.trim().is_empty();
+                    let next = &line[rest..];
+                    if (at_start || new_line.ends_with(' ')) && next.starts_with(' ') {
+                        rest += 1;
+                    } else if new_line.ends_with(' ') && !next.starts_with(char::is_alphanumeric) {
+                        new_line.pop();
+                    }
+                }
+            }
+        }
+        new_line.push_str(&line[rest..]);
+
+        if to.is_none() && new_line.trim().is_empty() && !line.trim().is_empty() {
+            continue;
+        }
+        retagged.push_str(&new_line);
+    }
+
+    changed.then_some(retagged)
+}
+
+/// what [`retag()`] would make of every entry with the tag, oldest first
+pub fn plan_retag(state: &State, from: &str, to: Option<&str>) -> Vec<(Date, String)> {
+    state
+        .entries
+        .iter()
+        .filter_map(|(date, content)| retag(content, from, to).map(|retagged| (*date, retagged)))
+        .collect()
+}
@@ -0,0 +1,88 @@
+//! module for templates: files that new entries start from, picked by the
+//! `[[templates]]` rules in the configuration file. Mondays can start from a
+//! weekly plan, the 1st of the month from a review, and every other day from
+//! a daily template (see [`template_for()`]).
+
+use chrono::Weekday;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::date::Date;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// one `[[templates]]` section of a configuration file. the dates it's for
+/// have to meet every condition given, so a rule without any is for every
+/// date.
+pub struct TemplateRule {
+    /// the template file
+    pub file: String,
+    /// only for this day of the week (like `"monday"` or `"mon"`)
+    pub weekday: Option<String>,
+    /// only for this day of the month, from 1 to 31, or -1 for the last day
+    pub day: Option<i8>,
+    /// only for this month, from 1 to 12
+    pub month: Option<u8>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+/// why a [`TemplateRule`] is invalid
+pub enum TemplateRuleError {
+    /// the day of the week isn't one
+    #[error("{0:?} isn't a day of the week")]
+    InvalidWeekday(String),
+    /// the day of the month isn't between 1 and 31 (or -1)
+    #[error("{0} isn't a day of the month (1 to 31, or -1 for the last day)")]
+    InvalidDay(i8),
+    /// the month isn't between 1 and 12
+    #[error("{0} isn't a month (1 to 12)")]
+    InvalidMonth(u8),
+}
+
+impl TemplateRule {
+    /// parses [`TemplateRule::weekday`]
+    pub fn get_weekday(&self) -> Result<Option<Weekday>, TemplateRuleError> {
+        self.weekday
+            .as_deref()
+            .map(|weekday| {
+                weekday
+                    .trim()
+                    .parse()
+                    .map_err(|_| TemplateRuleError::InvalidWeekday(weekday.into()))
+            })
+            .transpose()
+    }
+
+    /// checks that every condition can be met
+    pub fn check(&self) -> Result<(), TemplateRuleError> {
+        self.get_weekday()?;
+        if let Some(day) = self.day.filter(|day| !(1..=31).contains(day) && *day != -1) {
+            return Err(TemplateRuleError::InvalidDay(day));
+        }
+        if let Some(month) = self.month.filter(|month| !(1..=12).contains(month)) {
+            return Err(TemplateRuleError::InvalidMonth(month));
+        }
+        Ok(())
+    }
+
+    /// whether `date` meets every condition. an invalid condition is never
+    /// met.
+    pub fn matches(&self, date: &Date) -> bool {
+        let weekday = match self.get_weekday() {
+            Ok(weekday) => weekday.is_none_or(|weekday| date.weekday() == weekday),
+            Err(_) => false,
+        };
+        let day = self.day.is_none_or(|day| match day {
+            -1 => date.succ().is_none_or(|next| next.month() != date.month()),
+            day => i16::from(date.day()) == i16::from(day),
+        });
+        let month = self.month.is_none_or(|month| date.month() == month);
+
+        weekday && day && month
+    }
+}
+
+/// the first of `rules` for `date`, if any
+pub fn template_for<'a>(rules: &'a [TemplateRule], date: &Date) -> Option<&'a TemplateRule> {
+    rules.iter().find(|rule| rule.matches(date))
+}
@@ -0,0 +1,42 @@
+//! module for building journals to test with (needs the `test-util`
+//! feature). pair these with [`ZeroSecurity`] so tests don't wait on real
+//! encryption, and with [`InMemoryStorage`] so they don't touch the disk.
+
+use crate::{
+    date::Date,
+    db::{SaveError, State, StoredJournal},
+    encryptor::{Encryptor, ZeroSecurity},
+    storage::InMemoryStorage,
+};
+
+/// the password the fixtures are encrypted with
+pub const PASSWORD: &str = "password";
+
+/// parses `date` (like `2024-06-01`), panicking if it isn't a valid date
+pub fn date(date: &str) -> Date {
+    date.parse()
+        .unwrap_or_else(|e| panic!("invalid fixture date {date:?}: {e}"))
+}
+
+/// an unlocked journal with [`PASSWORD`] and the given entries (dates like
+/// `2024-06-01`)
+pub fn state<'a>(entries: impl IntoIterator<Item = (&'a str, &'a str)>) -> State {
+    let mut state = State::new();
+    state.change_password(PASSWORD);
+    for (day, content) in entries {
+        state.set_entry(&date(day), content);
+    }
+    state
+}
+
+/// `state` encrypted with `e`, as it would be written to a file
+pub fn stored_journal<E: Encryptor>(state: &State, e: &E) -> Result<StoredJournal, SaveError> {
+    Ok(e.encrypt_journal(state)?.into())
+}
+
+/// a storage holding `state`, encrypted with [`ZeroSecurity`]
+pub fn storage(state: &State) -> Result<InMemoryStorage, SaveError> {
+    let storage = InMemoryStorage::new();
+    state.save_to(&storage, &ZeroSecurity)?;
+    Ok(storage)
+}
@@ -0,0 +1,197 @@
+//! module for color themes. styles are written as space separated words, for
+//! example `"bright-red bold"`, `"black on-yellow"`, or `"none"`.
+
+use std::{fmt::Display, str::FromStr, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// sets the theme used for all output. can only be set once.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// the theme set by [`set_theme()`], or the default theme if none has been
+/// set (or the `no-color` theme if `$NO_COLOR` is set)
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(|| {
+        let preset = match std::env::var_os("NO_COLOR") {
+            Some(_) => "no-color",
+            None => "default",
+        };
+        Theme::preset(preset).expect("built-in preset")
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+/// the `[theme]` section of a configuration file. any style that is set
+/// overrides the one from the preset.
+pub struct ThemeConfig {
+    /// the built-in theme to start from: `"default"`, `"muted"`, or
+    /// `"no-color"`
+    pub preset: Option<String>,
+    /// the style for errors
+    pub error: Option<String>,
+    /// the style for warnings
+    pub warning: Option<String>,
+    /// the style for dates
+    pub date: Option<String>,
+    /// the style for headings
+    pub heading: Option<String>,
+    /// the style for highlighted text
+    pub highlight: Option<String>,
+    /// the style for words added by an edit
+    pub added: Option<String>,
+    /// the style for words removed by an edit
+    pub removed: Option<String>,
+    /// the styles for the days of `jrn heatmap`, from the fewest words to
+    /// the most (four of them)
+    pub heatmap: Option<Vec<String>>,
+}
+
+#[derive(Debug, Error)]
+/// how building a [`Theme`] could go wrong
+pub enum ThemeError {
+    /// there is no built-in preset with the given name
+    #[error("unknown theme preset {0:?}")]
+    UnknownPreset(String),
+    /// the given word is not a color or attribute
+    #[error("unknown color or style {0:?}")]
+    UnknownStyle(String),
+    /// the heatmap was given the wrong number of styles
+    #[error("heatmap needs 4 styles, from the fewest words to the most (got {0})")]
+    HeatmapLevels(usize),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/// a set of SGR codes applied to some text. the empty style leaves text as
+/// it is.
+pub struct Style {
+    codes: Vec<u8>,
+}
+
+impl Style {
+    /// wraps `text` in the escape codes for this style
+    pub fn paint(&self, text: impl Display) -> String {
+        if self.codes.is_empty() {
+            return text.to_string();
+        }
+        let codes = self
+            .codes
+            .iter()
+            .map(|code| code.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        format!("\x1b[{codes}m{text}\x1b[0m")
+    }
+}
+
+impl FromStr for Style {
+    type Err = ThemeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const COLORS: [&str; 8] = [
+            "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+        ];
+
+        let mut codes = Vec::new();
+        for word in s.split_whitespace() {
+            let word = word.to_lowercase();
+            let code = match word.as_str() {
+                "none" => continue,
+                "bold" => 1,
+                "dim" => 2,
+                "italic" => 3,
+                "underline" => 4,
+                "reverse" => 7,
+                _ => {
+                    // `on-<color>` is a background color
+                    let (base, name) = match word.strip_prefix("on-") {
+                        Some(name) => (40, name),
+                        None => (30, word.as_str()),
+                    };
+                    let (base, name) = match name.strip_prefix("bright-") {
+                        Some(name) => (base + 60, name),
+                        None => (base, name),
+                    };
+                    let index = COLORS.iter().position(|color| *color == name);
+                    match index {
+                        Some(index) => base + index as u8,
+                        None => return Err(ThemeError::UnknownStyle(word)),
+                    }
+                }
+            };
+            codes.push(code);
+        }
+
+        Ok(Self { codes })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// the styles used across all output
+pub struct Theme {
+    /// the style for errors
+    pub error: Style,
+    /// the style for warnings
+    pub warning: Style,
+    /// the style for dates
+    pub date: Style,
+    /// the style for headings
+    pub heading: Style,
+    /// the style for highlighted text
+    pub highlight: Style,
+    /// the style for words added by an edit
+    pub added: Style,
+    /// the style for words removed by an edit
+    pub removed: Style,
+    /// the styles for the days of a heatmap, from the fewest words to the
+    /// most
+    pub heatmap: [Style; 4],
+}
+
+impl Theme {
+    /// gets a built-in theme by name
+    pub fn preset(name: &str) -> Result<Self, ThemeError> {
+        let (styles, heatmap) = match name {
+            "default" => (
+                ["red bold", "yellow bold", "cyan", "bold underline", "black on-bright-yellow", "green", "red"],
+                ["green dim", "green", "bright-green", "bright-green bold"],
+            ),
+            "muted" => (["red", "yellow", "dim", "bold", "reverse", "underline", "dim"], ["dim", "none", "none", "bold"]),
+            "no-color" => (["none"; 7], ["none"; 4]),
+            _ => return Err(ThemeError::UnknownPreset(name.into())),
+        };
+        let [error, warning, date, heading, highlight, added, removed] =
+            styles.map(|style| Style::from_str(style).expect("built-in style"));
+        let heatmap = heatmap.map(|style| Style::from_str(style).expect("built-in style"));
+        Ok(Self { error, warning, date, heading, highlight, added, removed, heatmap })
+    }
+
+    /// builds a theme from a preset (`"default"` if none is given), and then
+    /// overrides any style that is set
+    pub fn from_config(config: &ThemeConfig) -> Result<Self, ThemeError> {
+        let mut theme = Self::preset(config.preset.as_deref().unwrap_or("default"))?;
+        let overrides = [
+            (&config.error, &mut theme.error),
+            (&config.warning, &mut theme.warning),
+            (&config.date, &mut theme.date),
+            (&config.heading, &mut theme.heading),
+            (&config.highlight, &mut theme.highlight),
+            (&config.added, &mut theme.added),
+            (&config.removed, &mut theme.removed),
+        ];
+        for (style, slot) in overrides {
+            if let Some(style) = style {
+                *slot = style.parse()?;
+            }
+        }
+        if let Some(styles) = &config.heatmap {
+            let styles = styles.iter().map(|style| style.parse()).collect::<Result<Vec<Style>, _>>()?;
+            theme.heatmap = styles.try_into().map_err(|styles: Vec<Style>| ThemeError::HeatmapLevels(styles.len()))?;
+        }
+        Ok(theme)
+    }
+}
@@ -0,0 +1,66 @@
+//! module for time-based one-time passwords ([RFC 6238](https://www.rfc-editor.org/rfc/rfc6238)),
+//! the six digit codes authenticator apps show. a journal with a TOTP secret
+//! (see [`State::totp`](crate::db::State::totp)) asks for a code after the
+//! password.
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+/// how many seconds a code is valid for
+pub const PERIOD: u64 = 30;
+
+/// how many digits a code has
+pub const DIGITS: u32 = 6;
+
+/// the base32 alphabet secrets are written in, the way authenticator apps
+/// expect them
+const ALPHABET: base32::Alphabet = base32::Alphabet::Rfc4648 { padding: false };
+
+/// a new random secret (160 bits, like most authenticator apps make), in
+/// base32
+pub fn new_secret() -> String {
+    base32::encode(ALPHABET, &rand::random::<[u8; 20]>())
+}
+
+/// the code for `secret` (in base32, ignoring spaces and case) at `time`
+/// (in seconds since the unix epoch), or `None` if the secret isn't base32
+pub fn code(secret: &str, time: u64) -> Option<String> {
+    let secret = secret.replace(' ', "").to_uppercase();
+    let secret = base32::decode(ALPHABET, &secret).filter(|secret| !secret.is_empty())?;
+
+    let mut mac = Hmac::<Sha1>::new_from_slice(&secret).ok()?;
+    mac.update(&(time / PERIOD).to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // "dynamic truncation", from RFC 4226
+    let offset = usize::from(digest[digest.len() - 1] & 0xf);
+    let truncated = u32::from_be_bytes(digest[offset..offset + 4].try_into().ok()?) & 0x7fff_ffff;
+    Some(format!(
+        "{:0width$}",
+        truncated % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    ))
+}
+
+/// whether `code` is right for `secret` at `time`. codes from one period
+/// before or after are accepted too, for clocks that are a little off.
+pub fn verify(secret: &str, code: &str, time: u64) -> bool {
+    let code = code.replace(' ', "");
+    [time.saturating_sub(PERIOD), time, time + PERIOD]
+        .into_iter()
+        .filter_map(|time| self::code(secret, time))
+        .any(|expected| expected == code)
+}
+
+/// an `otpauth://` URI for `secret`, which authenticator apps can import (for
+/// example from a QR code)
+pub fn uri(secret: &str, account: &str) -> String {
+    let account = account
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect::<String>();
+    format!("otpauth://totp/jrn:{account}?secret={secret}&issuer=jrn&digits={DIGITS}&period={PERIOD}")
+}
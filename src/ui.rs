@@ -1,22 +1,48 @@
 //! functions for interactive ui
 
 use std::{
-    collections::HashSet, fmt::{write, Debug, Display}, path::Path, process::exit, str::FromStr
+    collections::{BTreeMap, HashMap, HashSet}, fmt::Debug, io::{stdin, stdout, IsTerminal, Read, Write}, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Mutex}
 };
 
 use crate::{
     cli::{
-        Arguments, ChangePassword, Edit, EditToday, SubCommand, View,
-    }, config::Config, date::Date, db::{LoadError, State}, encryptor::Encryptor, fail
+        ArchiveCommand, ArchiveSubCommand, Arguments, Audit, Backfill, ChangePassword, ConfigCommand, ConfigShow, ConfigSubCommand, CopyEntry,
+        Edit, EditToday, Export, FileType, Heatmap, Import, List, Lock, Log, Merge, Move, Prune, Publish, Restore, Retag, Search, Share, Shift, Split, Star, Stats, SubCommand, TotpCommand, TotpSubCommand, Unlock, Unpublish, Unstar, Verify, View, ViewToday,
+    }, accessible, agent, browse, calendar, crash, editor, error, i18n::translate, plugin::{self, PluginError}, progress::Progress, signals, tr, warn
+};
+use jrn::{
+    archive::{Archive, ArchiveError, Contents}, audit::{self, AuditAction, AuditEvent}, backup::{self, RestoreError}, config::{Config, ConfigError, ResolvedConfig, DEFAULT_MAX_ATTEMPTS, DEFAULT_MAX_ENTRY_SIZE}, diff::{self, Change}, date::{self, Age, Date, DateRange, GroupBy, Period}, db::{Conflict, EncryptedJournal, Field, LoadError, MergeError, SaveError, ShiftError, State, StoredJournal}, encryptor::{ChainStatus, Encryptor, Secure, PBKDF2_ROUNDS}, export::{self, ExportError}, hooks::{run_hook, run_hook_lines, run_hook_output}, import::{self, DatePatternError, ImportFormat, DEFAULT_DATE_PATTERN}, index::SearchIndex, links::Backlinks, lint::{self, Finding}, merge, mood::Mood, private::{self, Passphrase, PrivateError}, redact::Redaction, render::Target, retention, search::{self, Query}, stats::{self, StatsOutput, Table}, storage::{write_private, FileStorage}, tags, template, theme::{self, Style}, totp
 };
 
 use enum_display::EnumDisplay;
 use enum_utils::FromStr;
-use requestty::{prompt_one, Question};
+use chrono::{Datelike, Duration, NaiveDate};
+use requestty::{prompt_one, question::Choice, Answer, ErrorKind, OnEsc, Question};
 use std::cmp::Ord;
+use thiserror::Error;
 
 const MASK_CHAR: char = '*';
 
+/// whether the password was read from stdin (`--password-file -`), so it
+/// can't be read again for anything else
+static PASSWORD_FROM_STDIN: AtomicBool = AtomicBool::new(false);
+
+/// the passphrase of private sections, asked for at most once a run (see
+/// [`with_private()`])
+static PRIVATE: Mutex<Option<Passphrase>> = Mutex::new(None);
+
+/// entries pruned into an archive journal, waiting for the journal to be
+/// saved (see [`save_archives()`])
+struct PendingArchive {
+    /// the archive journal
+    path: String,
+    /// the password of the journal they were pruned from
+    password: String,
+    entries: Vec<(Date, String)>,
+}
+
+static ARCHIVES: Mutex<Vec<PendingArchive>> = Mutex::new(Vec::new());
+
 /// Result of running app (returned after a full prompt cycle (root prompt -> action prompt -> root
 /// prompt))
 pub enum AppResult {
@@ -26,6 +52,170 @@ pub enum AppResult {
     DidntChangeState,
     /// App was quit
     Quit,
+    /// State was changed, but the user chose not to save the changes
+    DiscardedChanges,
+}
+
+#[derive(Debug, Error)]
+/// how an action could go wrong. in a looping ui, the error is shown and the
+/// user is prompted again.
+pub enum UiError {
+    /// the user couldn't be prompted
+    #[error("{}", tr!("couldn't prompt: {0}", .0))]
+    Prompt(#[from] requestty::ErrorKind),
+    /// the entry couldn't be edited in `$EDITOR`
+    #[error("{}", tr!("couldn't edit the entry: {0}", .0))]
+    Editor(#[source] std::io::Error),
+    /// the prompt gave back a different kind of answer than was asked for
+    #[error("{}", tr!("couldn't get an answer from the prompt"))]
+    UnexpectedAnswer,
+    /// two options that exclude each other were both given
+    #[error("{}", tr!("can't give both {0} and {1}", translate(.0), translate(.1)))]
+    Conflict(&'static str, &'static str),
+    /// the file at the given path couldn't be read
+    #[error("{}", tr!("couldn't read {0}: {1}", .0, .1))]
+    ReadFile(String, #[source] std::io::Error),
+    /// `-` was given for a file, but stdin was already read for the password
+    #[error("{}", tr!("stdin was already read for the password (`--password-file -`), so it can't be read for anything else"))]
+    StdinTaken,
+    /// an entry is larger than `max_entry_size`, with `refuse_large_entries`
+    #[error("{}", tr!("the entry for {0} is too large to save ({1} KB, more than max_entry_size), shorten it or save it anyway with --force", .0.label(), .1))]
+    EntryTooLarge(Date, u64),
+    /// more than two dates were given to `jrn move` or `jrn copy`
+    #[error("{}", tr!("give at most two dates: the entry's date and the new one"))]
+    TooManyDates,
+    /// the entry for the given date is locked
+    #[error("{}", tr!("the entry for {0} is locked (run `jrn unlock {0}` to change it)", .0))]
+    Locked(Date),
+    /// there is no entry for the given date
+    #[error("{}", tr!("no entry for {0}", .0))]
+    NoEntry(Date),
+    /// the journal couldn't be loaded
+    #[error(transparent)]
+    Load(#[from] LoadError),
+    /// there's no journal at the given path (see [`init_journal()`])
+    #[error("{}", tr!("there's no journal at {0}. to start one there, run `jrn init` (with the same `-f`, if any)", .0))]
+    NoJournal(String),
+    /// `jrn init` was run where there's already a file
+    #[error("{}", tr!("there's already a file at {0}, so no new journal was made there", .0))]
+    JournalExists(String),
+    /// the file at the given path isn't a journal
+    #[error("{}", tr!("{0} isn't a journal ({1}). check the path given with `-f` or `file_path`, or put a backup in its place with `jrn restore`", .0, .1))]
+    NotAJournal(String, #[source] serde_json::Error),
+    /// the configuration couldn't be read
+    #[error(transparent)]
+    Config(Box<ConfigError>),
+    /// the configuration file at the given path is invalid
+    #[error("{0}: {1}")]
+    InvalidConfig(String, #[source] Box<ConfigError>),
+    /// a journal (other than the main one) couldn't be saved
+    #[error(transparent)]
+    Save(#[from] SaveError),
+    /// `jrn prune` was run without an age, and there's no retention policy
+    #[error("{}", tr!("how old should pruned entries be? give --older-than or set older_than in [retention]"))]
+    NoRetention,
+    /// the journal's hash chain is missing or broken
+    #[error("{}", chain_message(.0))]
+    Chain(ChainStatus),
+    /// the journal couldn't be exported
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    /// the journal didn't open with the new password after it was saved
+    #[error("{}", tr!("the journal was saved, but doesn't open with the new password: {0}. backups of it (if any) still open with the old one", .0))]
+    NewPassword(String),
+    /// the entries couldn't be shifted
+    #[error(transparent)]
+    Shift(#[from] ShiftError),
+    /// an archive couldn't be made or opened
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+    /// the date pattern of `jrn import` is wrong
+    #[error(transparent)]
+    DatePattern(#[from] DatePatternError),
+    /// a private section couldn't be sealed or revealed
+    #[error(transparent)]
+    Private(#[from] PrivateError),
+    /// the passphrase of private sections was typed differently the second
+    /// time
+    #[error("{}", tr!("the passphrases don't match"))]
+    PassphraseMismatch,
+    /// a file that would be written exists already
+    #[error("{}", tr!("there's already a file at {0}, it wasn't replaced", .0))]
+    FileExists(String),
+    /// an entry from before `freeze_before` was changed
+    #[error("{}", tr!("the entry for {0} is from before freeze_before ({1}), so it can't be changed (save anyway with --force)", .0.label(), .1))]
+    Frozen(Date, Date),
+    /// a journal to share was given the journal's own password
+    #[error("{}", tr!("that's the password of this journal, give the shared one a password of its own"))]
+    SharedPassword,
+    /// another journal couldn't be merged in
+    #[error("{}", tr!("{0} (merge them with --overwrite or --append)", .0))]
+    Merge(#[from] MergeError),
+    /// some of the entries or files of a batch operation failed (the rest
+    /// were done, and the failures were printed as they're collected)
+    #[error("{}", tr!("{0} of {1} failed", .0, .1))]
+    Incomplete(usize, usize),
+    /// the configuration couldn't be serialized
+    #[error("couldn't serialize config: {0}")]
+    SerializeConfig(#[from] toml::ser::Error),
+    /// statistics couldn't be written as JSON
+    #[error("{}", tr!("couldn't write the statistics as JSON: {0}", .0))]
+    SerializeStats(#[from] serde_json::Error),
+    /// an external command failed
+    #[error(transparent)]
+    Plugin(#[from] PluginError),
+    /// `jrn audit` was run on a journal without an audit log
+    #[error("{}", tr!("the journal doesn't have an audit log (set `audit_log = true` to start one)"))]
+    NoAudit,
+    /// a backup couldn't be restored
+    #[error(transparent)]
+    Restore(#[from] RestoreError),
+    /// `jrn restore` was run, but the journal has no backups
+    #[error("{}", tr!("the journal has no backups (see [backup] in the config)"))]
+    NoBackups,
+    /// there's no backup with the given number
+    #[error("{}", tr!("there's no backup {0}, the journal has {1}", .0, .1))]
+    NoSuchBackup(usize, usize),
+    /// the backup doesn't open with the password
+    #[error("{}", tr!("the backup doesn't open with the password (restore it anyway with --no-check)"))]
+    BackupPassword,
+    /// the given tag isn't a valid tag
+    #[error("{}", tr!("{0} isn't a tag (tags are letters, digits, - and _, with at least one letter)", .0))]
+    InvalidTag(String),
+    /// no entry has the given tag
+    #[error("{}", tr!("no entries have #{0}", .0))]
+    NoTag(String),
+    /// the TOTP code was incorrect too many times
+    #[error("{}", tr!("incorrect code"))]
+    IncorrectCode,
+    /// the password prompt was left with Esc or Ctrl-C
+    #[error("{}", tr!("the journal wasn't unlocked"))]
+    UnlockCancelled,
+    /// the password was incorrect as many times as `max_attempts` allows
+    #[error("{}", tr!("incorrect password, and that was the last try (if typing it is the trouble, `--password-file` can read it from a file)"))]
+    TooManyAttempts,
+}
+
+fn chain_message(status: &ChainStatus) -> String {
+    match status {
+        ChainStatus::Missing => {
+            tr!("the journal doesn't have a hash chain (set `hash_chain = true` and save it)").into()
+        }
+        ChainStatus::Intact => tr!("the hash chain is intact").into(),
+        ChainStatus::BrokenAt(date) => tr!(
+            "the hash chain is broken at {0}: entries were changed or removed without the password",
+            date
+        ),
+        ChainStatus::Truncated => {
+            tr!("the hash chain is broken: entries were removed from the end without the password").into()
+        }
+    }
+}
+
+impl From<ConfigError> for UiError {
+    fn from(value: ConfigError) -> Self {
+        Self::Config(Box::new(value))
+    }
 }
 
 #[derive(EnumDisplay, Debug, FromStr, PartialEq, PartialOrd, Eq, Ord, Hash)]
@@ -38,6 +228,10 @@ pub enum PathWay {
     ChangePassword,
     /// list all entries in chronological order
     List,
+    /// find entries by their text, tags, or dates
+    Search,
+    /// find an entry by typing part of it, then view or edit it
+    Browse,
     /// view a given entry
     View,
     /// edit a given entry
@@ -48,6 +242,14 @@ pub enum PathWay {
     #[enumeration(rename = "Edit Today")]
     /// edit today's entry
     EditToday,
+    /// move an entry to another date
+    Move,
+    /// copy an entry to another date
+    Copy,
+    /// write entries for missed days
+    Backfill,
+    /// add timestamped lines to today's entry
+    Log,
     /// quit the application
     Quit,
 }
@@ -67,86 +269,194 @@ impl TryFrom<PathWay> for SubCommand {
         match value {
             PW::ChangePassword => Ok(SC::ChangePassword(Default::default())),
             PW::List => Ok(SC::List(Default::default())),
+            PW::Search => Ok(SC::Search(Default::default())),
+            PW::Browse => Ok(SC::Browse(Default::default())),
             PW::View => Ok(SC::View(Default::default())),
             PW::Edit => Ok(SC::Edit(Default::default())),
             PW::ViewToday => Ok(SC::ViewToday(Default::default())),
             PW::EditToday => Ok(SC::EditToday(Default::default())),
+            PW::Move => Ok(SC::Move(Default::default())),
+            PW::Copy => Ok(SC::Copy(Default::default())),
+            PW::Backfill => Ok(SC::Backfill(Default::default())),
+            PW::Log => Ok(SC::Log(Default::default())),
             PW::Quit => Err(SubCommandFromPathWayError::QuitVariantWasUsed),
         }
     }
 }
 /// initialize memory
-pub fn init<E: Encryptor>(config: &Config, e: &E) -> State {
-    let config = config.clone();
+pub fn init<E: Encryptor>(config: &Config, e: &E) -> Result<State, UiError> {
+    open_journal(config, e, None)
+}
+
+/// like [`init()`], but only decrypts the entries that might match `query`
+/// (see [`State::load_matching_from()`]). the state must not be saved.
+pub fn init_matching<E: Encryptor>(config: &Config, e: &E, query: &Query) -> Result<State, UiError> {
+    open_journal(config, e, Some(query))
+}
+
+fn open_journal<E: Encryptor>(config: &Config, e: &E, query: Option<&Query>) -> Result<State, UiError> {
     let jrn_path = config.file_path.as_deref().unwrap_or("./jrn.json");
     let mut state = State::new();
+    // a mistyped `-f` shouldn't quietly start a new, empty journal
     if !Path::new(jrn_path).exists() {
-        let pass = match (config.password, config.password_file) {
-            (None, None) => get_new_password(),
-            (Some(password), None) => password,
-            (None, Some(password_file)) => {
-                let password = std::fs::read_to_string(&password_file);
-                if let Err(e) = password {
-                    fail!("couldn't read password from file {password_file}: {e:?}");
-                }
-                password.unwrap().trim().into()
-            }
-            (Some(_), Some(_)) => {
-                fail!("can't give both password string and password file");
-            }
-        };
-        state.change_password(&pass);
-        return state;
+        return Err(UiError::NoJournal(jrn_path.into()));
     }
 
-    let mut pass = match (config.password, config.password_file) {
-        (None, None) => password("Please enter your password"),
-        (Some(password), None) => password,
-        (None, Some(password_file)) => {
-            let password = std::fs::read_to_string(&password_file);
-            if let Err(e) = password {
-                fail!(
-                    "couldn't read password from file {password_file}: {e:?}"
-                );
-            }
-            password.unwrap().trim().into()
+    // a running `jrn daemon` might still have the keys, and then there's no
+    // need for the password
+    let storage = FileStorage::new(jrn_path);
+    if let Some(keys) = agent::keys(jrn_path) {
+        if state.load_with_keys_from(&storage, &keys, e, query).is_ok() {
+            record_unlock(config.audit_log == Some(true), &storage, &mut state, e);
+            return Ok(state);
         }
+    }
+
+    // Esc or Ctrl-C at the prompt stops without complaining about the prompt
+    let ask = |message| {
+        password(message).map_err(|e| match e {
+            UiError::Prompt(ErrorKind::Interrupted | ErrorKind::Aborted | ErrorKind::Eof) => UiError::UnlockCancelled,
+            e => e,
+        })
+    };
+    let prompted = config.password.is_none() && config.password_file.is_none();
+    let mut pass = match (config.password.as_deref(), config.password_file.as_deref()) {
+        (None, None) => ask("Please enter your password")?,
+        (Some(password), None) => password.into(),
+        (None, Some(password_file)) => read_file(password_file)?.trim().into(),
         (Some(_), Some(_)) => {
-            fail!("can't give both password string and password file");
+            return Err(UiError::Conflict("password string", "password file"));
         }
     };
 
-    let mut loaded = state.load(jrn_path, &pass, e);
-
-    if let Err(LoadError::IncorrectPassword) = loaded {
-        loop {
-            pass = password("Try Again. Please enter password");
-            loaded = state.load(jrn_path, &pass, e);
+    let load = |state: &mut State, pass: &str| {
+        let loaded = match query {
+            Some(query) => state.load_matching_from(&storage, pass, e, query),
+            None => state.load(jrn_path, pass, e),
+        };
+        if let Err(LoadError::IncorrectPassword) = loaded {
+            record_failed_unlock(&storage);
+        }
+        loaded
+    };
+    let mut loaded = load(&mut state, &pass);
+
+    // only ask again if the password was typed in, so scripts fail instead of
+    // waiting for a prompt
+    let max_attempts = config.max_attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS);
+    let mut attempts = 1;
+    while prompted && matches!(loaded, Err(LoadError::IncorrectPassword)) {
+        if attempts >= max_attempts {
+            return Err(UiError::TooManyAttempts);
+        }
+        pass = ask("Try Again. Please enter password")?;
+        loaded = load(&mut state, &pass);
+        attempts += 1;
+    }
 
-            if let Err(LoadError::IncorrectPassword) = loaded {
-            } else {
-                break;
+    if let Err(LoadError::ParseError(e)) = loaded {
+        return Err(UiError::NotAJournal(jrn_path.into(), e));
+    }
+    loaded?;
+    // the keys are only given to the daemon once the code is checked too
+    if let Some(secret) = &state.totp {
+        if let Err(e) = ask_totp_code(secret) {
+            if let UiError::IncorrectCode = e {
+                record_failed_unlock(&storage);
             }
+            return Err(e);
         }
     }
+    if let Some(keys) = &state.keys {
+        if let Err(e) = agent::remember(jrn_path, keys) {
+            warn!("{e}");
+        }
+    }
+    record_unlock(config.audit_log == Some(true), &storage, &mut state, e);
+
+    Ok(state)
+}
 
-    if let Err(e) = loaded {
-        fail!("load error: {e:?}");
+/// creates a new, empty journal at the journal path, with the password given
+/// in the configuration or a new one asked for. there must be nothing at the
+/// path yet.
+pub fn init_journal<E: Encryptor>(config: &Config, e: &E) -> Result<(), UiError> {
+    let jrn_path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    if Path::new(jrn_path).exists() {
+        return Err(UiError::JournalExists(jrn_path.into()));
     }
 
-    state
+    let pass = match (config.password.as_deref(), config.password_file.as_deref()) {
+        (None, None) => get_new_password()?,
+        (Some(password), None) => password.into(),
+        (None, Some(password_file)) => read_file(password_file)?.trim().into(),
+        (Some(_), Some(_)) => {
+            return Err(UiError::Conflict("password string", "password file"));
+        }
+    };
+    let mut state = State::new();
+    state.change_password(&pass);
+    state.hash_chain = config.hash_chain == Some(true);
+    if config.search_index == Some(true) {
+        state.index = Some(SearchIndex::default());
+    }
+    if config.audit_log == Some(true) {
+        state.audit = Some(Vec::new());
+    }
+
+    let _saving = signals::saving();
+    state.save(jrn_path, e)?;
+    println!("{}", tr!("created a new journal at {0}", jrn_path));
+    Ok(())
+}
+
+/// notes a failed unlock in the journal's audit log, if it has one
+fn record_failed_unlock(storage: &FileStorage) {
+    if let Err(e) = audit::record_failed_unlock(storage) {
+        let message = tr!("couldn't write to the audit log: {0}", e);
+        warn!("{message}");
+    }
+}
+
+/// notes the unlock in the journal's audit log (starting one with
+/// `audit_log`), after warning about failed unlocks since the last one
+fn record_unlock<E: Encryptor>(audit_log: bool, storage: &FileStorage, state: &mut State, e: &E) {
+    if audit_log && state.audit.is_none() {
+        state.audit = Some(Vec::new());
+    }
+    let Some(events) = &state.audit else {
+        return;
+    };
+
+    let failed = audit::failed_since_unlock(events);
+    if failed > 0 {
+        let message = tr!(
+            "there were {0} failed attempts to unlock the journal since it was last unlocked (see `jrn audit`)",
+            failed
+        );
+        warn!("{message}");
+    }
+    state.record(AuditAction::Unlocked);
+    if let Err(e) = state.save_audit_to(storage, e) {
+        let message = tr!("couldn't write to the audit log: {0}", e);
+        warn!("{message}");
+    }
 }
 
 /// determine whether the UI should loop.
 /// ## Logic
 /// - If the config (command line arguments and config file information) contains
-/// the [`Config::do_loop`] flag and it is set to `true`, then do loop.
+///   the [`Config::do_loop`] flag and it is set to `true`, then do loop.
 /// - If the config contains the [`Config::dont_loop`] flag and it is set to 
-/// `true`, then don't loop
+///   `true`, then don't loop
 /// - If there is a subcommand specified, don't loop the program.
+///
 /// Each condition is evaluated if the conditions before it haven't been true.
 pub fn should_loop(config: &Config, subcommand: &Option<SubCommand>) -> bool {
-    let config = config.clone();
+    // it's for writing today's entry and getting on with the day
+    if let Some(SubCommand::Open(_)) = subcommand {
+        return false;
+    }
     if let Some(do_loop) = config.do_loop {
         if do_loop {
             return true;
@@ -164,349 +474,3022 @@ pub fn should_loop(config: &Config, subcommand: &Option<SubCommand>) -> bool {
     true
 }
 
-/// runs the app, looping if requested (see [`should_loop()`])
-pub fn app(config: &Config, subcommand: Option<SubCommand>, state: &mut State) -> AppResult {
+/// runs the app, looping if requested (see [`should_loop()`]). when looping,
+/// errors are shown and the user is prompted again instead of stopping.
+/// `force` is `--force`.
+pub fn app(config: &Config, subcommand: Option<SubCommand>, state: &mut State, force: bool) -> Result<AppResult, UiError> {
     let should_loop = should_loop(config, &subcommand);
     if should_loop {
-        let config = config.clone();
-        let mut subcommand = subcommand.clone();
+        // without autosave, Ctrl-C shouldn't lose the changes made so far
+        let ask_to_save = config.autosave == Some(false);
+        signals::catch_interrupts(ask_to_save);
+
+        let mut subcommand = subcommand;
         let mut ret = AppResult::DidntChangeState;
         loop {
-            let ar = _app(&config, subcommand, state);
+            let ar = _app(config, subcommand, state, force);
             subcommand = None;
             match ar {
-                AppResult::ChangedState => {
+                Ok(AppResult::ChangedState) => {
                     ret = AppResult::ChangedState;
                 }
-                AppResult::Quit => {
+                Ok(AppResult::Quit) => {
+                    break;
+                }
+                Ok(AppResult::DidntChangeState | AppResult::DiscardedChanges) => {}
+                // Ctrl-C in a prompt
+                Err(UiError::Prompt(ErrorKind::Interrupted)) if ask_to_save => {
                     break;
                 }
-                _ => {
-                    continue;
+                Err(e) => {
+                    error!("{e}");
                 }
             }
+            if signals::interrupted() {
+                break;
+            }
+        }
+
+        signals::catch_interrupts(false);
+        if ask_to_save && matches!(ret, AppResult::ChangedState) && !confirmation("Save your changes before quitting?")? {
+            return Ok(AppResult::DiscardedChanges);
         }
-        ret
+        Ok(ret)
     } else {
-        return _app(config, subcommand, state);
+        _app(config, subcommand, state, force)
     }
 }
 
 /// asks the user what course of action (see [`PathWay`]) they would like to
 /// take. not triggered if a subcommand has been supplied.
-pub fn prompt_pathway() -> PathWay {
+pub fn prompt_pathway() -> Result<PathWay, UiError> {
     let pathways: HashSet<PathWay> = HashSet::from([
         PathWay::ChangePassword,
         PathWay::List,
+        PathWay::Search,
+        PathWay::Browse,
         PathWay::View,
         PathWay::Edit,
         PathWay::ViewToday,
         PathWay::EditToday,
+        PathWay::Move,
+        PathWay::Copy,
+        PathWay::Backfill,
+        PathWay::Log,
         PathWay::Quit,
     ]);
 
-    let pathway =
-        choose(pathways, "Welcome to jrn. Please choose a course of action", false);
-
-    pathway
+    choose(
+        pathways,
+        "Welcome to jrn. Please choose a course of action",
+        false,
+        |pathway| translate(&pathway.to_string()).into(),
+    )
 }
 
-fn _app(config: &Config, subcommand: Option<SubCommand>, state: &mut State) -> AppResult {
+fn _app(config: &Config, subcommand: Option<SubCommand>, state: &mut State, force: bool) -> Result<AppResult, UiError> {
     use SubCommand as SC;
 
     let subcommand = match &subcommand {
         None => {
-            let pw = prompt_pathway();
-            let subcommand = SubCommand::try_from(pw);
-            if subcommand.is_err() {
-                return AppResult::Quit;
-            }
-            subcommand.unwrap()
+            let pw = prompt_pathway()?;
+            let Ok(subcommand) = SubCommand::try_from(pw) else {
+                return Ok(AppResult::Quit);
+            };
+            subcommand
         }
         Some(subcommand) => subcommand.clone(),
     };
 
     match subcommand {
         SC::ChangePassword(opts) => change_password(&opts, state),
-        SC::List(_) => list_entries(&state),
-        SC::View(opts) => view_entries(&opts, &state),
+        SC::List(opts) => list_entries(&opts, state),
+        SC::Search(opts) => search_entries(&opts, state),
+        SC::Browse(_) => browse(config, state),
+        SC::Reindex(_) => reindex(state),
+        SC::Totp(opts) => totp(config, &opts, state),
+        SC::View(opts) => view_entries(config, &opts, state),
         SC::Edit(opts) => edit_entry(config, &opts, state),
-        SC::ViewToday(_) => view_today(&state),
+        SC::ViewToday(opts) => view_today(config, &opts, state),
         SC::EditToday(opts) => edit_today(config, &opts, state),
+        SC::Open(_) => open_today(config, state),
+        SC::Stats(opts) => print_stats(&opts, state),
+        SC::Heatmap(opts) => print_heatmap(&opts, state),
+        SC::Tags(_) => list_tags(state),
+        SC::Retag(opts) => retag(config, &opts, state),
+        SC::Backfill(opts) => backfill(config, &opts, state),
+        SC::Log(opts) => log(config, &opts, state),
+        SC::Export(opts) => export_journal(config, &opts, state),
+        SC::Move(opts) => move_entry(config, &opts, state),
+        SC::Copy(opts) => copy_entry(config, &opts, state),
+        SC::Shift(opts) => shift(config, &opts, state),
+        SC::FileType(opts) => set_file_type(&opts, state),
+        SC::Star(opts) => star(&opts, state),
+        SC::Unstar(opts) => unstar(&opts, state),
+        SC::Publish(opts) => publish(&opts, state),
+        SC::Unpublish(opts) => unpublish(&opts, state),
+        SC::Lock(opts) => lock(&opts, state),
+        SC::Unlock(opts) => unlock(&opts, state),
+        SC::Verify(opts) => verify(config, &opts, state),
+        SC::Audit(opts) => print_audit(&opts, state),
+        SC::Prune(opts) => prune(config, &opts, state, force),
+        SC::Split(opts) => split_journal(config, &opts, state),
+        SC::Merge(opts) => merge_journals(&opts, state),
+        SC::Share(opts) => share(&opts, state),
+        SC::Import(opts) => import_journal(&opts, state),
+        // handled in `main` before the journal is unlocked
+        SC::Config(_) | SC::Init(_) | SC::Info(_) | SC::Restore(_) | SC::Archive(_) | SC::Daemon(_) => Ok(AppResult::DidntChangeState),
+        SC::Plugin(opts) => Ok(plugin::run(config, &opts, state)?),
     }
 }
 
-/// prompts the user to edit today's entry. will pull up an `$EDITOR` if no pre
-/// determined content has been given.
-pub fn edit_today(config: &Config, opts: &EditToday, state: &mut State) -> AppResult {
-    let opts = opts.clone();
-    let config = config.clone();
-
-    if opts.content.is_some() && opts.content_path.is_some() {
-        fail!("can't give both content string and content path");
+/// resolves the configuration for the given arguments (see
+/// [`Config::get_config()`]), warning about any deprecated keys
+pub fn get_config(args: &Arguments) -> Result<ResolvedConfig, ConfigError> {
+    let resolved = Config::get_config(args.config_file.as_deref(), &Config::from(args))?;
+    for warning in &resolved.warnings {
+        warn!("{warning}");
     }
+    Ok(resolved)
+}
 
-    let content = match (opts.content, opts.content_path) {
-        (None, None) => {
-            let content = state.get_today();
-            let new_content =
-                edit(content.as_deref(), &config.file_type.unwrap_or(".md".into()), "Press <Enter> to edit");
-            new_content
+/// fills in the password from `--password-fd` or stdin (`--password-file
+/// -`), or, without a password, from
+/// `$CREDENTIALS_DIRECTORY/jrn.password` (for systemd services, see
+/// `LoadCredential=`)
+pub fn read_password_source(args: &Arguments, config: &mut Config) -> Result<(), UiError> {
+    if let Some(fd) = args.password_fd {
+        if args.password.is_some() {
+            return Err(UiError::Conflict("password string", "password fd"));
         }
-        (Some(content), None) => content,
-        (None, Some(content_path)) => {
-            let content = std::fs::read_to_string(&content_path);
-            if let Err(e) = content {
-                fail!("couldn't read content from file {content_path}: {e:?}");
-            }
-            content.unwrap()
-        }
-        (Some(_), Some(_)) => {
-            fail!("can't give both content string and content path");
+        if args.password_file.is_some() {
+            return Err(UiError::Conflict("password file", "password fd"));
         }
-    };
+        config.password = Some(read_file(&format!("/dev/fd/{fd}"))?.trim().into());
+        config.password_file = None;
+        return Ok(());
+    }
 
-    let old_content = state.get_today();
-    state.set_today(&content);
+    if config.password_file.as_deref() == Some("-") {
+        if config.password.is_some() {
+            return Err(UiError::Conflict("password string", "password file"));
+        }
+        let mut line = String::new();
+        stdin()
+            .read_line(&mut line)
+            .map_err(|e| UiError::ReadFile("-".into(), e))?;
+        config.password = Some(line.trim().into());
+        config.password_file = None;
+        PASSWORD_FROM_STDIN.store(true, Ordering::Relaxed);
+        return Ok(());
+    }
 
-    if old_content.is_some() {
-        if old_content.unwrap() == content {
-            return AppResult::DidntChangeState;
+    if config.password.is_none() && config.password_file.is_none() {
+        let credential = std::env::var_os("CREDENTIALS_DIRECTORY")
+            .map(|dir| Path::new(&dir).join("jrn.password"))
+            .filter(|credential| credential.is_file());
+        if let Some(credential) = credential {
+            config.password_file = Some(credential.display().to_string());
         }
     }
 
-    AppResult::ChangedState
+    Ok(())
 }
 
-/// view today's entry. will print out "\<No Entry>" in the case of no entries
-/// for today
-pub fn view_today(state: &State) -> AppResult {
-    let entry = state.get_today().unwrap_or("<No Entry>".into());
-    println!("{entry}");
-
-    AppResult::DidntChangeState
+/// runs a configuration action. doesn't need the journal to be unlocked.
+pub fn config_command(args: &Arguments, opts: &ConfigCommand) -> Result<AppResult, UiError> {
+    match opts.subcommand {
+        ConfigSubCommand::Check(_) => check_config(args),
+        ConfigSubCommand::Show(ref opts) => show_config(args, opts),
+    }
 }
 
-/// edit any entry. prompts for a date if none are given. pulls up `$EDITOR` if
-/// no content is given.
-pub fn edit_entry(config: &Config, opts: &Edit, state: &mut State) -> AppResult {
-    let opts = opts.clone();
-    let config = config.clone();
+/// prints the configuration file, or (with `--effective`) the configuration
+/// actually used, annotated with where each value came from. passwords are
+/// masked.
+pub fn show_config(args: &Arguments, opts: &ConfigShow) -> Result<AppResult, UiError> {
+    if !opts.effective {
+        let Some(path) = Config::get_config_path(args.config_file.as_deref()) else {
+            println!("{}", tr!("no configuration file found, using defaults"));
+            return Ok(AppResult::DidntChangeState);
+        };
 
-    let date = match opts.date {
-        Some(date) => date,
-        None => {
-            let dates = get_dates(&state);
-            choose(dates, "Which entry do you want to edit?", true)
+        let (mut config, warnings) = Config::read_config_file(&path)?;
+        for warning in warnings {
+            warn!("{warning}");
         }
-    };
 
-    let old_content = state.get_entry(&date);
+        if config.password.is_some() {
+            config.password = Some(MASK_CHAR.to_string().repeat(8));
+        }
+        print!("{}", toml::to_string_pretty(&config)?);
+        return Ok(AppResult::DidntChangeState);
+    }
 
-    let new_content = match (opts.content, opts.content_path) {
-        (Some(_), Some(_)) => {
-            fail!("can't give both content and content path");
+    let ResolvedConfig { config, sources, .. } = get_config(args)?;
+
+    let table = toml::Table::try_from(&config)?;
+
+    let mut sections = Vec::new();
+    for (key, source) in sources {
+        match table.get(key) {
+            None => println!("# {key} is unset ({source})"),
+            Some(toml::Value::Table(section)) => sections.push((key, section, source)),
+            Some(_) if key == "password" => println!("{key} = \"{}\"  # {source}", MASK_CHAR.to_string().repeat(8)),
+            Some(value) => println!("{key} = {value}  # {source}"),
+        }
+    }
+
+    for (key, section, source) in sections {
+        println!("\n[{key}]  # {source}");
+        for (key, value) in section {
+            println!("{key} = {value}");
         }
-        (Some(content), None) => content,
-        (None, Some(content_path)) => {
-            let content = std::fs::read_to_string(&content_path);
-            if let Err(e) = content {
-                fail!("couldn't read content from file {content_path}: {e:?}");
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints facts about the journal file: its size, format version, how it's
+/// encrypted, and its entries' dates. doesn't need the journal to be unlocked.
+pub fn info(config: &Config) -> Result<AppResult, UiError> {
+    let path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    let json = read_file(path)?;
+    let metadata = std::fs::metadata(path).map_err(|e| UiError::ReadFile(path.into(), e))?;
+    let journal = serde_json::from_str::<StoredJournal>(&json).map_err(LoadError::from)?;
+
+    let mut dates = journal.entries.iter().map(|entry| entry.date).collect::<Vec<_>>();
+    dates.sort();
+
+    // a bcrypt hash looks like `$2b$<cost>$<salt and hash>`
+    let encryption = match journal.password_hash.split('$').collect::<Vec<_>>()[..] {
+        ["", "2a" | "2b" | "2x" | "2y", cost, _] => tr!(
+            "bcrypt (cost {0}), pbkdf2-hmac-sha256 ({1} rounds), AES-256-GCM-SIV",
+            cost,
+            PBKDF2_ROUNDS
+        ),
+        _ => tr!("unknown (not a bcrypt password hash)").into(),
+    };
+    let modified = metadata
+        .modified()
+        .map(|modified| date::to_local(modified.into()).format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|_| tr!("unknown").into());
+    let none = || tr!("none").to_string();
+
+    let facts = [
+        (tr!("path"), path.to_string()),
+        (tr!("size"), tr!("{0} bytes", metadata.len())),
+        (tr!("format version"), journal.version.to_string()),
+        (tr!("encryption"), encryption),
+        (tr!("second factor"), if journal.totp.is_some() { "TOTP".into() } else { none() }),
+        (tr!("entries"), dates.len().to_string()),
+        (tr!("public entries"), journal.entries.iter().filter(|entry| entry.text.is_some()).count().to_string()),
+        (tr!("first entry"), dates.first().map_or_else(none, Date::label)),
+        (tr!("last entry"), dates.last().map_or_else(none, Date::label)),
+        (tr!("last modified"), modified),
+    ];
+    let width = facts.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, value) in facts {
+        let name = format!("{name}:");
+        println!("{name:<0$} {value}", width + 1);
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// whether there's an entry for today, for `jrn view-today --exists`. the
+/// dates of the entries aren't encrypted, so the journal isn't unlocked.
+pub fn today_exists(config: &Config, opts: &ViewToday) -> Result<bool, UiError> {
+    if opts.header || opts.words {
+        return Err(UiError::Conflict("--exists", if opts.words { "--words" } else { "--header" }));
+    }
+    let path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    if !Path::new(path).exists() {
+        return Ok(false);
+    }
+    let json = read_file(path)?;
+    let journal = serde_json::from_str::<StoredJournal>(&json).map_err(LoadError::from)?;
+
+    let today = Date::today();
+    Ok(journal.entries.iter().any(|entry| entry.date == today))
+}
+
+/// replaces the journal with one of its backups (or any file), after checking
+/// that it's a journal that opens with the password. doesn't unlock the
+/// journal, so it works when the journal can't be read.
+pub fn restore<E: Encryptor>(config: &Config, opts: &Restore, e: &E) -> Result<AppResult, UiError> {
+    let path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    let backup_config = config.backup.clone().unwrap_or_default();
+
+    let chosen = match (opts.backup, &opts.file) {
+        (Some(_), Some(_)) => return Err(UiError::Conflict("--backup", "--file")),
+        (None, Some(file)) => PathBuf::from(file),
+        (number, None) => {
+            let backups = backup::list_backups(path, &backup_config).map_err(RestoreError::from)?;
+            if backups.is_empty() {
+                return Err(UiError::NoBackups);
+            }
+            match number {
+                Some(number) => match number.checked_sub(1).and_then(|i| backups.get(i)) {
+                    Some(chosen) => chosen.clone(),
+                    None => return Err(UiError::NoSuchBackup(number, backups.len())),
+                },
+                None => choose(backups.into_iter().collect(), "Which backup?", true, |backup| {
+                    backup.file_name().unwrap_or_default().to_string_lossy().into_owned()
+                })?,
             }
-            content.unwrap()
         }
-        (None, None) => {
-            edit(
-                old_content.as_deref(),
-                config.file_type.as_deref().unwrap_or(".md"), 
-                "Press <Enter> to edit",
-            )
+    };
+    let contents = backup::read_backup(&chosen)?;
+
+    if !opts.no_check {
+        let pass = given_password(config)?;
+        match State::new().load_from(&FileStorage::new(&chosen), &pass, e) {
+            Ok(()) => {}
+            Err(LoadError::IncorrectPassword) => return Err(UiError::BackupPassword),
+            Err(e) => return Err(e.into()),
         }
+    }
+
+    let name = chosen.display().to_string();
+    if !opts.yes && !confirmation(&tr!("Replace the journal with {0}?", name))? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let saving = signals::saving();
+    let replaced = backup::restore(path, &contents, &backup_config)?;
+    drop(saving);
+    match replaced {
+        Some(replaced) => println!("{}", tr!("restored {0} (the replaced journal is in {1})", name, replaced.display())),
+        None => println!("{}", tr!("restored {0}", name)),
+    }
+    Ok(AppResult::ChangedState)
+}
+
+/// the password given with `--password` or `--password-file`, or else asked
+/// for, for commands that don't unlock the journal
+fn given_password(config: &Config) -> Result<String, UiError> {
+    match (config.password.as_deref(), config.password_file.as_deref()) {
+        (None, None) => password("Please enter your password"),
+        (Some(password), None) => Ok(password.into()),
+        (None, Some(password_file)) => Ok(read_file(password_file)?.trim().into()),
+        (Some(_), Some(_)) => Err(UiError::Conflict("password string", "password file")),
+    }
+}
+
+/// bundles the journal and the configuration file into an archive, unpacks
+/// one, or checks one (see [`Archive`]). the journal isn't unlocked, but the
+/// password is needed all the same.
+pub fn archive<E: Encryptor>(config: &Config, args: &Arguments, opts: &ArchiveCommand, e: &E) -> Result<(), UiError> {
+    match &opts.subcommand {
+        ArchiveSubCommand::Create(opts) => create_archive(config, args, &opts.file, e),
+        ArchiveSubCommand::Extract(opts) => extract_archive(config, &opts.file, opts.out_dir.as_deref(), e),
+        ArchiveSubCommand::Verify(opts) => verify_archive(config, &opts.file, e),
+    }
+}
+
+/// the last part of `path`, so an archive can't write anywhere but where
+/// it's extracted to
+fn file_name_of(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.into())
+}
+
+fn create_archive<E: Encryptor>(config: &Config, args: &Arguments, file: &str, e: &E) -> Result<(), UiError> {
+    if Path::new(file).exists() {
+        return Err(UiError::FileExists(file.into()));
+    }
+    let journal_path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    if !Path::new(journal_path).exists() {
+        return Err(UiError::NoJournal(journal_path.into()));
+    }
+    let journal = read_file(journal_path)?;
+    let pass = given_password(config)?;
+    // only a password that opens the journal is used for the archive
+    State::new().load_json(&journal, &pass, e)?;
+
+    let config_file = Config::get_config_path(args.config_file.as_deref()).filter(|path| Path::new(path).is_file());
+    let contents = Contents {
+        journal_name: file_name_of(journal_path),
+        journal,
+        config: match config_file {
+            Some(path) => Some((file_name_of(&path), read_file(&path)?)),
+            None => None,
+        },
     };
+    let archive = Archive::create(&contents, &pass, chrono::Utc::now().timestamp(), e)?;
+    write_private(Path::new(file), archive.to_json().as_bytes()).map_err(SaveError::from)?;
 
-    state.set_entry(&date, &new_content);
+    match &contents.config {
+        Some((config_name, _)) => println!("{}", tr!("archived {0} and {1} in {2}", contents.journal_name, config_name, file)),
+        None => println!("{}", tr!("archived {0} in {1}", contents.journal_name, file)),
+    }
+    Ok(())
+}
 
-    if old_content.is_some() {
-        if old_content.unwrap() == new_content {
-            return AppResult::DidntChangeState;
-        }
+fn extract_archive<E: Encryptor>(config: &Config, file: &str, out_dir: Option<&str>, e: &E) -> Result<(), UiError> {
+    let archive = Archive::parse(&read_file(file)?)?;
+    let pass = given_password(config)?;
+    // nothing is written unless the journal opens
+    let (contents, _) = archive.verify(&pass, e)?;
+
+    let out_dir = Path::new(out_dir.unwrap_or("."));
+    let mut files = vec![(out_dir.join(file_name_of(&contents.journal_name)), contents.journal)];
+    if let Some((name, text)) = contents.config {
+        files.push((out_dir.join(file_name_of(&name)), text));
+    }
+    if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(UiError::FileExists(path.display().to_string()));
     }
 
-    AppResult::ChangedState
+    std::fs::create_dir_all(out_dir).map_err(SaveError::from)?;
+    for (path, text) in &files {
+        write_private(path, text.as_bytes()).map_err(SaveError::from)?;
+        println!("{}", tr!("wrote {0}", path.display()));
+    }
+    Ok(())
 }
 
-/// changes password. prompts for a new password if one isn't given.
-pub fn change_password(opts: &ChangePassword, state: &mut State) -> AppResult {
-    let opts = opts.clone();
+fn verify_archive<E: Encryptor>(config: &Config, file: &str, e: &E) -> Result<(), UiError> {
+    let archive = Archive::parse(&read_file(file)?)?;
+    let pass = given_password(config)?;
+    let (contents, state) = archive.verify(&pass, e)?;
+
+    let created = chrono::DateTime::from_timestamp(archive.created, 0)
+        .map(|created| date::to_local(created).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    println!(
+        "{}",
+        tr!("{0} opens: {1} with {2} entries, archived {3}", file, contents.journal_name, state.entries.len(), created)
+    );
+    if let Some((name, _)) = &contents.config {
+        println!("{}", tr!("it has the configuration file {0}", name));
+    }
+    Ok(())
+}
 
-    let new_password = match (opts.new_password, opts.new_password_file) {
-        (Some(_), Some(_)) => {
-            fail!("can't give both new password and new password file");
-        }
-        (Some(new_password), None) => new_password,
-        (None, Some(new_password_file)) => {
-            let new_password = std::fs::read_to_string(&new_password_file);
-            if let Err(e) = new_password {
-                fail!("couldn't read content from file {new_password_file}: {e:?}");
-            }
-            new_password.unwrap()
-        }
-        (None, None) => get_new_password(),
+/// lints the configuration file, reporting parse errors (with the offending
+/// line), unknown keys, and conflicting options.
+pub fn check_config(args: &Arguments) -> Result<AppResult, UiError> {
+    let path = Config::get_config_path(args.config_file.as_deref());
+
+    let Some(path) = path else {
+        println!("{}", tr!("no configuration file found, using defaults"));
+        return Ok(AppResult::DidntChangeState);
     };
 
-    let old_password = state.password.clone();
-    state.change_password(&new_password);
+    let (config, warnings) = Config::read_config_file(&path)?;
+    for warning in warnings {
+        warn!("{warning}");
+    }
 
-    match old_password == new_password {
-        true => AppResult::DidntChangeState,
-        false => AppResult::ChangedState,
+    if let Err(e) = config.validate() {
+        return Err(UiError::InvalidConfig(path, Box::new(e)));
     }
+
+    println!("{}", tr!("{0}: ok", path));
+
+    Ok(AppResult::DidntChangeState)
 }
 
-/// view any entry. prompts for a date if no date is given.
-pub fn view_entries(opts: &View, state: &State) -> AppResult {
-    let opts = opts.clone();
+/// prompts the user to edit today's entry. will pull up an `$EDITOR` if no pre
+/// determined content has been given.
+pub fn edit_today(config: &Config, opts: &EditToday, state: &mut State) -> Result<AppResult, UiError> {
+    if state.is_locked(&Date::today()) {
+        return Err(UiError::Locked(Date::today()));
+    }
 
-    let date = match opts.date {
-        Some(date) => date,
-        None => {
-            let dates = HashSet::from_iter(state.entries.keys().map(|a| a.clone()));
-            if dates.is_empty() {
-                println!("No entries to view!");
-                exit(0)
+    let mut template = None;
+    let content = match (opts.content.as_deref(), opts.content_path.as_deref()) {
+        (None, None) => {
+            template = new_entry_template(config, state, &Date::today())?;
+            let content = state.entries.get(&Date::today()).or(template.as_ref());
+            let file_type = file_type(config, state, &Date::today());
+            let edited = edit(config, &Date::today(), content.map(String::as_str), file_type, "Press <Enter> to edit")?;
+            if let Some(old) = state.entries.get(&Date::today()) {
+                print_edit_diff(config, old, &edited);
             }
-            choose(dates, "Please choose an entry", true)
+            edited
+        }
+        (Some(content), None) => content.into(),
+        (None, Some(content_path)) => read_file(content_path)?,
+        (Some(_), Some(_)) => {
+            return Err(UiError::Conflict("content string", "content path"));
         }
     };
+    // a template that wasn't filled in isn't an entry
+    let meta = MetaEdit {
+        mood: opts.mood,
+        fields: &opts.meta,
+        location: opts.location.as_deref(),
+    };
+    if template.as_ref() == Some(&content) && meta.is_empty() {
+        return Ok(AppResult::DidntChangeState);
+    }
 
-    let entry = state.get_entry(&date);
+    let new = !state.entries.contains_key(&Date::today());
+    let unchanged = state.entries.get(&Date::today()) == Some(&content);
+    state.set_today(&content);
+    let meta_changed = apply_meta(config, state, &Date::today(), &meta, new);
 
-    if entry.is_none() {
-        fail!("invalid date entered");
+    if unchanged && !meta_changed {
+        return Ok(AppResult::DidntChangeState);
     }
 
-    println!("{}", entry.unwrap());
+    post_edit(config, &[Date::today()]);
 
-    AppResult::DidntChangeState
+    Ok(AppResult::ChangedState)
 }
 
-/// lists all entries by date.
-pub fn list_entries(state: &State) -> AppResult {
-    let mut keys = state.entries.keys().collect::<Vec<_>>();
-    keys.sort();
-    for key in keys {
-        println!("- {key}");
+/// opens today's entry in `$EDITOR` without asking anything first. closing
+/// the editor without writing anything doesn't start an entry.
+pub fn open_today(config: &Config, state: &mut State) -> Result<AppResult, UiError> {
+    let today = Date::today();
+    if state.is_locked(&today) {
+        return Err(UiError::Locked(today));
     }
 
-    AppResult::DidntChangeState
-}
+    let old = state.entries.get(&today).map(String::as_str);
+    let template = new_entry_template(config, state, &today)?;
+    let content = open_editor(config, &today, old.or(template.as_deref()), file_type(config, state, &today))?;
+    if let Some(old) = old {
+        print_edit_diff(config, old, &content);
+    }
+    let untouched = content.trim().is_empty() || template.as_ref() == Some(&content);
+    if old == Some(content.as_str()) || (old.is_none() && untouched) {
+        return Ok(AppResult::DidntChangeState);
+    }
 
-fn confirmation(message: &str) -> bool {
-    let question = Question::confirm(message)
-        .message(format!("{message} (y/n)"))
-        .build();
+    state.set_today(&content);
+    post_edit(config, &[today]);
 
-    let answer = prompt_one(question);
+    Ok(AppResult::ChangedState)
+}
+
+/// lets the user find an entry by typing part of it (see
+/// [`browse::choose()`]), then view or edit it
+pub fn browse(config: &Config, state: &mut State) -> Result<AppResult, UiError> {
+    if state.entries.is_empty() {
+        println!("{}", tr!("No entries to view!"));
+        return Ok(AppResult::DidntChangeState);
+    }
 
-    if let Err(e) = answer {
-        fail!("couldn't prompt: {e:?}");
+    let date = match accessible::is_accessible() {
+        // the list redraws itself as you type, so a search comes first instead
+        true => {
+            let query = Query {
+                text: Some(input(tr!("Search for"))?).filter(|text| !text.trim().is_empty()),
+                ..Query::default()
+            };
+            let dates = search::search(state, &query).into_iter().map(|hit| hit.date).collect::<Vec<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("no entries contain that"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_entry(state, dates, "Which entry?")?
+        }
+        false => browse::choose(state, "Which entry? (type to narrow down the list)")?,
+    };
+    let label = |edit: &bool| match edit {
+        true => tr!("Edit it").to_string(),
+        false => tr!("View it").to_string(),
+    };
+    let opts = View {
+        dates: vec![date],
+        ..Default::default()
+    };
+    match choose(HashSet::from([false, true]), "What do you want to do with it?", false, label)? {
+        true => edit_entry(config, &Edit { dates: vec![date], ..Default::default() }, state),
+        false => view_entries(config, &opts, state),
     }
+}
 
-    let result = answer.unwrap().as_bool();
+/// view today's entry. will print out "\<No Entry>" in the case of no entries
+/// for today
+pub fn view_today(config: &Config, opts: &ViewToday, state: &State) -> Result<AppResult, UiError> {
+    if opts.words {
+        if opts.header {
+            return Err(UiError::Conflict("--header", "--words"));
+        }
+        let words = state.get_today().map_or(0, |entry| entry.split_whitespace().count());
+        println!("{words}");
+        return Ok(AppResult::DidntChangeState);
+    }
 
-    if let None = result {
-        fail!("coudln't get value from question");
+    let entry = match state.get_today() {
+        Some(entry) => rendered(config, state, &Date::today(), &shown_entry(config, state, &Date::today(), &entry, opts.show_private)?),
+        None => tr!("<No Entry>").into(),
+    };
+    if (opts.header || config.view_header == Some(true)) && state.entries.contains_key(&Date::today()) {
+        print_entry_header(state, &Date::today());
     }
+    println!("{entry}");
 
-    result.unwrap()
+    Ok(AppResult::DidntChangeState)
 }
 
-fn password(message: &str) -> String {
-    let question = Question::password(message)
-        .message(message)
-        .mask(MASK_CHAR)
-        .build();
+/// edit any entry. prompts for a date if none are given. pulls up `$EDITOR` if
+/// no content is given.
+pub fn edit_entry(config: &Config, opts: &Edit, state: &mut State) -> Result<AppResult, UiError> {
+    let dates = match opts.dates.as_slice() {
+        [] => {
+            let dates = get_dates(state);
+            vec![choose_day(state, dates, "Which entry do you want to edit?")?]
+        }
+        dates => dates.to_vec(),
+    };
+    if let Some(date) = dates.iter().find(|date| state.is_locked(date)) {
+        return Err(UiError::Locked(*date));
+    }
 
-    let answer = prompt_one(question);
+    let content = match (opts.content.as_deref(), opts.content_path.as_deref()) {
+        (Some(_), Some(_)) => {
+            return Err(UiError::Conflict("content", "content path"));
+        }
+        (Some(_), None) | (None, Some(_)) if dates.len() > 1 => {
+            return Err(UiError::Conflict("several dates", "content"));
+        }
+        (Some(content), None) => Some(content.to_string()),
+        (None, Some(content_path)) => Some(read_file(content_path)?),
+        (None, None) => None,
+    };
 
-    if let Err(e) = answer {
-        fail!("couldn't prompt: {e:?}");
+    let mut changed = Vec::new();
+    for (i, date) in dates.iter().enumerate() {
+        let message = match dates.len() {
+            1 => "Press <Enter> to edit".to_string(),
+            _ => tr!("Press <Enter> to edit {0} ({1} of {2})", date.label(), i + 1, dates.len()),
+        };
+        let meta = MetaEdit {
+            mood: opts.mood,
+            fields: &opts.meta,
+            location: opts.location.as_deref(),
+        };
+        match edit_one(config, date, content.clone(), &meta, &message, opts.show_private, state) {
+            Ok(true) => changed.push(*date),
+            Ok(false) => {}
+            // keep the entries edited so far if a later one fails
+            Err(e) if !changed.is_empty() => {
+                error!("{e}");
+                break;
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    let password = answer.unwrap();
-    let password = password.as_string();
-
-    if password.is_none() {
-        fail!("failed to retreive prompt data");
+    if changed.is_empty() {
+        return Ok(AppResult::DidntChangeState);
     }
 
-    password.unwrap().to_string()
+    post_edit(config, &changed);
+
+    Ok(AppResult::ChangedState)
 }
 
-fn edit(content: Option<&str>, filetype: &str, message: &str) -> String {
-    let question = Question::editor(message)
-        .message(message)
-        .extension(filetype)
-        .default(content.unwrap_or_default())
-        .build();
-    let answer = prompt_one(question);
-    if let Err(e) = answer {
-        fail!("couldn't prompt: {e:?}");
-    }
-    let string = answer.unwrap();
-    let string = string.as_string();
-    if string.is_none() {
-        fail!("could not convert to string");
+/// sets the entry for `date` to `content`, or to what's written in `$EDITOR`
+/// without it, and sets its metadata (see [`apply_meta()`]). returns whether
+/// anything changed.
+fn edit_one(
+    config: &Config,
+    date: &Date,
+    content: Option<String>,
+    meta: &MetaEdit,
+    message: &str,
+    show_private: bool,
+    state: &mut State,
+) -> Result<bool, UiError> {
+    let mut template = None;
+    let new_content = match content {
+        Some(content) => content,
+        None => {
+            template = new_entry_template(config, state, date)?;
+            let old = state.entries.get(date);
+            let revealed = match old {
+                Some(old) if show_private && private::has_sealed(old) => {
+                    Some(with_private(config, state, |passphrase| Ok(passphrase.reveal(old, date, &Secure)?))?)
+                }
+                _ => None,
+            };
+            let content = revealed.as_ref().or(old).or(template.as_ref()).map(String::as_str);
+            let edited = edit(config, date, content, file_type(config, state, date), message)?;
+            if let Some(old) = revealed.as_ref().or(old) {
+                print_edit_diff(config, old, &edited);
+            }
+            match (old, revealed) {
+                // the private sections stay sealed as they were
+                (Some(old), Some(revealed)) if revealed == edited => old.clone(),
+                _ => edited,
+            }
+        }
+    };
+    // a template that wasn't filled in isn't an entry
+    if template.as_ref() == Some(&new_content) && meta.is_empty() {
+        return Ok(false);
     }
 
-    string.unwrap().into()
+    let new = !state.entries.contains_key(date);
+    let unchanged = state.entries.get(date) == Some(&new_content);
+    state.set_entry(date, &new_content);
+    let meta_changed = apply_meta(config, state, date, meta, new);
+
+    Ok(!unchanged || meta_changed)
 }
 
-fn choose<T: Display + FromStr + Ord>(content: HashSet<T>, message: &str, reverse: bool) -> T
-where
-    <T as FromStr>::Err: Debug,
-{
-    let mut content_as_vec = content.into_iter().collect::<Vec<T>>();
-    content_as_vec.sort();
-    if reverse {
-        content_as_vec.reverse();
+/// what an edit sets besides the content
+struct MetaEdit<'a> {
+    mood: Option<Mood>,
+    fields: &'a [Field],
+    /// an empty location removes it
+    location: Option<&'a str>,
+}
+
+impl MetaEdit<'_> {
+    /// whether the edit sets none of it
+    fn is_empty(&self) -> bool {
+        self.mood.is_none() && self.fields.is_empty() && self.location.is_none()
     }
-    let content = content_as_vec.into_iter().map(|x| x.to_string());
-    let question = Question::select(message)
-        .message(message)
-        .choices(content)
-        .build();
+}
+
+/// sets the mood, custom fields, and location of the entry for `date`. a
+/// `new` entry without a location gets one from `location_hook`, if it's set.
+/// returns whether any of them changed.
+fn apply_meta(config: &Config, state: &mut State, date: &Date, meta: &MetaEdit, new: bool) -> bool {
+    let mut changed = false;
+    if let Some(mood) = meta.mood {
+        changed |= state.mood(date) != Some(mood);
+        state.set_mood(date, Some(mood));
+    }
+    changed |= state.set_fields(date, meta.fields);
+
+    let location = match (meta.location, &config.location_hook) {
+        (Some(location), _) => Some(location.trim().to_string()),
+        (None, Some(hook)) if new => {
+            let journal_path = config.file_path.as_deref().unwrap_or("./jrn.json");
+            match run_hook_output("location_hook", hook, journal_path, &[*date]) {
+                Ok(location) => Some(location).filter(|location| !location.is_empty()),
+                Err(e) => {
+                    let message = tr!("{0} failed: {1}", "location_hook", e);
+                    warn!("{message}");
+                    None
+                }
+            }
+        }
+        (None, _) => None,
+    };
+    if let Some(location) = location {
+        let location = Some(location.as_str()).filter(|location| !location.is_empty());
+        changed |= state.location(date) != location;
+        state.set_location(date, location);
+    }
+    changed
+}
 
-    let answer = prompt_one(question);
+/// walks through the days without an entry (see [`stats::missing_days()`]),
+/// oldest first, asking whether to write an entry for each in `$EDITOR`,
+/// skip it, or stop
+pub fn backfill(config: &Config, opts: &Backfill, state: &mut State) -> Result<AppResult, UiError> {
+    let range = DateRange { start: opts.since, end: None };
+    let days = stats::missing_days(state, &range, &Date::today());
+    if days.is_empty() {
+        println!("{}", tr!("No days are missing!"));
+        return Ok(AppResult::DidntChangeState);
+    }
 
-    if let Err(e) = answer {
-        fail!("couldn't prompt: {e:?}");
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+    enum Step {
+        Write,
+        Skip,
+        Stop,
     }
+    let label = |step: &Step| match step {
+        Step::Write => tr!("Write an entry").to_string(),
+        Step::Skip => tr!("Skip this day").to_string(),
+        Step::Stop => tr!("Stop").to_string(),
+    };
+
+    let (mut written, mut skipped) = (0, 0);
+    for (i, day) in days.iter().enumerate() {
+        let message = tr!("{0} ({1} of {2})", day.label(), i + 1, days.len());
+        let step = match choose(HashSet::from([Step::Write, Step::Skip, Step::Stop]), &message, false, label) {
+            Ok(step) => step,
+            // keep what was written so far if the prompt is aborted
+            Err(UiError::Prompt(_)) if written > 0 => Step::Stop,
+            Err(e) => return Err(e),
+        };
 
-    let list_item = answer.unwrap();
-    let list_item = list_item.as_list_item();
+        match step {
+            Step::Write => {}
+            Step::Skip => {
+                skipped += 1;
+                continue;
+            }
+            Step::Stop => break,
+        }
 
-    if list_item.is_none() {
-        fail!("couldn't get list item");
+        let template = new_entry_template(config, state, day)?;
+        let content = open_editor(config, day, template.as_deref(), file_type(config, state, day))?;
+        if content.trim().is_empty() || template.as_ref() == Some(&content) {
+            skipped += 1;
+            continue;
+        }
+        state.set_entry(day, &content);
+        post_edit(config, &[*day]);
+        written += 1;
     }
 
-    let value = T::from_str(&list_item.unwrap().text);
+    println!("{}", tr!("wrote {0} entries, skipped {1}", written, skipped));
 
-    if let Err(e) = value {
-        fail!("couldn't get value from str: {e:?}");
+    match written {
+        0 => Ok(AppResult::DidntChangeState),
+        _ => Ok(AppResult::ChangedState),
     }
+}
+
+/// adds lines to today's entry, each after the current time (like
+/// `09:41 standup ran long`). asks for lines until an empty one (or the prompt
+/// is aborted), unless a line is given.
+pub fn log(config: &Config, opts: &Log, state: &mut State) -> Result<AppResult, UiError> {
+    let today = Date::today();
+    if state.is_locked(&today) {
+        return Err(UiError::Locked(today));
+    }
+
+    let mut append = |line: &str| {
+        let line = format!("{} {}", date::now().format("%H:%M"), line.trim());
+        let content = match state.get_entry(&today) {
+            Some(content) if !content.trim().is_empty() => format!("{}\n{line}", content.trim_end()),
+            _ => line,
+        };
+        state.set_entry(&today, &content);
+    };
+
+    let mut added = 0;
+    if opts.line.is_empty() {
+        println!("{}", tr!("Adding to {0}. Enter an empty line to stop.", today.label()));
+        loop {
+            let line = match input(">") {
+                Ok(line) => line,
+                // keep what was added so far if the prompt is aborted
+                Err(UiError::Prompt(_)) if added > 0 => break,
+                Err(e) => return Err(e),
+            };
+            if line.trim().is_empty() {
+                break;
+            }
+            append(&line);
+            added += 1;
+        }
+    } else {
+        append(&opts.line.join(" "));
+        added += 1;
+    }
+
+    if added == 0 {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    post_edit(config, &[today]);
+
+    Ok(AppResult::ChangedState)
+}
+
+/// exports the journal (see [`export::export()`])
+pub fn export_journal(config: &Config, opts: &Export, state: &State) -> Result<AppResult, UiError> {
+    let output = opts.output.as_deref().unwrap_or("./jrn-export");
+    let query = Query {
+        text: None,
+        tags: opts.tag.clone(),
+        range: range_of(opts.range.as_ref(), opts.since, opts.until)?,
+        ..Query::default()
+    };
+    let selected;
+    let state = match query == Query::default() {
+        true => state,
+        false => {
+            selected = export::select(state, &query);
+            &selected
+        }
+    };
+
+    let private = match opts.show_private {
+        true => with_private(config, state, |passphrase| Ok(passphrase.reveal_state(state, &Secure)?))?,
+        false => private::hide_state(state),
+    };
+    let state = &private;
+
+    // each `--redact` is a pattern file if there's one by that name, or tags
+    let mut redaction = Redaction::default();
+    for redact in &opts.redact {
+        match Path::new(redact).is_file() {
+            true => redaction.extend(Redaction::parse(&read_file(redact)?)),
+            false => redaction.extend(Redaction::tags(redact.split(','))),
+        }
+    }
+    let redacted;
+    let state = match redaction.is_empty() {
+        true => state,
+        false => {
+            redacted = redaction.apply(state);
+            &redacted
+        }
+    };
+
+    let total = state.entries.len();
+    let mut progress = Progress::new(tr!("exporting entries"), total);
+    let exported =
+        export::export_with_progress(state, opts.format, Path::new(output), plugin::renderers(), &mut |done, total| progress.set(done, total))?;
+    for (date, e) in exported.failed {
+        progress.fail(date, e);
+    }
+    let failed = progress.finish();
+
+    println!("{}", tr!("exported {0} entries to {1}", total - failed, output));
+
+    match failed {
+        0 => Ok(AppResult::DidntChangeState),
+        failed => Err(UiError::Incomplete(failed, total)),
+    }
+}
+
+/// moves an entry to another date (see [`State::move_entry()`]). prompts for
+/// the dates if they aren't given, and for what to do if there's already an
+/// entry on the new date.
+pub fn move_entry(config: &Config, opts: &Move, state: &mut State) -> Result<AppResult, UiError> {
+    let conflict = conflict_option(opts.overwrite, opts.append, opts.merge)?;
+    let (from, to) = from_and_to(&opts.dates)?;
+    transfer(config, from, to, conflict, true, state)
+}
+
+/// copies an entry to another date (see [`State::copy_entry()`]), prompting
+/// like [`move_entry()`]
+pub fn copy_entry(config: &Config, opts: &CopyEntry, state: &mut State) -> Result<AppResult, UiError> {
+    let conflict = conflict_option(opts.overwrite, opts.append, opts.merge)?;
+    let (from, to) = from_and_to(&opts.dates)?;
+    transfer(config, from, to, conflict, false, state)
+}
+
+fn from_and_to(dates: &[Date]) -> Result<(Option<Date>, Option<Date>), UiError> {
+    match *dates {
+        [] => Ok((None, None)),
+        [from] => Ok((Some(from), None)),
+        [from, to] => Ok((Some(from), Some(to))),
+        _ => Err(UiError::TooManyDates),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// what to do about an entry already on the date another one is moved or
+/// copied to
+enum Resolution {
+    /// resolve it with a [`Conflict`]
+    Conflict(Conflict),
+    /// merge the two by hand (see [`merge_in_editor()`])
+    Merge,
+    /// leave both entries as they are
+    Cancel,
+}
+
+fn conflict_option(overwrite: bool, append: bool, merge: bool) -> Result<Option<Resolution>, UiError> {
+    match (overwrite, append, merge) {
+        (true, true, _) => Err(UiError::Conflict("overwrite", "append")),
+        (true, _, true) => Err(UiError::Conflict("overwrite", "merge")),
+        (_, true, true) => Err(UiError::Conflict("append", "merge")),
+        (true, false, false) => Ok(Some(Resolution::Conflict(Conflict::Overwrite))),
+        (false, true, false) => Ok(Some(Resolution::Conflict(Conflict::Append))),
+        (false, false, true) => Ok(Some(Resolution::Merge)),
+        (false, false, false) => Ok(None),
+    }
+}
+
+/// moves (or copies) the entry at `from` to `to`
+fn transfer(
+    config: &Config,
+    from: Option<Date>,
+    to: Option<Date>,
+    conflict: Option<Resolution>,
+    moving: bool,
+    state: &mut State,
+) -> Result<AppResult, UiError> {
+    let from = match from {
+        Some(from) => from,
+        None => {
+            if state.entries.is_empty() {
+                println!("{}", tr!("No entries to view!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            let message = match moving {
+                true => "Which entry do you want to move?",
+                false => "Which entry do you want to copy?",
+            };
+            choose_entry(state, state.entries.keys().copied().collect(), message)?
+        }
+    };
+    if !state.entries.contains_key(&from) {
+        return Err(UiError::NoEntry(from));
+    }
+    if moving && state.is_locked(&from) {
+        return Err(UiError::Locked(from));
+    }
+
+    let to = match to {
+        Some(to) => to,
+        None => input_date("To which date? (YYYY-MM-DD or today, optionally followed by +n or -n)")?,
+    };
+    if from == to {
+        return Ok(AppResult::DidntChangeState);
+    }
+    if state.is_locked(&to) {
+        return Err(UiError::Locked(to));
+    }
+
+    let resolution = match (conflict, state.entries.contains_key(&to)) {
+        (Some(resolution), true) => resolution,
+        (_, false) => Resolution::Conflict(Conflict::Overwrite),
+        (None, true) => {
+            let choices = HashSet::from([
+                Resolution::Conflict(Conflict::Overwrite),
+                Resolution::Conflict(Conflict::Append),
+                Resolution::Merge,
+                Resolution::Cancel,
+            ]);
+            let label = |choice: &Resolution| match choice {
+                Resolution::Conflict(Conflict::Overwrite) => tr!("Overwrite it").to_string(),
+                Resolution::Conflict(Conflict::Append) => tr!("Append to it").to_string(),
+                Resolution::Merge => tr!("Merge them in the editor").to_string(),
+                Resolution::Cancel => tr!("Cancel").to_string(),
+            };
+            let message = tr!("{0} already has an entry. What should happen to it?", to.label());
+            choose(choices, &message, false, label)?
+        }
+    };
+    let conflict = match resolution {
+        Resolution::Conflict(conflict) => conflict,
+        Resolution::Merge => {
+            let Some(merged) = merge_in_editor(config, &from, &to, moving, state)? else {
+                return Ok(AppResult::DidntChangeState);
+            };
+            // the entry that's there keeps its metadata, like when appending
+            state.set_entry(&to, &merged);
+            if moving {
+                state.remove_entry(&from);
+            }
+            let message = tr!("merged the entry for {0} into {1}", from.label(), to.label());
+            println!("{message}");
+            post_edit(config, &[to]);
+            return Ok(AppResult::ChangedState);
+        }
+        Resolution::Cancel => return Ok(AppResult::DidntChangeState),
+    };
+
+    let message = match moving {
+        true => {
+            state.move_entry(&from, &to, conflict);
+            tr!("moved the entry for {0} to {1}", from.label(), to.label())
+        }
+        false => {
+            state.copy_entry(&from, &to, conflict);
+            tr!("copied the entry for {0} to {1}", from.label(), to.label())
+        }
+    };
+    println!("{message}");
+
+    post_edit(config, &[to]);
+
+    Ok(AppResult::ChangedState)
+}
+
+/// opens the entries for `to` and `from` in `$EDITOR`, with the lines that
+/// differ between conflict markers (see [`merge::with_markers()`]), until the
+/// markers are gone. gives back the merged entry, or `None` if the user gave
+/// up.
+fn merge_in_editor(
+    config: &Config,
+    from: &Date,
+    to: &Date,
+    moving: bool,
+    state: &State,
+) -> Result<Option<String>, UiError> {
+    let from_label = match moving {
+        true => tr!("{0} (moved here)", from),
+        false => tr!("{0} (copied here)", from),
+    };
+    let mut merged = merge::with_markers(
+        &state.get_entry(to).unwrap_or_default(),
+        &tr!("{0} (already here)", to),
+        &state.get_entry(from).unwrap_or_default(),
+        &from_label,
+    );
+
+    let mut message = "Press <Enter> to merge the entries in your editor";
+    loop {
+        merged = edit(config, to, Some(&merged), file_type(config, state, to), message)?;
+        if !merge::has_markers(&merged) {
+            return Ok(Some(merged));
+        }
+        if !confirmation("The entry still has conflict markers (<<<<<<<, =======, >>>>>>>). Edit it again?")? {
+            return Ok(None);
+        }
+        message = "Press <Enter> to edit the entry again";
+    }
+}
+
+/// moves every entry in a range by the same number of days (see
+/// [`State::shift_entries()`]), asking first
+pub fn shift(config: &Config, opts: &Shift, state: &mut State) -> Result<AppResult, UiError> {
+    let range = range_of(opts.range.as_ref(), opts.since, opts.until)?;
+    if opts.by.days == 0 {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let shifts = state.plan_shift(&range, opts.by)?;
+    let (Some((first, _)), Some((last, _))) = (shifts.first(), shifts.last()) else {
+        println!("{}", tr!("No entries to shift!"));
+        return Ok(AppResult::DidntChangeState);
+    };
+    if let Some((locked, _)) = shifts.iter().find(|(from, _)| state.is_locked(from)) {
+        return Err(UiError::Locked(*locked));
+    }
+
+    let question = match shifts.len() {
+        1 => tr!("Shift the entry for {0} by {1}?", first.label(), opts.by),
+        n => tr!("Shift the {0} entries from {1} to {2} by {3}?", n, first.label(), last.label(), opts.by),
+    };
+    if !opts.yes && !confirmation(&question)? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.shift_entries(&range, opts.by)?;
+    println!("{}", tr!("shifted {0} entries by {1}", shifts.len(), opts.by));
+
+    let mut changed = shifts.iter().flat_map(|(from, to)| [*from, *to]).collect::<Vec<_>>();
+    changed.sort();
+    changed.dedup();
+    post_edit(config, &changed);
+
+    Ok(AppResult::ChangedState)
+}
+
+/// lists every tag and how many entries have it, most used first
+pub fn list_tags(state: &State) -> Result<AppResult, UiError> {
+    let counts = tags::tag_counts(state);
+    if counts.is_empty() {
+        println!("{}", tr!("no entries have tags"));
+    }
+    let width = counts.iter().map(|(tag, _)| tag.chars().count() + 1).max().unwrap_or(0);
+    for (tag, count) in counts {
+        println!("{:<width$}  {count}", format!("#{tag}"));
+    }
+    Ok(AppResult::DidntChangeState)
+}
+
+/// renames a tag in every entry, or removes it (see [`tags::retag()`]),
+/// asking for the tag and its new name if they aren't given
+pub fn retag(config: &Config, opts: &Retag, state: &mut State) -> Result<AppResult, UiError> {
+    if opts.to.is_some() && opts.remove {
+        return Err(UiError::Conflict("--to", "--remove"));
+    }
+
+    let from = match &opts.from {
+        Some(from) => tags::parse_tag(from).ok_or_else(|| UiError::InvalidTag(from.clone()))?.to_lowercase(),
+        None => {
+            let counts = tags::tag_counts(state).into_iter().collect::<HashMap<_, _>>();
+            if counts.is_empty() {
+                println!("{}", tr!("no entries have tags"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose(counts.keys().cloned().collect(), "Which tag?", false, |tag| {
+                format!("#{tag} ({})", counts[tag])
+            })?
+        }
+    };
+    let to = match (&opts.to, opts.remove) {
+        (Some(to), _) => Some(to.clone()),
+        (None, true) => None,
+        (None, false) => {
+            Some(input(&tr!("Rename #{0} to (leave empty to remove it)", from))?).filter(|to| !to.trim().is_empty())
+        }
+    };
+    let to = match to {
+        Some(to) => Some(tags::parse_tag(&to).ok_or_else(|| UiError::InvalidTag(to.clone()))?.to_string()),
+        None => None,
+    };
+
+    let changes = tags::plan_retag(state, &from, to.as_deref());
+    if changes.is_empty() {
+        return Err(UiError::NoTag(from));
+    }
+    if let Some((locked, _)) = changes.iter().find(|(date, _)| state.is_locked(date)) {
+        return Err(UiError::Locked(*locked));
+    }
+
+    let question = match &to {
+        Some(to) => tr!("Rename #{0} to #{1} in {2} entries?", from, to, changes.len()),
+        None => tr!("Remove #{0} from {1} entries?", from, changes.len()),
+    };
+    if !opts.yes && !confirmation(&question)? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let dates = changes.iter().map(|(date, _)| *date).collect::<Vec<_>>();
+    for (date, content) in changes {
+        state.set_entry(&date, &content);
+    }
+    match &to {
+        Some(to) => println!("{}", tr!("renamed #{0} to #{1} in {2} entries", from, to, dates.len())),
+        None => println!("{}", tr!("removed #{0} from {1} entries", from, dates.len())),
+    }
+    post_edit(config, &dates);
+
+    Ok(AppResult::ChangedState)
+}
+
+/// sets the file type of the entries in a range (or all of them), see
+/// [`State::set_file_type()`]
+pub fn set_file_type(opts: &FileType, state: &mut State) -> Result<AppResult, UiError> {
+    let file_type = format!(".{}", opts.file_type.trim().trim_start_matches('.'));
+    let range = opts.range.clone().unwrap_or_default();
+    let dates = state
+        .entries
+        .keys()
+        .filter(|date| range.contains(date) && state.file_type(date) != Some(file_type.as_str()))
+        .copied()
+        .collect::<Vec<_>>();
+    for date in &dates {
+        state.set_file_type(date, Some(&file_type));
+    }
+
+    println!("{}", tr!("set the file type of {0} entries to {1}", dates.len(), file_type));
+
+    match dates.is_empty() {
+        true => Ok(AppResult::DidntChangeState),
+        false => Ok(AppResult::ChangedState),
+    }
+}
+
+/// locks an entry (see [`State::set_locked()`]), prompting for its date if
+/// it isn't given
+pub fn lock(opts: &Lock, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| !state.is_locked(date))
+                .copied()
+                .collect::<Vec<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries to lock!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_entry(state, dates, "Which entry do you want to lock?")?
+        }
+    };
+    if !state.entries.contains_key(&date) {
+        return Err(UiError::NoEntry(date));
+    }
+    if state.is_locked(&date) {
+        println!("{}", tr!("the entry for {0} is already locked", date.label()));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_locked(&date, true);
+    println!("{}", tr!("locked the entry for {0}", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// unlocks an entry after asking (unless `--yes` is given), prompting for its
+/// date if it isn't given
+pub fn unlock(opts: &Unlock, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| state.is_locked(date))
+                .copied()
+                .collect::<Vec<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries are locked!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_entry(state, dates, "Which entry do you want to unlock?")?
+        }
+    };
+    if !state.is_locked(&date) {
+        println!("{}", tr!("the entry for {0} isn't locked", date.label()));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let question = tr!("Unlock the entry for {0}, so it can be changed again?", date.label());
+    if !opts.yes && !confirmation(&question)? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_locked(&date, false);
+    println!("{}", tr!("unlocked the entry for {0}", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// stars an entry (see [`State::set_starred()`]), prompting for its date if
+/// it isn't given
+pub fn star(opts: &Star, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| !state.is_starred(date))
+                .cloned()
+                .collect::<HashSet<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries to star!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose(dates, "Which entry do you want to star?", true, Date::label)?
+        }
+    };
+    if !state.entries.contains_key(&date) {
+        return Err(UiError::NoEntry(date));
+    }
+    if state.is_starred(&date) {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_starred(&date, true);
+    println!("{}", tr!("starred the entry for {0}", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// unstars an entry, prompting for its date if it isn't given
+pub fn unstar(opts: &Unstar, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| state.is_starred(date))
+                .cloned()
+                .collect::<HashSet<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries are starred!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose(dates, "Which entry do you want to unstar?", true, Date::label)?
+        }
+    };
+    if !state.is_starred(&date) {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_starred(&date, false);
+    println!("{}", tr!("unstarred the entry for {0}", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// makes an entry public (see [`State::set_public()`]), prompting for its
+/// date if it isn't given. asks first, since the entry is then readable by
+/// anyone with the journal file.
+pub fn publish(opts: &Publish, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| !state.is_public(date))
+                .copied()
+                .collect::<Vec<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries to publish!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_entry(state, dates, "Which entry do you want to publish?")?
+        }
+    };
+    if !state.entries.contains_key(&date) {
+        return Err(UiError::NoEntry(date));
+    }
+    if state.is_public(&date) {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let question = tr!(
+        "Store the entry for {0} without encryption? Anyone who can read the journal file (or its backups) will be able to read it.",
+        date.label()
+    );
+    if !opts.yes && !confirmation(&question)? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_public(&date, true);
+    println!("{}", tr!("published the entry for {0}", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// makes a public entry private again, prompting for its date if it isn't
+/// given
+pub fn unpublish(opts: &Unpublish, state: &mut State) -> Result<AppResult, UiError> {
+    let date = match opts.date {
+        Some(date) => date,
+        None => {
+            let dates = state
+                .entries
+                .keys()
+                .filter(|date| state.is_public(date))
+                .copied()
+                .collect::<Vec<_>>();
+            if dates.is_empty() {
+                println!("{}", tr!("No entries are public!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_entry(state, dates, "Which entry do you want to unpublish?")?
+        }
+    };
+    if !state.is_public(&date) {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    state.set_public(&date, false);
+    println!("{}", tr!("the entry for {0} is encrypted again", date.label()));
+
+    Ok(AppResult::ChangedState)
+}
+
+/// warns about the entries for `dates` larger than
+/// [`Config::max_entry_size`], or, with [`Config::refuse_large_entries`]
+/// (and without `force`), refuses to save them
+pub fn check_entry_sizes(config: &Config, state: &State, dates: &[Date], force: bool) -> Result<(), UiError> {
+    let limit = config.max_entry_size.unwrap_or(DEFAULT_MAX_ENTRY_SIZE);
+    for date in dates {
+        let Some(content) = state.entries.get(date) else {
+            continue;
+        };
+        let size = (content.len() as u64).div_ceil(1000);
+        if size <= limit {
+            continue;
+        }
+        if config.refuse_large_entries == Some(true) && !force {
+            return Err(UiError::EntryTooLarge(*date, size));
+        }
+        let message = tr!(
+            "the entry for {0} is {1} KB, more than max_entry_size ({2} KB), which slows down every save and load",
+            date.label(),
+            size,
+            limit
+        );
+        warn!("{message}");
+    }
+    Ok(())
+}
+
+/// refuses to save changes to the entries for `dates` that are before
+/// [`Config::freeze_before`], unless `force` is given
+pub fn check_frozen(config: &Config, dates: &[Date], force: bool) -> Result<(), UiError> {
+    let Some(freeze_before) = config.get_freeze_before()? else {
+        return Ok(());
+    };
+    match dates.iter().find(|date| date.without_time() < freeze_before.without_time()) {
+        Some(date) if !force => Err(UiError::Frozen(*date, freeze_before)),
+        _ => Ok(()),
+    }
+}
+
+/// calls `f` with the passphrase of private sections, reading it from
+/// `private_password_file` or asking for it the first time. it has to open a
+/// section sealed before, if there is one, and is asked for twice otherwise,
+/// so every section has the same one.
+fn with_private<T>(
+    config: &Config,
+    state: &State,
+    f: impl FnOnce(&mut Passphrase) -> Result<T, UiError>,
+) -> Result<T, UiError> {
+    let mut private = PRIVATE.lock().unwrap_or_else(|e| e.into_inner());
+    let passphrase = match private.take() {
+        Some(passphrase) => passphrase,
+        None => {
+            let (text, typed) = match &config.private_password_file {
+                Some(file) => (read_file(file)?.trim().to_string(), false),
+                None => (password("Please enter the passphrase of private sections")?, true),
+            };
+            let mut passphrase = Passphrase::new(&text);
+            if !passphrase.check(state, &Secure)? && typed && password("Please repeat the passphrase")? != text {
+                return Err(UiError::PassphraseMismatch);
+            }
+            passphrase
+        }
+    };
+    f(private.insert(passphrase))
+}
+
+/// seals the private sections of the entries for `dates` that aren't sealed
+/// yet (see [`Passphrase::seal()`]), so they're never saved as text
+pub fn seal_private(config: &Config, state: &mut State, dates: &[Date]) -> Result<(), UiError> {
+    let unsealed = dates
+        .iter()
+        .filter(|date| state.entries.get(date).is_some_and(|content| private::has_unsealed(content)))
+        .copied()
+        .collect::<Vec<_>>();
+    if unsealed.is_empty() {
+        return Ok(());
+    }
+
+    let sealed = with_private(config, state, |passphrase| {
+        unsealed
+            .iter()
+            .map(|date| Ok((*date, passphrase.seal(&state.entries[date], date, &Secure)?)))
+            .collect::<Result<Vec<_>, UiError>>()
+    })?;
+    for (date, content) in sealed {
+        state.set_entry(&date, &content);
+        // the draft has the sections as text
+        crash::keep_draft(&date, &content);
+    }
+    Ok(())
+}
+
+/// `content`, the entry for `date`, as it's shown: with its private sections
+/// revealed with `show_private`, or hidden without it
+fn shown_entry(config: &Config, state: &State, date: &Date, content: &str, show_private: bool) -> Result<String, UiError> {
+    match show_private {
+        false => Ok(private::hide(content)),
+        true if !private::has_sealed(content) => Ok(content.into()),
+        true => with_private(config, state, |passphrase| Ok(passphrase.reveal(content, date, &Secure)?)),
+    }
+}
+
+/// runs [`Config::post_edit_hook`] (if there is one) for the edited dates
+fn post_edit(config: &Config, dates: &[Date]) {
+    let Some(hook) = &config.post_edit_hook else {
+        return;
+    };
+
+    let journal_path = config.file_path.as_deref().unwrap_or("./jrn.json");
+
+    if let Err(e) = run_hook("post_edit_hook", hook, journal_path, dates) {
+        let message = tr!("{0} failed: {1}", "post_edit_hook", e);
+        warn!("{message}");
+    }
+}
+
+/// changes password. prompts for a new password if one isn't given.
+pub fn change_password(opts: &ChangePassword, state: &mut State) -> Result<AppResult, UiError> {
+    let opts = opts.clone();
+
+    let new_password = match (opts.new_password, opts.new_password_file) {
+        (Some(_), Some(_)) => {
+            return Err(UiError::Conflict("new password", "new password file"));
+        }
+        (Some(new_password), None) => new_password,
+        (None, Some(new_password_file)) => read_file(&new_password_file)?,
+        (None, None) => get_new_password()?,
+    };
+
+    let old_password = state.password.clone();
+    state.change_password(&new_password);
+
+    match old_password == new_password {
+        true => Ok(AppResult::DidntChangeState),
+        false => Ok(AppResult::ChangedState),
+    }
+}
+
+/// saves the journal after its password changed. every entry is encrypted
+/// with a new key, so on a terminal, how many are done is shown.
+pub fn save_with_new_password(file: &str, state: &State) -> Result<(), SaveError> {
+    let mut progress = Progress::new(tr!("encrypting entries with the new password"), state.entries.len());
+    let saved = state.save_to_with_progress(&FileStorage::new(file), &Secure, &mut |done, total| progress.set(done, total));
+    progress.finish();
+    saved
+}
+
+/// opens the journal saved at `file` again with the new password, so the
+/// password is only said to have changed once it's known to work
+pub fn check_new_password(file: &str, state: &State) -> Result<(), UiError> {
+    let mut saved = State::new();
+    saved
+        .load(file, &state.password, &Secure)
+        .map_err(|e| UiError::NewPassword(e.to_string()))?;
+    if saved.entries != state.entries {
+        return Err(UiError::NewPassword(tr!("its entries aren't the ones that were saved").to_string()));
+    }
+
+    println!("{}", tr!("changed the password (and checked that the journal opens with it)"));
+    Ok(())
+}
+
+/// prints the audit log, oldest first (see [`State::audit`])
+pub fn print_audit(opts: &Audit, state: &State) -> Result<AppResult, UiError> {
+    let Some(events) = &state.audit else {
+        return Err(UiError::NoAudit);
+    };
+
+    let (date_style, failed_style) = match stdout().is_terminal() {
+        true => (theme::current().date.clone(), theme::current().warning.clone()),
+        false => (Style::default(), Style::default()),
+    };
+    let events = events
+        .iter()
+        .filter(|event| !opts.failed || event.action == AuditAction::FailedUnlock)
+        .collect::<Vec<&AuditEvent>>();
+    if events.is_empty() {
+        println!("{}", tr!("nothing in the audit log"));
+    }
+    for event in events {
+        let time = date::to_local(event.date_time()).format("%Y-%m-%d %H:%M:%S");
+        let action = match &event.action {
+            AuditAction::Unlocked => tr!("unlocked").to_string(),
+            AuditAction::FailedUnlock => failed_style.paint(tr!("failed to unlock")),
+            AuditAction::Modified { dates } if dates.is_empty() => tr!("changed").to_string(),
+            AuditAction::Modified { dates } => {
+                let dates = dates.iter().map(Date::to_string).collect::<Vec<_>>().join(", ");
+                tr!("changed {0}", dates)
+            }
+        };
+        println!("- {}: {action}", date_style.paint(time));
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// turns the TOTP second factor on (with a new secret, once a code from it
+/// was entered, so it's known to be set up) or off
+pub fn totp(config: &Config, opts: &TotpCommand, state: &mut State) -> Result<AppResult, UiError> {
+    match opts.subcommand {
+        TotpSubCommand::Enable(_) if state.totp.is_some() => {
+            println!("{}", tr!("TOTP is already on (run `jrn totp disable` first for a new secret)"));
+            Ok(AppResult::DidntChangeState)
+        }
+        TotpSubCommand::Enable(_) => {
+            let secret = totp::new_secret();
+            let journal = config.file_path.as_deref().unwrap_or("./jrn.json");
+            let account = Path::new(journal)
+                .file_stem()
+                .map_or("jrn".into(), |stem| stem.to_string_lossy());
+            println!("{}", tr!("add this secret to your authenticator app:"));
+            println!("    {secret}");
+            println!("{}", tr!("or import this URI (for example from a QR code):"));
+            println!("    {}", totp::uri(&secret, &account));
+            ask_totp_code(&secret)?;
+
+            state.totp = Some(secret);
+            println!("{}", tr!("TOTP is on: jrn will ask for a code after the password"));
+            Ok(AppResult::ChangedState)
+        }
+        TotpSubCommand::Disable(_) if state.totp.take().is_some() => {
+            println!("{}", tr!("TOTP is off"));
+            Ok(AppResult::ChangedState)
+        }
+        TotpSubCommand::Disable(_) => {
+            println!("{}", tr!("TOTP is already off"));
+            Ok(AppResult::DidntChangeState)
+        }
+    }
+}
+
+/// asks for a code from `secret`, up to three times
+fn ask_totp_code(secret: &str) -> Result<(), UiError> {
+    for attempt in 0..3 {
+        let message = match attempt {
+            0 => "Please enter the code from your authenticator app",
+            _ => "Try Again. Please enter the code",
+        };
+        let code = input(translate(message))?;
+        let now = chrono::Utc::now().timestamp().try_into().unwrap_or_default();
+        if totp::verify(secret, code.trim(), now) {
+            return Ok(());
+        }
+    }
+
+    Err(UiError::IncorrectCode)
+}
+
+/// view any entry. prompts for a date if no date is given, and then offers to
+/// jump to the entries of dates mentioned in it. with a range, views every
+/// entry in it under a heading.
+pub fn view_entries(config: &Config, opts: &View, state: &State) -> Result<AppResult, UiError> {
+    let interactive = opts.dates.is_empty();
+    let header = opts.header || config.view_header == Some(true);
+
+    if let Some(range) = &opts.range {
+        if !opts.dates.is_empty() {
+            return Err(UiError::Conflict("a date", "a range"));
+        }
+
+        let mut dates = state
+            .entries
+            .keys()
+            .filter(|date| range.contains(date))
+            .copied()
+            .collect::<Vec<_>>();
+        dates.sort();
+        print_entries(config, state, &dates, header, opts.show_private)?;
+
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    if opts.dates.len() > 1 {
+        if let Some(date) = opts.dates.iter().find(|date| !state.entries.contains_key(date)) {
+            return Err(UiError::NoEntry(*date));
+        }
+        print_entries(config, state, &opts.dates, header, opts.show_private)?;
+
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let date = match opts.dates.first() {
+        Some(date) => *date,
+        None => {
+            if state.entries.is_empty() {
+                println!("{}", tr!("No entries to view!"));
+                return Ok(AppResult::DidntChangeState);
+            }
+            choose_day(state, state.entries.keys().copied().collect(), "Please choose an entry")?
+        }
+    };
+
+    let Some(entry) = state.entries.get(&date) else {
+        return Err(UiError::NoEntry(date));
+    };
+
+    if header {
+        print_entry_header(state, &date);
+    }
+    println!("{}", rendered(config, state, &date, &shown_entry(config, state, &date, entry, opts.show_private)?));
+
+    let backlinks = Backlinks::new(state);
+    print_backlinks(&backlinks, &date);
+    if interactive {
+        follow_mentions(config, state, &backlinks, date, opts.show_private)?;
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints each of the entries for `dates` under a heading with its date, or
+/// with `header`, under its [header](print_entry_header)
+fn print_entries(config: &Config, state: &State, dates: &[Date], header: bool, show_private: bool) -> Result<(), UiError> {
+    let heading = match stdout().is_terminal() {
+        true => theme::current().heading.clone(),
+        false => Style::default(),
+    };
+    for (i, date) in dates.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        match header {
+            true => print_entry_header(state, date),
+            false => println!("{}", heading.paint(format!("# {}", date.label()))),
+        }
+        println!("{}", rendered(config, state, date, &shown_entry(config, state, date, &state.entries[date], show_private)?));
+    }
+    Ok(())
+}
+
+/// prints what's known about the entry for `date`: its date, title (a
+/// heading on its first line), tags, mood, location, custom fields, how many
+/// words it has, and when it was created and last changed
+fn print_entry_header(state: &State, date: &Date) {
+    let heading = match stdout().is_terminal() {
+        true => theme::current().heading.clone(),
+        false => Style::default(),
+    };
+    let content = state.entries.get(date).map(String::as_str).unwrap_or_default();
+    let time = |time: Option<i64>| {
+        time.and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+            .map(|time| date::to_local(time).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| tr!("unknown").into())
+    };
+
+    let mut facts = vec![(tr!("date"), date.label())];
+    // a markdown heading, not a line starting with a #tag
+    let first_line = content.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+    let title = first_line.trim_start_matches('#');
+    if title.len() < first_line.len() && title.starts_with(' ') && !title.trim().is_empty() {
+        facts.push((tr!("title"), title.trim().into()));
+    }
+    let tags = search::tags(content);
+    if !tags.is_empty() {
+        facts.push((tr!("tags"), tags.iter().map(|tag| format!("#{tag}")).collect::<Vec<_>>().join(" ")));
+    }
+    if let Some(mood) = state.mood(date) {
+        facts.push((tr!("mood"), format!("{}/5 {}", mood.value(), translate(mood.name()))));
+    }
+    if let Some(location) = state.location(date) {
+        facts.push((tr!("location"), location.into()));
+    }
+    for (key, value) in state.fields(date).into_iter().flatten() {
+        facts.push((key, value.clone()));
+    }
+    facts.push((tr!("words"), content.split_whitespace().count().to_string()));
+    facts.push((tr!("created"), time(state.created(date))));
+    facts.push((tr!("modified"), time(state.modified(date))));
+
+    let width = facts.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, value) in facts {
+        let name = format!("{name}:");
+        println!("{} {value}", heading.paint(format!("{name:<0$}", width + 1)));
+    }
+    println!();
+}
+
+/// an entry as it's printed: on a terminal, rendered for its file type (see
+/// [`plugin::renderers()`]), with the dates it mentions highlighted
+fn rendered(config: &Config, state: &State, date: &Date, content: &str) -> String {
+    if !stdout().is_terminal() {
+        return content.into();
+    }
+    let content = plugin::renderers().render(content, Some(file_type(config, state, date)), Target::Terminal);
+    highlight_mentions(&content)
+}
+
+/// styles the dates mentioned in an entry (see [`date::mentioned_dates()`])
+/// when printing to a terminal
+fn highlight_mentions(entry: &str) -> String {
+    if !stdout().is_terminal() {
+        return entry.into();
+    }
+
+    let style = &theme::current().date;
+    let mut highlighted = String::new();
+    let mut last = 0;
+    for (range, _) in date::mentioned_dates(entry) {
+        highlighted.push_str(&entry[last..range.start]);
+        highlighted.push_str(&style.paint(&entry[range.clone()]));
+        last = range.end;
+    }
+    highlighted.push_str(&entry[last..]);
+
+    highlighted
+}
+
+/// prints the entries linking to the entry for `date` (see [`links`]) when
+/// printing to a terminal
+fn print_backlinks(backlinks: &Backlinks, date: &Date) {
+    let linked_from = backlinks.to(date);
+    if linked_from.is_empty() || !stdout().is_terminal() {
+        return;
+    }
+
+    let labels = linked_from.iter().map(Date::label).collect::<Vec<_>>().join(", ");
+    println!();
+    println!("{}", tr!("Referenced by: {0}", labels));
+}
+
+/// offers to jump to the entries of dates mentioned in the entry for `date`,
+/// or linking to it, and keeps offering until the user is done
+fn follow_mentions(config: &Config, state: &State, backlinks: &Backlinks, mut date: Date, show_private: bool) -> Result<(), UiError> {
+    loop {
+        let mentioned = date::mentioned_dates(&state.entries[&date])
+            .into_iter()
+            .map(|(_, mentioned)| mentioned)
+            .filter(|mentioned| *mentioned != date && state.entries.contains_key(mentioned))
+            .collect::<HashSet<_>>();
+        let linked_from = backlinks.to(&date);
+        let choices = mentioned
+            .iter()
+            .chain(&linked_from)
+            .copied()
+            .map(Some)
+            .chain([None])
+            .collect::<HashSet<_>>();
+
+        if choices.len() == 1 {
+            return Ok(());
+        }
+
+        let label = |choice: &Option<Date>| match choice {
+            Some(date) if !mentioned.contains(date) => tr!("{0} (links here)", date.label()),
+            Some(date) => date.label(),
+            None => tr!("Done").into(),
+        };
+        let Some(next) = choose(choices, "Jump to a mentioned entry?", false, label)? else {
+            return Ok(());
+        };
+
+        date = next;
+        println!("{}", rendered(config, state, &date, &shown_entry(config, state, &date, &state.entries[&date], show_private)?));
+        print_backlinks(backlinks, &date);
+    }
+}
+
+/// reports that every entry was decrypted (which [`init()`] already checked),
+/// and with `--chain`, checks the journal's hash chain
+pub fn verify(config: &Config, opts: &Verify, state: &State) -> Result<AppResult, UiError> {
+    println!("{}", tr!("{0} entries decrypted", state.entries.len()));
+
+    if !opts.chain {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let path = config.file_path.as_deref().unwrap_or("./jrn.json");
+    let stored = serde_json::from_str::<StoredJournal>(&read_file(path)?).map_err(LoadError::from)?;
+    let encrypted = EncryptedJournal::try_from(stored).map_err(LoadError::from)?;
+
+    match Secure.verify_chain(&encrypted, &state.password).map_err(LoadError::from)? {
+        ChainStatus::Intact => println!("{}", chain_message(&ChainStatus::Intact)),
+        status => return Err(UiError::Chain(status)),
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// removes (or archives) entries older than `--older-than`, or the
+/// configured retention policy, after listing them and asking. refuses to
+/// prune entries before `freeze_before` without `force` (see
+/// [`check_frozen()`]).
+pub fn prune(config: &Config, opts: &Prune, state: &mut State, force: bool) -> Result<AppResult, UiError> {
+    let retention = config.retention.clone().unwrap_or_default();
+    let age = match opts.older_than {
+        Some(age) => age,
+        // checked by `Config::validate()`
+        None => retention.get_older_than().ok().flatten().ok_or(UiError::NoRetention)?,
+    };
+    let archive = opts.archive.clone().or(retention.archive);
+
+    let dates = retention::expired(state, &age, &Date::today());
+    check_frozen(config, &dates, force)?;
+    prune_entries(state, dates, &age, archive.as_deref(), !opts.yes, opts.dry_run)
+}
+
+/// applies the configured retention policy, if there is one, asking before
+/// pruning anything. only runs in a terminal. entries before
+/// `freeze_before` are left alone, unless `force` is given. returns whether
+/// any entries were pruned.
+pub fn auto_prune(config: &Config, state: &mut State, force: bool) -> Result<bool, UiError> {
+    let Some(retention) = &config.retention else {
+        return Ok(false);
+    };
+    let Ok(Some(age)) = retention.get_older_than() else {
+        return Ok(false);
+    };
+    let mut dates = retention::expired(state, &age, &Date::today());
+    dates.retain(|date| check_frozen(config, &[*date], force).is_ok());
+    if !stdin().is_terminal() || dates.is_empty() {
+        return Ok(false);
+    }
+
+    println!("{}", tr!("these entries are past the retention policy ({0}):", age));
+    let pruned = prune_entries(state, dates, &age, retention.archive.as_deref(), true, false)?;
+
+    Ok(matches!(pruned, AppResult::ChangedState))
+}
+
+/// removes (or archives) the entries for `dates`, which are older than
+/// `age`, after listing them and (with `confirm`) asking
+fn prune_entries(
+    state: &mut State,
+    dates: Vec<Date>,
+    age: &Age,
+    archive: Option<&str>,
+    confirm: bool,
+    dry_run: bool,
+) -> Result<AppResult, UiError> {
+    if dates.is_empty() {
+        println!("{}", tr!("no entries are older than {0}", age));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    for date in &dates {
+        println!("- {}", date.label());
+    }
+    if dry_run {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let question = match archive {
+        Some(archive) => tr!("Move these {0} entries to {1}?", dates.len(), archive),
+        None => tr!("Delete these {0} entries?", dates.len()),
+    };
+    if confirm && !confirmation(&question)? {
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    // written when the journal is saved, so the archive doesn't get entries
+    // the journal ends up keeping
+    if let Some(archive) = archive {
+        ARCHIVES.lock().unwrap_or_else(|e| e.into_inner()).push(PendingArchive {
+            path: archive.into(),
+            password: state.password.clone(),
+            entries: dates.iter().map(|date| (*date, state.entries[date].clone())).collect(),
+        });
+    }
+
+    for date in &dates {
+        state.remove_entry(date);
+    }
+    let message = match archive {
+        Some(archive) => tr!("moved {0} entries to {1}", dates.len(), archive),
+        None => tr!("deleted {0} entries", dates.len()),
+    };
+    println!("{message}");
+
+    Ok(AppResult::ChangedState)
+}
+
+/// writes the entries pruned into archive journals (see [`prune()`]) since
+/// the journal was unlocked. called right before the journal is saved, once
+/// nothing refused to save it.
+pub fn save_archives() -> Result<(), UiError> {
+    let pending = std::mem::take(&mut *ARCHIVES.lock().unwrap_or_else(|e| e.into_inner()));
+    for archive in pending {
+        let mut archived = State::new();
+        match Path::new(&archive.path).exists() {
+            true => archived.load(&archive.path, &archive.password, &Secure)?,
+            false => archived.change_password(&archive.password),
+        }
+        for (date, content) in &archive.entries {
+            archived.set_entry(date, content);
+        }
+        archived.save(&archive.path, &Secure)?;
+    }
+    Ok(())
+}
+
+/// writes the entries of every year (or month) into a journal of its own (see
+/// [`State::extract()`]), named after the journal and the period, like
+/// `jrn-2024.json`. nothing is written if any of them exists already.
+pub fn split_journal(config: &Config, opts: &Split, state: &State) -> Result<AppResult, UiError> {
+    let by = opts.by.unwrap_or(GroupBy::Year);
+    let journal = Path::new(config.file_path.as_deref().unwrap_or("./jrn.json"));
+    let name = journal.file_stem().and_then(|name| name.to_str()).unwrap_or("jrn");
+    let out_dir = match &opts.out_dir {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => journal.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+
+    let mut periods = BTreeMap::<Period, Vec<Date>>::new();
+    for date in state.entries.keys() {
+        periods.entry(by.period_of(date)).or_default().push(*date);
+    }
+    if periods.is_empty() {
+        println!("{}", tr!("No entries to split!"));
+        return Ok(AppResult::DidntChangeState);
+    }
+    let parts = periods
+        .into_iter()
+        .map(|(period, dates)| (out_dir.join(format!("{name}-{period}.json")), dates))
+        .collect::<Vec<_>>();
+    if let Some((path, _)) = parts.iter().find(|(path, _)| path.exists()) {
+        return Err(UiError::JournalExists(path.display().to_string()));
+    }
+
+    std::fs::create_dir_all(&out_dir).map_err(SaveError::from)?;
+    let _saving = signals::saving();
+    // a journal that can't be written doesn't stop the others
+    let mut progress = Progress::new(tr!("writing journals"), parts.len());
+    for (path, dates) in &parts {
+        let path = path.display().to_string();
+        match state.extract(dates).save(&path, &Secure) {
+            Ok(()) => {
+                progress.clear();
+                println!("{}", tr!("wrote {0} entries to {1}", dates.len(), path));
+                progress.advance();
+            }
+            Err(e) => progress.fail(path, e),
+        }
+    }
+
+    match progress.finish() {
+        0 => Ok(AppResult::DidntChangeState),
+        failed => Err(UiError::Incomplete(failed, parts.len())),
+    }
+}
+
+/// adds the entries of other journals (see [`State::merge_from()`]), trying
+/// this journal's password first and asking for theirs if it doesn't work
+pub fn merge_journals(opts: &Merge, state: &mut State) -> Result<AppResult, UiError> {
+    let conflict = merge_conflict(opts.overwrite, opts.append)?;
+
+    // a journal that can't be merged doesn't stop the others
+    let mut merged = state.clone();
+    let mut counts = Vec::with_capacity(opts.files.len());
+    let mut progress = Progress::new(tr!("merging journals"), opts.files.len());
+    for file in &opts.files {
+        match merge_file(file, conflict, state, &merged, &mut progress) {
+            Ok((with_file, count)) => {
+                merged = with_file;
+                counts.push((file, count));
+                progress.advance();
+            }
+            // leaving a prompt stops the whole merge
+            Err(UiError::Prompt(e)) => return Err(UiError::Prompt(e)),
+            Err(e) => progress.fail(file, e),
+        }
+    }
+    let failed = progress.finish();
+
+    for (file, count) in &counts {
+        println!("{}", tr!("merged {0} entries from {1}", count, file));
+    }
+    if counts.iter().all(|(_, count)| *count == 0) {
+        return match failed {
+            0 => Ok(AppResult::DidntChangeState),
+            failed => Err(UiError::Incomplete(failed, opts.files.len())),
+        };
+    }
+    *state = merged;
+    Ok(AppResult::ChangedState)
+}
+
+/// [`merge_journals()`] for one of the journals: a copy of `merged` with its
+/// entries, and how many there were. the copy is thrown away if it fails, so
+/// none of its entries are left half merged.
+fn merge_file(
+    file: &str,
+    conflict: Option<Conflict>,
+    state: &State,
+    merged: &State,
+    progress: &mut Progress,
+) -> Result<(State, usize), UiError> {
+    let mut other = State::new();
+    match other.load(file, &state.password, &Secure) {
+        Err(LoadError::IncorrectPassword) => {
+            progress.clear();
+            let password = password(&tr!("Password for {0}", file))?;
+            other.load(file, &password, &Secure)?;
+        }
+        loaded => loaded?,
+    }
+
+    let mut with_file = merged.clone();
+    let dates = with_file.merge_from(&other, conflict)?;
+    // the locks of this journal hold for merged entries too
+    if let Some(date) = dates.iter().find(|date| state.is_locked(date)) {
+        return Err(UiError::Locked(*date));
+    }
+    Ok((with_file, dates.len()))
+}
+
+/// what to do about different entries on the same dates when merging, from
+/// `--overwrite` and `--append` (`None` refuses to merge them)
+fn merge_conflict(overwrite: bool, append: bool) -> Result<Option<Conflict>, UiError> {
+    match (overwrite, append) {
+        (true, true) => Err(UiError::Conflict("overwrite", "append")),
+        (true, false) => Ok(Some(Conflict::Overwrite)),
+        (false, true) => Ok(Some(Conflict::Append)),
+        (false, false) => Ok(None),
+    }
+}
+
+/// adds the entries of a file in another format (see [`ImportFormat`]),
+/// like [`merge_journals()`]. headings that aren't real dates, and text
+/// before the first heading, are warned about.
+pub fn import_journal(opts: &Import, state: &mut State) -> Result<AppResult, UiError> {
+    let conflict = merge_conflict(opts.overwrite, opts.append)?;
+    let pattern = import::date_pattern(opts.date_pattern.as_deref().unwrap_or(DEFAULT_DATE_PATTERN))?;
+    let text = read_file(&opts.file)?;
+    let found = match opts.from.unwrap_or_default() {
+        ImportFormat::Plaintext => import::plaintext(&text, &pattern),
+    };
+
+    for (line, heading) in &found.invalid {
+        let message = tr!("line {0} looks like a heading, but \"{1}\" isn't a date, so it was kept in the entry before it", line, heading);
+        warn!("{message}");
+    }
+    let (Some(first), Some(last)) = (found.entries.keys().next(), found.entries.keys().next_back()) else {
+        println!("{}", tr!("no date headings found in {0} (see --date-pattern)", opts.file));
+        return Ok(AppResult::DidntChangeState);
+    };
+    if !found.preamble.is_empty() {
+        let message = tr!("the text before the first date heading wasn't imported");
+        warn!("{message}");
+    }
+    if opts.dry_run {
+        println!("{}", tr!("would import {0} entries, from {1} to {2}", found.entries.len(), first, last));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    // the locks of this journal hold for imported entries too
+    let locked = found
+        .entries
+        .iter()
+        .find(|(date, content)| state.is_locked(date) && state.entries.get(date) != Some(content));
+    if let Some((date, _)) = locked {
+        return Err(UiError::Locked(*date));
+    }
+    let imported = state.merge_from(&found.to_state(), conflict)?;
+
+    println!("{}", tr!("imported {0} entries from {1}", imported.len(), opts.file));
+    match imported.is_empty() {
+        true => Ok(AppResult::DidntChangeState),
+        false => Ok(AppResult::ChangedState),
+    }
+}
+
+/// writes the chosen entries into a new journal with a password of its own
+/// (see [`State::extract()`]). it doesn't get the second factor, search
+/// index, audit log, or hash chain of this one.
+pub fn share(opts: &Share, state: &State) -> Result<AppResult, UiError> {
+    let output = opts.output.as_deref().unwrap_or("./jrn-share.json");
+    if Path::new(output).exists() {
+        return Err(UiError::JournalExists(output.into()));
+    }
+    if state.entries.is_empty() {
+        println!("{}", tr!("No entries to share!"));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let mut dates = opts.dates.clone();
+    if let Some(date) = dates.iter().find(|date| !state.entries.contains_key(date)) {
+        return Err(UiError::NoEntry(*date));
+    }
+    if let Some(range) = &opts.range {
+        dates.extend(state.entries.keys().filter(|date| range.contains(date)));
+    }
+    if opts.dates.is_empty() && opts.range.is_none() {
+        dates.push(choose_day(state, state.entries.keys().copied().collect(), "Which entry do you want to share?")?);
+    }
+    dates.sort();
+    dates.dedup();
+    if dates.is_empty() {
+        println!("{}", tr!("no entries are in that range"));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let password = match (opts.new_password.as_deref(), opts.new_password_file.as_deref()) {
+        (Some(_), Some(_)) => return Err(UiError::Conflict("new password", "new password file")),
+        (Some(password), None) => password.into(),
+        (None, Some(password_file)) => read_file(password_file)?.trim().into(),
+        (None, None) => get_new_password()?,
+    };
+    // sharing the journal's own password would defeat the point
+    if password == state.password {
+        return Err(UiError::SharedPassword);
+    }
+
+    let mut shared = state.extract(&dates);
+    shared.change_password(&password);
+    shared.totp = None;
+    shared.index = None;
+    shared.audit = None;
+    shared.hash_chain = false;
+
+    let _saving = signals::saving();
+    shared.save(output, &Secure)?;
+    println!("{}", tr!("shared {0} entries in {1}", dates.len(), output));
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// lists all entries (or only those in the given range) by date.
+pub fn list_entries(opts: &List, state: &State) -> Result<AppResult, UiError> {
+    let range = range_of(opts.range.as_ref(), opts.since, opts.until)?;
+    let style = match stdout().is_terminal() {
+        true => theme::current().date.clone(),
+        false => Style::default(),
+    };
+
+    if opts.missing {
+        let lines = stats::missing_days(state, &range, &Date::today())
+            .into_iter()
+            .map(|day| (day, format!("- {} ({})", style.paint(day), day.annotation())))
+            .collect();
+        print_grouped(lines, opts.group_by, |days| tr!("{0} days without an entry", days));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let mut keys = state
+        .entries
+        .keys()
+        .filter(|date| range.contains(date))
+        .filter(|date| !opts.starred || state.is_starred(date))
+        .collect::<Vec<_>>();
+    keys.sort();
+    // annotations take a while to work out, so each one only once
+    let rows = keys
+        .into_iter()
+        .map(|key| {
+            let annotation = key.annotation();
+            let width = format!("{key} ({annotation})").chars().count();
+            (key, annotation, width)
+        })
+        .collect::<Vec<_>>();
+    // moods go in a column of their own, if any entry has one
+    let with_mood = rows.iter().any(|(key, _, _)| state.mood(key).is_some());
+    let width = rows.iter().map(|(_, _, width)| *width).max().unwrap_or(0);
+    let today = Date::today();
+    let mut lines = Vec::with_capacity(rows.len());
+    for (key, annotation, key_width) in rows {
+        let star = if state.is_starred(key) { " *" } else { "" };
+        let public = match state.is_public(key) {
+            true => format!(" ({})", tr!("public")),
+            false => String::new(),
+        };
+        let scheduled = match stats::is_scheduled(key, &today) {
+            true => format!(" ({})", tr!("scheduled")),
+            false => String::new(),
+        };
+        let mood = match (with_mood, state.mood(key)) {
+            (false, _) => String::new(),
+            (true, mood) => {
+                let padding = width - key_width;
+                let mood = mood.map_or("-".into(), |mood| format!("{}/5 {}", mood.value(), translate(mood.name())));
+                format!("{:padding$}  {mood}", "")
+            }
+        };
+        lines.push((*key, format!("- {} ({annotation}){mood}{star}{public}{scheduled}", style.paint(key))));
+    }
+    print_grouped(lines, opts.group_by, |entries| tr!("{0} entries", entries));
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints the lines (which are in order of their dates), under a heading for
+/// every month or year with `group_by`. `count` says how many lines a group
+/// has, for its heading.
+fn print_grouped(lines: Vec<(Date, String)>, group_by: Option<GroupBy>, count: impl Fn(usize) -> String) {
+    let stdout = stdout();
+    let style = match stdout.is_terminal() {
+        true => theme::current().heading.clone(),
+        false => Style::default(),
+    };
+    let mut out = std::io::BufWriter::new(stdout.lock());
+    let Some(group_by) = group_by else {
+        for (_, line) in lines {
+            let _ = writeln!(out, "{line}");
+        }
+        return;
+    };
+
+    for (i, group) in lines.chunk_by(|(a, _), (b, _)| group_by.period_of(a) == group_by.period_of(b)).enumerate() {
+        if i > 0 {
+            let _ = writeln!(out);
+        }
+        let heading = format!("{} ({})", group_by.period_of(&group[0].0).name(), count(group.len()));
+        let _ = writeln!(out, "{}", style.paint(heading));
+        for (_, line) in group {
+            let _ = writeln!(out, "{line}");
+        }
+    }
+}
+
+/// the range given either as a range or by `--since` and `--until`
+fn range_of(range: Option<&DateRange>, since: Option<Date>, until: Option<Date>) -> Result<DateRange, UiError> {
+    match (range, since, until) {
+        (Some(_), Some(_), _) => Err(UiError::Conflict("a range", "--since")),
+        (Some(_), _, Some(_)) => Err(UiError::Conflict("a range", "--until")),
+        (Some(range), None, None) => Ok(range.clone()),
+        (None, start, end) => Ok(DateRange { start, end }),
+    }
+}
+
+/// the query for the words, tags, and dates given. asks for the words if
+/// nothing else was given.
+fn search_query(opts: &Search) -> Result<Query, UiError> {
+    let mut query = Query {
+        text: Some(opts.text.join(" ")).filter(|text| !text.trim().is_empty()),
+        tags: opts.tag.clone(),
+        range: range_of(None, opts.since, opts.until)?,
+        fields: opts.meta.clone(),
+    };
+    if query == Query::default() {
+        query.text = Some(input(tr!("Search for"))?);
+    }
+    Ok(query)
+}
+
+/// prints the entries matching the words, tags, and dates given (see
+/// [`search::search()`]), with the line of the first match
+pub fn search_entries(opts: &Search, state: &State) -> Result<AppResult, UiError> {
+    let query = search_query(opts)?;
+    print_hits(search::search(state, &query));
+    Ok(AppResult::DidntChangeState)
+}
+
+/// like [`search_entries()`], but unlocks the journal itself, only
+/// decrypting the entries its search index finds (see [`init_matching()`])
+pub fn quick_search<E: Encryptor>(config: &Config, opts: &Search, e: &E) -> Result<AppResult, UiError> {
+    let query = search_query(opts)?;
+    let state = init_matching(config, e, &query)?;
+    print_hits(search::search(&state, &query));
+    Ok(AppResult::DidntChangeState)
+}
+
+/// rebuilds the search index from every entry (see [`State::index`])
+pub fn reindex(state: &mut State) -> Result<AppResult, UiError> {
+    let index = SearchIndex::build(state);
+    println!("{}", tr!("indexed {0} entries ({1} different words and tags)", index.entries.len(), index.words.len()));
+    state.index = Some(index);
+
+    Ok(AppResult::ChangedState)
+}
+
+fn print_hits(hits: Vec<search::Hit>) {
+    if hits.is_empty() {
+        println!("{}", tr!("no entries found"));
+        return;
+    }
+
+    let (date_style, highlight_style) = match stdout().is_terminal() {
+        true => (theme::current().date.clone(), theme::current().highlight.clone()),
+        false => (Style::default(), Style::default()),
+    };
+    for hit in hits {
+        let excerpt = match hit.highlight {
+            Some(highlight) => format!(
+                "{}{}{}",
+                &hit.excerpt[..highlight.start],
+                highlight_style.paint(&hit.excerpt[highlight.clone()]),
+                &hit.excerpt[highlight.end..]
+            ),
+            None => hit.excerpt,
+        };
+        println!("- {} ({}): {excerpt}", date_style.paint(hit.date), hit.date.annotation());
+    }
+}
+
+/// prints how many entries and words the journal has, or (with `--mood`)
+/// the average mood of every month, as a bar
+pub fn print_stats(opts: &Stats, state: &State) -> Result<AppResult, UiError> {
+    let range = stats::until(&opts.range.clone().unwrap_or_else(DateRange::all), &Date::today());
+
+    let modes = [("--mood", opts.mood), ("--words", opts.words), ("--habits", opts.habits)];
+    if let [(first, _), (second, _), ..] = modes.iter().filter(|(_, on)| *on).collect::<Vec<_>>()[..] {
+        return Err(UiError::Conflict(first, second));
+    }
+
+    if let Some(output) = opts.output.filter(|output| *output != StatsOutput::Text) {
+        return print_stats_table(opts, state, &range, output);
+    }
+
+    if opts.habits {
+        print_habits(state, &range);
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    if opts.words {
+        return print_word_stats(opts, state, &range);
+    }
+
+    if opts.mood {
+        let trends = stats::mood_by_month(state, &range);
+        if trends.is_empty() {
+            println!("{}", tr!("no entries have a mood (set one with --mood when editing)"));
+            return Ok(AppResult::DidntChangeState);
+        }
+        for trend in trends {
+            // eighths of a block, so small changes still show
+            let eighths = (trend.average * 8.0).round() as usize;
+            let bar = "█".repeat(eighths / 8) + ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"][eighths % 8];
+            let entries = tr!("{0} entries", trend.entries);
+            println!("{}  {:.1}  {bar:<5}  ({entries})", trend.period, trend.average);
+        }
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let summary = stats::summary(state, &range);
+    let none = || tr!("none").to_string();
+    let facts = [
+        (tr!("entries"), summary.entries.to_string()),
+        (tr!("words"), summary.words.to_string()),
+        (tr!("words per entry"), format!("{:.0}", summary.average_words())),
+        (tr!("first entry"), summary.first.as_ref().map_or_else(none, Date::label)),
+        (tr!("last entry"), summary.last.as_ref().map_or_else(none, Date::label)),
+    ];
+    let width = facts.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, value) in facts {
+        let name = format!("{name}:");
+        println!("{name:<0$} {value}", width + 1);
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints the statistics `opts` asks for as JSON or CSV
+fn print_stats_table(opts: &Stats, state: &State, range: &DateRange, output: StatsOutput) -> Result<AppResult, UiError> {
+    if opts.cloud {
+        return Err(UiError::Conflict("--cloud", "--output"));
+    }
+
+    let table = if opts.habits {
+        Table::habits(&stats::by_weekday(state, range), &stats::by_hour(state, range))
+    } else if opts.words {
+        let top = opts.top.unwrap_or(10);
+        Table::words(&stats::word_counts(state, range), &stats::word_counts_by_month(state, range), top)
+    } else if opts.mood {
+        Table::moods(&stats::mood_by_month(state, range))
+    } else {
+        Table::summary(&stats::summary(state, range))
+    };
+
+    match output {
+        StatsOutput::Csv => print!("{}", table.to_csv()),
+        _ => println!("{}", serde_json::to_string_pretty(&table.to_json())?),
+    }
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints a grid with a column for every week of the year and a row for every
+/// day of the week, darker the more was written that day
+pub fn print_heatmap(opts: &Heatmap, state: &State) -> Result<AppResult, UiError> {
+    let today = Date::today();
+    let year = opts.year.unwrap_or(today.year());
+    let weeks = stats::heatmap(state, year, &today);
+    let most = weeks.iter().flatten().flatten().copied().max().unwrap_or(0);
+    let styles = match stdout().is_terminal() {
+        true => theme::current().heatmap.clone(),
+        false => Default::default(),
+    };
+    let cell = |level: usize| match level {
+        0 => "·".to_string(),
+        level => styles[level - 1].paint(["░", "▒", "▓", "█"][level - 1]),
+    };
+
+    let Some(first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+        return Ok(AppResult::DidntChangeState);
+    };
+    let abbreviated = |name: String| name.chars().take(3).collect::<String>();
+    // any week will do for the names of its days
+    let monday = first - Duration::days(first.weekday().num_days_from_monday().into());
+    let weekdays = (0..7)
+        .map(|offset| abbreviated(Date::from(monday + Duration::days(offset)).weekday_name()))
+        .collect::<Vec<_>>();
+    let width = weekdays.iter().map(|name| name.chars().count()).max().unwrap_or(0) + 1;
+
+    // a month's name goes over the week it starts in, if there's room
+    let mut months = " ".repeat(width);
+    for month in (1..=12).filter_map(|month| NaiveDate::from_ymd_opt(year, month, 1)) {
+        let week = (month.ordinal0() + first.weekday().num_days_from_monday()) as usize / 7;
+        let column = width + week;
+        if months.chars().count() <= column {
+            let padding = column - months.chars().count();
+            months += &" ".repeat(padding);
+            months += &abbreviated(Date::from(month).month_name());
+            months.push(' ');
+        }
+    }
+    println!("{}", months.trim_end());
+
+    for (weekday, name) in weekdays.iter().enumerate() {
+        let row = weeks
+            .iter()
+            .map(|week| week[weekday].map_or(" ".to_string(), |words| cell(stats::heatmap_level(words, most))))
+            .collect::<String>();
+        println!("{}", format!("{name:<width$}{row}").trim_end());
+    }
+
+    let days = weeks.iter().flatten().flatten().filter(|words| **words > 0).count();
+    let legend = (0..=4).map(cell).collect::<String>();
+    println!();
+    println!("{}", tr!("{0} days written in {1}, less {2} more", days, year, legend));
+    Ok(AppResult::DidntChangeState)
+}
+
+/// prints how many entries were written, and how long they were, on every
+/// day of the week and at every hour
+fn print_habits(state: &State, range: &DateRange) {
+    let weekdays = stats::by_weekday(state, range)
+        .into_iter()
+        .map(|(weekday, summary)| (date::weekday_name(weekday), summary))
+        .collect::<Vec<_>>();
+    print_habit_rows(&weekdays);
+
+    let hours = stats::by_hour(state, range)
+        .into_iter()
+        .map(|(hour, summary)| (format!("{hour:02}:00"), summary))
+        .collect::<Vec<_>>();
+    if !hours.is_empty() {
+        println!();
+        print_habit_rows(&hours);
+    }
+}
+
+/// prints a row for every group of entries, with a bar as long as its share
+/// of the busiest group
+fn print_habit_rows(rows: &[(String, stats::Summary)]) {
+    let most = rows.iter().map(|(_, summary)| summary.entries).max().unwrap_or(0).max(1);
+    let width = rows.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    for (name, summary) in rows {
+        let entries = tr!("{0} entries", summary.entries);
+        let words = tr!("{0} words per entry", format!("{:.0}", summary.average_words()));
+        let bar = "█".repeat((summary.entries * 20).div_ceil(most));
+        let row = format!("{name:<width$}  {entries:<12}  {words:<20}  {bar}");
+        println!("{}", row.trim_end());
+    }
+}
+
+/// prints the most used words overall and for every month, or (with
+/// `--cloud`) every word as JSON
+fn print_word_stats(opts: &Stats, state: &State, range: &DateRange) -> Result<AppResult, UiError> {
+    let top = opts.top.unwrap_or(10);
+    let words = stats::word_counts(state, range);
+
+    if opts.cloud {
+        println!("{}", serde_json::to_string_pretty(&words)?);
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    if words.is_empty() {
+        println!("{}", tr!("no words to count"));
+        return Ok(AppResult::DidntChangeState);
+    }
+
+    let width = words.iter().take(top).map(|count| count.word.chars().count()).max().unwrap_or(0);
+    println!("{}", tr!("most used words:"));
+    for count in words.iter().take(top) {
+        println!("  {:<width$}  {}", count.word, count.count);
+    }
+
+    println!();
+    for (period, words) in stats::word_counts_by_month(state, range) {
+        let words = words
+            .iter()
+            .take(top.min(5))
+            .map(|count| format!("{} ({})", count.word, count.count))
+            .collect::<Vec<_>>();
+        println!("{period}: {}", words.join(", "));
+    }
+
+    Ok(AppResult::DidntChangeState)
+}
+
+fn confirmation(message: &str) -> Result<bool, UiError> {
+    if accessible::is_accessible() {
+        return Ok(accessible::confirm(&format!("{} (y/n)", translate(message)))?);
+    }
+    let question = Question::confirm(message)
+        .message(format!("{} (y/n)", translate(message)))
+        .build();
+
+    let answer = prompt_one(question)?;
+
+    answer.as_bool().ok_or(UiError::UnexpectedAnswer)
+}
+
+/// asks for a line of text. `message` is shown as it is, so it should be
+/// translated already.
+fn input(message: &str) -> Result<String, UiError> {
+    if accessible::is_accessible() {
+        return Ok(accessible::input(message)?);
+    }
+    let question = Question::input(message).message(message).build();
+
+    string_answer(prompt_one(question)?)
+}
+
+/// asks for a date, in any format [`Date`] can be parsed from
+fn input_date(message: &str) -> Result<Date, UiError> {
+    if accessible::is_accessible() {
+        loop {
+            match input(translate(message))?.trim().parse() {
+                Ok(date) => return Ok(date),
+                Err(e) => println!("{e}"),
+            }
+        }
+    }
+    let question = Question::input(message)
+        .message(translate(message))
+        .validate(|answer, _| answer.trim().parse::<Date>().map(|_| ()).map_err(|e| e.to_string()))
+        .build();
+
+    let answer = string_answer(prompt_one(question)?)?;
+
+    answer.trim().parse().map_err(|_| UiError::UnexpectedAnswer)
+}
+
+fn password(message: &str) -> Result<String, UiError> {
+    if accessible::is_accessible() {
+        return Ok(accessible::password(translate(message))?);
+    }
+    let question = Question::password(message)
+        .message(translate(message))
+        .mask(MASK_CHAR)
+        .on_esc(OnEsc::Terminate)
+        .build();
+
+    let answer = prompt_one(question)?;
+
+    string_answer(answer)
+}
+
+/// waits for <Enter>, then opens the entry in `$EDITOR` (see
+/// [`open_editor()`])
+fn edit(
+    config: &Config,
+    date: &Date,
+    content: Option<&str>,
+    file_type: &str,
+    message: &str,
+) -> Result<String, UiError> {
+    if stdin().is_terminal() {
+        print!("? {} ", translate(message));
+        let _ = stdout().flush();
+        stdin()
+            .read_line(&mut String::new())
+            .map_err(UiError::Editor)?;
+    }
+
+    open_editor(config, date, content, file_type)
+}
+
+/// after an edit in `$EDITOR`, prints how many words and lines changed (with
+/// `edit_summary`), and the changed lines with the removed and added words
+/// marked (with `word_diff`)
+fn print_edit_diff(config: &Config, old: &str, new: &str) {
+    let summary = config.edit_summary != Some(false);
+    let word_diff = config.word_diff == Some(true);
+    let diff = diff::diff(old, new);
+    if diff.is_empty() || !(summary || word_diff) {
+        return;
+    }
+    let (added, removed) = match stdout().is_terminal() {
+        true => (theme::current().added.clone(), theme::current().removed.clone()),
+        false => (Style::default(), Style::default()),
+    };
+
+    if word_diff {
+        for hunk in &diff.hunks {
+            let line = hunk
+                .iter()
+                .map(|change| match change {
+                    Change::Same(text) => text.to_string(),
+                    Change::Removed(text) => removed.paint(format!("[-{text}-]")),
+                    Change::Added(text) => added.paint(format!("{{+{text}+}}")),
+                })
+                .collect::<String>();
+            println!("{line}");
+        }
+    }
+    if summary {
+        let message = tr!(
+            "{0}/{1} words, {2} lines changed",
+            added.paint(format!("+{}", diff.added_words)),
+            removed.paint(format!("−{}", diff.removed_words)),
+            diff.changed_lines
+        );
+        println!("{message}");
+    }
+}
+
+/// what a new entry for `date` starts from: the template for it (see
+/// [`template`]), if it doesn't have an entry yet and there is one
+fn new_entry_template(config: &Config, state: &State, date: &Date) -> Result<Option<String>, UiError> {
+    if state.entries.contains_key(date) {
+        return Ok(None);
+    }
+    let rules = config.templates.as_deref().unwrap_or_default();
+    template::template_for(rules, date)
+        .map(|rule| read_file(&rule.file))
+        .transpose()
+}
+
+/// the file type to edit the entry for `date` as: the one it was written in
+/// (see [`State::file_type()`]), or the default for new entries
+fn file_type<'a>(config: &'a Config, state: &'a State, date: &Date) -> &'a str {
+    state
+        .file_type(date)
+        .or(config.file_type.as_deref())
+        .unwrap_or(".md")
+}
+
+/// opens the entry for `date` in `$EDITOR` (see [`editor::edit()`]) right
+/// away, as a `file_type` file. what was written is kept as a draft until the
+/// journal is saved, in case `jrn` crashes before then (see [`crash`]). if
+/// `lint` or `lint_hook` find something, it's shown, with a chance to go back
+/// and fix it.
+fn open_editor(config: &Config, date: &Date, content: Option<&str>, file_type: &str) -> Result<String, UiError> {
+    let mut content = content.unwrap_or_default().to_string();
+    loop {
+        // Ctrl-C in the editor is meant for the editor
+        let catching = signals::catch_interrupts(true);
+        let edited = editor::edit(&content, file_type, config.editor_args.as_deref().unwrap_or_default());
+        signals::interrupted();
+        signals::catch_interrupts(catching);
+        let edited = edited.map_err(UiError::Editor)?;
+        crash::keep_draft(date, &edited);
+
+        // an entry left as it was (like a template nobody filled in) isn't
+        // checked again
+        let findings = match edited == content {
+            true => Vec::new(),
+            false => lint_entry(config, date, &edited),
+        };
+        content = edited;
+        if findings.is_empty() {
+            return Ok(content);
+        }
+        for finding in &findings {
+            let message = describe_finding(finding);
+            warn!("{message}");
+        }
+        if !stdin().is_terminal() || !confirmation("Go back to the editor?")? {
+            return Ok(content);
+        }
+    }
+}
+
+/// runs the checks in `lint`, and `lint_hook`, on an edited entry. a hook
+/// that can't be run only gets a warning.
+fn lint_entry(config: &Config, date: &Date, content: &str) -> Vec<Finding> {
+    // checked by `Config::validate()`
+    let checks = config.get_lint().unwrap_or_default();
+    let mut findings = lint::lint(content, &checks);
+
+    if let Some(hook) = &config.lint_hook {
+        let journal_path = config.file_path.as_deref().unwrap_or("./jrn.json");
+        match run_hook_lines("lint_hook", hook, journal_path, &[*date], content) {
+            Ok(lines) => findings.extend(lines.into_iter().map(Finding::Hook)),
+            Err(e) => {
+                let message = tr!("{0} failed: {1}", "lint_hook", e);
+                warn!("{message}");
+            }
+        }
+    }
+
+    findings
+}
+
+/// a [`Finding`] as a warning
+fn describe_finding(finding: &Finding) -> String {
+    match finding {
+        Finding::Todo(line, text) => tr!("line {0} still has a TODO: {1}", line, text),
+        Finding::Placeholder(line, text) => tr!("line {0} has a placeholder left in: {1}", line, text),
+        Finding::EmptyHeading(line, text) => tr!("line {0} is a heading with nothing under it: {1}", line, text),
+        Finding::Hook(line) => format!("lint_hook: {line}"),
+    }
+}
+
+/// offers back the drafts of entries kept when `jrn` crashed (see
+/// [`crash::recover()`]). gives back whether they were restored, so the
+/// journal gets saved.
+pub fn recover_drafts(journal: &str, state: &mut State) -> bool {
+    let Some(mut drafts) = crash::recover(journal, state) else {
+        return false;
+    };
+    drafts.retain(|(date, content)| state.get_entry(date).as_deref() != Some(content.as_str()));
+    if drafts.is_empty() {
+        crash::discard(journal);
+        return false;
+    }
+
+    let dates = drafts.iter().map(|(date, _)| date.to_string()).collect::<Vec<_>>().join(", ");
+    let message = tr!("jrn crashed before your changes to {0} were saved", dates);
+    warn!("{message}");
+    // without a terminal to ask on, they're kept for next time
+    if !stdin().is_terminal() {
+        for (date, content) in &drafts {
+            crash::keep_draft(date, content);
+        }
+        return false;
+    }
+    match confirmation("Restore the changes? (replaces those entries in the journal)") {
+        Ok(true) => {
+            for (date, content) in drafts {
+                state.set_entry(&date, &content);
+                crash::keep_draft(&date, &content);
+            }
+            true
+        }
+        Ok(false) => {
+            crash::discard(journal);
+            false
+        }
+        Err(_) => {
+            for (date, content) in &drafts {
+                crash::keep_draft(date, content);
+            }
+            false
+        }
+    }
+}
+
+fn string_answer(answer: Answer) -> Result<String, UiError> {
+    match answer {
+        Answer::String(string) => Ok(string),
+        _ => Err(UiError::UnexpectedAnswer),
+    }
+}
+
+/// reads the file at `path`, or all of stdin if it's `-`
+fn read_file(path: &str) -> Result<String, UiError> {
+    if path != "-" {
+        return std::fs::read_to_string(path).map_err(|e| UiError::ReadFile(path.into(), e));
+    }
+    if PASSWORD_FROM_STDIN.load(Ordering::Relaxed) {
+        return Err(UiError::StdinTaken);
+    }
+    let mut content = String::new();
+    stdin()
+        .read_to_string(&mut content)
+        .map_err(|e| UiError::ReadFile(path.into(), e))?;
+    Ok(content)
+}
+
+fn choose<T: Ord>(
+    content: HashSet<T>,
+    message: &str,
+    reverse: bool,
+    label: impl Fn(&T) -> String,
+) -> Result<T, UiError> {
+    let mut content_as_vec = content.into_iter().collect::<Vec<T>>();
+    content_as_vec.sort();
+    if reverse {
+        content_as_vec.reverse();
+    }
+    let labels = content_as_vec.iter().map(label);
+    if accessible::is_accessible() {
+        let index = accessible::select(translate(message), &labels.collect::<Vec<_>>())?;
+        return Ok(content_as_vec.swap_remove(index));
+    }
+    let question = Question::select(message)
+        .message(translate(message))
+        .choices(labels)
+        .build();
+
+    let answer = prompt_one(question)?;
+
+    let Some(list_item) = answer.as_list_item() else {
+        return Err(UiError::UnexpectedAnswer);
+    };
+
+    Ok(content_as_vec.swap_remove(list_item.index))
+}
+
+/// like [`choose_entry()`], but on a calendar if the terminal has room for one
+/// (see [`calendar::choose()`]). if the chosen day has more than one entry,
+/// the list asks which one after.
+fn choose_day(state: &State, dates: Vec<Date>, message: &str) -> Result<Date, UiError> {
+    if accessible::is_accessible() || !stdin().is_terminal() || !stdout().is_terminal() {
+        return choose_entry(state, dates, message);
+    }
+    let Some(day) = calendar::choose(&dates, message)? else {
+        return choose_entry(state, dates, message);
+    };
+
+    let on_day = dates.into_iter().filter(|date| date.without_time() == day).collect::<Vec<_>>();
+    match on_day.as_slice() {
+        [date] => Ok(*date),
+        _ => choose_entry(state, on_day, "Which entry on that day?"),
+    }
+}
+
+/// like [`choose()`] for entries (newest first), with the starred ones in a
+/// section of their own at the top
+fn choose_entry(state: &State, mut dates: Vec<Date>, message: &str) -> Result<Date, UiError> {
+    dates.sort_unstable_by(|a, b| b.cmp(a));
+    // a numbered list has no sections, so the starred entries are marked
+    // instead
+    if accessible::is_accessible() {
+        let labels = dates
+            .iter()
+            .map(|date| match state.is_starred(date) {
+                true => tr!("{0} (starred)", date.label()),
+                false => date.label(),
+            })
+            .collect::<Vec<_>>();
+        return Ok(dates[accessible::select(translate(message), &labels)?]);
+    }
+    let starred = dates
+        .iter()
+        .filter(|date| state.is_starred(date))
+        .copied()
+        .collect::<Vec<_>>();
+
+    // separators count as choices, so they're `None` here
+    let mut choices = Vec::with_capacity(dates.len() + starred.len() + 2);
+    if !starred.is_empty() {
+        choices.push(None);
+        choices.extend(starred.into_iter().map(Some));
+        choices.push(None);
+    }
+    choices.extend(dates.into_iter().map(Some));
+
+    let mut separators = [tr!("Starred"), tr!("All entries")].into_iter();
+    let question = Question::select(message)
+        .message(translate(message))
+        .choices(choices.iter().map(|choice| match choice {
+            Some(date) => Choice::Choice(date.label()),
+            None => Choice::Separator(format!("-- {} --", separators.next().unwrap_or_default())),
+        }))
+        .build();
+
+    let answer = prompt_one(question)?;
 
-    value.unwrap()
+    answer
+        .as_list_item()
+        .and_then(|list_item| choices.get(list_item.index).copied().flatten())
+        .ok_or(UiError::UnexpectedAnswer)
 }
 
-fn get_new_password() -> String {
-    let mut pass1 = password("New password please");
-    let mut pass2 = password("Please repeat password");
+fn get_new_password() -> Result<String, UiError> {
+    let mut pass1 = password("New password please")?;
+    let mut pass2 = password("Please repeat password")?;
 
     if pass1 != pass2 {
         loop {
-            pass1 = password("Try again. New password please");
-            pass2 = password("Please repeat password");
+            pass1 = password("Try again. New password please")?;
+            pass2 = password("Please repeat password")?;
 
             if pass1 == pass2 {
                 break;
@@ -514,15 +3497,13 @@ fn get_new_password() -> String {
         }
     }
 
-    pass1
+    Ok(pass1)
 }
 
-fn get_dates(state: &State) -> HashSet<Date>{
-    let state_keys = HashSet::from_iter(state.entries.keys().map(|a| a.clone()));
-    if state_keys.is_empty() {
-        /*TODO Remove me! */ println!("aaa");
-        return HashSet::from([Date::today()]);
+fn get_dates(state: &State) -> Vec<Date> {
+    if state.entries.is_empty() {
+        return vec![Date::today()];
     }
 
-    state_keys
+    state.entries.keys().copied().collect()
 }
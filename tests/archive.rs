@@ -0,0 +1,37 @@
+use jrn::{
+    archive::{Archive, ArchiveError, Contents, VERSION},
+    encryptor::ZeroSecurity,
+    test_util::{self, PASSWORD},
+};
+
+fn contents() -> Contents {
+    let state = test_util::state([("2024-06-01", "packed"), ("2024-06-02", "and moved")]);
+    let journal = serde_json::to_string(&test_util::stored_journal(&state, &ZeroSecurity).unwrap()).unwrap();
+    Contents {
+        journal_name: "jrn.json".into(),
+        journal,
+        config: Some(("config.toml".into(), "editor = \"vi\"\n".into())),
+    }
+}
+
+#[test]
+fn archives_open_with_the_password() {
+    let contents = contents();
+    let archive = Archive::create(&contents, PASSWORD, 1_717_200_000, &ZeroSecurity).unwrap();
+    let archive = Archive::parse(&archive.to_json()).unwrap();
+
+    let (opened, state) = archive.verify(PASSWORD, &ZeroSecurity).unwrap();
+    assert_eq!(opened, contents);
+    assert_eq!(state.entries.len(), 2);
+    assert!(matches!(archive.open("wrong", &ZeroSecurity), Err(ArchiveError::IncorrectPassword)));
+}
+
+#[test]
+fn only_archives_of_a_known_version_are_opened() {
+    let mut archive = Archive::create(&contents(), PASSWORD, 0, &ZeroSecurity).unwrap();
+    archive.version = VERSION + 1;
+    let result = Archive::parse(&archive.to_json());
+    assert!(matches!(result, Err(ArchiveError::UnsupportedVersion(version)) if version == VERSION + 1));
+
+    assert!(matches!(Archive::parse("{\"entries\": []}"), Err(ArchiveError::NotAnArchive(_))));
+}
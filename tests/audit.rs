@@ -0,0 +1,67 @@
+use jrn::{
+    audit::{self, AuditAction},
+    db::State,
+    encryptor::ZeroSecurity,
+    storage::Storage,
+    test_util::{self, date, PASSWORD},
+};
+
+fn actions(state: &State) -> Vec<AuditAction> {
+    state.audit.iter().flatten().map(|event| event.action.clone()).collect()
+}
+
+#[test]
+fn failed_unlocks_need_an_audit_log() {
+    let storage = test_util::storage(&test_util::state([("2024-06-01", "first")])).unwrap();
+    let before = storage.read().unwrap();
+
+    assert!(!audit::record_failed_unlock(&storage).unwrap());
+    assert_eq!(storage.read().unwrap(), before);
+}
+
+#[test]
+fn events_are_kept_and_failed_unlocks_are_folded_in() {
+    let mut state = test_util::state([("2024-06-01", "first")]);
+    state.audit = Some(Vec::new());
+    state.record(AuditAction::Modified {
+        dates: vec![date("2024-06-01")],
+    });
+    let storage = test_util::storage(&state).unwrap();
+
+    assert!(audit::record_failed_unlock(&storage).unwrap());
+    assert!(audit::record_failed_unlock(&storage).unwrap());
+    assert!(storage.read().unwrap().contains("failed_unlocks"));
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    let events = loaded.audit.as_deref().unwrap();
+    assert_eq!(audit::failed_since_unlock(events), 2);
+
+    loaded.record(AuditAction::Unlocked);
+    assert_eq!(audit::failed_since_unlock(loaded.audit.as_deref().unwrap()), 0);
+    loaded.save_audit_to(&storage, &ZeroSecurity).unwrap();
+    assert!(!storage.read().unwrap().contains("failed_unlocks"));
+
+    let mut reloaded = State::new();
+    reloaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    assert_eq!(reloaded.entries, state.entries);
+    assert_eq!(
+        actions(&reloaded),
+        [
+            AuditAction::Modified {
+                dates: vec![date("2024-06-01")]
+            },
+            AuditAction::FailedUnlock,
+            AuditAction::FailedUnlock,
+            AuditAction::Unlocked,
+        ]
+    );
+}
+
+#[test]
+fn nothing_is_recorded_without_an_audit_log() {
+    let mut state = test_util::state([("2024-06-01", "first")]);
+    state.record(AuditAction::Unlocked);
+
+    assert_eq!(state.audit, None);
+}
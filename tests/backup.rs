@@ -0,0 +1,57 @@
+use std::{fs, path::PathBuf};
+
+use jrn::{
+    backup::{self, BackupConfig, RestoreError},
+    db::State,
+    encryptor::ZeroSecurity,
+    storage::FileStorage,
+    test_util::{self, date, PASSWORD},
+};
+
+/// an empty directory of its own for each test
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("jrn-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn restoring_keeps_the_replaced_journal() {
+    let directory = directory("restore");
+    let journal = directory.join("jrn.json");
+    let journal = journal.to_str().unwrap();
+    let config = BackupConfig::default();
+    let storage = FileStorage::new(journal);
+
+    test_util::state([("2024-06-01", "first")]).save_to(&storage, &ZeroSecurity).unwrap();
+    let first = backup::backup(journal, &config).unwrap().unwrap();
+    test_util::state([("2024-06-01", "changed")]).save_to(&storage, &ZeroSecurity).unwrap();
+
+    // backups are named by the millisecond they're taken in
+    std::thread::sleep(std::time::Duration::from_millis(2));
+    let contents = backup::read_backup(&first).unwrap();
+    let replaced = backup::restore(journal, &contents, &config).unwrap().unwrap();
+    let mut restored = State::new();
+    restored.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    let mut kept = State::new();
+    kept.load_from(&FileStorage::new(&replaced), PASSWORD, &ZeroSecurity).unwrap();
+    let backups = backup::list_backups(journal, &config).unwrap();
+    let _ = fs::remove_dir_all(&directory);
+
+    assert_eq!(restored.get_entry(&date("2024-06-01")).as_deref(), Some("first"));
+    assert_eq!(kept.get_entry(&date("2024-06-01")).as_deref(), Some("changed"));
+    assert_eq!(backups, [replaced, first]);
+}
+
+#[test]
+fn only_journals_are_restored() {
+    let directory = directory("restore-invalid");
+    let file = directory.join("notes.txt");
+    fs::write(&file, "not a journal").unwrap();
+
+    let read = backup::read_backup(&file);
+    let _ = fs::remove_dir_all(&directory);
+
+    assert!(matches!(read, Err(RestoreError::Invalid(..))));
+}
@@ -0,0 +1,24 @@
+use chrono::{NaiveDateTime, NaiveTime};
+use jrn::{
+    date::{self, Date},
+    test_util::date,
+};
+
+fn at(time: &str) -> Option<NaiveDateTime> {
+    Some(NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").unwrap())
+}
+
+#[test]
+fn today_follows_the_fake_clock_and_the_rollover_hour() {
+    date::set_day_rollover_hour(4);
+
+    date::set_fake_now(at("2024-06-02T02:30"));
+    assert_eq!(Date::today(), date("2024-06-01"));
+    assert_eq!(Date::now().time(), NaiveTime::from_hms_opt(2, 30, 0));
+
+    date::set_fake_now(at("2024-06-02T04:00"));
+    assert_eq!(Date::today(), date("2024-06-02"));
+
+    date::set_fake_now(None);
+    assert_ne!(Date::today(), date("2024-06-02"));
+}
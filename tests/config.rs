@@ -0,0 +1,61 @@
+use std::fs;
+
+use jrn::config::{Config, ConfigError, Source};
+
+const RENAMED: &[(u32, &str, &str)] = &[(1, "editor", "editor_command"), (1, "theme.accent", "theme.highlight")];
+
+#[test]
+fn renamed_keys_are_migrated_and_the_new_key_wins() {
+    let mut table: toml::Table = "editor = \"vi\"\n[theme]\naccent = \"red\"\nhighlight = \"blue\"\n".parse().unwrap();
+
+    let warnings = Config::migrate_keys(&mut table, 0, RENAMED);
+
+    assert_eq!(table["editor_command"].as_str(), Some("vi"));
+    assert!(!table.contains_key("editor"));
+    assert_eq!(table["theme"]["highlight"].as_str(), Some("blue"));
+    assert!(table["theme"].get("accent").is_none());
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[1].ends_with("using `theme.highlight`"));
+}
+
+#[test]
+fn keys_renamed_before_the_file_s_version_are_left_alone() {
+    let mut table: toml::Table = "editor = \"vi\"\n".parse().unwrap();
+
+    assert!(Config::migrate_keys(&mut table, 1, RENAMED).is_empty());
+    assert_eq!(table["editor"].as_str(), Some("vi"));
+}
+
+#[test]
+fn config_version_has_to_be_a_version() {
+    let path = std::env::temp_dir().join(format!("jrn-test-config-{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    for version in ["-1", "\"1\"", "4294967296"] {
+        fs::write(path, format!("config_version = {version}\n")).unwrap();
+        assert!(matches!(
+            Config::read_config_file(path),
+            Err(ConfigError::InvalidValue("config_version", _))
+        ));
+    }
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn options_the_file_doesnt_set_come_from_the_defaults() {
+    let path = std::env::temp_dir().join(format!("jrn-test-config-layers-{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::write(path, "file_type = \".org\"\n").unwrap();
+    let overrides = toml::from_str::<Config>("autosave = false").unwrap();
+
+    let resolved = Config::get_config(Some(path), &overrides).unwrap();
+    fs::remove_file(path).unwrap();
+    let source = |key| resolved.sources.iter().find(|(option, _)| *option == key).map(|(_, source)| source.clone());
+
+    assert_eq!(resolved.config.file_type.as_deref(), Some(".org"));
+    assert_eq!(source("file_type"), Some(Source::File(path.into())));
+    assert_eq!(resolved.config.autosave, Some(false));
+    assert_eq!(source("autosave"), Some(Source::Flag("autosave")));
+    assert_eq!(resolved.config.max_attempts, Some(3));
+    assert_eq!(source("max_attempts"), Some(Source::Default));
+}
@@ -0,0 +1,28 @@
+use jrn::diff::{self, Change};
+
+#[test]
+fn changed_words_are_counted() {
+    let old = "went to the park\nsaw a dog\nwent home";
+    let new = "went to the big park\nwent home\nslept early";
+
+    let diff = diff::diff(old, new);
+
+    assert_eq!(diff.added_words, 3);
+    assert_eq!(diff.removed_words, 3);
+    assert_eq!(diff.changed_lines, 3);
+    assert_eq!(
+        diff.hunks,
+        [
+            vec![Change::Same("went to the "), Change::Added("big "), Change::Same("park"), Change::Removed("saw a dog")],
+            vec![Change::Added("slept early")],
+        ]
+    );
+}
+
+#[test]
+fn nothing_changed() {
+    let diff = diff::diff("same\ntext", "same\ntext");
+
+    assert!(diff.is_empty());
+    assert_eq!(diff.changed_lines, 0);
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path, process::Command};
+
+/// `jrn` with a journal, config, and temporary directory of its own in
+/// `directory`
+fn jrn(directory: &Path) -> Command {
+    let mut jrn = Command::new(env!("CARGO_BIN_EXE_jrn"));
+    jrn.current_dir(directory)
+        .env("HOME", directory)
+        .env("XDG_RUNTIME_DIR", directory.join("run"))
+        .args(["-c", "config.toml", "-P", "password"])
+        .stdin(std::process::Stdio::null());
+    jrn
+}
+
+#[test]
+fn the_edited_file_is_overwritten_and_removed() {
+    let directory = std::env::temp_dir().join(format!("jrn-test-editor-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("run")).unwrap();
+    fs::write(directory.join("config.toml"), "file_path = \"jrn.json\"\n").unwrap();
+    fs::write(directory.join("password"), "hunter2\n").unwrap();
+    fs::set_permissions(directory.join("password"), fs::Permissions::from_mode(0o600)).unwrap();
+    // the editor keeps a second name for the file, to see what's left in it
+    let editor = directory.join("editor.sh");
+    fs::write(&editor, "#!/bin/sh\nln \"$1\" kept\necho 'dear diary' > \"$1\"\n").unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o700)).unwrap();
+
+    assert!(jrn(&directory).arg("init").status().unwrap().success());
+    assert!(jrn(&directory).env("EDITOR", &editor).args(["edit", "2024-06-01"]).status().unwrap().success());
+
+    let kept = fs::read(directory.join("kept")).unwrap();
+    assert_eq!(kept, vec![0; "dear diary\n".len()]);
+    assert_eq!(fs::read_dir(directory.join("run")).unwrap().count(), 0);
+    let view = jrn(&directory).args(["view", "2024-06-01"]).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&view.stdout).trim(), "dear diary");
+
+    fs::remove_dir_all(&directory).unwrap();
+}
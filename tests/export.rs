@@ -0,0 +1,126 @@
+use std::fs;
+
+use jrn::{
+    export::{self, ExportFormat},
+    render::Renderers,
+    search::Query,
+    test_util::{self, date},
+};
+
+#[test]
+fn web_export_keeps_entries_encrypted() {
+    let state = test_util::state([("2024-06-01", "a secret </script>")]);
+    let output = std::env::temp_dir().join(format!("jrn-test-export-{}", std::process::id()));
+
+    let files = export::export(&state, ExportFormat::Web, &output).unwrap();
+    let page = fs::read_to_string(output.join("index.html")).unwrap();
+    fs::remove_dir_all(&output).unwrap();
+
+    assert_eq!(files, [output.join("index.html")]);
+    assert!(page.contains("2024-06-01"));
+    assert!(!page.contains("a secret"));
+    assert!(!page.contains("{{JOURNAL}}"));
+}
+
+#[test]
+fn mdbook_export_has_a_chapter_per_month() {
+    let state = test_util::state([
+        ("2023-12-31", "last"),
+        ("2024-06-01", "first"),
+        ("2024-06-02T21:30", "second"),
+    ]);
+    let output = std::env::temp_dir().join(format!("jrn-test-mdbook-{}", std::process::id()));
+
+    export::export(&state, ExportFormat::Mdbook, &output).unwrap();
+    let summary = fs::read_to_string(output.join("src/SUMMARY.md")).unwrap();
+    let entry = fs::read_to_string(output.join("src/2024/06/2024-06-02T21-30.md")).unwrap();
+    assert!(output.join("book.toml").exists());
+    fs::remove_dir_all(&output).unwrap();
+
+    let lines = summary.lines().collect::<Vec<_>>();
+    assert_eq!(lines[2], "- [2023](2023/README.md)");
+    assert_eq!(lines[3], "  - [December](2023/12/README.md)");
+    assert_eq!(lines[5], "- [2024](2024/README.md)");
+    assert_eq!(lines[7], "    - [2024-06-01 (Sat)](2024/06/2024-06-01.md)");
+    assert!(entry.ends_with("\n\nsecond\n"));
+}
+
+#[test]
+fn mdbook_export_links_mentioned_dates_with_an_entry() {
+    let state = test_util::state([
+        ("2023-12-31", "last"),
+        ("2024-06-01", "since 2023-12-31, not 2024-01-01\n```\n2023-12-31\n```"),
+    ]);
+    let output = std::env::temp_dir().join(format!("jrn-test-mdbook-links-{}", std::process::id()));
+
+    export::export(&state, ExportFormat::Mdbook, &output).unwrap();
+    let entry = fs::read_to_string(output.join("src/2024/06/2024-06-01.md")).unwrap();
+    fs::remove_dir_all(&output).unwrap();
+
+    assert!(entry.ends_with("\n\nsince [2023-12-31](../../2023/12/2023-12-31.md), not 2024-01-01\n```\n2023-12-31\n```\n"));
+}
+
+#[test]
+fn mdbook_export_keeps_other_file_types_as_they_are() {
+    let mut state = test_util::state([("2024-06-01", "* not a heading\n```"), ("2024-06-02", "# a heading")]);
+    state.set_file_type(&test_util::date("2024-06-01"), Some(".rst"));
+    let output = std::env::temp_dir().join(format!("jrn-test-file-types-{}", std::process::id()));
+
+    export::export(&state, ExportFormat::Mdbook, &output).unwrap();
+    let rst = fs::read_to_string(output.join("src/2024/06/2024-06-01.md")).unwrap();
+    let markdown = fs::read_to_string(output.join("src/2024/06/2024-06-02.md")).unwrap();
+    fs::remove_dir_all(&output).unwrap();
+
+    assert!(rst.ends_with("\n\n````rst\n* not a heading\n```\n````\n"));
+    assert!(markdown.ends_with("\n\n# a heading\n"));
+}
+
+#[test]
+fn part_of_a_journal_can_be_exported() {
+    let mut state = test_util::state([
+        ("2023-12-31", "new year's eve #travel"),
+        ("2024-06-01", "berlin #travel"),
+        ("2024-06-02", "at home"),
+    ]);
+    state.set_starred(&date("2024-06-02"), true);
+
+    let query = Query {
+        tags: vec!["travel".into()],
+        range: "2024".parse().unwrap(),
+        ..Query::default()
+    };
+    let selected = export::select(&state, &query);
+    assert_eq!(selected.entries.keys().collect::<Vec<_>>(), [&date("2024-06-01")]);
+    assert!(!selected.is_starred(&date("2024-06-02")));
+
+    let output = std::env::temp_dir().join(format!("jrn-test-export-part-{}", std::process::id()));
+    let files = export::export(&selected, ExportFormat::Mdbook, &output);
+    let book = fs::read_to_string(output.join("src").join("SUMMARY.md"));
+    fs::remove_dir_all(&output).unwrap();
+    files.unwrap();
+    let book = book.unwrap();
+    assert!(book.contains("2024"));
+    assert!(!book.contains("2023"));
+}
+
+#[test]
+fn mdbook_export_carries_on_past_entries_it_cant_write() {
+    let state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second"), ("2024-06-03", "third")]);
+    let output = std::env::temp_dir().join(format!("jrn-test-failures-{}", std::process::id()));
+    // a directory where the page of the second entry goes
+    fs::create_dir_all(output.join("src/2024/06/2024-06-02.md/taken")).unwrap();
+
+    let mut calls = Vec::new();
+    let exported =
+        export::export_with_progress(&state, ExportFormat::Mdbook, &output, &Renderers::new(), &mut |done, total| calls.push((done, total)))
+            .unwrap();
+    let summary = fs::read_to_string(output.join("src/SUMMARY.md")).unwrap();
+    let third = output.join("src/2024/06/2024-06-03.md").exists();
+    fs::remove_dir_all(&output).unwrap();
+
+    assert_eq!(calls, [(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(exported.failed.len(), 1);
+    assert_eq!(exported.failed[0].0, date("2024-06-02"));
+    assert!(third);
+    assert!(!summary.contains("2024-06-02"));
+}
@@ -0,0 +1,26 @@
+use jrn::{
+    import::{self, DatePatternError, DEFAULT_DATE_PATTERN},
+    test_util::date,
+};
+
+#[test]
+fn text_is_split_at_every_date_heading() {
+    let text = "a preamble\n\n2024-06-01 - the first\nline one\n\n2024-02-30\n\n2024-06-02:\n\nline two\n\n2024-06-01\nagain\n";
+    let found = import::plaintext(text, &import::date_pattern(DEFAULT_DATE_PATTERN).unwrap());
+
+    assert_eq!(found.preamble, "a preamble");
+    assert_eq!(found.invalid, [(6, "2024-02-30".to_string())]);
+    assert_eq!(found.entries.len(), 2);
+    assert_eq!(found.entries[&date("2024-06-01")], "the first\nline one\n\n2024-02-30\n\nagain");
+    assert_eq!(found.entries[&date("2024-06-02")], "line two");
+}
+
+#[test]
+fn headings_can_have_month_names() {
+    let pattern = import::date_pattern(r"^(?<month>[A-Za-z]+) (?<day>\d+), (?<year>\d{4})$").unwrap();
+    let found = import::plaintext("June 1, 2024\nsummer\nDec 24, 2023\nwinter", &pattern);
+    assert_eq!(found.entries[&date("2024-06-01")], "summer");
+    assert_eq!(found.entries[&date("2023-12-24")], "winter");
+
+    assert!(matches!(import::date_pattern(r"(?<day>\d+)"), Err(DatePatternError::MissingGroups)));
+}
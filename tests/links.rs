@@ -0,0 +1,41 @@
+use jrn::{
+    links::{self, Backlinks},
+    test_util::{self, date},
+};
+
+#[test]
+fn links_are_found_in_text() {
+    let text = "see [[2024-05-01]], [[ 2024-05-02 ]], [[[2024-05-03]], 2024-05-04, [[2024-05-05 and]] [[2024-13-01]]";
+    let links = links::links(text)
+        .into_iter()
+        .map(|(range, linked)| (&text[range], linked))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        links,
+        [
+            ("[[2024-05-01]]", date("2024-05-01")),
+            ("[[ 2024-05-02 ]]", date("2024-05-02")),
+            ("[[2024-05-03]]", date("2024-05-03")),
+        ]
+    );
+}
+
+#[test]
+fn backlinks_point_back_at_the_linking_entries() {
+    let state = test_util::state([
+        ("2024-05-01", "the trip starts, see [[2024-05-01]]"),
+        ("2024-05-02", "day two of [[2024-05-01]]"),
+        ("2024-05-03", "back home, like [[2024-05-01]] and [[2024-05-02]] planned"),
+        ("2024-05-04", "nothing, 2024-05-01 isn't a link"),
+    ]);
+    let mut backlinks = Backlinks::new(&state);
+
+    assert_eq!(backlinks.to(&date("2024-05-01")), [date("2024-05-02"), date("2024-05-03")]);
+    assert_eq!(backlinks.to(&date("2024-05-02")), [date("2024-05-03")]);
+    assert!(backlinks.to(&date("2024-05-04")).is_empty());
+
+    backlinks.remove(&date("2024-05-03"));
+    backlinks.insert(&date("2024-05-03"), "back home");
+    assert_eq!(backlinks.to(&date("2024-05-01")), [date("2024-05-02")]);
+    assert!(backlinks.to(&date("2024-05-02")).is_empty());
+}
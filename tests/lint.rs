@@ -0,0 +1,30 @@
+use jrn::lint::{lint, Check, Finding};
+
+const ALL: [Check; 3] = [Check::Todo, Check::Placeholders, Check::EmptyHeadings];
+
+#[test]
+fn finds_todos_placeholders_and_empty_headings() {
+    let entry = "# Today\nwent for a walk\nTODO: call mom\n\n## Mood\n{{mood}}\n\n## Grateful for\n\n## Tomorrow\n";
+    assert_eq!(
+        lint(entry, &ALL),
+        vec![
+            Finding::Todo(3, "TODO: call mom".into()),
+            Finding::Placeholder(6, "{{mood}}".into()),
+            Finding::EmptyHeading(8, "## Grateful for".into()),
+            Finding::EmptyHeading(10, "## Tomorrow".into()),
+        ]
+    );
+}
+
+#[test]
+fn only_runs_the_checks_it_is_given() {
+    let entry = "## Mood\nTODO\n{{weather}}";
+    assert_eq!(lint(entry, &[Check::Placeholders]), vec![Finding::Placeholder(3, "{{weather}}".into())]);
+    assert!(lint(entry, &[]).is_empty());
+}
+
+#[test]
+fn todo_has_to_be_a_word_and_headings_count_what_is_under_subheadings() {
+    let entry = "# Plans\n## Morning\nTODOS are fine, and so is #TODOLIST\n#hashtag";
+    assert!(lint(entry, &ALL).is_empty());
+}
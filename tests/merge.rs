@@ -0,0 +1,37 @@
+use jrn::merge;
+
+#[test]
+fn only_the_lines_that_differ_are_marked() {
+    let ours = "woke up early\nwent for a run\nmade coffee\n";
+    let theirs = "woke up early\nslept in\nmade coffee\n";
+
+    let merged = merge::with_markers(ours, "ours", theirs, "theirs");
+
+    assert_eq!(
+        merged,
+        "woke up early\n<<<<<<< ours\nwent for a run\n=======\nslept in\n>>>>>>> theirs\nmade coffee\n"
+    );
+    assert!(merge::has_markers(&merged));
+}
+
+#[test]
+fn added_lines_are_marked_against_nothing() {
+    let merged = merge::with_markers("one\ntwo", "ours", "one\ntwo\nthree", "theirs");
+
+    assert_eq!(merged, "one\ntwo\n<<<<<<< ours\n=======\nthree\n>>>>>>> theirs\n");
+}
+
+#[test]
+fn the_same_entry_has_nothing_to_merge() {
+    let entry = "the same\nevery time\n";
+
+    assert_eq!(merge::with_markers(entry, "ours", entry, "theirs"), entry);
+    assert!(!merge::has_markers(entry));
+}
+
+#[test]
+fn markers_have_to_be_whole_lines() {
+    assert!(!merge::has_markers("a heading\n=========\n"));
+    assert!(!merge::has_markers("look: <<<<<<< ours\n"));
+    assert!(merge::has_markers("resolved\n=======\n"));
+}
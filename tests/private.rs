@@ -0,0 +1,48 @@
+use jrn::{
+    db::State,
+    encryptor::{Secure, ZeroSecurity},
+    private::{self, Passphrase, PrivateError},
+    test_util::date,
+};
+
+const ENTRY: &str = "Went hiking.\n:::private\nthe real story\n:::\nHome by six.\n";
+
+#[test]
+fn sealed_sections_are_hidden_and_revealed_again() {
+    let day = date("2024-06-04");
+    let mut passphrase = Passphrase::new("sekrit");
+    let sealed = passphrase.seal(ENTRY, &day, &ZeroSecurity).unwrap();
+
+    assert!(!sealed.contains("real story"));
+    assert!(private::has_sealed(&sealed));
+    assert!(!private::has_unsealed(&sealed));
+    assert_eq!(private::hide(&sealed), "Went hiking.\n[private]\nHome by six.\n");
+    assert_eq!(passphrase.reveal(&sealed, &day, &ZeroSecurity).unwrap(), ENTRY);
+    // sealing again leaves sealed sections as they are
+    assert_eq!(passphrase.seal(&sealed, &day, &ZeroSecurity).unwrap(), sealed);
+}
+
+#[test]
+fn unsealed_and_unclosed_sections_are_hidden_too() {
+    assert!(private::has_unsealed(ENTRY));
+    assert_eq!(private::hide("a\n:::private\nb\nc"), "a\n[private]");
+
+    let mut state = State::new();
+    state.set_entry(&date("2024-06-04"), ENTRY);
+    assert_eq!(private::hide_state(&state).entries[&date("2024-06-04")], "Went hiking.\n[private]\nHome by six.\n");
+}
+
+#[test]
+fn another_passphrase_doesnt_open_sections() {
+    let day = date("2024-06-04");
+    let mut state = State::new();
+    assert!(!Passphrase::new("sekrit").check(&state, &Secure).unwrap());
+
+    let sealed = Passphrase::new("sekrit").seal(ENTRY, &day, &Secure).unwrap();
+    state.set_entry(&day, &sealed);
+    assert!(Passphrase::new("sekrit").check(&state, &Secure).unwrap());
+    assert!(matches!(
+        Passphrase::new("guess").reveal_state(&state, &Secure),
+        Err(PrivateError::IncorrectPassphrase)
+    ));
+}
@@ -0,0 +1,33 @@
+use jrn::{
+    redact::{Redaction, MASK},
+    test_util::{self, date},
+};
+
+#[test]
+fn tagged_paragraphs_are_masked() {
+    let redaction = Redaction::tags(["#private"]);
+
+    let redacted = redaction.redact("saw the old town\n\nfought with Sam #private\n\ngelato by the river");
+
+    assert_eq!(redacted.as_deref(), Some(format!("saw the old town\n\n{MASK}\n\ngelato by the river").as_str()));
+}
+
+#[test]
+fn entries_with_nothing_left_are_left_out() {
+    let state = test_util::state([("2024-06-01", "a #Private day"), ("2024-06-02", "the museum")]);
+
+    let redacted = Redaction::tags(["private"]).apply(&state);
+
+    assert!(!redacted.entries.contains_key(&date("2024-06-01")));
+    assert_eq!(redacted.get_entry(&date("2024-06-02")).as_deref(), Some("the museum"));
+}
+
+#[test]
+fn pattern_files_mask_phrases() {
+    let redaction = Redaction::parse("#health\n\nHotel Miramar\n");
+    assert_eq!(redaction.tags, ["health"]);
+
+    let redacted = redaction.redact("stayed at the hotel miramar again\n\nback hurts #health");
+
+    assert_eq!(redacted.as_deref(), Some(format!("stayed at the {MASK} again\n\n{MASK}").as_str()));
+}
@@ -0,0 +1,39 @@
+use jrn::render::{Renderer, Renderers, Target};
+
+#[test]
+fn org_entries_are_exported_as_markdown() {
+    let org = "#+TITLE: Hiking\n* Morning\nSaw a *heron* and /two/ ducks, see [[https://example.com][the map]].\n+ water\n#+BEGIN_SRC sh\necho *hi*\n#+END_SRC\nin /usr/bin/ and 2*3*4\n";
+    assert_eq!(
+        Renderers::new().render(org, Some(".org"), Target::Markdown),
+        "# Hiking\n# Morning\nSaw a **heron** and *two* ducks, see [the map](https://example.com).\n- water\n```sh\necho *hi*\n```\nin /usr/bin/ and 2*3*4\n"
+    );
+}
+
+#[test]
+fn file_types_without_a_renderer_are_plain_text() {
+    let renderers = Renderers::new();
+    assert_eq!(renderers.render("# a heading", None, Target::Markdown), "# a heading");
+    assert_eq!(renderers.render("# a heading", Some(".txt"), Target::Markdown), "```text\n# a heading\n```");
+    assert_eq!(renderers.render("* an item", Some(".rst"), Target::Terminal), "* an item");
+}
+
+struct Shout;
+
+impl Renderer for Shout {
+    fn render(&self, content: &str, target: Target) -> Option<String> {
+        (target == Target::Markdown).then(|| content.to_uppercase())
+    }
+}
+
+#[test]
+fn registered_renderers_come_first_and_fall_back_to_the_ones_before() {
+    let mut renderers = Renderers::new();
+    renderers.register(".org", Shout);
+    assert_eq!(renderers.render("* hi", Some("org"), Target::Markdown), "* HI");
+    // `Shout` doesn't render for terminals, so the built-in one does
+    assert_eq!(
+        renderers.render("hi *there*", Some("org"), Target::Terminal),
+        Renderers::new().render("hi *there*", Some("org"), Target::Terminal)
+    );
+    assert!(renderers.file_types().any(|file_type| file_type == "org"));
+}
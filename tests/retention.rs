@@ -0,0 +1,14 @@
+use jrn::{
+    retention,
+    test_util::{self, date},
+};
+
+#[test]
+fn locked_entries_never_expire() {
+    let mut state = test_util::state([("2020-01-01", "old"), ("2020-06-01", "old and locked"), ("2024-06-01", "new")]);
+    state.set_locked(&date("2020-06-01"), true);
+
+    let expired = retention::expired(&state, &"1y".parse().unwrap(), &date("2024-06-02"));
+
+    assert_eq!(expired, [date("2020-01-01")]);
+}
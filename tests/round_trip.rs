@@ -0,0 +1,319 @@
+use std::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+use jrn::{
+    db::{Conflict, EncryptedJournal, LoadError, State, StoredJournal},
+    encryptor::{ChainStatus, DecryptError, Encryptor, Secure, ZeroSecurity},
+    storage::{Blocking, FileStorage, InMemoryStorage, Storage},
+    test_util::{self, date, PASSWORD},
+};
+
+/// runs a future that never waits (like those from [`Blocking`])
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("future wasn't ready"),
+    }
+}
+
+#[test]
+fn save_then_load() {
+    let state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+    let storage = test_util::storage(&state).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+
+    assert_eq!(loaded.entries, state.entries);
+    assert_eq!(loaded.get_entry(&date("2024-06-02")).as_deref(), Some("second"));
+}
+
+#[test]
+fn incorrect_password() {
+    let storage = test_util::storage(&test_util::state([("2024-06-01", "first")])).unwrap();
+
+    let loaded = State::new().load_from(&storage, "not the password", &ZeroSecurity);
+
+    assert!(matches!(loaded, Err(LoadError::IncorrectPassword)));
+}
+
+#[test]
+fn nothing_stored() {
+    let storage = InMemoryStorage::new();
+    assert!(!storage.exists());
+
+    let loaded = State::new().load_from(&storage, PASSWORD, &ZeroSecurity);
+
+    assert!(matches!(loaded, Err(LoadError::NotAccessible(_))));
+}
+
+#[test]
+fn stored_journal_matches_saved_json() {
+    let state = test_util::state([("2024-06-01", "first")]);
+    let stored = test_util::stored_journal(&state, &ZeroSecurity).unwrap();
+
+    let mut loaded = State::new();
+    loaded
+        .load_json(&serde_json::to_string(&stored).unwrap(), PASSWORD, &ZeroSecurity)
+        .unwrap();
+
+    assert_eq!(loaded.entries, state.entries);
+}
+
+#[test]
+fn file_round_trip() {
+    let state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+    let path = std::env::temp_dir().join(format!("jrn-round-trip-{}.json", std::process::id()));
+    let storage = FileStorage::new(&path);
+
+    state.save_to(&storage, &ZeroSecurity).unwrap();
+    let mut loaded = State::new();
+    let result = loaded.load_from(&storage, PASSWORD, &ZeroSecurity);
+    let _ = std::fs::remove_file(&path);
+
+    result.unwrap();
+    assert_eq!(loaded.entries, state.entries);
+}
+
+#[test]
+fn saving_replaces_the_file_at_once() {
+    let state = test_util::state([("2024-06-01", "first")]);
+    let path = std::env::temp_dir().join(format!("jrn-replace-{}.json", std::process::id()));
+    let temporary = path.with_file_name(format!("jrn-replace-{}.json.tmp", std::process::id()));
+    std::fs::write(&path, "the old journal").unwrap();
+    let storage = FileStorage::new(&path);
+
+    let failed = storage.write_with(&mut |file| {
+        file.write_all(b"half of a journal")?;
+        Err(std::io::ErrorKind::Interrupted.into())
+    });
+    let kept = std::fs::read_to_string(&path);
+    state.save_to(&storage, &ZeroSecurity).unwrap();
+    let mut loaded = State::new();
+    let result = loaded.load_from(&storage, PASSWORD, &ZeroSecurity);
+    let left_behind = temporary.exists();
+    let _ = std::fs::remove_file(&path);
+
+    assert!(failed.is_err());
+    assert_eq!(kept.unwrap(), "the old journal");
+    result.unwrap();
+    assert!(!left_behind);
+}
+
+#[test]
+fn saving_reports_progress() {
+    let mut state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+    state.change_password("a new password");
+    let storage = InMemoryStorage::new();
+
+    let mut reported = Vec::new();
+    state
+        .save_to_with_progress(&storage, &ZeroSecurity, &mut |done, total| reported.push((done, total)))
+        .unwrap();
+    let mut loaded = State::new();
+    loaded.load_from(&storage, "a new password", &ZeroSecurity).unwrap();
+
+    assert_eq!(reported, [(0, 2), (1, 2), (2, 2)]);
+    assert_eq!(loaded.entries, state.entries);
+}
+
+#[test]
+fn async_round_trip() {
+    let state = test_util::state([("2024-06-01", "first")]);
+    let storage = Blocking(InMemoryStorage::new());
+
+    block_on(state.save_to_async(&storage, &ZeroSecurity)).unwrap();
+    let mut loaded = State::new();
+    block_on(loaded.load_from_async(&storage, PASSWORD, &ZeroSecurity)).unwrap();
+
+    assert_eq!(loaded.entries, state.entries);
+}
+
+#[test]
+fn secure_round_trip() {
+    let state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+    let storage = InMemoryStorage::new();
+    state.save_to(&storage, &Secure).unwrap();
+
+    assert!(!storage.contents().unwrap().contains("first"));
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &Secure).unwrap();
+    assert_eq!(loaded.entries, state.entries);
+
+    let loaded = State::new().load_from(&storage, "not the password", &Secure);
+    assert!(matches!(loaded, Err(LoadError::IncorrectPassword)));
+}
+
+#[test]
+fn identical_entries_share_a_digest() {
+    let stub = "## Morning\n\n## Evening\n\n## Grateful for\n";
+    let mut state = test_util::state([("2024-06-01", stub), ("2024-06-02", stub), ("2024-06-03", stub)]);
+    state.set_entry(&date("2024-06-04"), "actually wrote something");
+    let stored = test_util::stored_journal(&state, &Secure).unwrap();
+
+    assert_eq!(stored.blobs.len(), 1);
+    let shared = stored.entries.iter().filter(|entry| entry.blob.is_some()).count();
+    assert_eq!(shared, 3);
+
+    let mut loaded = State::new();
+    loaded
+        .load_json(&serde_json::to_string(&stored).unwrap(), PASSWORD, &Secure)
+        .unwrap();
+    assert_eq!(loaded.entries, state.entries);
+}
+
+#[test]
+fn hash_chain_notices_removed_entries() {
+    let mut state = test_util::state([
+        ("2024-06-01", "first"),
+        ("2024-06-02", "second"),
+        ("2024-06-03", "third"),
+    ]);
+    state.hash_chain = true;
+    let journal = ZeroSecurity.encrypt_journal(&state).unwrap();
+    assert_eq!(
+        ZeroSecurity.verify_chain(&journal, PASSWORD).unwrap(),
+        ChainStatus::Intact
+    );
+
+    let mut removed = journal.clone();
+    removed.entries.retain(|entry| entry.date != date("2024-06-02"));
+    assert_eq!(
+        ZeroSecurity.verify_chain(&removed, PASSWORD).unwrap(),
+        ChainStatus::BrokenAt(date("2024-06-03"))
+    );
+
+    let mut truncated = journal.clone();
+    truncated.entries.retain(|entry| entry.date != date("2024-06-03"));
+    assert_eq!(
+        ZeroSecurity.verify_chain(&truncated, PASSWORD).unwrap(),
+        ChainStatus::Truncated
+    );
+}
+
+#[test]
+fn hash_chain_is_kept() {
+    let mut state = test_util::state([("2024-06-01", "first")]);
+    state.hash_chain = true;
+    let storage = test_util::storage(&state).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+
+    assert!(loaded.hash_chain);
+}
+
+#[test]
+fn move_and_copy_entries() {
+    let mut state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+
+    assert!(state.copy_entry(&date("2024-06-01"), &date("2024-06-03"), Conflict::Overwrite));
+    assert_eq!(state.get_entry(&date("2024-06-01")).as_deref(), Some("first"));
+    assert_eq!(state.get_entry(&date("2024-06-03")).as_deref(), Some("first"));
+
+    assert!(state.move_entry(&date("2024-06-01"), &date("2024-06-02"), Conflict::Append));
+    assert_eq!(state.get_entry(&date("2024-06-01")), None);
+    assert_eq!(state.get_entry(&date("2024-06-02")).as_deref(), Some("second\n\nfirst"));
+
+    assert!(!state.move_entry(&date("2024-06-01"), &date("2024-06-04"), Conflict::Overwrite));
+    assert_eq!(state.entries.len(), 2);
+}
+
+#[test]
+fn entry_metadata_is_kept() {
+    let mut state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "second")]);
+    state.set_locked(&date("2024-06-01"), true);
+    state.set_starred(&date("2024-06-02"), true);
+    let storage = test_util::storage(&state).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    assert!(loaded.is_locked(&date("2024-06-01")));
+    assert!(!loaded.is_locked(&date("2024-06-02")));
+    assert!(loaded.is_starred(&date("2024-06-02")));
+    assert!(!loaded.is_starred(&date("2024-06-01")));
+
+    loaded.move_entry(&date("2024-06-01"), &date("2024-06-03"), Conflict::Overwrite);
+    assert!(loaded.is_locked(&date("2024-06-03")));
+    assert!(!loaded.is_locked(&date("2024-06-01")));
+}
+
+#[test]
+fn entries_keep_their_file_type() {
+    let mut state = test_util::state([("2024-06-01", "written in org"), ("2024-06-02", "no file type")]);
+    state.set_file_type(&date("2024-06-01"), Some(".org"));
+    let storage = test_util::storage(&state).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    assert_eq!(loaded.file_type(&date("2024-06-01")), Some(".org"));
+    assert_eq!(loaded.file_type(&date("2024-06-02")), None);
+
+    loaded.copy_entry(&date("2024-06-01"), &date("2024-06-02"), Conflict::Overwrite);
+    assert_eq!(loaded.file_type(&date("2024-06-02")), Some(".org"));
+}
+
+#[test]
+fn public_entries_are_stored_in_the_clear() {
+    let mut state = test_util::state([("2024-06-01", "shared with the team"), ("2024-06-02", "just for me")]);
+    state.hash_chain = true;
+    state.set_public(&date("2024-06-01"), true);
+    let storage = InMemoryStorage::new();
+    state.save_to(&storage, &Secure).unwrap();
+
+    let contents = storage.contents().unwrap();
+    assert!(contents.contains("shared with the team"));
+    assert!(!contents.contains("just for me"));
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &Secure).unwrap();
+    assert_eq!(loaded.entries, state.entries);
+    assert!(loaded.is_public(&date("2024-06-01")));
+    assert!(!loaded.is_public(&date("2024-06-02")));
+    let stored = serde_json::from_str::<StoredJournal>(&contents).unwrap();
+    let encrypted = EncryptedJournal::try_from(stored).unwrap();
+    assert_eq!(Secure.verify_chain(&encrypted, PASSWORD).unwrap(), ChainStatus::Intact);
+}
+
+#[test]
+fn keys_unlock_until_the_password_changes() {
+    let state = test_util::state([("2024-06-01", "first")]);
+    let storage = InMemoryStorage::new();
+    state.save_to(&storage, &Secure).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &Secure).unwrap();
+    let keys = loaded.keys.clone().unwrap();
+
+    // saving again keeps the keys, so they still unlock the journal
+    loaded.set_entry(&date("2024-06-02"), "second");
+    loaded.save_to(&storage, &Secure).unwrap();
+    let mut unlocked = State::new();
+    unlocked.load_with_keys_from(&storage, &keys, &Secure, None).unwrap();
+    assert_eq!(unlocked.entries, loaded.entries);
+
+    unlocked.change_password("a new password");
+    unlocked.save_to(&storage, &Secure).unwrap();
+    let stale = State::new().load_with_keys_from(&storage, &keys, &Secure, None);
+    assert!(matches!(stale, Err(LoadError::DecryptError(DecryptError::StaleKeys))));
+}
+
+#[test]
+fn entries_keep_when_they_changed() {
+    let mut state = test_util::state([("2024-06-01", "first"), ("2024-06-02", "from before")]);
+    state.touch(&date("2024-06-01"), 1_717_200_000, true);
+    state.touch(&date("2024-06-01"), 1_717_300_000, false);
+    let storage = test_util::storage(&state).unwrap();
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &ZeroSecurity).unwrap();
+    assert_eq!(loaded.created(&date("2024-06-01")), Some(1_717_200_000));
+    assert_eq!(loaded.modified(&date("2024-06-01")), Some(1_717_300_000));
+    assert_eq!(loaded.created(&date("2024-06-02")), None);
+    assert_eq!(loaded.modified(&date("2024-06-02")), None);
+}
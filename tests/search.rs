@@ -0,0 +1,122 @@
+use jrn::{
+    db::{Field, State},
+    encryptor::ZeroSecurity,
+    index::SearchIndex,
+    search::{self, Query},
+    test_util::{self, date, PASSWORD},
+};
+
+#[test]
+fn tags_are_found_in_text() {
+    let tags = search::tags("#Travel to Berlin #travel, see page#top and issue #42 &#39; #work-trip_");
+    assert_eq!(tags, ["travel", "work-trip"]);
+}
+
+#[test]
+fn filters_are_combined() {
+    let state = test_util::state([
+        ("2022-12-30", "Berlin for new year #travel"),
+        ("2023-05-04", "Flew to Berlin today.\nLong day in the Berlin office. #travel #work"),
+        ("2023-08-01", "berlin again"),
+        ("2024-01-01", "Munich #travel"),
+    ]);
+
+    let query = Query {
+        text: Some("berlin".into()),
+        tags: vec!["#Travel".into()],
+        range: "2023-01-01..2023-12-31".parse().unwrap(),
+        ..Query::default()
+    };
+    let hits = search::search(&state, &query);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].date, date("2023-05-04"));
+    assert_eq!(hits[0].excerpt, "Flew to Berlin today.");
+    assert_eq!(hits[0].highlight, Some(8..14));
+
+    let query = Query {
+        text: Some("BERLIN office".into()),
+        ..Query::default()
+    };
+    let dates = search::search(&state, &query).into_iter().map(|hit| hit.date).collect::<Vec<_>>();
+    assert_eq!(dates, [date("2023-05-04")]);
+
+    let query = Query {
+        tags: vec!["travel".into()],
+        ..Query::default()
+    };
+    assert_eq!(search::search(&state, &query).len(), 3);
+}
+
+#[test]
+fn custom_fields_are_searched() {
+    let mut state = test_util::state([("2024-06-01", "walk"), ("2024-06-02", "bus"), ("2024-06-03", "home")]);
+    let field = |field: &str| field.parse::<Field>().unwrap();
+    assert!(state.set_fields(&date("2024-06-01"), &[field("Weather=Rain"), field("steps=9000")]));
+    assert!(state.set_fields(&date("2024-06-02"), &[field("weather=sun")]));
+    assert!(!state.set_fields(&date("2024-06-02"), &[field("weather=sun")]));
+    assert!(!state.set_fields(&date("2024-06-03"), &[field("weather=")]));
+
+    let query = |fields: &[&str]| Query {
+        fields: fields.iter().map(|text| field(text)).collect(),
+        ..Query::default()
+    };
+    let dates = |query: Query| search::search(&state, &query).into_iter().map(|hit| hit.date).collect::<Vec<_>>();
+    assert_eq!(dates(query(&["weather=rain"])), [date("2024-06-01")]);
+    assert_eq!(dates(query(&["weather="])), [date("2024-06-01"), date("2024-06-02")]);
+    assert!(dates(query(&["weather=sun", "steps="])).is_empty());
+    assert!("weather".parse::<Field>().is_err());
+    assert!("=rain".parse::<Field>().is_err());
+}
+
+#[test]
+fn index_finds_candidates_and_keeps_up() {
+    let mut state = test_util::state([
+        ("2023-05-04", "Flew to Berlin today. #travel"),
+        ("2023-08-01", "New-York again"),
+        ("2024-01-01", "home"),
+    ]);
+    let mut index = SearchIndex::build(&state);
+    assert!(index.covers(state.entries.keys()));
+
+    let query = |text: &str| Query {
+        text: Some(text.into()),
+        ..Query::default()
+    };
+    assert_eq!(index.candidates(&query("berl")), [date("2023-05-04")].into());
+    assert_eq!(index.candidates(&query("w-yo")), [date("2023-08-01")].into());
+    let travel = Query {
+        tags: vec!["travel".into()],
+        ..Query::default()
+    };
+    assert_eq!(index.candidates(&travel), [date("2023-05-04")].into());
+
+    state.set_entry(&date("2023-08-01"), "Paris #travel");
+    state.remove_entry(&date("2024-01-01"));
+    assert_eq!(index.update(&state), 1);
+    assert_eq!(index, SearchIndex::build(&state));
+    assert!(index.candidates(&query("york")).is_empty());
+    assert_eq!(index.candidates(&travel).len(), 2);
+}
+
+#[test]
+fn searching_with_an_index_only_decrypts_candidates() {
+    let mut state = test_util::state([("2023-05-04", "Berlin"), ("2023-08-01", "Paris")]);
+    state.index = Some(SearchIndex::default());
+    let storage = test_util::storage(&state).unwrap();
+
+    let query = Query {
+        text: Some("paris".into()),
+        ..Query::default()
+    };
+    let mut loaded = State::new();
+    loaded.load_matching_from(&storage, PASSWORD, &ZeroSecurity, &query).unwrap();
+    assert_eq!(loaded.entries.keys().collect::<Vec<_>>(), [&date("2023-08-01")]);
+    assert_eq!(loaded.index, Some(SearchIndex::build(&state)));
+
+    // without an index, every entry is decrypted
+    state.index = None;
+    let storage = test_util::storage(&state).unwrap();
+    loaded.load_matching_from(&storage, PASSWORD, &ZeroSecurity, &query).unwrap();
+    assert_eq!(loaded.entries.len(), 2);
+    assert_eq!(search::search(&loaded, &query).len(), 1);
+}
@@ -0,0 +1,42 @@
+use jrn::{
+    date::{DateRange, Offset},
+    db::ShiftError,
+    test_util::{self, date},
+};
+
+#[test]
+fn entries_in_the_range_are_shifted_with_their_metadata() {
+    let mut state = test_util::state([("2024-06-01", "one"), ("2024-06-02", "two"), ("2024-06-05", "five")]);
+    state.set_starred(&date("2024-06-01"), true);
+    let range = "2024-06-01..2024-06-02".parse::<DateRange>().unwrap();
+
+    let shifts = state.shift_entries(&range, "+1d".parse().unwrap()).unwrap();
+
+    assert_eq!(shifts.len(), 2);
+    assert_eq!(state.get_entry(&date("2024-06-01")), None);
+    assert_eq!(state.get_entry(&date("2024-06-02")).as_deref(), Some("one"));
+    assert_eq!(state.get_entry(&date("2024-06-03")).as_deref(), Some("two"));
+    assert_eq!(state.get_entry(&date("2024-06-05")).as_deref(), Some("five"));
+    assert!(state.is_starred(&date("2024-06-02")));
+    assert!(!state.is_starred(&date("2024-06-03")));
+}
+
+#[test]
+fn landing_on_an_entry_outside_the_range_changes_nothing() {
+    let mut state = test_util::state([("2024-06-01", "one"), ("2024-06-02", "two"), ("2024-06-03", "three")]);
+    let before = state.entries.clone();
+    let range = "2024-06-01..2024-06-02".parse::<DateRange>().unwrap();
+
+    let result = state.shift_entries(&range, "+1d".parse().unwrap());
+
+    assert!(matches!(result, Err(ShiftError::Collision(dates)) if dates == [date("2024-06-03")]));
+    assert_eq!(state.entries, before);
+}
+
+#[test]
+fn offsets_are_in_days_or_weeks() {
+    assert_eq!("+1d".parse::<Offset>().unwrap().days, 1);
+    assert_eq!("-2".parse::<Offset>().unwrap().days, -2);
+    assert_eq!("+1w".parse::<Offset>().unwrap().days, 7);
+    assert!("1m".parse::<Offset>().is_err());
+}
@@ -0,0 +1,39 @@
+use jrn::{
+    db::{Conflict, MergeError, State},
+    test_util::{self, date},
+};
+
+#[test]
+fn split_journals_merge_back_together() {
+    let mut state = test_util::state([("2023-12-31", "old"), ("2024-01-01", "new"), ("2024-06-01", "summer")]);
+    state.set_starred(&date("2024-06-01"), true);
+
+    let (old, new): (Vec<_>, Vec<_>) = state.entries.keys().partition(|day| day.year() == 2023);
+    let old = state.extract(&old);
+    let new = state.extract(&new);
+    assert_eq!(old.entries.len(), 1);
+    assert_eq!(new.entries.len(), 2);
+    assert_eq!(new.password, state.password);
+
+    let mut merged = State::new();
+    merged.merge_from(&old, None).unwrap();
+    merged.merge_from(&new, None).unwrap();
+    assert_eq!(merged.entries, state.entries);
+    assert!(merged.is_starred(&date("2024-06-01")));
+    // merging the same entries again changes nothing
+    assert!(merged.merge_from(&new, None).unwrap().is_empty());
+}
+
+#[test]
+fn different_entries_on_the_same_date_need_a_conflict() {
+    let mut state = test_util::state([("2024-06-01", "mine"), ("2024-06-02", "two")]);
+    let other = test_util::state([("2024-06-01", "theirs"), ("2024-06-03", "three")]);
+
+    let result = state.merge_from(&other, None);
+    assert!(matches!(result, Err(MergeError::Collision(dates)) if dates == [date("2024-06-01")]));
+    assert_eq!(state.entries.len(), 2);
+
+    let merged = state.merge_from(&other, Some(Conflict::Append)).unwrap();
+    assert_eq!(merged, [date("2024-06-01"), date("2024-06-03")]);
+    assert_eq!(state.get_entry(&date("2024-06-01")).as_deref(), Some("mine\n\ntheirs"));
+}
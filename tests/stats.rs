@@ -0,0 +1,158 @@
+use chrono::Weekday;
+
+use jrn::{
+    date::{DateRange, Period},
+    mood::Mood,
+    stats::{self, Table},
+    test_util::{self, date},
+};
+
+#[test]
+fn summary_counts_words_in_range() {
+    let state = test_util::state([
+        ("2024-05-31", "not counted"),
+        ("2024-06-01", "one two three"),
+        ("2024-06-02", "four\nfive"),
+    ]);
+
+    let summary = stats::summary(&state, &"2024-06".parse().unwrap());
+
+    assert_eq!(summary.entries, 2);
+    assert_eq!(summary.words, 5);
+    assert_eq!(summary.average_words(), 2.5);
+    assert_eq!(summary.first, Some(date("2024-06-01")));
+    assert_eq!(summary.last, Some(date("2024-06-02")));
+}
+
+#[test]
+fn mood_is_averaged_by_month() {
+    let mut state = test_util::state([
+        ("2024-06-01", "first"),
+        ("2024-06-02", "second"),
+        ("2024-06-03", "no mood"),
+        ("2024-07-01", "third"),
+    ]);
+    state.set_mood(&date("2024-06-01"), "great".parse().ok());
+    state.set_mood(&date("2024-06-02"), Mood::new(2));
+    state.set_mood(&date("2024-07-01"), "okay".parse().ok());
+
+    let trends = stats::mood_by_month(&state, &DateRange::all());
+
+    assert_eq!(trends.len(), 2);
+    assert_eq!(trends[0].period, Period::Month { year: 2024, month: 6 });
+    assert_eq!(trends[0].average, 3.5);
+    assert_eq!(trends[0].entries, 2);
+    assert_eq!(trends[1].average, 3.0);
+}
+
+#[test]
+fn missing_days_until_today() {
+    let state = test_util::state([("2024-06-01", "first"), ("2024-06-03T21:30", "third")]);
+
+    let missing = stats::missing_days(&state, &DateRange::all(), &date("2024-06-05"));
+    assert_eq!(missing, [date("2024-06-02"), date("2024-06-04"), date("2024-06-05")]);
+
+    let missing = stats::missing_days(&state, &"2024-05-30..2024-06-01".parse().unwrap(), &date("2024-06-05"));
+    assert_eq!(missing, [date("2024-05-30"), date("2024-05-31")]);
+}
+
+#[test]
+fn meaningful_words_are_counted() {
+    let state = test_util::state([
+        ("2024-06-01", "Coffee with Anna, then more coffee."),
+        ("2024-06-02", "The coffee shop was closed; Anna's idea."),
+        ("2024-07-01", "Rain. I'm at home with a book"),
+    ]);
+
+    let words = stats::word_counts(&state, &DateRange::all());
+    let top = words.iter().take(2).map(|count| (count.word.as_str(), count.count)).collect::<Vec<_>>();
+    assert_eq!(top, [("coffee", 3), ("anna", 1)]);
+    assert!(words.iter().all(|count| !["the", "with", "i'm", "at", "a"].contains(&count.word.as_str())));
+    assert!(words.iter().any(|count| count.word == "anna's"));
+
+    let months = stats::word_counts_by_month(&state, &DateRange::all());
+    assert_eq!(months.len(), 2);
+    assert_eq!(months[1].0, Period::Month { year: 2024, month: 7 });
+    assert_eq!(months[1].1.iter().map(|count| count.word.as_str()).collect::<Vec<_>>(), ["book", "home", "rain"]);
+}
+
+#[test]
+fn entries_by_weekday_and_hour() {
+    let state = test_util::state([
+        ("2024-06-01", "a saturday"),
+        ("2024-06-03T21:30", "a monday evening"),
+        ("2024-06-10T21:05", "another monday evening entry"),
+        ("2024-06-11T07:45", "tuesday"),
+    ]);
+
+    let weekdays = stats::by_weekday(&state, &DateRange::all());
+    assert_eq!(weekdays.len(), 7);
+    assert_eq!(weekdays[0].0, Weekday::Mon);
+    assert_eq!(weekdays[0].1.entries, 2);
+    assert_eq!(weekdays[0].1.average_words(), 3.5);
+    assert_eq!(weekdays[2].1.entries, 0);
+    assert_eq!(weekdays[5].1.entries, 1);
+
+    let hours = stats::by_hour(&state, &DateRange::all())
+        .into_iter()
+        .map(|(hour, summary)| (hour, summary.entries))
+        .collect::<Vec<_>>();
+    assert_eq!(hours, [(7, 1), (21, 2)]);
+}
+
+#[test]
+fn scheduled_entries_count_once_their_day_comes() {
+    let state = test_util::state([("2024-06-01", "written"), ("2024-06-09", "written ahead")]);
+    let today = date("2024-06-05");
+
+    assert!(stats::is_scheduled(&date("2024-06-09"), &today));
+    assert!(!stats::is_scheduled(&date("2024-06-05T23:59"), &today));
+
+    let summary = stats::summary(&state, &stats::until(&DateRange::all(), &today));
+    assert_eq!(summary.entries, 1);
+    assert_eq!(summary.last, Some(date("2024-06-01")));
+
+    let range = stats::until(&"2024-06-01..2024-06-03".parse().unwrap(), &today);
+    assert_eq!(range.end, Some(date("2024-06-03")));
+    let summary = stats::summary(&state, &stats::until(&DateRange::all(), &date("2024-06-09")));
+    assert_eq!(summary.entries, 2);
+}
+
+#[test]
+fn statistics_as_json_and_csv() {
+    let state = test_util::state([("2024-06-01", "coffee, then \"coffee\""), ("2024-07-01", "tea")]);
+    let range = DateRange::all();
+
+    let summary = Table::summary(&stats::summary(&state, &range));
+    let words = Table::words(&stats::word_counts(&state, &range), &stats::word_counts_by_month(&state, &range), 1);
+
+    assert_eq!(
+        summary.to_json(),
+        serde_json::json!([{"entries": 2, "words": 4, "words_per_entry": 2.0, "first": "2024-06-01", "last": "2024-07-01"}])
+    );
+    assert_eq!(
+        summary.to_csv(),
+        "entries,words,words_per_entry,first,last\n2,4,2.0,2024-06-01,2024-07-01\n"
+    );
+    assert_eq!(
+        words.to_csv(),
+        "period,word,count\n,coffee,2\n2024-06,coffee,2\n2024-07,tea,1\n"
+    );
+}
+
+#[test]
+fn heatmap_has_a_column_for_every_week() {
+    let state = test_util::state([("2024-01-01", "one two three"), ("2024-01-07", "four"), ("2024-03-01", "later")]);
+
+    let weeks = stats::heatmap(&state, 2024, &date("2024-01-10"));
+
+    // 2024 starts on a Monday and has 366 days
+    assert_eq!(weeks.len(), 53);
+    assert_eq!(weeks[0], [Some(3), Some(0), Some(0), Some(0), Some(0), Some(0), Some(1)]);
+    assert_eq!(weeks[1][2], Some(0));
+    assert_eq!(weeks[1][3], None);
+    assert!(weeks[8..].iter().flatten().all(Option::is_none));
+    assert_eq!(stats::heatmap_level(0, 3), 0);
+    assert_eq!(stats::heatmap_level(1, 3), 2);
+    assert_eq!(stats::heatmap_level(3, 3), 4);
+}
@@ -0,0 +1,44 @@
+use jrn::{
+    tags,
+    test_util::{self, date},
+};
+
+#[test]
+fn tags_are_counted_once_per_entry() {
+    let state = test_util::state([
+        ("2024-06-01", "#travel to #Berlin, #travel again"),
+        ("2024-06-02", "back from #berlin"),
+        ("2024-06-03", "no tags"),
+    ]);
+
+    let counts = tags::tag_counts(&state);
+
+    assert_eq!(counts, [("berlin".to_string(), 2), ("travel".to_string(), 1)]);
+}
+
+#[test]
+fn tags_are_renamed_and_removed() {
+    let text = "went #hiking today #Hiking-trip\n#hiking\nsee #HIKING.";
+
+    assert_eq!(
+        tags::retag(text, "hiking", Some("hike")).as_deref(),
+        Some("went #hike today #Hiking-trip\n#hike\nsee #hike.")
+    );
+    assert_eq!(
+        tags::retag(text, "#hiking", None).as_deref(),
+        Some("went today #Hiking-trip\nsee.")
+    );
+    assert_eq!(tags::retag(text, "travel", None), None);
+}
+
+#[test]
+fn retagging_only_plans_entries_with_the_tag() {
+    let state = test_util::state([("2024-06-01", "#old and #new"), ("2024-06-02", "nothing")]);
+
+    let changes = tags::plan_retag(&state, "old", Some("new"));
+
+    assert_eq!(changes, [(date("2024-06-01"), "#new and #new".to_string())]);
+    assert_eq!(tags::parse_tag("#ok-tag"), Some("ok-tag"));
+    assert_eq!(tags::parse_tag("two words"), None);
+    assert_eq!(tags::parse_tag("2024"), None);
+}
@@ -0,0 +1,55 @@
+use jrn::{
+    template::{self, TemplateRule, TemplateRuleError},
+    test_util::date,
+};
+
+fn rule(file: &str) -> TemplateRule {
+    TemplateRule {
+        file: file.into(),
+        ..TemplateRule::default()
+    }
+}
+
+#[test]
+fn the_first_rule_for_a_date_wins() {
+    let rules = [
+        TemplateRule {
+            weekday: Some("Monday".into()),
+            ..rule("weekly.md")
+        },
+        TemplateRule {
+            day: Some(1),
+            ..rule("review.md")
+        },
+        TemplateRule {
+            day: Some(-1),
+            month: Some(12),
+            ..rule("year.md")
+        },
+        rule("daily.md"),
+    ];
+    let file = |day| template::template_for(&rules, &date(day)).map(|rule| rule.file.as_str());
+
+    // a monday and the 1st of the month
+    assert_eq!(file("2024-07-01"), Some("weekly.md"));
+    assert_eq!(file("2024-06-01"), Some("review.md"));
+    assert_eq!(file("2024-12-31"), Some("year.md"));
+    assert_eq!(file("2024-11-30"), Some("daily.md"));
+    assert_eq!(template::template_for(&rules[..1], &date("2024-06-02")), None);
+}
+
+#[test]
+fn invalid_rules_are_caught() {
+    let weekday = TemplateRule {
+        weekday: Some("someday".into()),
+        ..rule("a.md")
+    };
+    assert_eq!(weekday.check(), Err(TemplateRuleError::InvalidWeekday("someday".into())));
+    assert!(!weekday.matches(&date("2024-06-01")));
+
+    let day = TemplateRule { day: Some(32), ..rule("a.md") };
+    assert_eq!(day.check(), Err(TemplateRuleError::InvalidDay(32)));
+    let month = TemplateRule { month: Some(0), ..rule("a.md") };
+    assert_eq!(month.check(), Err(TemplateRuleError::InvalidMonth(0)));
+    assert_eq!(rule("a.md").check(), Ok(()));
+}
@@ -0,0 +1,16 @@
+use jrn::theme::{Style, Theme};
+
+#[test]
+fn the_default_highlight_is_black_on_yellow() {
+    let theme = Theme::preset("default").unwrap();
+    assert_eq!(theme.highlight.paint("x"), "\x1b[30;103mx\x1b[0m");
+    assert_eq!(Theme::preset("muted").unwrap().highlight.paint("x"), "\x1b[7mx\x1b[0m");
+}
+
+#[test]
+fn styles_can_have_background_colors() {
+    let style = "bright-white on-blue bold".parse::<Style>().unwrap();
+    assert_eq!(style.paint("x"), "\x1b[97;44;1mx\x1b[0m");
+    assert_eq!("on-bright-black".parse::<Style>().unwrap().paint("x"), "\x1b[100mx\x1b[0m");
+    assert!("on-bold".parse::<Style>().is_err());
+}
@@ -0,0 +1,54 @@
+use jrn::{
+    db::State,
+    encryptor::Secure,
+    storage::InMemoryStorage,
+    test_util::{self, PASSWORD},
+    totp,
+};
+
+/// the secret of the test vectors in RFC 6238 (`12345678901234567890`), in
+/// base32
+const SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+#[test]
+fn codes_match_rfc_6238() {
+    for (time, expected) in [
+        (59, "287082"),
+        (1111111109, "081804"),
+        (1234567890, "005924"),
+        (20000000000, "353130"),
+    ] {
+        assert_eq!(totp::code(SECRET, time).as_deref(), Some(expected));
+    }
+
+    assert_eq!(totp::code("not base32!", 59), None);
+    assert_eq!(totp::code("", 59), None);
+}
+
+#[test]
+fn codes_are_verified_with_some_leeway() {
+    assert!(totp::verify(SECRET, "287082", 59));
+    assert!(totp::verify(SECRET, "287 082", 59 + totp::PERIOD));
+    assert!(!totp::verify(SECRET, "287082", 59 + 3 * totp::PERIOD));
+    assert!(!totp::verify(SECRET, "123456", 59));
+
+    let secret = totp::new_secret();
+    assert_eq!(secret.len(), 32);
+    assert!(totp::verify(&secret.to_lowercase(), &totp::code(&secret, 1000).unwrap(), 1000));
+    assert!(totp::uri(&secret, "my journal").starts_with("otpauth://totp/jrn:my%20journal?secret="));
+}
+
+#[test]
+fn secret_is_kept_encrypted() {
+    let mut state = test_util::state([("2024-06-01", "first")]);
+    state.totp = Some(SECRET.into());
+    let storage = InMemoryStorage::new();
+    state.save_to(&storage, &Secure).unwrap();
+
+    assert!(storage.contents().unwrap().contains("\"totp\""));
+    assert!(!storage.contents().unwrap().contains(SECRET));
+
+    let mut loaded = State::new();
+    loaded.load_from(&storage, PASSWORD, &Secure).unwrap();
+    assert_eq!(loaded.totp.as_deref(), Some(SECRET));
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+
+use jrn::{
+    hooks::{self, WebhookError, WebhookPayload},
+    test_util::date,
+};
+
+/// a server that answers one request with `status`, and gives back the
+/// request it got
+fn server(status: &'static str) -> (String, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/jrn", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        loop {
+            let read = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .and_then(|length| length.parse::<usize>().ok())
+                    .unwrap_or(0);
+                if read == 0 || body.len() >= length {
+                    break;
+                }
+            }
+        }
+        stream.write_all(format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n").as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+    (url, handle)
+}
+
+fn payload<'a>(dates: &'a [jrn::date::Date]) -> WebhookPayload<'a> {
+    WebhookPayload {
+        hook: "post_save_webhook",
+        journal: "/home/me/jrn.json",
+        dates,
+        changed: dates.len(),
+        entries: 12,
+    }
+}
+
+#[test]
+fn webhooks_get_dates_and_counts() {
+    let (url, handle) = server("204 No Content");
+    let dates = [date("2024-06-01"), date("2024-06-02")];
+
+    hooks::post_webhook(&url, &payload(&dates)).unwrap();
+    let request = handle.join().unwrap();
+    let (head, body) = request.split_once("\r\n\r\n").unwrap();
+    let body = serde_json::from_str::<serde_json::Value>(body).unwrap();
+
+    assert!(head.starts_with("POST /jrn HTTP/1.1\r\n"));
+    assert!(head.contains("Content-Type: application/json"));
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "hook": "post_save_webhook",
+            "journal": "/home/me/jrn.json",
+            "dates": ["2024-06-01", "2024-06-02"],
+            "changed": 2,
+            "entries": 12,
+        })
+    );
+}
+
+#[test]
+fn failing_webhooks_are_reported() {
+    let (url, handle) = server("500 Internal Server Error");
+
+    let posted = hooks::post_webhook(&url, &payload(&[]));
+    handle.join().unwrap();
+
+    assert!(matches!(posted, Err(WebhookError::Failed(status)) if status == "500 Internal Server Error"));
+    assert!(hooks::check_webhook_url("https://example.com/jrn").is_err());
+    assert!(hooks::check_webhook_url("http://localhost:8123/api/webhook/jrn").is_ok());
+}
+
+#[test]
+fn webhook_urls_can_have_a_query_without_a_path() {
+    let (url, handle) = server("200 OK");
+    let url = format!("{}?token=abc#ignored", url.trim_end_matches("/jrn"));
+
+    hooks::post_webhook(&url, &payload(&[])).unwrap();
+    let request = handle.join().unwrap();
+
+    assert!(request.starts_with("POST /?token=abc HTTP/1.1\r\n"));
+    assert!(!request.contains("ignored"));
+}